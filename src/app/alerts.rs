@@ -0,0 +1,116 @@
+use crate::chart::{
+    CandlestickSeriesApi, ChartApi, Color, LineStyle, PriceLineApi, PriceLineOptions,
+};
+use relm4::gtk;
+use relm4::gtk::gio;
+use relm4::gtk::prelude::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+struct AlertsState {
+    lines: Vec<PriceLineApi>,
+    /// Close the next update is compared with
+    last_close: Option<f64>,
+}
+
+/// Price alerts on the candlestick series, shown as draggable price lines.
+/// An alert fires once, when a close crosses its line, and then goes away.
+/// Adding or deleting an alert can be undone and redone; firing one is not
+/// part of the history.
+#[derive(Clone)]
+pub struct PriceAlerts {
+    chart: ChartApi,
+    series: CandlestickSeriesApi,
+    window: gtk::ApplicationWindow,
+    state: Rc<RefCell<AlertsState>>,
+}
+
+impl PriceAlerts {
+    pub fn new(
+        chart: &ChartApi,
+        series: &CandlestickSeriesApi,
+        window: &gtk::ApplicationWindow,
+        last_close: Option<f64>,
+    ) -> Self {
+        Self {
+            chart: chart.clone(),
+            series: series.clone(),
+            window: window.clone(),
+            state: Rc::new(RefCell::new(AlertsState {
+                lines: Vec::new(),
+                last_close,
+            })),
+        }
+    }
+
+    /// Adds an alert at `price`.
+    pub fn add(&self, price: f64) {
+        self.chart.record_price_lines(self.series.series_id());
+        let line = self.series.create_price_line(PriceLineOptions {
+            price,
+            color: Color::new(0.98, 0.6, 0.2),
+            line_style: LineStyle::Dashed,
+            title: Some("Alert".to_string()),
+            draggable: true,
+            ..PriceLineOptions::default()
+        });
+        self.state.borrow_mut().lines.push(line);
+    }
+
+    /// Whether price line `line` of the candlestick series is an alert.
+    pub fn contains(&self, series: usize, line: usize) -> bool {
+        series == self.series.series_id()
+            && self
+                .state
+                .borrow()
+                .lines
+                .iter()
+                .any(|alert| alert.line_id() == line)
+    }
+
+    /// Compares a new close with the previous one, firing the alerts whose
+    /// price lies between them.
+    pub fn check(&self, close: f64) {
+        let fired = {
+            let mut state = self.state.borrow_mut();
+            let Some(previous) = state.last_close.replace(close) else {
+                return;
+            };
+            let mut fired = Vec::new();
+            // A line missing from the chart was undone or deleted, and undo or
+            // redo may bring it back under the same id, so it stays an alert.
+            state.lines.retain(|line| {
+                let Some(price) = line.options().map(|options| options.price) else {
+                    return true;
+                };
+                let crossed =
+                    (previous < price && close >= price) || (previous > price && close <= price);
+                if crossed {
+                    line.remove();
+                    fired.push(price);
+                }
+                !crossed
+            });
+            fired
+        };
+        for price in fired {
+            self.notify(price);
+        }
+    }
+
+    /// Sets the close to compare with after the data was replaced, so the
+    /// jump does not fire alerts.
+    pub fn rebase(&self, last_close: Option<f64>) {
+        self.state.borrow_mut().last_close = last_close;
+    }
+
+    fn notify(&self, price: f64) {
+        let Some(app) = self.window.application() else {
+            return;
+        };
+        let notification = gio::Notification::new("Price alert");
+        let price = self.series.format_price(price);
+        notification.set_body(Some(&format!("Price crossed {price}")));
+        app.send_notification(None, &notification);
+    }
+}
@@ -0,0 +1,409 @@
+use crate::chart::{ChartApi, Drawing, DrawingId, DrawingTool, HitTarget, PanelRole, PriceScale};
+use crate::settings_ui::SettingsControls;
+use relm4::gtk;
+use relm4::gtk::prelude::*;
+use std::rc::Rc;
+
+use super::alerts::PriceAlerts;
+use super::drawing_tools::DrawingTools;
+use super::settings_wiring::without_settings_history;
+
+/// Looks up how to remove series `series` through whatever added it, such
+/// as an indicator or a script. `None` for series nothing owns, like the
+/// price candles, which the menu then offers no Remove for.
+pub type SeriesRemoval = Rc<dyn Fn(usize) -> Option<Rc<dyn Fn()>>>;
+
+/// Entries of the price axis mode menu, in the order of the settings combo.
+const SCALE_MODES: [&str; 4] = ["Normal", "Logarithmic", "Percentage", "Indexed to 100"];
+
+struct MenuState {
+    chart: ChartApi,
+    drawing_area: gtk::DrawingArea,
+    tools: DrawingTools,
+    alerts: PriceAlerts,
+    settings: SettingsControls,
+    settings_window: gtk::Window,
+    settings_stack: gtk::Stack,
+    remove_series: SeriesRemoval,
+    popover: gtk::Popover,
+}
+
+/// Right-click menu with actions for whatever is under the pointer: a
+/// drawing, a price line, a series, a price axis or the empty main plot.
+/// Price axis changes go through the settings controls, so the settings
+/// window stays in step.
+#[derive(Clone)]
+pub struct ChartContextMenu {
+    state: Rc<MenuState>,
+}
+
+impl ChartContextMenu {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        chart: &ChartApi,
+        drawing_area: &gtk::DrawingArea,
+        tools: &DrawingTools,
+        alerts: &PriceAlerts,
+        settings: &SettingsControls,
+        settings_window: &gtk::Window,
+        settings_stack: &gtk::Stack,
+        remove_series: SeriesRemoval,
+    ) -> Self {
+        let popover = gtk::Popover::new();
+        popover.set_parent(drawing_area);
+        popover.set_has_arrow(false);
+        Self {
+            state: Rc::new(MenuState {
+                chart: chart.clone(),
+                drawing_area: drawing_area.clone(),
+                tools: tools.clone(),
+                alerts: alerts.clone(),
+                settings: settings.clone(),
+                settings_window: settings_window.clone(),
+                settings_stack: settings_stack.clone(),
+                remove_series,
+                popover,
+            }),
+        }
+    }
+
+    /// Opens the menu for the object at `(x, y)`. Returns false when there
+    /// is none, leaving the click to the panel menu.
+    pub fn open(&self, x: f64, y: f64) -> bool {
+        let chart = &self.state.chart;
+        let width = self.state.drawing_area.width() as f64;
+        let height = self.state.drawing_area.height() as f64;
+        if chart.tooltip_icon_at(x, y).is_some() {
+            return false;
+        }
+        let content = gtk::Box::new(gtk::Orientation::Vertical, 0);
+        if let Some(hit) = chart.drawing_hit_test(x, y, width, height) {
+            self.drawing_items(&content, hit.id, x, y);
+        } else if let Some(target) = chart.hit_test(x, y) {
+            match target {
+                HitTarget::PriceLine { series, line } => {
+                    self.price_line_items(&content, series, line, x, y)
+                }
+                HitTarget::Candle { series, .. } => self.series_items(&content, series, "candles"),
+                HitTarget::Line { series, .. }
+                | HitTarget::Histogram { series, .. }
+                | HitTarget::Marker { series, .. } => self.series_items(&content, series, "series"),
+                HitTarget::Position { .. }
+                | HitTarget::Order { .. }
+                | HitTarget::OrderCancel { .. } => return false,
+            }
+        } else if let Some(side) = chart.price_scale_at(x, y) {
+            self.scale_items(&content, side);
+        } else if chart
+            .panel_at(x, y, width, height)
+            .is_some_and(|panel| matches!(chart.panel_role(panel), Some(PanelRole::Main)))
+        {
+            self.plot_items(&content, x, y, width, height);
+        } else {
+            return false;
+        }
+        self.show(&content, x, y);
+        true
+    }
+
+    fn show(&self, content: &gtk::Box, x: f64, y: f64) {
+        let popover = &self.state.popover;
+        popover.set_child(Some(content));
+        let rect = gtk::gdk::Rectangle::new(x as i32, y as i32, 1, 1);
+        popover.set_pointing_to(Some(&rect));
+        popover.popup();
+    }
+
+    /// Adds a menu entry that closes the menu and runs `action`.
+    fn item(&self, content: &gtk::Box, label: &str, action: impl Fn(&Self) + 'static) {
+        let button = gtk::Button::with_label(label);
+        button.add_css_class("flat");
+        if let Some(child) = button.child() {
+            child.set_halign(gtk::Align::Start);
+        }
+        let menu = self.clone();
+        button.connect_clicked(move |_| {
+            menu.state.popover.popdown();
+            action(&menu);
+            menu.state.drawing_area.queue_draw();
+        });
+        content.append(&button);
+    }
+
+    fn drawing_items(&self, content: &gtk::Box, id: DrawingId, x: f64, y: f64) {
+        let Some(drawing) = self.state.chart.drawing(id) else {
+            return;
+        };
+        self.item(content, "Edit…", move |menu| {
+            menu.state.tools.edit(id, x, y)
+        });
+        self.item(content, "Clone", move |menu| {
+            let Some(mut copy) = menu.state.chart.drawing(id) else {
+                return;
+            };
+            copy.locked = false;
+            let copy = menu.state.chart.add_drawing(copy);
+            menu.state.chart.select_drawing(Some(copy));
+        });
+        let label = if drawing.locked { "Unlock" } else { "Lock" };
+        self.item(content, label, move |menu| {
+            if let Some(mut drawing) = menu.state.chart.drawing(id) {
+                drawing.locked = !drawing.locked;
+                menu.state.chart.update_drawing(id, drawing);
+            }
+        });
+        if !drawing.locked {
+            self.item(content, "Delete", move |menu| {
+                menu.state.chart.remove_drawing(id);
+            });
+        }
+    }
+
+    fn price_line_items(&self, content: &gtk::Box, series: usize, line: usize, x: f64, y: f64) {
+        let Some(options) = self
+            .state
+            .chart
+            .price_line(series, line)
+            .and_then(|line| line.options())
+        else {
+            return;
+        };
+        self.item(content, "Edit…", move |menu| {
+            menu.edit_price_line(series, line, x, y)
+        });
+        self.item(content, "Clone", move |menu| {
+            let chart = &menu.state.chart;
+            let Some(options) = chart
+                .price_line(series, line)
+                .and_then(|line| line.options())
+            else {
+                return;
+            };
+            if menu.state.alerts.contains(series, line) {
+                menu.state.alerts.add(options.price);
+            } else {
+                chart.record_price_lines(series);
+                chart.create_price_line(series, options);
+            }
+        });
+        let label = if options.draggable { "Lock" } else { "Unlock" };
+        self.item(content, label, move |menu| {
+            let Some(handle) = menu.state.chart.price_line(series, line) else {
+                return;
+            };
+            if let Some(mut options) = handle.options() {
+                options.draggable = !options.draggable;
+                menu.state.chart.record_price_lines(series);
+                handle.apply_options(options);
+            }
+        });
+        self.item(content, "Delete", move |menu| {
+            if let Some(handle) = menu.state.chart.price_line(series, line) {
+                menu.state.chart.record_price_lines(series);
+                handle.remove();
+            }
+        });
+    }
+
+    /// Reopens the menu as a small form for the price and title of a price
+    /// line.
+    fn edit_price_line(&self, series: usize, line: usize, x: f64, y: f64) {
+        let Some(handle) = self.state.chart.price_line(series, line) else {
+            return;
+        };
+        let Some(options) = handle.options() else {
+            return;
+        };
+        let price = gtk::SpinButton::with_range(f64::MIN, f64::MAX, 0.01);
+        price.set_digits(2);
+        price.set_value(options.price);
+        let title = gtk::Entry::new();
+        title.set_placeholder_text(Some("Title"));
+        title.set_text(options.title.as_deref().unwrap_or_default());
+        let apply = gtk::Button::with_label("Apply");
+        let content = gtk::Box::new(gtk::Orientation::Vertical, 6);
+        content.append(&price);
+        content.append(&title);
+        content.append(&apply);
+
+        let commit = {
+            let menu = self.clone();
+            let price = price.clone();
+            let title = title.clone();
+            move || {
+                menu.state.popover.popdown();
+                let Some(mut options) = handle.options() else {
+                    return;
+                };
+                options.price = price.value();
+                let text = title.text();
+                options.title = (!text.is_empty()).then(|| text.to_string());
+                menu.state.chart.record_price_lines(series);
+                handle.apply_options(options);
+                menu.state.drawing_area.queue_draw();
+            }
+        };
+        title.connect_activate({
+            let commit = commit.clone();
+            move |_| commit()
+        });
+        apply.connect_clicked(move |_| commit());
+        self.show(&content, x, y);
+    }
+
+    fn series_items(&self, content: &gtk::Box, series: usize, settings_page: &'static str) {
+        self.item(content, "Settings…", move |menu| {
+            menu.state
+                .settings_stack
+                .set_visible_child_name(settings_page);
+            menu.state.settings_window.present();
+        });
+        self.item(content, "Hide", move |menu| {
+            menu.state.chart.set_series_visible(series, false)
+        });
+        let chart = &self.state.chart;
+        if chart.series_panel(series) != Some(chart.main_panel_id()) {
+            self.item(content, "Move to main panel", move |menu| {
+                menu.move_series(series, false)
+            });
+        } else if !chart.rsi_panel_has_data() {
+            // The RSI line pins the indicator panel to 0-100, which series on
+            // their own scale cannot share.
+            let label = if chart.has_rsi_panel() {
+                "Move to indicator panel"
+            } else {
+                "Move to new panel"
+            };
+            self.item(content, label, move |menu| menu.move_series(series, true));
+        }
+        if let Some(remove) = (self.state.remove_series)(series) {
+            self.item(content, "Remove", move |menu| {
+                remove();
+                // The emptied series may be reused by the next indicator.
+                menu.state.chart.set_series_visible(series, true);
+            });
+        }
+    }
+
+    /// Moves series `series` to the indicator panel, or back to the main
+    /// panel, as one undo step. Undoing a move that added the indicator
+    /// panel takes the panel off again once it is empty.
+    fn move_series(&self, series: usize, to_indicator: bool) {
+        let chart = self.state.chart.clone();
+        let added_panel = to_indicator && !chart.has_rsi_panel();
+        if !place_series(&chart, series, to_indicator) {
+            return;
+        }
+        let undo: Rc<dyn Fn()> = {
+            let chart = chart.clone();
+            Rc::new(move || {
+                place_series(&chart, series, !to_indicator);
+                if added_panel && !chart.rsi_panel_has_plots() {
+                    chart.clear_rsi_panel();
+                }
+            })
+        };
+        let redo: Rc<dyn Fn()> = {
+            let chart = chart.clone();
+            Rc::new(move || {
+                place_series(&chart, series, to_indicator);
+            })
+        };
+        chart.record_edit(undo, redo);
+    }
+
+    fn scale_items(&self, content: &gtk::Box, side: PriceScale) {
+        let controls = match side {
+            PriceScale::Left => self.state.settings.price_scale.left.clone(),
+            PriceScale::Right => self.state.settings.price_scale.right.clone(),
+        };
+        let current = controls.mode_combo.active();
+        for (index, mode) in SCALE_MODES.iter().enumerate() {
+            let label = if current == Some(index as u32) {
+                format!("✓ {mode}")
+            } else {
+                mode.to_string()
+            };
+            let combo = controls.mode_combo.clone();
+            self.item(content, &label, move |_| {
+                combo.set_active(Some(index as u32))
+            });
+        }
+        content.append(&gtk::Separator::new(gtk::Orientation::Horizontal));
+        let invert = controls.invert.clone();
+        let label = if invert.state() {
+            "✓ Invert scale"
+        } else {
+            "Invert scale"
+        };
+        self.item(content, label, move |_| invert.set_state(!invert.state()));
+        let auto_scale = controls.auto_scale.clone();
+        let label = if auto_scale.state() {
+            "✓ Auto scale"
+        } else {
+            "Auto scale"
+        };
+        self.item(content, label, move |menu| {
+            without_settings_history(&menu.state.settings, || {
+                auto_scale.set_state(!auto_scale.state())
+            })
+        });
+    }
+
+    fn plot_items(&self, content: &gtk::Box, x: f64, y: f64, width: f64, height: f64) {
+        self.item(content, "Reset scale", |menu| {
+            let price_scale = &menu.state.settings.price_scale;
+            for (side, controls) in [
+                (PriceScale::Left, &price_scale.left),
+                (PriceScale::Right, &price_scale.right),
+            ] {
+                menu.state.chart.reset_autoscale(side);
+                without_settings_history(&menu.state.settings, || {
+                    controls.auto_scale.set_state(true)
+                });
+            }
+        });
+        if let Some((side, price)) = self.state.chart.price_at(x, y, width, height) {
+            self.item(content, "Add alert here", move |menu| {
+                menu.state.alerts.add(price)
+            });
+            self.item(content, "Add horizontal line", move |menu| {
+                let chart = &menu.state.chart;
+                let Some(mut anchor) = chart.drawing_anchor_at(x, y, width, height) else {
+                    return;
+                };
+                anchor.price = price;
+                chart.add_drawing(Drawing::new(DrawingTool::HorizontalLine, vec![anchor]));
+            });
+            self.item(content, "Copy price", move |menu| {
+                let text = menu.state.chart.format_price(side, price);
+                menu.state.drawing_area.clipboard().set_text(&text);
+            });
+        }
+        let hidden = self.state.chart.hidden_series();
+        if !hidden.is_empty() {
+            self.item(content, "Show hidden series", move |menu| {
+                for &series in &hidden {
+                    menu.state.chart.set_series_visible(series, true);
+                }
+            });
+        }
+    }
+}
+
+/// Puts series `series` on the indicator panel, adding the panel when there
+/// is none, or on the main panel.
+fn place_series(chart: &ChartApi, series: usize, on_indicator: bool) -> bool {
+    let panel = if on_indicator {
+        if !chart.has_rsi_panel() {
+            chart.set_rsi_panel("Indicators".to_string(), Vec::new());
+        }
+        match chart.rsi_panel_id() {
+            Some(panel) => panel,
+            None => return false,
+        }
+    } else {
+        chart.main_panel_id()
+    };
+    chart.move_series_to_panel(series, panel)
+}
@@ -4,14 +4,22 @@ use std::{thread, time};
 use tungstenite::{connect, Message};
 use url::Url;
 
-use super::market_data::{fetch_binance_klines_batch, KlineEvent, MarketBatch, MarketStore};
+use super::market_data::{
+    detail_interval, fetch_binance_klines_batch, KlineEvent, MarketBatch, MarketStore,
+};
 
 pub enum DataEvent {
     Prepend(MarketBatch),
     Kline(KlineEvent),
     LoadFailed(String),
+    ReplayDetail(MarketBatch),
 }
 
+/// Detail bars fetched per request for intrabar replay.
+const DETAIL_BATCH: usize = 1000;
+/// Most detail bars fetched for one replay.
+const DETAIL_LIMIT: usize = 50_000;
+
 pub struct LazyLoader {
     batch_size: usize,
     threshold_bars: i64,
@@ -83,6 +91,44 @@ impl LazyLoader {
     }
 }
 
+/// Fetches lower-timeframe bars covering the bars hidden by replay, sending
+/// them in batches as they arrive so the first bars can form right away.
+pub fn spawn_replay_detail_fetch(store: &MarketStore, sender: Sender<DataEvent>) {
+    let (Some((from_ms, to_ms)), Some(interval)) =
+        (store.replay_span(), detail_interval(&store.interval))
+    else {
+        return;
+    };
+    let symbol = store.symbol.clone();
+    thread::spawn(move || {
+        let mut start_ms = from_ms;
+        let mut fetched = 0;
+        while start_ms < to_ms && fetched < DETAIL_LIMIT {
+            let batch = match fetch_binance_klines_batch(
+                &symbol,
+                interval,
+                DETAIL_BATCH,
+                Some(start_ms),
+                Some(to_ms - 1),
+            ) {
+                Ok(batch) => batch,
+                Err(err) => {
+                    eprintln!("Replay detail fetch failed: {err}");
+                    return;
+                }
+            };
+            let Some(last) = batch.candles.last() else {
+                return;
+            };
+            start_ms = last.time.unix_timestamp() * 1000 + 1;
+            fetched += batch.candles.len();
+            if sender.send(DataEvent::ReplayDetail(batch)).is_err() {
+                return;
+            }
+        }
+    });
+}
+
 pub fn spawn_kline_stream(symbol: String, interval: String, sender: Sender<DataEvent>) {
     thread::spawn(move || {
         let stream = format!("{}@kline_{}", symbol.to_lowercase(), interval);
@@ -0,0 +1,634 @@
+use crate::chart::{
+    ChartApi, Color, Drawing, DrawingAnchor, DrawingId, DrawingLevel, DrawingStyle, DrawingTool,
+    LineStyle,
+};
+use relm4::gtk;
+use relm4::gtk::glib;
+use relm4::gtk::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+/// Version written to drawing files. Files from a newer version are read as
+/// far as they are understood and keep their version when saved back.
+const FORMAT_VERSION: u64 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ColorRecord {
+    r: f64,
+    g: f64,
+    b: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct AnchorRecord {
+    /// Unix timestamp in seconds
+    time: i64,
+    price: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+struct StyleRecord {
+    color: ColorRecord,
+    width: f64,
+    line_style: String,
+    extend_left: bool,
+    extend_right: bool,
+    fill: bool,
+    fill_alpha: f64,
+    font_size: f64,
+    text_background: Option<ColorRecord>,
+    text_border: bool,
+}
+
+impl Default for StyleRecord {
+    fn default() -> Self {
+        style_record(&DrawingStyle::default())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LevelRecord {
+    value: f64,
+    color: ColorRecord,
+    visible: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DrawingRecord {
+    tool: String,
+    anchors: Vec<AnchorRecord>,
+    #[serde(default)]
+    style: StyleRecord,
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    levels: Vec<LevelRecord>,
+    #[serde(default)]
+    pinned: Option<(f64, f64)>,
+    #[serde(default)]
+    locked: bool,
+}
+
+fn color_record(color: Color) -> ColorRecord {
+    ColorRecord {
+        r: color.r,
+        g: color.g,
+        b: color.b,
+    }
+}
+
+fn color_from_record(record: &ColorRecord) -> Color {
+    Color::new(record.r, record.g, record.b)
+}
+
+fn style_record(style: &DrawingStyle) -> StyleRecord {
+    StyleRecord {
+        color: color_record(style.color),
+        width: style.width,
+        line_style: match style.line_style {
+            LineStyle::Solid => "Solid".to_string(),
+            LineStyle::Dotted => "Dotted".to_string(),
+            LineStyle::Dashed => "Dashed".to_string(),
+        },
+        extend_left: style.extend_left,
+        extend_right: style.extend_right,
+        fill: style.fill,
+        fill_alpha: style.fill_alpha,
+        font_size: style.font_size,
+        text_background: style.text_background.map(color_record),
+        text_border: style.text_border,
+    }
+}
+
+fn drawing_record(drawing: &Drawing) -> DrawingRecord {
+    DrawingRecord {
+        tool: drawing.tool.name().to_string(),
+        anchors: drawing
+            .anchors
+            .iter()
+            .map(|anchor| AnchorRecord {
+                time: anchor.time.unix_timestamp(),
+                price: anchor.price,
+            })
+            .collect(),
+        style: style_record(&drawing.style),
+        text: drawing.text.clone(),
+        levels: drawing
+            .levels
+            .iter()
+            .map(|level| LevelRecord {
+                value: level.value,
+                color: color_record(level.color),
+                visible: level.visible,
+            })
+            .collect(),
+        pinned: drawing.pinned,
+        locked: drawing.locked,
+    }
+}
+
+/// Converts a record back to a drawing, or `None` when it uses a tool this
+/// version does not have or has an unusable anchor.
+fn drawing_from_record(record: DrawingRecord) -> Option<Drawing> {
+    let tool = DrawingTool::from_name(&record.tool)?;
+    let anchors = record
+        .anchors
+        .iter()
+        .map(|anchor| {
+            OffsetDateTime::from_unix_timestamp(anchor.time)
+                .ok()
+                .map(|time| DrawingAnchor {
+                    time,
+                    price: anchor.price,
+                })
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let style = &record.style;
+    Some(Drawing {
+        tool,
+        anchors,
+        style: DrawingStyle {
+            color: color_from_record(&style.color),
+            width: style.width,
+            line_style: match style.line_style.as_str() {
+                "Dotted" => LineStyle::Dotted,
+                "Dashed" => LineStyle::Dashed,
+                _ => LineStyle::Solid,
+            },
+            extend_left: style.extend_left,
+            extend_right: style.extend_right,
+            fill: style.fill,
+            fill_alpha: style.fill_alpha,
+            font_size: style.font_size,
+            text_background: style.text_background.as_ref().map(color_from_record),
+            text_border: style.text_border,
+        },
+        text: record.text,
+        levels: record
+            .levels
+            .iter()
+            .map(|level| DrawingLevel {
+                value: level.value,
+                color: color_from_record(&level.color),
+                visible: level.visible,
+            })
+            .collect(),
+        pinned: record.pinned,
+        locked: record.locked,
+    })
+}
+
+/// Overlays `known` onto `original` so fields this version does not know
+/// about survive loading and saving a file. Fields are updated in place, so
+/// they keep their order.
+fn merge(original: Value, known: Value) -> Value {
+    match (original, known) {
+        (Value::Object(mut original), Value::Object(known)) => {
+            for (key, value) in known {
+                match original.get_mut(&key) {
+                    Some(old) => *old = merge(old.take(), value),
+                    None => {
+                        original.insert(key, value);
+                    }
+                }
+            }
+            Value::Object(original)
+        }
+        (Value::Array(original), Value::Array(known)) => Value::Array(
+            known
+                .into_iter()
+                .enumerate()
+                .map(|(idx, value)| match original.get(idx) {
+                    Some(old) => merge(old.clone(), value),
+                    None => value,
+                })
+                .collect(),
+        ),
+        (_, known) => known,
+    }
+}
+
+/// A drawings file split into what this version understands and what it
+/// has to carry along untouched.
+struct ParsedFile {
+    /// The whole document, for its unknown top-level fields
+    document: Map<String, Value>,
+    /// Drawings with the JSON they were read from
+    drawings: Vec<(Drawing, Value)>,
+    /// Records that could not be turned into drawings, with their index in
+    /// the file's `drawings` list
+    unknown: Vec<(usize, Value)>,
+}
+
+fn read_file(path: &Path) -> io::Result<ParsedFile> {
+    parse_file(&fs::read_to_string(path)?)
+}
+
+fn parse_file(text: &str) -> io::Result<ParsedFile> {
+    let mut document: Map<String, Value> = serde_json::from_str(text)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    let records = match document.remove("drawings") {
+        Some(Value::Array(records)) => records,
+        _ => Vec::new(),
+    };
+    let mut drawings = Vec::new();
+    let mut unknown = Vec::new();
+    for (index, record) in records.into_iter().enumerate() {
+        match serde_json::from_value(record.clone())
+            .ok()
+            .and_then(drawing_from_record)
+        {
+            Some(drawing) => drawings.push((drawing, record)),
+            None => unknown.push((index, record)),
+        }
+    }
+    Ok(ParsedFile {
+        document,
+        drawings,
+        unknown,
+    })
+}
+
+/// The saved form of `drawings`, in chart order, with each unknown record
+/// put back at the index it was read from.
+fn file_document(
+    mut document: Map<String, Value>,
+    symbol: &str,
+    mut drawings: Vec<Value>,
+    unknown: &[(usize, Value)],
+) -> Value {
+    for (index, record) in unknown {
+        drawings.insert((*index).min(drawings.len()), record.clone());
+    }
+    let version = document
+        .get("version")
+        .and_then(Value::as_u64)
+        .unwrap_or(0)
+        .max(FORMAT_VERSION);
+    document.insert("version".to_string(), Value::from(version));
+    document.insert("symbol".to_string(), Value::from(symbol));
+    document.insert("drawings".to_string(), Value::Array(drawings));
+    Value::Object(document)
+}
+
+struct DrawingFilesState {
+    /// Top-level fields of the loaded file, without its drawings
+    document: Map<String, Value>,
+    /// JSON each loaded drawing came from
+    originals: HashMap<DrawingId, Value>,
+    /// Records kept as read because this version cannot show them, with
+    /// their index in the saved `drawings` list
+    unknown: Vec<(usize, Value)>,
+    saved_revision: u64,
+    /// Whether a save is already scheduled
+    save_pending: bool,
+}
+
+/// Keeps the chart's drawings in a versioned JSON file per symbol, in a
+/// `drawings` folder beside the settings profiles folder. Anchors are
+/// timestamps, so one file serves every interval.
+#[derive(Clone)]
+pub struct DrawingFiles {
+    chart: ChartApi,
+    drawing_area: gtk::DrawingArea,
+    symbol: String,
+    profiles_folder: Rc<dyn Fn() -> PathBuf>,
+    state: Rc<RefCell<DrawingFilesState>>,
+}
+
+impl DrawingFiles {
+    pub fn new(
+        chart: &ChartApi,
+        drawing_area: &gtk::DrawingArea,
+        symbol: &str,
+        profiles_folder: impl Fn() -> PathBuf + 'static,
+    ) -> Self {
+        Self {
+            chart: chart.clone(),
+            drawing_area: drawing_area.clone(),
+            symbol: symbol.to_string(),
+            profiles_folder: Rc::new(profiles_folder),
+            state: Rc::new(RefCell::new(DrawingFilesState {
+                document: Map::new(),
+                originals: HashMap::new(),
+                unknown: Vec::new(),
+                saved_revision: chart.drawing_revision(),
+                save_pending: false,
+            })),
+        }
+    }
+
+    fn path(&self) -> PathBuf {
+        let folder = (self.profiles_folder)().with_file_name("drawings");
+        let name: String = self
+            .symbol
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        folder.join(format!("{name}.json"))
+    }
+
+    /// Replaces the chart's drawings with the symbol's saved ones, then saves
+    /// changes as they happen.
+    pub fn load_and_autosave(&self) {
+        match read_file(&self.path()) {
+            Ok(file) => {
+                self.chart.clear_drawings();
+                let mut state = self.state.borrow_mut();
+                state.document = file.document;
+                state.originals.clear();
+                state.unknown = file.unknown;
+                drop(state);
+                self.add_drawings(file.drawings);
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => eprintln!("Failed to load drawings: {err}"),
+        }
+        self.state.borrow_mut().saved_revision = self.chart.drawing_revision();
+
+        let files = self.clone();
+        self.chart
+            .subscribe_drawings_change(move || files.schedule_save());
+    }
+
+    /// Saves shortly after a change, so a drag or an import writes the file
+    /// once rather than per step.
+    fn schedule_save(&self) {
+        let mut state = self.state.borrow_mut();
+        if state.save_pending {
+            return;
+        }
+        state.save_pending = true;
+        let files = self.clone();
+        glib::timeout_add_local_once(Duration::from_millis(500), move || {
+            files.state.borrow_mut().save_pending = false;
+            files.save_if_changed();
+        });
+    }
+
+    fn add_drawings(&self, drawings: Vec<(Drawing, Value)>) {
+        // An import undoes in one step.
+        self.chart.begin_edit_group();
+        for (drawing, original) in drawings {
+            let id = self.chart.add_drawing(drawing);
+            self.state.borrow_mut().originals.insert(id, original);
+        }
+        self.chart.end_edit_group();
+        self.drawing_area.queue_draw();
+    }
+
+    fn save_if_changed(&self) {
+        let revision = self.chart.drawing_revision();
+        if self.state.borrow().saved_revision == revision {
+            return;
+        }
+        let path = self.path();
+        if let Some(folder) = path.parent() {
+            let _ = fs::create_dir_all(folder);
+        }
+        match self.write(&path) {
+            Ok(()) => self.state.borrow_mut().saved_revision = revision,
+            Err(err) => eprintln!("Failed to save drawings: {err}"),
+        }
+    }
+
+    fn write(&self, path: &Path) -> io::Result<()> {
+        let text = serde_json::to_string_pretty(&self.document())
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        fs::write(path, text)
+    }
+
+    fn document(&self) -> Value {
+        let state = self.state.borrow();
+        let drawings: Vec<Value> = self
+            .chart
+            .drawings()
+            .iter()
+            .filter_map(|(id, drawing)| {
+                let known = serde_json::to_value(drawing_record(drawing)).ok()?;
+                Some(match state.originals.get(id) {
+                    Some(original) => merge(original.clone(), known),
+                    None => known,
+                })
+            })
+            .collect();
+        file_document(
+            state.document.clone(),
+            &self.symbol,
+            drawings,
+            &state.unknown,
+        )
+    }
+
+    pub fn export_to(&self, path: &Path) {
+        if let Err(err) = self.write(path) {
+            eprintln!("Failed to export drawings: {err}");
+        }
+    }
+
+    /// Adds the drawings of another file, e.g. one sent by a colleague, to
+    /// the chart.
+    pub fn import_from(&self, path: &Path) {
+        match read_file(path) {
+            Ok(file) => {
+                // The file's records go after everything already saved.
+                let offset = self.chart.drawings().len() + self.state.borrow().unknown.len();
+                self.state.borrow_mut().unknown.extend(
+                    file.unknown
+                        .into_iter()
+                        .map(|(index, record)| (offset + index, record)),
+                );
+                self.add_drawings(file.drawings);
+            }
+            Err(err) => eprintln!("Failed to import drawings: {err}"),
+        }
+    }
+}
+
+fn file_dialog(
+    parent: &gtk::ApplicationWindow,
+    title: &str,
+    action: gtk::FileChooserAction,
+    accept: &str,
+) -> gtk::FileChooserNative {
+    let dialog = gtk::FileChooserNative::new(
+        Some(title),
+        Some(parent),
+        action,
+        Some(accept),
+        Some("Cancel"),
+    );
+    let filter = gtk::FileFilter::new();
+    filter.set_name(Some("Drawings (JSON)"));
+    filter.add_pattern("*.json");
+    dialog.add_filter(&filter);
+    dialog
+}
+
+pub fn build_drawings_menu(
+    files: &DrawingFiles,
+    parent: &gtk::ApplicationWindow,
+) -> gtk::MenuButton {
+    let import = gtk::Button::with_label("Import Drawings…");
+    let export = gtk::Button::with_label("Export Drawings…");
+    for button in [&import, &export] {
+        button.add_css_class("flat");
+    }
+    let content = gtk::Box::new(gtk::Orientation::Vertical, 2);
+    content.append(&import);
+    content.append(&export);
+    let popover = gtk::Popover::new();
+    popover.set_child(Some(&content));
+    let menu = gtk::MenuButton::new();
+    menu.set_label("Drawings");
+    menu.set_popover(Some(&popover));
+
+    import.connect_clicked({
+        let files = files.clone();
+        let parent = parent.clone();
+        let popover = popover.clone();
+        move |_| {
+            popover.popdown();
+            let dialog = file_dialog(
+                &parent,
+                "Import drawings",
+                gtk::FileChooserAction::Open,
+                "Import",
+            );
+            dialog.connect_response({
+                let files = files.clone();
+                move |dialog, response| {
+                    if response == gtk::ResponseType::Accept {
+                        if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                            files.import_from(&path);
+                        }
+                    }
+                    dialog.destroy();
+                }
+            });
+            dialog.show();
+        }
+    });
+    export.connect_clicked({
+        let files = files.clone();
+        let parent = parent.clone();
+        let popover = popover.clone();
+        move |_| {
+            popover.popdown();
+            let dialog = file_dialog(
+                &parent,
+                "Export drawings",
+                gtk::FileChooserAction::Save,
+                "Export",
+            );
+            dialog.set_current_name(&format!("{}.json", files.symbol));
+            dialog.connect_response({
+                let files = files.clone();
+                move |dialog, response| {
+                    if response == gtk::ResponseType::Accept {
+                        if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                            files.export_to(&path);
+                        }
+                    }
+                    dialog.destroy();
+                }
+            });
+            dialog.show();
+        }
+    });
+    menu
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn record(tool: DrawingTool, time: i64) -> Value {
+        let anchors = vec![
+            DrawingAnchor {
+                time: OffsetDateTime::from_unix_timestamp(time).unwrap(),
+                price: 100.0,
+            },
+            DrawingAnchor {
+                time: OffsetDateTime::from_unix_timestamp(time + 3600).unwrap(),
+                price: 110.0,
+            },
+        ];
+        serde_json::to_value(drawing_record(&Drawing::new(tool, anchors))).unwrap()
+    }
+
+    /// Loads `file` and saves it back, as the chart would with no edits.
+    fn load_and_save(file: &Value) -> Value {
+        let parsed = parse_file(&file.to_string()).unwrap();
+        let drawings = parsed
+            .drawings
+            .into_iter()
+            .map(|(drawing, original)| {
+                merge(
+                    original,
+                    serde_json::to_value(drawing_record(&drawing)).unwrap(),
+                )
+            })
+            .collect();
+        file_document(parsed.document, "BTCUSDT", drawings, &parsed.unknown)
+    }
+
+    #[test]
+    fn unknown_top_level_fields_survive_saving() {
+        let file = json!({
+            "version": 1,
+            "symbol": "BTCUSDT",
+            "workspace": { "theme": "dark", "tabs": [1, 2] },
+            "drawings": [record(DrawingTool::TrendLine, 0)],
+        });
+        assert_eq!(load_and_save(&file), file);
+    }
+
+    #[test]
+    fn unknown_drawing_fields_survive_saving() {
+        let mut drawing = record(DrawingTool::TrendLine, 0);
+        drawing["group"] = json!("swings");
+        drawing["style"]["glow"] = json!(2.5);
+        let file = json!({
+            "version": 1,
+            "symbol": "BTCUSDT",
+            "drawings": [drawing],
+        });
+        assert_eq!(load_and_save(&file), file);
+    }
+
+    #[test]
+    fn unknown_drawing_kinds_keep_their_position() {
+        let file = json!({
+            "version": 2,
+            "symbol": "BTCUSDT",
+            "drawings": [
+                { "tool": "Pitchfan", "anchors": [] },
+                record(DrawingTool::TrendLine, 0),
+                { "tool": "ElliottWave", "points": [1, 2, 3] },
+                record(DrawingTool::HorizontalLine, 7200),
+                { "tool": "Gann" },
+            ],
+        });
+        let parsed = parse_file(&file.to_string()).unwrap();
+        let indexes: Vec<usize> = parsed.unknown.iter().map(|(index, _)| *index).collect();
+        assert_eq!(indexes, [0, 2, 4]);
+        assert_eq!(load_and_save(&file), file);
+    }
+}
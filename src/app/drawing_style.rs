@@ -0,0 +1,302 @@
+use super::helpers::{color_from_rgba, line_style_from_combo, rgba_from_color};
+use crate::chart::{level_color, ChartApi, DrawingId, DrawingLevel, LineStyle};
+use relm4::gtk;
+use relm4::gtk::prelude::*;
+use relm4::RelmWidgetExt;
+use std::cell::Cell;
+use std::rc::Rc;
+
+fn style_row(label: &str, widget: &impl IsA<gtk::Widget>) -> gtk::Box {
+    let row = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    let label = gtk::Label::new(Some(label));
+    label.set_halign(gtk::Align::Start);
+    label.set_hexpand(true);
+    row.append(&label);
+    row.append(widget);
+    row
+}
+
+fn levels_text(levels: &[DrawingLevel]) -> String {
+    levels
+        .iter()
+        .filter(|level| level.visible)
+        .map(|level| level.value.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Shows exactly the levels listed in `text`. Known levels keep their colors
+/// and unlisted ones are hidden rather than dropped, so they can come back.
+fn apply_levels_text(levels: &mut Vec<DrawingLevel>, text: &str) {
+    let values: Vec<f64> = text
+        .split(',')
+        .filter_map(|part| part.trim().parse().ok())
+        .collect();
+    for level in levels.iter_mut() {
+        level.visible = values.contains(&level.value);
+    }
+    for value in values {
+        if !levels.iter().any(|level| level.value == value) {
+            levels.push(DrawingLevel {
+                value,
+                color: level_color(levels.len()),
+                visible: true,
+            });
+        }
+    }
+    levels.sort_by(|a, b| a.value.total_cmp(&b.value));
+}
+
+/// Popover for editing one drawing's style and label. Changes apply to the
+/// chart as they are made.
+#[derive(Clone)]
+pub struct DrawingStyleEditor {
+    chart: ChartApi,
+    drawing_area: gtk::DrawingArea,
+    popover: gtk::Popover,
+    color: gtk::ColorButton,
+    width: gtk::SpinButton,
+    line_style: gtk::ComboBoxText,
+    extend_left: gtk::CheckButton,
+    extend_right: gtk::CheckButton,
+    fill: gtk::CheckButton,
+    levels: gtk::Entry,
+    levels_row: gtk::Box,
+    font_size: gtk::SpinButton,
+    background: gtk::CheckButton,
+    background_color: gtk::ColorButton,
+    border: gtk::CheckButton,
+    text_box_rows: gtk::Box,
+    text: gtk::TextView,
+    fields: gtk::Box,
+    locked_hint: gtk::Label,
+    editing: Rc<Cell<Option<DrawingId>>>,
+    loading: Rc<Cell<bool>>,
+}
+
+impl DrawingStyleEditor {
+    pub fn new(chart: &ChartApi, drawing_area: &gtk::DrawingArea) -> Self {
+        let popover = gtk::Popover::new();
+        popover.set_parent(drawing_area);
+        popover.set_has_arrow(false);
+
+        let content = gtk::Box::new(gtk::Orientation::Vertical, 6);
+        content.set_margin_all(6);
+        let color = gtk::ColorButton::new();
+        let width = gtk::SpinButton::with_range(0.5, 8.0, 0.5);
+        width.set_digits(1);
+        let line_style = gtk::ComboBoxText::new();
+        for label in ["Solid", "Dotted", "Dashed"] {
+            line_style.append_text(label);
+        }
+        let extend_left = gtk::CheckButton::new();
+        let extend_right = gtk::CheckButton::new();
+        let fill = gtk::CheckButton::new();
+        let levels = gtk::Entry::new();
+        levels.set_tooltip_text(Some("Comma-separated levels, Enter to apply"));
+        let levels_row = style_row("Levels", &levels);
+        let font_size = gtk::SpinButton::with_range(6.0, 48.0, 1.0);
+        let background = gtk::CheckButton::new();
+        let background_color = gtk::ColorButton::new();
+        let background_box = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        background_box.append(&background);
+        background_box.append(&background_color);
+        let border = gtk::CheckButton::new();
+        let text_box_rows = gtk::Box::new(gtk::Orientation::Vertical, 6);
+        text_box_rows.append(&style_row("Background", &background_box));
+        text_box_rows.append(&style_row("Border", &border));
+        let text = gtk::TextView::new();
+        text.set_wrap_mode(gtk::WrapMode::WordChar);
+        text.set_accepts_tab(false);
+        let text_frame = gtk::Frame::new(None);
+        text_frame.set_child(Some(&text));
+        text_frame.set_size_request(200, 60);
+        let delete = gtk::Button::with_label("Delete");
+        delete.add_css_class("destructive-action");
+        let locked_hint = gtk::Label::new(Some("Locked. Unlock it to edit or delete."));
+        locked_hint.add_css_class("dim-label");
+
+        let fields = gtk::Box::new(gtk::Orientation::Vertical, 6);
+        fields.append(&style_row("Color", &color));
+        fields.append(&style_row("Width", &width));
+        fields.append(&style_row("Style", &line_style));
+        fields.append(&style_row("Extend left", &extend_left));
+        fields.append(&style_row("Extend right", &extend_right));
+        fields.append(&style_row("Fill", &fill));
+        fields.append(&levels_row);
+        fields.append(&style_row("Font size", &font_size));
+        fields.append(&text_box_rows);
+        fields.append(&gtk::Label::new(Some("Text")));
+        fields.append(&text_frame);
+        fields.append(&delete);
+        content.append(&locked_hint);
+        content.append(&fields);
+        popover.set_child(Some(&content));
+        // Everything changed while the popover is open undoes as one step.
+        popover.connect_closed({
+            let chart = chart.clone();
+            move |_| chart.end_edit_group()
+        });
+
+        let editor = Self {
+            chart: chart.clone(),
+            drawing_area: drawing_area.clone(),
+            popover,
+            color,
+            width,
+            line_style,
+            extend_left,
+            extend_right,
+            fill,
+            levels,
+            levels_row,
+            font_size,
+            background,
+            background_color,
+            border,
+            text_box_rows,
+            text,
+            fields,
+            locked_hint,
+            editing: Rc::new(Cell::new(None)),
+            loading: Rc::new(Cell::new(false)),
+        };
+
+        editor.color.connect_color_set({
+            let editor = editor.clone();
+            move |_| editor.apply()
+        });
+        for spin in [&editor.width, &editor.font_size] {
+            spin.connect_value_changed({
+                let editor = editor.clone();
+                move |_| editor.apply()
+            });
+        }
+        editor.background_color.connect_color_set({
+            let editor = editor.clone();
+            move |_| editor.apply()
+        });
+        editor.line_style.connect_changed({
+            let editor = editor.clone();
+            move |_| editor.apply()
+        });
+        for check in [
+            &editor.extend_left,
+            &editor.extend_right,
+            &editor.fill,
+            &editor.background,
+            &editor.border,
+        ] {
+            check.connect_toggled({
+                let editor = editor.clone();
+                move |_| editor.apply()
+            });
+        }
+        editor.levels.connect_activate({
+            let editor = editor.clone();
+            move |_| editor.apply()
+        });
+        editor.text.buffer().connect_changed({
+            let editor = editor.clone();
+            move |_| editor.apply()
+        });
+        delete.connect_clicked({
+            let editor = editor.clone();
+            move |_| {
+                if let Some(id) = editor.editing.take() {
+                    if editor.is_unlocked(id) {
+                        editor.chart.remove_drawing(id);
+                        editor.drawing_area.queue_draw();
+                    }
+                }
+                editor.popover.popdown();
+            }
+        });
+        editor
+    }
+
+    pub fn open(&self, id: DrawingId, x: f64, y: f64) {
+        let Some(drawing) = self.chart.drawing(id) else {
+            return;
+        };
+        self.loading.set(true);
+        self.color.set_rgba(&rgba_from_color(drawing.style.color));
+        self.width.set_value(drawing.style.width);
+        self.line_style
+            .set_active(Some(match drawing.style.line_style {
+                LineStyle::Solid => 0,
+                LineStyle::Dotted => 1,
+                LineStyle::Dashed => 2,
+            }));
+        self.extend_left.set_active(drawing.style.extend_left);
+        self.extend_right.set_active(drawing.style.extend_right);
+        self.fill.set_active(drawing.style.fill);
+        self.levels.set_text(&levels_text(&drawing.levels));
+        self.levels_row.set_visible(!drawing.levels.is_empty());
+        self.font_size.set_value(drawing.style.font_size);
+        self.background
+            .set_active(drawing.style.text_background.is_some());
+        if let Some(background) = drawing.style.text_background {
+            self.background_color.set_rgba(&rgba_from_color(background));
+        }
+        self.border.set_active(drawing.style.text_border);
+        self.text_box_rows.set_visible(drawing.tool.has_text_box());
+        self.text.buffer().set_text(&drawing.text);
+        // Locked drawings can be looked at but not restyled or deleted, the
+        // same as from their handles and the context menu.
+        self.fields.set_sensitive(!drawing.locked);
+        self.locked_hint.set_visible(drawing.locked);
+        self.loading.set(false);
+        self.editing.set(Some(id));
+
+        let rect = gtk::gdk::Rectangle::new(x as i32, y as i32, 1, 1);
+        self.popover.set_pointing_to(Some(&rect));
+        if !self.popover.is_visible() {
+            self.chart.begin_edit_group();
+        }
+        self.popover.popup();
+    }
+
+    fn apply(&self) {
+        if self.loading.get() {
+            return;
+        }
+        let Some(id) = self.editing.get() else {
+            return;
+        };
+        let Some(mut drawing) = self.chart.drawing(id) else {
+            return;
+        };
+        if drawing.locked {
+            return;
+        }
+        drawing.style.color = color_from_rgba(self.color.rgba());
+        drawing.style.width = self.width.value();
+        drawing.style.line_style = line_style_from_combo(&self.line_style);
+        drawing.style.extend_left = self.extend_left.is_active();
+        drawing.style.extend_right = self.extend_right.is_active();
+        drawing.style.fill = self.fill.is_active();
+        if !drawing.levels.is_empty() {
+            apply_levels_text(&mut drawing.levels, &self.levels.text());
+        }
+        drawing.style.font_size = self.font_size.value();
+        drawing.style.text_background = self
+            .background
+            .is_active()
+            .then(|| color_from_rgba(self.background_color.rgba()));
+        drawing.style.text_border = self.border.is_active();
+        let buffer = self.text.buffer();
+        drawing.text = buffer
+            .text(&buffer.start_iter(), &buffer.end_iter(), false)
+            .to_string();
+        self.chart.update_drawing(id, drawing);
+        self.drawing_area.queue_draw();
+    }
+
+    fn is_unlocked(&self, id: DrawingId) -> bool {
+        self.chart
+            .drawing(id)
+            .map(|drawing| !drawing.locked)
+            .unwrap_or(false)
+    }
+}
@@ -0,0 +1,505 @@
+use super::drawing_style::DrawingStyleEditor;
+use crate::chart::{
+    ChartApi, Drawing, DrawingAnchor, DrawingHit, DrawingHitTarget, DrawingId, DrawingTool,
+    PriceScale,
+};
+use relm4::gtk;
+use relm4::gtk::prelude::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A drawing being dragged by a handle or by its line.
+struct DrawingDrag {
+    hit: DrawingHit,
+    original: Drawing,
+    start: (f64, f64),
+}
+
+struct DrawingToolsState {
+    active: Option<DrawingTool>,
+    pending: Vec<DrawingAnchor>,
+    buttons: Vec<(DrawingTool, gtk::ToggleButton)>,
+    drag: Option<DrawingDrag>,
+    /// Where a measure drag started
+    measure_start: Option<(f64, f64)>,
+    /// Whether the next drag zooms to a box
+    zooming: bool,
+    /// Start and current corner of a zoom box drag
+    zoom_drag: Option<((f64, f64), (f64, f64))>,
+    zoom_button: Option<gtk::ToggleButton>,
+    zoom_out_button: Option<gtk::Button>,
+    /// Told when a zoom changes whether a price scale auto scales
+    on_auto_scale: Option<Rc<dyn Fn(PriceScale, bool)>>,
+}
+
+/// Places and edits drawings from pointer input. A tool is picked from the
+/// toolbar, then each click adds an anchor until the drawing is complete.
+/// Without a tool, pressing on a drawing selects it for dragging or deleting.
+#[derive(Clone)]
+pub struct DrawingTools {
+    chart: ChartApi,
+    drawing_area: gtk::DrawingArea,
+    state: Rc<RefCell<DrawingToolsState>>,
+    editor: DrawingStyleEditor,
+}
+
+impl DrawingTools {
+    pub fn new(chart: &ChartApi, drawing_area: &gtk::DrawingArea) -> Self {
+        Self {
+            chart: chart.clone(),
+            drawing_area: drawing_area.clone(),
+            state: Rc::new(RefCell::new(DrawingToolsState {
+                active: None,
+                pending: Vec::new(),
+                buttons: Vec::new(),
+                drag: None,
+                measure_start: None,
+                zooming: false,
+                zoom_drag: None,
+                zoom_button: None,
+                zoom_out_button: None,
+                on_auto_scale: None,
+            })),
+            editor: DrawingStyleEditor::new(chart, drawing_area),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.state.borrow().active.is_some()
+    }
+
+    fn active(&self) -> Option<DrawingTool> {
+        self.state.borrow().active
+    }
+
+    pub fn is_zooming(&self) -> bool {
+        self.state.borrow().zooming
+    }
+
+    /// Sets the handler told when zooming turns a price scale's auto scale
+    /// on or off, so toggles showing it can follow.
+    pub fn set_auto_scale_handler(&self, handler: Rc<dyn Fn(PriceScale, bool)>) {
+        self.state.borrow_mut().on_auto_scale = Some(handler);
+    }
+
+    /// Turns the zoom tool on or off. The zoom tool and drawing tools
+    /// exclude each other.
+    pub fn set_zooming(&self, zooming: bool) {
+        if zooming && self.is_active() {
+            self.select(None);
+        }
+        let button = {
+            let mut state = self.state.borrow_mut();
+            state.zooming = zooming;
+            state.zoom_drag = None;
+            state.zoom_button.clone()
+        };
+        if let Some(button) = button.filter(|button| button.is_active() != zooming) {
+            button.set_active(zooming);
+        }
+        self.chart.cancel_box_zoom();
+        self.drawing_area.queue_draw();
+    }
+
+    /// Returns to the view before the last box zoom.
+    pub fn zoom_out(&self) -> bool {
+        if !self.chart.zoom_out() {
+            // Replacing the data forgets saved views, so the button may be
+            // stale.
+            self.update_zoom_out_button();
+            return false;
+        }
+        self.notify_auto_scale(&[PriceScale::Left, PriceScale::Right]);
+        self.update_zoom_out_button();
+        self.drawing_area.queue_draw();
+        true
+    }
+
+    fn notify_auto_scale(&self, sides: &[PriceScale]) {
+        let handler = self.state.borrow().on_auto_scale.clone();
+        if let Some(handler) = handler {
+            for &side in sides {
+                handler(side, self.chart.price_scale_auto_scale(side));
+            }
+        }
+    }
+
+    fn update_zoom_out_button(&self) {
+        let button = self.state.borrow().zoom_out_button.clone();
+        if let Some(button) = button {
+            button.set_sensitive(self.chart.can_zoom_out());
+        }
+    }
+
+    /// Switches to `tool`, dropping any half-placed drawing.
+    pub fn select(&self, tool: Option<DrawingTool>) {
+        if tool.is_some() && self.is_zooming() {
+            self.set_zooming(false);
+        }
+        let buttons = {
+            let mut state = self.state.borrow_mut();
+            state.active = tool;
+            state.pending.clear();
+            state.buttons.clone()
+        };
+        for (button_tool, button) in buttons {
+            if Some(button_tool) != tool && button.is_active() {
+                button.set_active(false);
+            }
+        }
+        self.chart.set_drawing_preview(None);
+        self.drawing_area.queue_draw();
+    }
+
+    /// Drops the active tool and any measurement. Returns `false` when there
+    /// was nothing to cancel.
+    pub fn cancel(&self) -> bool {
+        if self.is_zooming() {
+            self.set_zooming(false);
+            return true;
+        }
+        let measuring = self.chart.measurement().is_some();
+        self.chart.set_measurement(None);
+        if self.is_active() {
+            self.select(None);
+            return true;
+        }
+        if measuring {
+            self.drawing_area.queue_draw();
+        }
+        measuring
+    }
+
+    /// Handles a primary click. Returns `true` when the click was used to
+    /// place an anchor and should not reach the chart.
+    pub fn click(&self, x: f64, y: f64, width: f64, height: f64) -> bool {
+        let Some(tool) = self.active() else {
+            return false;
+        };
+        // Measuring is done by dragging, see `begin_drag`.
+        if tool == DrawingTool::Measure {
+            return true;
+        }
+        let Some(anchor) = self.chart.drawing_anchor_at(x, y, width, height) else {
+            return true;
+        };
+        let anchors = {
+            let mut state = self.state.borrow_mut();
+            state.pending.push(anchor);
+            if state.pending.len() < tool.anchor_count() {
+                return true;
+            }
+            std::mem::take(&mut state.pending)
+        };
+        let mut drawing = Drawing::new(tool, anchors);
+        if tool == DrawingTool::Note {
+            drawing.pinned = self.chart.pinned_position_at(x, y, width, height);
+        }
+        let id = self.chart.add_drawing(drawing);
+        self.select(None);
+        self.chart.select_drawing(Some(id));
+        true
+    }
+
+    /// Starts dragging the drawing under the pointer, selecting it. Pressing
+    /// on empty space clears the selection. With `measure` (Shift held) or
+    /// the measure tool active, the drag measures instead. Returns `true`
+    /// when the drag should not pan the chart.
+    pub fn begin_drag(&self, x: f64, y: f64, width: f64, height: f64, measure: bool) -> bool {
+        if self.is_zooming() {
+            self.state.borrow_mut().zoom_drag = Some(((x, y), (x, y)));
+            return true;
+        }
+        // A shown measurement is pinned by pressing on it and dismissed by
+        // pressing anywhere else.
+        if self.chart.measurement().is_some() {
+            if self.chart.measurement_contains(x, y, width, height) {
+                let id = self.chart.pin_measurement();
+                self.chart.select_drawing(id);
+                self.drawing_area.queue_draw();
+                return true;
+            }
+            self.chart.set_measurement(None);
+            self.drawing_area.queue_draw();
+        }
+        let measuring = measure || self.active() == Some(DrawingTool::Measure);
+        if measuring && self.chart.drawing_anchor_at(x, y, width, height).is_some() {
+            self.state.borrow_mut().measure_start = Some((x, y));
+            return true;
+        }
+        if self.is_active() {
+            return false;
+        }
+        let hit = self.chart.drawing_hit_test(x, y, width, height);
+        self.chart.select_drawing(hit.map(|hit| hit.id));
+        self.drawing_area.queue_draw();
+        let drag = hit.and_then(|hit| {
+            self.chart
+                .drawing(hit.id)
+                .filter(|original| !original.locked)
+                .map(|original| DrawingDrag {
+                    hit,
+                    original,
+                    start: (x, y),
+                })
+        });
+        let grabbed = drag.is_some();
+        if grabbed {
+            // The whole drag undoes as one step.
+            self.chart.begin_edit_group();
+        }
+        self.state.borrow_mut().drag = drag;
+        grabbed
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        let state = self.state.borrow();
+        state.drag.is_some() || state.measure_start.is_some() || state.zoom_drag.is_some()
+    }
+
+    pub fn drag_to(&self, offset_x: f64, offset_y: f64, width: f64, height: f64) {
+        if let Some(((x, y), corner)) = self.state.borrow_mut().zoom_drag.as_mut() {
+            *corner = (*x + offset_x, *y + offset_y);
+            self.chart.preview_box_zoom(*x, *y, corner.0, corner.1);
+            return;
+        }
+        let state = self.state.borrow();
+        if let Some((x, y)) = state.measure_start {
+            let anchors = [(x, y), (x + offset_x, y + offset_y)]
+                .into_iter()
+                .map(|(x, y)| self.chart.drawing_anchor_at(x, y, width, height))
+                .collect::<Option<Vec<_>>>();
+            if let Some(anchors) = anchors {
+                self.chart
+                    .set_measurement(Some(Drawing::new(DrawingTool::Measure, anchors)));
+            }
+            return;
+        }
+        let Some(drag) = state.drag.as_ref() else {
+            return;
+        };
+        let moved = match drag.hit.target {
+            DrawingHitTarget::Anchor(index) => self
+                .chart
+                .drawing_anchor_at(
+                    drag.start.0 + offset_x,
+                    drag.start.1 + offset_y,
+                    width,
+                    height,
+                )
+                .map(|anchor| {
+                    let mut drawing = drag.original.clone();
+                    drawing.anchors[index] = anchor;
+                    drawing
+                }),
+            DrawingHitTarget::Body => {
+                self.chart
+                    .translate_drawing(&drag.original, offset_x, offset_y, width, height)
+            }
+        };
+        if let Some(drawing) = moved {
+            self.chart.update_drawing(drag.hit.id, drawing);
+        }
+    }
+
+    pub fn end_drag(&self) {
+        let (dragged, measured, zoom) = {
+            let mut state = self.state.borrow_mut();
+            (
+                state.drag.take().is_some(),
+                state.measure_start.take().is_some(),
+                state.zoom_drag.take(),
+            )
+        };
+        if let Some(((x0, y0), (x1, y1))) = zoom {
+            // A box too small to zoom keeps the tool for another try.
+            if let Some(sides) = self.chart.box_zoom(x0, y0, x1, y1) {
+                self.notify_auto_scale(&sides);
+                self.set_zooming(false);
+                self.update_zoom_out_button();
+            }
+            self.drawing_area.queue_draw();
+        }
+        if dragged {
+            self.chart.end_edit_group();
+        }
+        if measured && self.active() == Some(DrawingTool::Measure) {
+            self.select(None);
+        }
+    }
+
+    /// Removes the selected drawing, if any and not locked.
+    pub fn delete_selected(&self) -> bool {
+        let Some(id) = self.chart.selected_drawing() else {
+            return false;
+        };
+        if self.chart.drawing(id).is_some_and(|drawing| drawing.locked) {
+            return false;
+        }
+        self.chart.remove_drawing(id);
+        self.drawing_area.queue_draw();
+        true
+    }
+
+    /// Opens the style popover for the drawing under the pointer.
+    pub fn edit_at(&self, x: f64, y: f64, width: f64, height: f64) -> bool {
+        let Some(hit) = self.chart.drawing_hit_test(x, y, width, height) else {
+            return false;
+        };
+        self.edit(hit.id, x, y);
+        true
+    }
+
+    /// Opens the style popover for drawing `id`, pointing at `(x, y)`.
+    pub fn edit(&self, id: DrawingId, x: f64, y: f64) {
+        self.chart.select_drawing(Some(id));
+        self.editor.open(id, x, y);
+        self.drawing_area.queue_draw();
+    }
+
+    /// Shows the drawing being placed as if the next anchor were at the
+    /// pointer.
+    pub fn pointer_moved(&self, x: f64, y: f64, width: f64, height: f64) {
+        let Some(tool) = self.active() else {
+            return;
+        };
+        let preview = self
+            .chart
+            .drawing_anchor_at(x, y, width, height)
+            .map(|anchor| {
+                let mut anchors = self.state.borrow().pending.clone();
+                anchors.push(anchor);
+                Drawing::new(tool, anchors)
+            });
+        self.chart.set_drawing_preview(preview);
+    }
+}
+
+fn tool_glyph(tool: DrawingTool) -> &'static str {
+    match tool {
+        DrawingTool::TrendLine => "╱",
+        DrawingTool::ExtendedLine => "⤢",
+        DrawingTool::Ray => "↗",
+        DrawingTool::HorizontalRay => "→",
+        DrawingTool::HorizontalLine => "─",
+        DrawingTool::VerticalLine => "│",
+        DrawingTool::FibRetracement => "Fib",
+        DrawingTool::FibExtension => "Ext",
+        DrawingTool::FibFan => "Fan",
+        DrawingTool::FibArcs => "Arc",
+        DrawingTool::FibTimeZones => "TZ",
+        DrawingTool::ParallelChannel => "Chn",
+        DrawingTool::RegressionChannel => "Reg",
+        DrawingTool::Pitchfork => "Pf",
+        DrawingTool::SchiffPitchfork => "PfS",
+        DrawingTool::ModifiedSchiffPitchfork => "PfM",
+        DrawingTool::Rectangle => "▭",
+        DrawingTool::Ellipse => "◯",
+        DrawingTool::Triangle => "△",
+        DrawingTool::Xabcd => "XA",
+        DrawingTool::HeadAndShoulders => "H&S",
+        DrawingTool::Text => "T",
+        DrawingTool::Callout => "Cal",
+        DrawingTool::Note => "Note",
+        DrawingTool::Arrow => "➔",
+        DrawingTool::Measure => "⟷",
+        DrawingTool::RiskReward => "R:R",
+    }
+}
+
+/// Tool buttons two to a row, with each category starting a new row.
+pub fn build_drawing_toolbar(tools: &DrawingTools) -> gtk::Grid {
+    let toolbar = gtk::Grid::new();
+    toolbar.set_row_spacing(2);
+    toolbar.set_column_spacing(2);
+    toolbar.set_halign(gtk::Align::Start);
+    toolbar.set_valign(gtk::Align::Center);
+    toolbar.set_margin_start(4);
+
+    let (mut row, mut column) = (-1, 2);
+    let mut category = "";
+    for &tool in DrawingTool::all() {
+        if column == 2 || tool.category() != category {
+            row += 1;
+            column = 0;
+            category = tool.category();
+        }
+        let button = gtk::ToggleButton::with_label(tool_glyph(tool));
+        let tooltip = match tool {
+            DrawingTool::Measure => "Measure (Shift+drag); click the result to keep it",
+            DrawingTool::RiskReward => {
+                "Risk/Reward: click the entry, then the target, then the stop"
+            }
+            _ => tool.label(),
+        };
+        button.set_tooltip_text(Some(tooltip));
+        button.set_focus_on_click(false);
+        button.add_css_class("flat");
+        button.connect_toggled({
+            let tools = tools.clone();
+            move |button| {
+                if button.is_active() {
+                    tools.select(Some(tool));
+                } else if tools.active() == Some(tool) {
+                    tools.select(None);
+                }
+            }
+        });
+        tools
+            .state
+            .borrow_mut()
+            .buttons
+            .push((tool, button.clone()));
+        toolbar.attach(&button, column, row, 1, 1);
+        column += 1;
+    }
+
+    toolbar.attach(
+        &gtk::Separator::new(gtk::Orientation::Horizontal),
+        0,
+        row + 1,
+        2,
+        1,
+    );
+    let magnet = gtk::ToggleButton::with_label("⊙");
+    magnet.set_tooltip_text(Some("Magnet: snap anchors to OHLC"));
+    magnet.set_focus_on_click(false);
+    magnet.add_css_class("flat");
+    magnet.connect_toggled({
+        let chart = tools.chart.clone();
+        move |button| chart.set_drawing_magnet(button.is_active())
+    });
+    toolbar.attach(&magnet, 0, row + 2, 1, 1);
+
+    let zoom = gtk::ToggleButton::with_label("⊕");
+    zoom.set_tooltip_text(Some("Zoom: drag a box to zoom in"));
+    zoom.set_focus_on_click(false);
+    zoom.add_css_class("flat");
+    zoom.connect_toggled({
+        let tools = tools.clone();
+        move |button| {
+            if button.is_active() != tools.is_zooming() {
+                tools.set_zooming(button.is_active());
+            }
+        }
+    });
+    toolbar.attach(&zoom, 0, row + 3, 1, 1);
+    let zoom_out = gtk::Button::with_label("⊖");
+    zoom_out.set_tooltip_text(Some("Zoom out to the previous view"));
+    zoom_out.set_focus_on_click(false);
+    zoom_out.add_css_class("flat");
+    zoom_out.set_sensitive(false);
+    zoom_out.connect_clicked({
+        let tools = tools.clone();
+        move |_| {
+            tools.zoom_out();
+        }
+    });
+    toolbar.attach(&zoom_out, 1, row + 3, 1, 1);
+    {
+        let mut state = tools.state.borrow_mut();
+        state.zoom_button = Some(zoom);
+        state.zoom_out_button = Some(zoom_out);
+    }
+    toolbar
+}
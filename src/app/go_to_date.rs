@@ -0,0 +1,86 @@
+use crate::chart::ChartApi;
+use relm4::gtk;
+use relm4::gtk::prelude::*;
+use time::{Date, Month, OffsetDateTime, PrimitiveDateTime, Time};
+
+/// Parses `YYYY-MM-DD` with an optional `HH:MM`, in UTC like the time axis.
+pub(super) fn parse_date(text: &str) -> Option<OffsetDateTime> {
+    let mut parts = text.split_whitespace();
+    let date = parts.next()?;
+    let time = parts.next();
+    if parts.next().is_some() {
+        return None;
+    }
+    let mut fields = date.split('-').map(|field| field.parse::<i32>().ok());
+    let (year, month, day) = (fields.next()??, fields.next()??, fields.next()??);
+    if fields.next().is_some() {
+        return None;
+    }
+    let month = Month::try_from(u8::try_from(month).ok()?).ok()?;
+    let date = Date::from_calendar_date(year, month, u8::try_from(day).ok()?).ok()?;
+    let (hour, minute) = match time {
+        Some(time) => {
+            let (hour, minute) = time.split_once(':')?;
+            (hour.parse().ok()?, minute.parse().ok()?)
+        }
+        None => (0, 0),
+    };
+    let time = Time::from_hms(hour, minute, 0).ok()?;
+    Some(PrimitiveDateTime::new(date, time).assume_utc())
+}
+
+/// Header menu that scrolls the chart to a typed date, or back to the
+/// latest bar.
+pub fn build_go_to_date_menu(chart: &ChartApi, drawing_area: &gtk::DrawingArea) -> gtk::MenuButton {
+    let entry = gtk::Entry::new();
+    entry.set_placeholder_text(Some("YYYY-MM-DD HH:MM"));
+    entry.set_width_chars(18);
+    let go = gtk::Button::with_label("Go");
+    let latest = gtk::Button::with_label("Latest Bar");
+    latest.add_css_class("flat");
+
+    let row = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+    row.append(&entry);
+    row.append(&go);
+    let content = gtk::Box::new(gtk::Orientation::Vertical, 6);
+    content.append(&row);
+    content.append(&latest);
+    let popover = gtk::Popover::new();
+    popover.set_child(Some(&content));
+    let menu = gtk::MenuButton::new();
+    menu.set_label("Go to");
+    menu.set_tooltip_text(Some("Go to date"));
+    menu.set_popover(Some(&popover));
+
+    let go_to = {
+        let chart = chart.clone();
+        let drawing_area = drawing_area.clone();
+        let entry = entry.clone();
+        let popover = popover.clone();
+        move || {
+            let Some(date) = parse_date(entry.text().as_str()) else {
+                entry.add_css_class("error");
+                return;
+            };
+            entry.remove_css_class("error");
+            popover.popdown();
+            chart.scroll_to_time(date.unix_timestamp() as f64, true);
+            drawing_area.queue_draw();
+        }
+    };
+    entry.connect_activate({
+        let go_to = go_to.clone();
+        move |_| go_to()
+    });
+    go.connect_clicked(move |_| go_to());
+    latest.connect_clicked({
+        let chart = chart.clone();
+        let drawing_area = drawing_area.clone();
+        move |_| {
+            popover.popdown();
+            chart.scroll_to_real_time();
+            drawing_area.queue_draw();
+        }
+    });
+    menu
+}
@@ -0,0 +1,353 @@
+use crate::chart::{
+    Candle, ChartApi, Color, HistogramPoint, LinePoint, LineSeriesApi, PanelId, PriceScale,
+};
+use crate::indicators::graph::{
+    GraphError, IndicatorGraph, IndicatorSource, NodeId, PriceField, Study,
+};
+
+use super::indicator_modal::IndicatorKind;
+
+const PALETTE: [Color; 6] = [
+    Color::new(0.96, 0.65, 0.14),
+    Color::new(0.67, 0.45, 0.96),
+    Color::new(0.2, 0.78, 0.82),
+    Color::new(0.95, 0.42, 0.62),
+    Color::new(0.55, 0.8, 0.3),
+    Color::new(0.98, 0.85, 0.35),
+];
+
+struct Placement {
+    node: NodeId,
+    kind: IndicatorKind,
+    origin: PanelId,
+    panel: PanelId,
+    color: Color,
+    lines: Vec<LineSeriesApi>,
+}
+
+/// An indicator taken off the chain, with what is needed to add it back.
+#[derive(Clone)]
+pub struct RemovedIndicator {
+    pub origin: PanelId,
+    pub kind: IndicatorKind,
+    node: NodeId,
+    study: Study,
+    source: IndicatorSource,
+}
+
+pub struct IndicatorChain {
+    chart: ChartApi,
+    graph: IndicatorGraph,
+    placements: Vec<Placement>,
+    spare_lines: Vec<LineSeriesApi>,
+    next_color: usize,
+}
+
+impl IndicatorChain {
+    pub fn new(chart: ChartApi) -> Self {
+        Self {
+            chart,
+            graph: IndicatorGraph::new(),
+            placements: Vec::new(),
+            spare_lines: Vec::new(),
+            next_color: 0,
+        }
+    }
+
+    pub fn study_for(kind: IndicatorKind) -> Option<Study> {
+        match kind {
+            IndicatorKind::Rsi => Some(Study::Rsi { period: 14 }),
+            IndicatorKind::Macd => Some(Study::Macd {
+                fast: 12,
+                slow: 26,
+                signal: 9,
+            }),
+            IndicatorKind::Obv => Some(Study::Obv),
+            IndicatorKind::Ema => Some(Study::Ema { period: 9 }),
+            IndicatorKind::Sma => Some(Study::Sma { period: 20 }),
+            IndicatorKind::Bollinger => Some(Study::Bollinger {
+                period: 20,
+                mult: 2.0,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn sources(&self) -> Vec<(String, IndicatorSource)> {
+        let mut sources: Vec<(String, IndicatorSource)> = PriceField::all()
+            .iter()
+            .map(|field| (field.label().to_string(), IndicatorSource::Price(*field)))
+            .collect();
+        for placement in &self.placements {
+            let Some(node) = self.graph.node(placement.node) else {
+                continue;
+            };
+            for output in 0..node.study.output_names().len() {
+                let source = IndicatorSource::Node {
+                    id: placement.node,
+                    output,
+                };
+                let label = format!(
+                    "{} on panel {}",
+                    self.graph.describe_source(source),
+                    placement.panel.0
+                );
+                sources.push((label, source));
+            }
+        }
+        sources
+    }
+
+    pub fn add(
+        &mut self,
+        kind: IndicatorKind,
+        study: Study,
+        origin: PanelId,
+        source: IndicatorSource,
+    ) -> Result<NodeId, GraphError> {
+        let node = self.graph.add(study, source)?;
+        let main_panel = self.chart.main_panel_id();
+        let panel = match source {
+            // Oscillators are not in price units, so they always get the
+            // indicator panel, whatever they are computed from.
+            _ if kind.is_oscillator() => {
+                if !self.chart.has_rsi_panel() {
+                    self.chart.set_rsi_panel("RSI".to_string(), Vec::new());
+                }
+                self.chart.rsi_panel_id().unwrap_or(main_panel)
+            }
+            IndicatorSource::Node { id, .. } => self
+                .placements
+                .iter()
+                .find(|placement| placement.node == id)
+                .map(|placement| placement.panel)
+                .unwrap_or(main_panel),
+            // Only the main panel and the RSI panel can hold chain plots, so
+            // studies opened from any other panel go to the main one.
+            IndicatorSource::Price(_) if self.chart.rsi_panel_id() == Some(origin) => origin,
+            IndicatorSource::Price(_) => main_panel,
+        };
+        let color = PALETTE[self.next_color % PALETTE.len()];
+        self.next_color += 1;
+        self.placements.push(Placement {
+            node,
+            kind,
+            origin,
+            panel,
+            color,
+            lines: Vec::new(),
+        });
+        Ok(node)
+    }
+
+    pub fn remove(&mut self, origin: PanelId, kind: IndicatorKind) -> Vec<RemovedIndicator> {
+        let node = self
+            .placements
+            .iter()
+            .rev()
+            .find(|placement| placement.origin == origin && placement.kind == kind)
+            .map(|placement| placement.node);
+        match node {
+            Some(node) => self.remove_nodes(&[node]),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn remove_panel(&mut self, panel: PanelId) -> Vec<RemovedIndicator> {
+        let nodes: Vec<NodeId> = self
+            .placements
+            .iter()
+            .filter(|placement| placement.panel == panel)
+            .map(|placement| placement.node)
+            .collect();
+        self.remove_nodes(&nodes)
+    }
+
+    /// Whether an indicator on the chain is drawn as series `series`.
+    pub fn owns_series(&self, series: usize) -> bool {
+        self.node_for_series(series).is_some()
+    }
+
+    /// Removes the indicator drawn as series `series`, with the indicators
+    /// fed by it. Empty when no indicator draws that series.
+    pub fn remove_series(&mut self, series: usize) -> Vec<RemovedIndicator> {
+        match self.node_for_series(series) {
+            Some(node) => self.remove_node(node),
+            None => Vec::new(),
+        }
+    }
+
+    /// Removes the indicator computed by `node`, with the indicators fed by
+    /// it. Empty when `node` is not on the chain.
+    pub fn remove_node(&mut self, node: NodeId) -> Vec<RemovedIndicator> {
+        self.remove_nodes(&[node])
+    }
+
+    pub fn refresh(&mut self, candles: &[Candle], volumes: &[HistogramPoint]) {
+        if let Err(err) = self.graph.recompute(candles, volumes) {
+            eprintln!("Indicator chain not recomputed: {err}");
+            return;
+        }
+
+        let rsi_panel = self.chart.rsi_panel_id();
+        let mut panel_base_set = false;
+        let mut panel_lines: Vec<(Vec<LinePoint>, Color)> = Vec::new();
+        let mut panel_histograms: Vec<(Vec<HistogramPoint>, Color)> = Vec::new();
+        for placement in &mut self.placements {
+            let Some(node) = self.graph.node(placement.node) else {
+                continue;
+            };
+            if Some(placement.panel) == rsi_panel {
+                let is_base = !panel_base_set
+                    && placement.kind == IndicatorKind::Rsi
+                    && matches!(node.source, IndicatorSource::Price(_));
+                if is_base {
+                    panel_base_set = true;
+                    if let Some(output) = node.outputs.first() {
+                        self.chart.set_rsi_panel_data(output.clone());
+                    }
+                } else {
+                    for (idx, output) in node.outputs.iter().enumerate() {
+                        if node.study.is_histogram(idx) {
+                            let bars = output
+                                .iter()
+                                .map(|point| HistogramPoint {
+                                    time: point.time,
+                                    value: point.value,
+                                    color: None,
+                                })
+                                .collect();
+                            panel_histograms.push((bars, placement.color));
+                        } else {
+                            panel_lines.push((output.clone(), placement.color));
+                        }
+                    }
+                }
+                continue;
+            }
+
+            while placement.lines.len() < node.outputs.len() {
+                let series = self
+                    .spare_lines
+                    .pop()
+                    .unwrap_or_else(|| self.chart.add_line_series());
+                // A spare line may have been moved to the indicator panel.
+                self.chart
+                    .move_series_to_panel(series.series_id(), self.chart.main_panel_id());
+                series.set_price_scale(PriceScale::Right);
+                series.set_color(placement.color);
+                series.set_line_width(1.5);
+                series.set_price_line_visible(false);
+                series.set_last_value_visible(false);
+                placement.lines.push(series);
+            }
+            for (series, output) in placement.lines.iter().zip(&node.outputs) {
+                series.set_data(output.clone());
+            }
+        }
+        if rsi_panel.is_some() {
+            self.chart
+                .set_rsi_panel_plots("chain", panel_lines, panel_histograms);
+        }
+    }
+
+    /// Adds back indicators returned by `remove` or `remove_panel`. They are
+    /// re-added in their original order, so indicators fed by another
+    /// removed indicator are re-pointed at its new node.
+    ///
+    /// # Returns
+    ///
+    /// The indicators that were added back, with their new nodes.
+    pub fn restore(
+        &mut self,
+        removed: &[RemovedIndicator],
+    ) -> Vec<(PanelId, IndicatorKind, NodeId)> {
+        let mut nodes: Vec<(NodeId, NodeId)> = Vec::new();
+        let mut restored = Vec::new();
+        for indicator in removed {
+            let source = match indicator.source {
+                IndicatorSource::Node { id, output } => IndicatorSource::Node {
+                    id: nodes
+                        .iter()
+                        .find(|(old, _)| *old == id)
+                        .map(|(_, new)| *new)
+                        .unwrap_or(id),
+                    output,
+                },
+                source => source,
+            };
+            match self.add(indicator.kind, indicator.study, indicator.origin, source) {
+                Ok(node) => {
+                    nodes.push((indicator.node, node));
+                    restored.push((indicator.origin, indicator.kind, node));
+                }
+                Err(err) => eprintln!("Indicator {:?} not restored: {err}", indicator.kind),
+            }
+        }
+        restored
+    }
+
+    fn node_for_series(&self, series: usize) -> Option<NodeId> {
+        self.placements
+            .iter()
+            .find(|placement| {
+                placement
+                    .lines
+                    .iter()
+                    .any(|line| line.series_id() == series)
+            })
+            .map(|placement| placement.node)
+    }
+
+    fn remove_nodes(&mut self, nodes: &[NodeId]) -> Vec<RemovedIndicator> {
+        let studies: Vec<(NodeId, Study, IndicatorSource)> = self
+            .placements
+            .iter()
+            .filter_map(|placement| self.graph.node(placement.node))
+            .map(|node| (node.id, node.study, node.source))
+            .collect();
+        let mut removed_ids = Vec::new();
+        for node in nodes {
+            removed_ids.extend(self.graph.remove(*node));
+        }
+        let mut removed = Vec::new();
+        let mut kept = Vec::new();
+        for placement in self.placements.drain(..) {
+            if removed_ids.contains(&placement.node) {
+                for series in placement.lines {
+                    series.set_data(Vec::new());
+                    self.spare_lines.push(series);
+                }
+                if let Some((_, study, source)) =
+                    studies.iter().find(|(node, _, _)| *node == placement.node)
+                {
+                    removed.push(RemovedIndicator {
+                        origin: placement.origin,
+                        kind: placement.kind,
+                        node: placement.node,
+                        study: *study,
+                        source: *source,
+                    });
+                }
+            } else {
+                kept.push(placement);
+            }
+        }
+        self.placements = kept;
+        if let Some(rsi_panel) = self.chart.rsi_panel_id() {
+            if !self
+                .placements
+                .iter()
+                .any(|placement| placement.panel == rsi_panel)
+            {
+                self.chart.set_rsi_panel_data(Vec::new());
+                self.chart
+                    .set_rsi_panel_plots("chain", Vec::new(), Vec::new());
+                if !self.chart.rsi_panel_has_plots() {
+                    self.chart.clear_rsi_panel();
+                }
+            }
+        }
+        removed
+    }
+}
@@ -1,4 +1,5 @@
 use crate::chart::PanelId;
+use crate::indicators::graph::{IndicatorSource, PriceField};
 use relm4::gtk;
 use relm4::gtk::prelude::*;
 use relm4::RelmWidgetExt;
@@ -6,15 +7,33 @@ use std::cell::Cell;
 use std::collections::HashSet;
 use std::rc::Rc;
 
+use super::overlays::{PIVOT_VARIANTS, VOLUME_PROFILE_VARIANTS, ZIGZAG_VARIANTS};
+use super::script_indicators::ScriptSummary;
+
+const SCRIPT_TEMPLATE: &str = "study \"EMA cross\"
+panel main
+fast = ema(close, 9)
+slow = ema(close, 21)
+plot line \"Fast\" = fast color \"#f5a623\"
+plot line \"Slow\" = slow color \"#7b61ff\"
+plot markers \"Cross\" = crossover(fast, slow)
+";
+
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum IndicatorKind {
     Rsi,
     Macd,
+    Obv,
     Stochastic,
     StochRsi,
     Bollinger,
     Ema,
     Sma,
+    Ichimoku,
+    Pivots,
+    ZigZag,
+    AutoFib,
+    VolumeProfile,
 }
 
 impl IndicatorKind {
@@ -22,11 +41,17 @@ impl IndicatorKind {
         match self {
             IndicatorKind::Rsi => "RSI",
             IndicatorKind::Macd => "MACD",
+            IndicatorKind::Obv => "On-Balance Volume",
             IndicatorKind::Stochastic => "Stochastic",
             IndicatorKind::StochRsi => "Stochastic RSI",
             IndicatorKind::Bollinger => "Bollinger Bands",
             IndicatorKind::Ema => "EMA",
             IndicatorKind::Sma => "SMA",
+            IndicatorKind::Ichimoku => "Ichimoku Cloud",
+            IndicatorKind::Pivots => "Pivot Points",
+            IndicatorKind::ZigZag => "ZigZag",
+            IndicatorKind::AutoFib => "Auto Fib Retracement",
+            IndicatorKind::VolumeProfile => "Volume Profile",
         }
     }
 
@@ -34,19 +59,60 @@ impl IndicatorKind {
         &[
             IndicatorKind::Rsi,
             IndicatorKind::Macd,
+            IndicatorKind::Obv,
             IndicatorKind::Stochastic,
             IndicatorKind::StochRsi,
             IndicatorKind::Bollinger,
             IndicatorKind::Ema,
             IndicatorKind::Sma,
+            IndicatorKind::Ichimoku,
+            IndicatorKind::Pivots,
+            IndicatorKind::ZigZag,
+            IndicatorKind::AutoFib,
+            IndicatorKind::VolumeProfile,
         ]
     }
+
+    pub fn accepts_source(self) -> bool {
+        matches!(
+            self,
+            IndicatorKind::Rsi
+                | IndicatorKind::Macd
+                | IndicatorKind::Obv
+                | IndicatorKind::Bollinger
+                | IndicatorKind::Ema
+                | IndicatorKind::Sma
+        )
+    }
+
+    /// Whether the indicator is an oscillator drawn in the indicator panel
+    /// rather than over the price.
+    pub fn is_oscillator(self) -> bool {
+        matches!(
+            self,
+            IndicatorKind::Rsi | IndicatorKind::Macd | IndicatorKind::Obv
+        )
+    }
+
+    /// Preset labels offered in the options picker, indexed by the variant
+    /// passed to the toggle callback.
+    pub fn variants(self) -> Vec<&'static str> {
+        match self {
+            IndicatorKind::Pivots => PIVOT_VARIANTS.iter().map(|variant| variant.0).collect(),
+            IndicatorKind::ZigZag | IndicatorKind::AutoFib => {
+                ZIGZAG_VARIANTS.iter().map(|variant| variant.0).collect()
+            }
+            IndicatorKind::VolumeProfile => VOLUME_PROFILE_VARIANTS.to_vec(),
+            _ => Vec::new(),
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct IndicatorModalUi {
     pub window: gtk::Window,
     pub list: gtk::ListBox,
+    pub scripts: gtk::Box,
     pub current_panel: Rc<Cell<PanelId>>,
 }
 
@@ -54,7 +120,7 @@ pub fn build_indicator_modal(parent: &gtk::ApplicationWindow) -> IndicatorModalU
     let window = gtk::Window::new();
     window.set_title(Some("Indicators"));
     window.set_default_width(320);
-    window.set_default_height(360);
+    window.set_default_height(620);
     window.set_modal(true);
     window.set_transient_for(Some(parent));
 
@@ -65,12 +131,20 @@ pub fn build_indicator_modal(parent: &gtk::ApplicationWindow) -> IndicatorModalU
     list.add_css_class("boxed-list");
     list.set_selection_mode(gtk::SelectionMode::None);
     content.append(&list);
-    window.set_child(Some(&content));
+
+    let scripts = gtk::Box::new(gtk::Orientation::Vertical, 6);
+    content.append(&scripts);
+
+    let scroller = gtk::ScrolledWindow::new();
+    scroller.set_hscrollbar_policy(gtk::PolicyType::Never);
+    scroller.set_child(Some(&content));
+    window.set_child(Some(&scroller));
 
     let current_panel = Rc::new(Cell::new(PanelId(1)));
     IndicatorModalUi {
         window,
         list,
+        scripts,
         current_panel,
     }
 }
@@ -79,7 +153,9 @@ pub fn configure_indicator_modal(
     ui: &IndicatorModalUi,
     panel_id: PanelId,
     active: &HashSet<IndicatorKind>,
-    on_toggle: impl Fn(PanelId, IndicatorKind, bool) + 'static,
+    sources: &[(String, IndicatorSource)],
+    on_toggle: impl Fn(PanelId, IndicatorKind, bool, IndicatorSource, usize) -> Result<(), String>
+        + 'static,
 ) {
     ui.current_panel.set(panel_id);
     while let Some(child) = ui.list.first_child() {
@@ -87,11 +163,12 @@ pub fn configure_indicator_modal(
     }
 
     let on_toggle = Rc::new(on_toggle);
+    let source_labels: Vec<&str> = sources.iter().map(|(label, _)| label.as_str()).collect();
+    let sources: Rc<Vec<IndicatorSource>> =
+        Rc::new(sources.iter().map(|(_, source)| *source).collect());
 
     for indicator in IndicatorKind::all() {
         let row = gtk::Box::new(gtk::Orientation::Horizontal, 8);
-        row.set_margin_top(6);
-        row.set_margin_bottom(6);
 
         let label = gtk::Label::new(Some(indicator.label()));
         label.set_halign(gtk::Align::Start);
@@ -103,26 +180,186 @@ pub fn configure_indicator_modal(
         let action = gtk::Button::with_label(action_label);
         action.add_css_class("flat");
 
+        let source_picker = if indicator.accepts_source() && !is_active {
+            let picker = gtk::DropDown::from_strings(&source_labels);
+            picker.set_tooltip_text(Some("Source"));
+            Some(picker)
+        } else {
+            None
+        };
+        let variants = indicator.variants();
+        let variant_picker = if !variants.is_empty() && !is_active {
+            let picker = gtk::DropDown::from_strings(&variants);
+            picker.set_tooltip_text(Some("Options"));
+            Some(picker)
+        } else {
+            None
+        };
+
+        // Why the last click changed nothing, e.g. an indicator that would
+        // feed on its own output.
+        let error = gtk::Label::new(None);
+        error.set_halign(gtk::Align::Start);
+        error.set_wrap(true);
+        error.add_css_class("error");
+        error.set_visible(false);
+
         action.connect_clicked({
             let on_toggle = on_toggle.clone();
             let indicator = *indicator;
             let action = action.clone();
+            let error = error.clone();
             let state = state.clone();
+            let sources = sources.clone();
+            let source_picker = source_picker.clone();
+            let variant_picker = variant_picker.clone();
             move |_| {
                 let next = !state.get();
-                on_toggle(panel_id, indicator, next);
+                let source = source_picker
+                    .as_ref()
+                    .and_then(|picker| sources.get(picker.selected() as usize).copied())
+                    .unwrap_or(IndicatorSource::Price(PriceField::Close));
+                let variant = variant_picker
+                    .as_ref()
+                    .map(|picker| picker.selected() as usize)
+                    .unwrap_or(0);
+                if let Err(message) = on_toggle(panel_id, indicator, next, source, variant) {
+                    error.set_text(&message);
+                    error.set_visible(true);
+                    return;
+                }
+                error.set_visible(false);
                 state.set(next);
                 action.set_label(if next { "Remove" } else { "Add" });
+                for picker in [&source_picker, &variant_picker].into_iter().flatten() {
+                    picker.set_sensitive(!next);
+                }
             }
         });
 
         row.append(&label);
+        for picker in [&source_picker, &variant_picker].into_iter().flatten() {
+            row.append(picker);
+        }
         row.append(&action);
 
+        let cell = gtk::Box::new(gtk::Orientation::Vertical, 2);
+        cell.set_margin_top(6);
+        cell.set_margin_bottom(6);
+        cell.append(&row);
+        cell.append(&error);
+
         let list_row = gtk::ListBoxRow::new();
-        list_row.set_child(Some(&row));
+        list_row.set_child(Some(&cell));
         list_row.set_selectable(false);
         list_row.set_activatable(false);
         ui.list.append(&list_row);
     }
 }
+
+pub fn configure_script_section(
+    ui: &IndicatorModalUi,
+    scripts: &[ScriptSummary],
+    on_add: impl Fn(String) -> Result<ScriptSummary, String> + 'static,
+    on_remove: impl Fn(usize) + 'static,
+) {
+    while let Some(child) = ui.scripts.first_child() {
+        ui.scripts.remove(&child);
+    }
+
+    let title = gtk::Label::new(Some("Scripts"));
+    title.set_halign(gtk::Align::Start);
+    title.add_css_class("heading");
+    ui.scripts.append(&title);
+
+    let list = gtk::ListBox::new();
+    list.add_css_class("boxed-list");
+    list.set_selection_mode(gtk::SelectionMode::None);
+    ui.scripts.append(&list);
+
+    let on_remove: Rc<dyn Fn(usize)> = Rc::new(on_remove);
+    let append_row = {
+        let list = list.clone();
+        move |summary: &ScriptSummary| {
+            let row = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+            row.set_margin_top(6);
+            row.set_margin_bottom(6);
+
+            let labels = gtk::Box::new(gtk::Orientation::Vertical, 2);
+            labels.set_hexpand(true);
+            let name = gtk::Label::new(Some(&summary.name));
+            name.set_halign(gtk::Align::Start);
+            labels.append(&name);
+            if let Some(error) = &summary.error {
+                let error = gtk::Label::new(Some(error));
+                error.set_halign(gtk::Align::Start);
+                error.set_wrap(true);
+                error.add_css_class("error");
+                labels.append(&error);
+            }
+
+            let remove = gtk::Button::with_label("Remove");
+            remove.add_css_class("flat");
+
+            row.append(&labels);
+            row.append(&remove);
+
+            let list_row = gtk::ListBoxRow::new();
+            list_row.set_child(Some(&row));
+            list_row.set_selectable(false);
+            list_row.set_activatable(false);
+            list.append(&list_row);
+
+            remove.connect_clicked({
+                let on_remove = on_remove.clone();
+                let list = list.clone();
+                let id = summary.id;
+                move |_| {
+                    on_remove(id);
+                    list.remove(&list_row);
+                }
+            });
+        }
+    };
+    for summary in scripts {
+        append_row(summary);
+    }
+
+    let buffer = gtk::TextBuffer::new(None);
+    buffer.set_text(SCRIPT_TEMPLATE);
+    let editor = gtk::TextView::with_buffer(&buffer);
+    editor.set_monospace(true);
+    editor.set_top_margin(6);
+    editor.set_left_margin(6);
+    let editor_scroller = gtk::ScrolledWindow::new();
+    editor_scroller.set_min_content_height(160);
+    editor_scroller.set_child(Some(&editor));
+    ui.scripts.append(&editor_scroller);
+
+    let error = gtk::Label::new(None);
+    error.set_halign(gtk::Align::Start);
+    error.set_wrap(true);
+    error.set_selectable(true);
+    error.add_css_class("error");
+    error.set_visible(false);
+    ui.scripts.append(&error);
+
+    let add = gtk::Button::with_label("Add script");
+    add.set_halign(gtk::Align::End);
+    add.connect_clicked(move |_| {
+        let source = buffer
+            .text(&buffer.start_iter(), &buffer.end_iter(), false)
+            .to_string();
+        match on_add(source) {
+            Ok(summary) => {
+                error.set_visible(false);
+                append_row(&summary);
+            }
+            Err(message) => {
+                error.set_text(&message);
+                error.set_visible(true);
+            }
+        }
+    });
+    ui.scripts.append(&add);
+}
@@ -1,6 +1,7 @@
 use crate::chart::{
     ChartApi, PanelControlAction, PanelId, PanelResizeHandle, PriceScale, TrackingModeExitMode,
 };
+use super::drawing_tools::DrawingTools;
 use relm4::gtk::{self, gdk, glib};
 use relm4::gtk::prelude::*;
 use std::cell::{Cell, RefCell};
@@ -39,12 +40,43 @@ fn stop_kinetic(state: &Rc<RefCell<KineticState>>) {
     }
 }
 
+/// Binds Ctrl+Z to undo and Ctrl+Shift+Z or Ctrl+Y to redo. The controller
+/// sits on the window in the bubble phase, so text fields keep their own
+/// undo while focused.
+pub fn install_undo_shortcuts(
+    window: &gtk::ApplicationWindow,
+    drawing_area: &gtk::DrawingArea,
+    chart: ChartApi,
+) {
+    let keys = gtk::EventControllerKey::new();
+    let drawing_area = drawing_area.clone();
+    keys.connect_key_pressed(move |_, key, _, state| {
+        if !state.contains(gdk::ModifierType::CONTROL_MASK) {
+            return glib::Propagation::Proceed;
+        }
+        let shift = state.contains(gdk::ModifierType::SHIFT_MASK);
+        let done = match key.to_lower() {
+            gdk::Key::z if shift => chart.redo(),
+            gdk::Key::z => chart.undo(),
+            gdk::Key::y => chart.redo(),
+            _ => return glib::Propagation::Proceed,
+        };
+        if done {
+            drawing_area.queue_draw();
+        }
+        glib::Propagation::Stop
+    });
+    window.add_controller(keys);
+}
+
 pub fn install_interactions(
     drawing_area: &gtk::DrawingArea,
     chart: ChartApi,
     on_price_axis_zoom: Option<Rc<dyn Fn(PriceScale)>>,
     on_panel_menu: Option<Rc<dyn Fn(PanelId, f64, f64)>>,
     on_panel_control: Option<Rc<dyn Fn(PanelId, PanelControlAction)>>,
+    drawing_tools: Option<DrawingTools>,
+    on_context_menu: Option<Rc<dyn Fn(f64, f64) -> bool>>,
 ) {
     let last_pointer_x = Rc::new(Cell::new(0.0));
     let last_pointer_y = Rc::new(Cell::new(0.0));
@@ -56,10 +88,16 @@ pub fn install_interactions(
         let last_pointer_y = last_pointer_y.clone();
         let chart = chart.clone();
         let drawing_area = drawing_area.clone();
+        let drawing_tools = drawing_tools.clone();
         move |_, x, y| {
             last_pointer_x.set(x);
             last_pointer_y.set(y);
             chart.set_crosshair(x, y);
+            if let Some(tools) = drawing_tools.as_ref() {
+                let width = drawing_area.width() as f64;
+                let height = drawing_area.height() as f64;
+                tools.pointer_moved(x, y, width, height);
+            }
             drawing_area.queue_draw();
         }
     });
@@ -101,7 +139,9 @@ pub fn install_interactions(
         let chart = chart.clone();
         let drawing_area = drawing_area.clone();
         let on_panel_control = on_panel_control.clone();
+        let drawing_tools = drawing_tools.clone();
         move |_, n_press, x, y| {
+            drawing_area.grab_focus();
             if n_press == 1 {
                 if let Some((panel, action)) = chart.panel_control_at(x, y) {
                     if let Some(handler) = on_panel_control.as_ref() {
@@ -111,6 +151,13 @@ pub fn install_interactions(
                     return;
                 }
             }
+            if let Some(tools) = drawing_tools.as_ref().filter(|tools| tools.is_active()) {
+                let width = drawing_area.width() as f64;
+                let height = drawing_area.height() as f64;
+                tools.click(x, y, width, height);
+                drawing_area.queue_draw();
+                return;
+            }
             if chart.tracking_mode_active() {
                 let tracking = chart.tracking_mode_options();
                 if matches!(tracking.exit_mode, TrackingModeExitMode::OnNextTap) {
@@ -119,9 +166,18 @@ pub fn install_interactions(
                     return;
                 }
             }
+            if n_press == 1 {
+                chart.handle_click(x, y);
+            }
             if n_press == 2 {
                 let width = drawing_area.width() as f64;
                 let height = drawing_area.height() as f64;
+                if drawing_tools
+                    .as_ref()
+                    .is_some_and(|tools| tools.edit_at(x, y, width, height))
+                {
+                    return;
+                }
                 chart.handle_double_click(x, y, width, height);
                 drawing_area.queue_draw();
             }
@@ -129,15 +185,37 @@ pub fn install_interactions(
     });
     drawing_area.add_controller(click);
 
+    if let Some(tools) = drawing_tools.clone() {
+        drawing_area.set_focusable(true);
+        let keys = gtk::EventControllerKey::new();
+        keys.connect_key_pressed(move |_, key, _, _| {
+            if matches!(key, gdk::Key::Delete | gdk::Key::BackSpace) && tools.delete_selected() {
+                return glib::Propagation::Stop;
+            }
+            glib::Propagation::Proceed
+        });
+        drawing_area.add_controller(keys);
+    }
+
     let right_click = gtk::GestureClick::new();
     right_click.set_button(3);
     right_click.connect_pressed({
         let chart = chart.clone();
         let drawing_area = drawing_area.clone();
         let on_panel_menu = on_panel_menu.clone();
+        let drawing_tools = drawing_tools.clone();
         move |_, _, x, y| {
+            if on_context_menu.as_ref().is_some_and(|handler| handler(x, y)) {
+                return;
+            }
             let width = drawing_area.width() as f64;
             let height = drawing_area.height() as f64;
+            if drawing_tools
+                .as_ref()
+                .is_some_and(|tools| tools.edit_at(x, y, width, height))
+            {
+                return;
+            }
             let panel = chart
                 .tooltip_icon_at(x, y)
                 .or_else(|| chart.panel_at(x, y, width, height));
@@ -187,18 +265,38 @@ pub fn install_interactions(
         let chart = chart.clone();
         let drawing_area = drawing_area.clone();
         let kinetic_state = kinetic_state.clone();
-        move |_, start_x, start_y| {
+        let drawing_tools = drawing_tools.clone();
+        move |gesture, start_x, start_y| {
             last_drag_x.set(0.0);
             last_drag_y.set(0.0);
             stop_kinetic(&kinetic_state);
             let mut state = kinetic_state.borrow_mut();
             state.velocity_x = 0.0;
             state.last_time = glib::monotonic_time();
+            let tool_active = drawing_tools
+                .as_ref()
+                .is_some_and(|tools| tools.is_active() || tools.is_zooming());
+            if !tool_active
+                && !is_touch_device(gesture.device())
+                && chart.begin_price_line_drag(start_x, start_y)
+            {
+                drawing_area.queue_draw();
+                return;
+            }
             let width = drawing_area.width() as f64;
             let height = drawing_area.height() as f64;
             let handle = chart.panel_resize_handle_at(start_y, width, height);
             resize_handle.set(handle);
-            let _ = start_x;
+            if handle.is_some() {
+                // A resize drag undoes as one step.
+                chart.begin_edit_group();
+            }
+            if let (None, Some(tools)) = (handle, drawing_tools.as_ref()) {
+                let shift = gesture
+                    .current_event_state()
+                    .contains(gdk::ModifierType::SHIFT_MASK);
+                tools.begin_drag(start_x, start_y, width, height, shift);
+            }
         }
     });
     drag.connect_drag_update({
@@ -211,10 +309,30 @@ pub fn install_interactions(
         let last_pointer_y = last_pointer_y.clone();
         let on_price_axis_zoom = on_price_axis_zoom.clone();
         let kinetic_state = kinetic_state.clone();
+        let drawing_tools = drawing_tools.clone();
         move |gesture, offset_x, offset_y| {
             if is_touch_device(gesture.device()) {
                 return;
             }
+            if chart.is_dragging_price_line() {
+                if let Some((_, start_y)) = gesture.start_point() {
+                    chart.drag_price_line(start_y + offset_y);
+                    drawing_area.queue_draw();
+                }
+                return;
+            }
+            if let Some(tools) = drawing_tools.as_ref() {
+                if tools.is_dragging() {
+                    let width = drawing_area.width() as f64;
+                    let height = drawing_area.height() as f64;
+                    tools.drag_to(offset_x, offset_y, width, height);
+                    drawing_area.queue_draw();
+                    return;
+                }
+                if tools.is_active() {
+                    return;
+                }
+            }
             let delta_x = offset_x - last_drag_x.get();
             let delta_y = offset_y - last_drag_y.get();
             last_drag_x.set(offset_x);
@@ -259,11 +377,23 @@ pub fn install_interactions(
         let last_pointer_y = last_pointer_y.clone();
         let kinetic_state = kinetic_state.clone();
         let resize_handle = resize_handle.clone();
+        let drawing_tools = drawing_tools.clone();
         move |gesture, _, _| {
+            if chart.is_dragging_price_line() {
+                chart.end_price_line_drag();
+                drawing_area.queue_draw();
+                return;
+            }
+            if resize_handle.take().is_some() {
+                chart.end_edit_group();
+            }
+            if let Some(tools) = drawing_tools.as_ref().filter(|tools| tools.is_dragging()) {
+                tools.end_drag();
+                return;
+            }
             if is_touch_device(gesture.device()) {
                 return;
             }
-            resize_handle.set(None);
             let options = chart.kinetic_scroll_options();
             if !options.mouse || chart.tracking_mode_active() {
                 return;
@@ -0,0 +1,164 @@
+use crate::chart::{ChartApi, PriceScale};
+use relm4::gtk::prelude::*;
+use relm4::gtk::{self, gdk, glib};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::drawing_tools::DrawingTools;
+
+/// Time scale zoom per press of the zoom keys.
+const KEY_ZOOM_FACTOR: f64 = 0.8;
+
+/// Keyboard shortcuts for chart navigation, in GTK accelerator syntax such as
+/// `<Shift>Left` or `<Alt>r`. Each action takes any number of shortcuts.
+/// Stored in the settings profile under `[keyboard]`; missing entries keep
+/// their defaults.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub pan_left: Vec<String>,
+    pub pan_right: Vec<String>,
+    pub page_left: Vec<String>,
+    pub page_right: Vec<String>,
+    pub zoom_in: Vec<String>,
+    pub zoom_out: Vec<String>,
+    pub first_bar: Vec<String>,
+    pub last_bar: Vec<String>,
+    pub reset_autoscale: Vec<String>,
+    pub cancel_tool: Vec<String>,
+    pub crosshair_previous: Vec<String>,
+    pub crosshair_next: Vec<String>,
+}
+
+fn keys(accelerators: &[&str]) -> Vec<String> {
+    accelerators.iter().map(|accel| accel.to_string()).collect()
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            pan_left: keys(&["Left"]),
+            pan_right: keys(&["Right"]),
+            page_left: keys(&["<Shift>Left"]),
+            page_right: keys(&["<Shift>Right"]),
+            zoom_in: keys(&["plus", "equal", "KP_Add"]),
+            zoom_out: keys(&["minus", "KP_Subtract"]),
+            first_bar: keys(&["Home"]),
+            last_bar: keys(&["End"]),
+            reset_autoscale: keys(&["<Alt>r"]),
+            cancel_tool: keys(&["Escape"]),
+            crosshair_previous: keys(&["<Ctrl>Left"]),
+            crosshair_next: keys(&["<Ctrl>Right"]),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum KeyAction {
+    PanLeft,
+    PanRight,
+    PageLeft,
+    PageRight,
+    ZoomIn,
+    ZoomOut,
+    FirstBar,
+    LastBar,
+    ResetAutoscale,
+    CancelTool,
+    CrosshairPrevious,
+    CrosshairNext,
+}
+
+impl KeyBindings {
+    fn action(&self, key: gdk::Key, state: gdk::ModifierType) -> Option<KeyAction> {
+        let bindings = [
+            (&self.pan_left, KeyAction::PanLeft),
+            (&self.pan_right, KeyAction::PanRight),
+            (&self.page_left, KeyAction::PageLeft),
+            (&self.page_right, KeyAction::PageRight),
+            (&self.zoom_in, KeyAction::ZoomIn),
+            (&self.zoom_out, KeyAction::ZoomOut),
+            (&self.first_bar, KeyAction::FirstBar),
+            (&self.last_bar, KeyAction::LastBar),
+            (&self.reset_autoscale, KeyAction::ResetAutoscale),
+            (&self.cancel_tool, KeyAction::CancelTool),
+            (&self.crosshair_previous, KeyAction::CrosshairPrevious),
+            (&self.crosshair_next, KeyAction::CrosshairNext),
+        ];
+        bindings
+            .into_iter()
+            .find(|(accels, _)| accels.iter().any(|accel| matches(accel, key, state)))
+            .map(|(_, action)| action)
+    }
+}
+
+/// Whether `key` pressed with `state` is the shortcut `accel`. Shift is
+/// ignored for symbols unless the shortcut names it, so `plus` matches
+/// however the layout produces it.
+fn matches(accel: &str, key: gdk::Key, state: gdk::ModifierType) -> bool {
+    let Some((bound_key, bound_mods)) = gtk::accelerator_parse(accel) else {
+        return false;
+    };
+    let mask = gdk::ModifierType::CONTROL_MASK
+        | gdk::ModifierType::SHIFT_MASK
+        | gdk::ModifierType::ALT_MASK;
+    let mut mods = state & mask;
+    let symbol = key.to_unicode().is_some_and(|ch| !ch.is_alphabetic());
+    if symbol && !bound_mods.contains(gdk::ModifierType::SHIFT_MASK) {
+        mods.remove(gdk::ModifierType::SHIFT_MASK);
+    }
+    key.to_lower() == bound_key.to_lower() && mods == bound_mods & mask
+}
+
+/// Handles the navigation shortcuts while the chart has focus.
+/// `on_autoscale_reset` is told about each side put back on auto scale so
+/// toggles can follow.
+pub fn install_keyboard_navigation(
+    drawing_area: &gtk::DrawingArea,
+    chart: ChartApi,
+    bindings: Rc<RefCell<KeyBindings>>,
+    drawing_tools: Option<DrawingTools>,
+    on_autoscale_reset: Option<Rc<dyn Fn(PriceScale)>>,
+) {
+    drawing_area.set_focusable(true);
+    let keys = gtk::EventControllerKey::new();
+    let area = drawing_area.clone();
+    keys.connect_key_pressed(move |_, key, _, state| {
+        let Some(action) = bindings.borrow().action(key, state) else {
+            return glib::Propagation::Proceed;
+        };
+        match action {
+            KeyAction::PanLeft => chart.pan_by_bars(-1.0),
+            KeyAction::PanRight => chart.pan_by_bars(1.0),
+            KeyAction::PageLeft => chart.pan_by_pages(-1.0),
+            KeyAction::PageRight => chart.pan_by_pages(1.0),
+            KeyAction::ZoomIn => chart.zoom_at_last_bar(KEY_ZOOM_FACTOR),
+            KeyAction::ZoomOut => chart.zoom_at_last_bar(1.0 / KEY_ZOOM_FACTOR),
+            KeyAction::FirstBar => chart.scroll_to_first_bar(),
+            KeyAction::LastBar => chart.scroll_to_last_bar(),
+            KeyAction::ResetAutoscale => {
+                for side in [PriceScale::Left, PriceScale::Right] {
+                    chart.reset_autoscale(side);
+                    if let Some(handler) = on_autoscale_reset.as_ref() {
+                        handler(side);
+                    }
+                }
+            }
+            KeyAction::CancelTool => {
+                if !drawing_tools.as_ref().is_some_and(|tools| tools.cancel()) {
+                    return glib::Propagation::Proceed;
+                }
+            }
+            KeyAction::CrosshairPrevious => {
+                chart.step_crosshair(-1);
+            }
+            KeyAction::CrosshairNext => {
+                chart.step_crosshair(1);
+            }
+        }
+        area.queue_draw();
+        glib::Propagation::Stop
+    });
+    drawing_area.add_controller(keys);
+}
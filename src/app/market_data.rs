@@ -1,6 +1,7 @@
 use crate::chart::{sample_candles, Candle, ChartStyle, HistogramPoint, LinePoint};
 use crate::indicators::rsi::compute_rsi;
 use serde_json::Value;
+use std::collections::VecDeque;
 use time::OffsetDateTime;
 
 pub struct MarketData {
@@ -25,8 +26,12 @@ pub struct MarketStore {
     pub interval_ms: i64,
     pub earliest_ms: i64,
     pub latest_ms: i64,
+    /// Bars hidden by replay mode, revealed one step at a time through the
+    /// same path as live klines. `None` outside replay.
+    pub replay: Option<ReplayQueue>,
 }
 
+#[derive(Clone)]
 pub struct KlineEvent {
     pub open_time_ms: i64,
     pub open: f64,
@@ -42,9 +47,69 @@ pub struct KlineApplyResult {
     pub volume: HistogramPoint,
 }
 
+#[derive(Default)]
+pub struct ReplayQueue {
+    /// Bars still hidden, oldest first
+    bars: VecDeque<KlineEvent>,
+    /// Remaining updates of the bar being built up from `detail`, ending
+    /// with the bar itself
+    forming: VecDeque<KlineEvent>,
+    /// Lower-timeframe bars for intrabar replay, by open time
+    detail: Vec<KlineEvent>,
+}
+
+/// Builds bars of one interval out of bars of a shorter one, such as 1m
+/// klines into a forming 1h candle.
+pub struct BarAggregator {
+    interval_ms: i64,
+    origin_ms: i64,
+    forming: Option<KlineEvent>,
+}
+
+impl BarAggregator {
+    /// Aggregates into `interval` bars, one of which opens at `origin_ms`.
+    /// `None` for an interval `interval_to_millis` does not know.
+    pub fn new(interval: &str, origin_ms: i64) -> Option<Self> {
+        Some(Self {
+            interval_ms: interval_to_millis(interval)?,
+            origin_ms,
+            forming: None,
+        })
+    }
+
+    /// Folds in the next finer bar and returns the bar it belongs to as it
+    /// stands so far. A finer bar from a later period starts a new bar.
+    pub fn push(&mut self, fine: &KlineEvent) -> KlineEvent {
+        let offset = (fine.open_time_ms - self.origin_ms).div_euclid(self.interval_ms);
+        let open_time_ms = self.origin_ms + offset * self.interval_ms;
+        let bar = match self.forming.take() {
+            Some(mut bar) if bar.open_time_ms == open_time_ms => {
+                bar.high = bar.high.max(fine.high);
+                bar.low = bar.low.min(fine.low);
+                bar.close = fine.close;
+                bar.volume += fine.volume;
+                bar
+            }
+            _ => KlineEvent {
+                open_time_ms,
+                is_final: false,
+                ..fine.clone()
+            },
+        };
+        self.forming = Some(bar.clone());
+        bar
+    }
+}
+
 const BINANCE_SYMBOL: &str = "BTCUSDT";
 const BINANCE_INTERVAL: &str = "1m";
 const BINANCE_LIMIT: usize = 500;
+/// Binance intervals intrabar replay can draw on, shortest first.
+const DETAIL_INTERVALS: [&str; 12] = [
+    "1s", "1m", "3m", "5m", "15m", "30m", "1h", "2h", "4h", "6h", "8h", "12h",
+];
+/// Fewest updates a bar should take to form in intrabar replay.
+const DETAIL_STEPS: i64 = 60;
 
 impl MarketStore {
     pub fn new(
@@ -66,6 +131,7 @@ impl MarketStore {
             interval_ms,
             earliest_ms,
             latest_ms,
+            replay: None,
         }
     }
 
@@ -96,7 +162,24 @@ impl MarketStore {
         true
     }
 
-    pub fn apply_kline(&mut self, event: KlineEvent, style: &ChartStyle) -> KlineApplyResult {
+    /// Applies a live kline. While replaying it is queued behind the hidden
+    /// bars instead and `None` is returned.
+    pub fn apply_kline(
+        &mut self,
+        event: KlineEvent,
+        style: &ChartStyle,
+    ) -> Option<KlineApplyResult> {
+        if let Some(replay) = self.replay.as_mut() {
+            match replay.bars.back_mut() {
+                Some(last) if last.open_time_ms == event.open_time_ms => *last = event,
+                _ => replay.bars.push_back(event),
+            }
+            return None;
+        }
+        Some(self.reveal_kline(event, style))
+    }
+
+    fn reveal_kline(&mut self, event: KlineEvent, style: &ChartStyle) -> KlineApplyResult {
         let time = time_from_ms(event.open_time_ms).unwrap_or_else(|_| {
             OffsetDateTime::from_unix_timestamp(0).unwrap()
         });
@@ -136,6 +219,165 @@ impl MarketStore {
 
         KlineApplyResult { candle, volume }
     }
+
+    pub fn is_replaying(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    /// Number of bars still hidden by replay mode.
+    pub fn replay_remaining(&self) -> usize {
+        self.replay.as_ref().map_or(0, |replay| replay.bars.len())
+    }
+
+    /// Enters replay mode with every bar after `start_ms` hidden, ending any
+    /// replay already running first. Returns false when no bar falls on
+    /// either side of `start_ms`.
+    pub fn start_replay(&mut self, start_ms: i64, style: &ChartStyle) -> bool {
+        self.stop_replay(style);
+        let split = self
+            .candles
+            .partition_point(|candle| time_to_ms(candle.time) <= start_ms);
+        if split == 0 || split == self.candles.len() {
+            return false;
+        }
+        let hidden = self.candles.split_off(split);
+        let volume_split = self
+            .volumes
+            .partition_point(|point| time_to_ms(point.time) <= start_ms);
+        let hidden_volumes = self.volumes.split_off(volume_split);
+        let bars = hidden
+            .into_iter()
+            .map(|candle| {
+                let volume = hidden_volumes
+                    .binary_search_by(|point| point.time.cmp(&candle.time))
+                    .map_or(0.0, |index| hidden_volumes[index].value);
+                KlineEvent {
+                    open_time_ms: time_to_ms(candle.time),
+                    open: candle.open,
+                    high: candle.high,
+                    low: candle.low,
+                    close: candle.close,
+                    volume,
+                    is_final: true,
+                }
+            })
+            .collect();
+        self.replay = Some(ReplayQueue {
+            bars,
+            ..ReplayQueue::default()
+        });
+        self.latest_ms = self
+            .candles
+            .last()
+            .map(|c| time_to_ms(c.time))
+            .unwrap_or(self.latest_ms);
+        self.rsi = compute_rsi(&self.candles, 14);
+        true
+    }
+
+    /// Open time of the first hidden bar and close time of the last, the
+    /// span intrabar replay needs detail for.
+    pub fn replay_span(&self) -> Option<(i64, i64)> {
+        let bars = &self.replay.as_ref()?.bars;
+        let first = bars.front()?.open_time_ms;
+        let last = bars.back()?.open_time_ms;
+        Some((first, last + self.interval_ms))
+    }
+
+    /// Adds lower-timeframe bars for intrabar replay. Hidden bars they cover
+    /// are then revealed as a forming candle, one finer bar at a time.
+    pub fn add_replay_detail(&mut self, batch: MarketBatch) -> bool {
+        let Some(replay) = self.replay.as_mut() else {
+            return false;
+        };
+        if batch.candles.is_empty() {
+            return false;
+        }
+        for candle in batch.candles {
+            let volume = batch
+                .volumes
+                .binary_search_by(|point| point.time.cmp(&candle.time))
+                .map_or(0.0, |index| batch.volumes[index].value);
+            replay.detail.push(KlineEvent {
+                open_time_ms: time_to_ms(candle.time),
+                open: candle.open,
+                high: candle.high,
+                low: candle.low,
+                close: candle.close,
+                volume,
+                is_final: true,
+            });
+        }
+        replay.detail.sort_by_key(|event| event.open_time_ms);
+        replay.detail.dedup_by_key(|event| event.open_time_ms);
+        true
+    }
+
+    /// Updates each hidden bar takes to reveal: the number of detail bars
+    /// per bar once detail has arrived, otherwise one.
+    pub fn replay_steps_per_bar(&self) -> i64 {
+        let has_detail = self
+            .replay
+            .as_ref()
+            .is_some_and(|replay| !replay.detail.is_empty());
+        let detail_ms = detail_interval(&self.interval).and_then(interval_to_millis);
+        match detail_ms {
+            Some(detail_ms) if has_detail => (self.interval_ms / detail_ms).max(1),
+            _ => 1,
+        }
+    }
+
+    /// Reveals the next update: a step of the forming bar, or the next
+    /// hidden bar. Returns `None` when replay has caught up or is not
+    /// running.
+    pub fn replay_step(&mut self, style: &ChartStyle) -> Option<KlineApplyResult> {
+        let replay = self.replay.as_mut()?;
+        if replay.forming.is_empty() {
+            let bar = replay.bars.pop_front()?;
+            replay.forming = expand_bar(&self.interval, bar, &replay.detail);
+        }
+        let event = replay.forming.pop_front()?;
+        Some(self.reveal_kline(event, style))
+    }
+
+    /// Leaves replay mode, putting back every bar still hidden. Returns
+    /// false when replay was not running.
+    pub fn stop_replay(&mut self, style: &ChartStyle) -> bool {
+        let Some(replay) = self.replay.take() else {
+            return false;
+        };
+        for event in replay.forming.into_iter().last().into_iter().chain(replay.bars) {
+            self.reveal_kline(event, style);
+        }
+        true
+    }
+}
+
+/// The updates that reveal `bar`: its state after each detail bar inside it,
+/// then the bar itself so it ends exactly as recorded.
+fn expand_bar(interval: &str, bar: KlineEvent, detail: &[KlineEvent]) -> VecDeque<KlineEvent> {
+    let mut steps = VecDeque::new();
+    if let Some(mut aggregator) = BarAggregator::new(interval, bar.open_time_ms) {
+        let end = bar.open_time_ms + aggregator.interval_ms;
+        let from = detail.partition_point(|fine| fine.open_time_ms < bar.open_time_ms);
+        let to = detail.partition_point(|fine| fine.open_time_ms < end);
+        // The last detail bar completes the bar, which `bar` itself does.
+        for fine in &detail[from..to.max(from + 1) - 1] {
+            steps.push_back(aggregator.push(fine));
+        }
+    }
+    steps.push_back(bar);
+    steps
+}
+
+/// Lower-timeframe interval to build `interval` bars from in intrabar
+/// replay: the longest that divides it into at least `DETAIL_STEPS` parts.
+pub fn detail_interval(interval: &str) -> Option<&'static str> {
+    let bar_ms = interval_to_millis(interval)?;
+    DETAIL_INTERVALS.into_iter().rev().find(|detail| {
+        interval_to_millis(detail)
+            .is_some_and(|ms| ms * DETAIL_STEPS <= bar_ms && bar_ms % ms == 0)
+    })
 }
 
 pub fn load_market_data() -> MarketData {
@@ -351,6 +593,7 @@ fn interval_to_millis(interval: &str) -> Option<i64> {
     let (num_part, unit_part) = interval.split_at(interval.len() - 1);
     let value: i64 = num_part.parse().ok()?;
     let multiplier = match unit_part {
+        "s" => 1_000,
         "m" => 60_000,
         "h" => 60_000 * 60,
         "d" => 60_000 * 60 * 24,
@@ -390,3 +633,106 @@ fn update_sorted_volumes(data: &mut Vec<HistogramPoint>, item: HistogramPoint) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: i64 = 60_000;
+    const HOUR: i64 = 60 * MINUTE;
+
+    fn kline(open_time_ms: i64, open: f64, high: f64, low: f64, close: f64) -> KlineEvent {
+        KlineEvent {
+            open_time_ms,
+            open,
+            high,
+            low,
+            close,
+            volume: 1.0,
+            is_final: true,
+        }
+    }
+
+    #[test]
+    fn aggregator_folds_bars_of_one_period() {
+        let mut aggregator = BarAggregator::new("1h", 0).unwrap();
+        let first = aggregator.push(&kline(0, 10.0, 12.0, 9.0, 11.0));
+        assert_eq!(first.open_time_ms, 0);
+        assert!(!first.is_final);
+        let bar = aggregator.push(&kline(MINUTE, 11.0, 15.0, 8.0, 14.0));
+        assert_eq!(bar.open_time_ms, 0);
+        assert_eq!(bar.open, 10.0);
+        assert_eq!(bar.high, 15.0);
+        assert_eq!(bar.low, 8.0);
+        assert_eq!(bar.close, 14.0);
+        assert_eq!(bar.volume, 2.0);
+    }
+
+    #[test]
+    fn aggregator_starts_a_new_bar_for_a_later_period() {
+        let mut aggregator = BarAggregator::new("1h", 0).unwrap();
+        aggregator.push(&kline(0, 10.0, 12.0, 9.0, 11.0));
+        let bar = aggregator.push(&kline(HOUR + MINUTE, 20.0, 21.0, 19.0, 20.5));
+        assert_eq!(bar.open_time_ms, HOUR);
+        assert_eq!(bar.open, 20.0);
+        assert_eq!(bar.low, 19.0);
+        assert_eq!(bar.volume, 1.0);
+    }
+
+    #[test]
+    fn aggregator_aligns_periods_to_the_origin() {
+        let mut aggregator = BarAggregator::new("1h", 30 * MINUTE).unwrap();
+        assert_eq!(
+            aggregator.push(&kline(0, 1.0, 1.0, 1.0, 1.0)).open_time_ms,
+            -30 * MINUTE
+        );
+        assert_eq!(
+            aggregator
+                .push(&kline(45 * MINUTE, 1.0, 1.0, 1.0, 1.0))
+                .open_time_ms,
+            30 * MINUTE
+        );
+        assert!(BarAggregator::new("1x", 0).is_none());
+    }
+
+    #[test]
+    fn expand_bar_steps_through_detail_and_ends_with_the_bar() {
+        let bar = kline(HOUR, 10.0, 16.0, 7.0, 12.0);
+        let detail = vec![
+            kline(0, 1.0, 1.0, 1.0, 1.0),
+            kline(HOUR, 10.0, 11.0, 9.0, 10.5),
+            kline(HOUR + MINUTE, 10.5, 16.0, 10.0, 15.0),
+            kline(HOUR + 2 * MINUTE, 15.0, 15.0, 7.0, 12.0),
+            kline(2 * HOUR, 1.0, 1.0, 1.0, 1.0),
+        ];
+        let steps: Vec<KlineEvent> = expand_bar("1h", bar, &detail).into();
+        assert_eq!(steps.len(), 3);
+        assert!(steps.iter().all(|step| step.open_time_ms == HOUR));
+        assert_eq!(steps[0].high, 11.0);
+        assert_eq!(steps[0].close, 10.5);
+        assert_eq!(steps[1].high, 16.0);
+        assert_eq!(steps[1].low, 9.0);
+        assert_eq!(steps[1].close, 15.0);
+        assert!(steps[2].is_final);
+        assert_eq!(steps[2].low, 7.0);
+        assert_eq!(steps[2].close, 12.0);
+    }
+
+    #[test]
+    fn expand_bar_without_detail_reveals_the_bar_at_once() {
+        let bar = kline(HOUR, 10.0, 16.0, 7.0, 12.0);
+        let steps = expand_bar("1h", bar, &[]);
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].close, 12.0);
+    }
+
+    #[test]
+    fn detail_interval_picks_longest_dividing_interval() {
+        assert_eq!(detail_interval("1m"), Some("1s"));
+        assert_eq!(detail_interval("1h"), Some("1m"));
+        assert_eq!(detail_interval("4h"), Some("3m"));
+        assert_eq!(detail_interval("1d"), Some("15m"));
+        assert_eq!(detail_interval("1s"), None);
+        assert_eq!(detail_interval("bogus"), None);
+    }
+}
@@ -1,10 +1,21 @@
+mod alerts;
 mod helpers;
 mod interaction;
+mod keyboard;
 mod data_feed;
+mod drawing_files;
+mod drawing_style;
+mod context_menu;
+mod drawing_tools;
+mod go_to_date;
 mod market_data;
+mod indicator_chain;
 mod indicator_modal;
+mod overlays;
 mod panel_picker;
 mod panel_settings;
+mod replay;
+mod script_indicators;
 mod settings_wiring;
 
 use crate::chart::{
@@ -12,22 +23,59 @@ use crate::chart::{
     MarkerPosition, MarkerShape, PanelControlAction, PanelId, PanelRole, PriceLineOptions,
     PriceScale,
 };
-use data_feed::{spawn_kline_stream, DataEvent, LazyLoader};
-use market_data::{load_market_data, MarketData, MarketStore};
+use alerts::PriceAlerts;
+use context_menu::{ChartContextMenu, SeriesRemoval};
+use data_feed::{spawn_kline_stream, spawn_replay_detail_fetch, DataEvent, LazyLoader};
+use drawing_files::{build_drawings_menu, DrawingFiles};
+use drawing_tools::{build_drawing_toolbar, DrawingTools};
+use go_to_date::build_go_to_date_menu;
+use market_data::{load_market_data, KlineApplyResult, MarketData, MarketStore};
+use overlays::{
+    AutoFibOverlay, IchimokuOverlay, PivotOverlay, VolumeProfileOverlay, ZigZagOverlay,
+};
 use crate::settings_ui::build_settings;
+use crate::indicators::graph::{IndicatorSource, NodeId, PriceField};
 use relm4::gtk;
 use relm4::gtk::glib;
 use relm4::gtk::prelude::*;
 use relm4::prelude::*;
 
-use interaction::install_interactions;
-use indicator_modal::{build_indicator_modal, configure_indicator_modal, IndicatorKind};
+use interaction::{install_interactions, install_undo_shortcuts};
+use keyboard::{install_keyboard_navigation, KeyBindings};
+use indicator_chain::{IndicatorChain, RemovedIndicator};
+use indicator_modal::{
+    build_indicator_modal, configure_indicator_modal, configure_script_section, IndicatorKind,
+};
 use panel_picker::build_panel_picker;
 use panel_settings::{build_panel_settings_ui, configure_panel_settings};
-use settings_wiring::{wire_chart_draw, wire_settings_panel};
+use replay::{build_replay_menu, Replay, ReplayBar, ReplayRefresh, ReplayReload};
+use script_indicators::ScriptIndicators;
+use settings_wiring::{
+    profiles_folder, wire_chart_draw, wire_settings_panel, without_settings_history,
+};
 use std::collections::{HashMap, HashSet};
 use std::time::Duration;
 
+/// Adds or removes an indicator and returns the indicators removed, or why
+/// nothing changed, such as an indicator that could not be added.
+type IndicatorToggle = std::rc::Rc<
+    dyn Fn(
+        PanelId,
+        IndicatorKind,
+        bool,
+        IndicatorSource,
+        usize,
+    ) -> Result<Vec<RemovedIndicator>, String>,
+>;
+type IndicatorChange =
+    std::rc::Rc<dyn Fn(PanelId, IndicatorKind, bool, IndicatorSource, usize) -> Result<(), String>>;
+type IndicatorRestore = std::rc::Rc<dyn Fn(&[RemovedIndicator]) -> Option<NodeId>>;
+/// What closing the indicator panel took off it: indicators, script
+/// sources and the ids of the series moved back to the main panel.
+type RemovedPanelContent = (Vec<RemovedIndicator>, Vec<String>, Vec<usize>);
+type IndicatorPanelRemoval = std::rc::Rc<dyn Fn(PanelId) -> RemovedPanelContent>;
+type IndicatorPanelRestore = std::rc::Rc<dyn Fn(PanelId, &RemovedPanelContent)>;
+
 struct AppModel;
 
 #[relm4::component]
@@ -171,6 +219,13 @@ impl SimpleComponent for AppModel {
         let volumes_for_chart = volumes.clone();
         hist_series.set_data(volumes_for_chart);
         hist_series.set_price_scale(PriceScale::Left);
+        let volume_profile = VolumeProfileOverlay::new(&candle_series, &hist_series);
+        // Series draw in the order they were added, so overlays come after
+        // the price series to draw on top of them.
+        let ichimoku = IchimokuOverlay::new(&chart);
+        let pivots = PivotOverlay::new(&chart);
+        let zigzag = ZigZagOverlay::new(&chart);
+        let auto_fib = AutoFibOverlay::new(&chart);
 
         candle_series.set_price_scale(PriceScale::Right);
 
@@ -189,8 +244,8 @@ impl SimpleComponent for AppModel {
         price_line_opts.line_style = LineStyle::Dotted;
         let _price_line = candle_series.create_price_line(price_line_opts);
 
-        if candles.len() > 9 {
-            candle_series.set_markers(vec![
+        let demo_markers = if candles.len() > 9 {
+            vec![
                 Marker {
                     time: candles[5].time,
                     position: MarkerPosition::Above,
@@ -243,8 +298,12 @@ impl SimpleComponent for AppModel {
                     label_offset_x: 0.0,
                     label_offset_y: 0.0,
                 },
-            ]);
-        }
+            ]
+        } else {
+            Vec::new()
+        };
+        candle_series.set_markers(demo_markers.clone());
+        let scripts = ScriptIndicators::new(&chart, &candle_series, demo_markers);
 
         chart.set_main_header(symbol.clone(), interval.clone());
         chart.set_rsi_panel("RSI".to_string(), rsi.clone());
@@ -253,6 +312,18 @@ impl SimpleComponent for AppModel {
         let widgets = view_output!();
         widgets.chart_overlay.add_overlay(&widgets.auto_scale_left);
         widgets.chart_overlay.add_overlay(&widgets.auto_scale_right);
+        let drawing_tools = DrawingTools::new(&chart, &widgets.drawing_area);
+        drawing_tools.set_auto_scale_handler({
+            let left_button = widgets.auto_scale_left.clone();
+            let right_button = widgets.auto_scale_right.clone();
+            std::rc::Rc::new(move |side: PriceScale, auto_scale: bool| match side {
+                PriceScale::Left => left_button.set_active(auto_scale),
+                PriceScale::Right => right_button.set_active(auto_scale),
+            })
+        });
+        widgets
+            .chart_overlay
+            .add_overlay(&build_drawing_toolbar(&drawing_tools));
         let settings = build_settings(&widgets.settings_stack, &widgets.settings_sidebar);
 
         widgets
@@ -289,6 +360,16 @@ impl SimpleComponent for AppModel {
         });
         widgets.header_bar.pack_end(&add_panel_button);
         widgets.header_bar.pack_end(&settings_button);
+        let drawing_files = DrawingFiles::new(&chart, &widgets.drawing_area, &symbol, {
+            let settings = settings.clone();
+            move || profiles_folder(&settings)
+        });
+        widgets
+            .header_bar
+            .pack_end(&build_drawings_menu(&drawing_files, &widgets.main_window));
+        widgets
+            .header_bar
+            .pack_end(&build_go_to_date_menu(&chart, &widgets.drawing_area));
 
         wire_chart_draw(&widgets.drawing_area, chart.clone());
 
@@ -306,10 +387,237 @@ impl SimpleComponent for AppModel {
         if chart.has_rsi_panel() {
             indicator_state
                 .borrow_mut()
-                .entry(PanelId(1))
+                .entry(chart.main_panel_id())
                 .or_insert_with(HashSet::new)
                 .insert(IndicatorKind::Rsi);
         }
+        let indicator_chain =
+            std::rc::Rc::new(std::cell::RefCell::new(IndicatorChain::new(chart.clone())));
+        if let Some(study) = IndicatorChain::study_for(IndicatorKind::Rsi) {
+            let mut chain = indicator_chain.borrow_mut();
+            let source = IndicatorSource::Price(PriceField::Close);
+            let origin = chart.main_panel_id();
+            if chain.add(IndicatorKind::Rsi, study, origin, source).is_ok() {
+                let store_ref = store.borrow();
+                chain.refresh(&store_ref.candles, &store_ref.volumes);
+            }
+        }
+
+        let toggle_indicator: IndicatorToggle = {
+            let indicator_state = indicator_state.clone();
+            let indicator_chain = indicator_chain.clone();
+            let ichimoku = ichimoku.clone();
+            let pivots = pivots.clone();
+            let zigzag = zigzag.clone();
+            let auto_fib = auto_fib.clone();
+            let volume_profile = volume_profile.clone();
+            let scripts = scripts.clone();
+            let chart = chart.clone();
+            let store = store.clone();
+            std::rc::Rc::new(move |panel_id, indicator, enabled, source, variant| {
+                let is_overlay = matches!(
+                    indicator,
+                    IndicatorKind::Ichimoku
+                        | IndicatorKind::Pivots
+                        | IndicatorKind::ZigZag
+                        | IndicatorKind::AutoFib
+                        | IndicatorKind::VolumeProfile
+                );
+                if is_overlay {
+                    let active = indicator_state
+                        .borrow()
+                        .get(&panel_id)
+                        .is_some_and(|set| set.contains(&indicator));
+                    if !enabled && !active {
+                        return Err(format!("{} is not on this panel.", indicator.label()));
+                    }
+                    let candles = &store.borrow().candles;
+                    match indicator {
+                        IndicatorKind::Ichimoku if enabled => {
+                            // Drawn on the panel it was added from, unless the
+                            // RSI line pins that panel to 0-100.
+                            let panel = if chart.rsi_panel_id() == Some(panel_id)
+                                && !chart.rsi_panel_has_data()
+                            {
+                                panel_id
+                            } else {
+                                chart.main_panel_id()
+                            };
+                            for series in ichimoku.series_ids() {
+                                chart.move_series_to_panel(series, panel);
+                            }
+                            ichimoku.enable(candles)
+                        }
+                        IndicatorKind::Ichimoku => ichimoku.disable(),
+                        IndicatorKind::Pivots if enabled => pivots.enable(candles, variant),
+                        IndicatorKind::Pivots => pivots.disable(),
+                        IndicatorKind::ZigZag if enabled => zigzag.enable(candles, variant),
+                        IndicatorKind::ZigZag => zigzag.disable(),
+                        IndicatorKind::AutoFib if enabled => auto_fib.enable(candles, variant),
+                        IndicatorKind::AutoFib => auto_fib.disable(),
+                        IndicatorKind::VolumeProfile if enabled => {
+                            volume_profile.enable(candles, variant)
+                        }
+                        _ => volume_profile.disable(),
+                    }
+                    if enabled {
+                        indicator_state
+                            .borrow_mut()
+                            .entry(panel_id)
+                            .or_insert_with(HashSet::new)
+                            .insert(indicator);
+                    } else if let Some(set) = indicator_state.borrow_mut().get_mut(&panel_id) {
+                        set.remove(&indicator);
+                    }
+                    return Ok(Vec::new());
+                }
+                let Some(study) = IndicatorChain::study_for(indicator) else {
+                    return Err(format!("{} is not available yet.", indicator.label()));
+                };
+                // The RSI line pins the indicator panel to 0-100, so it cannot
+                // share the panel with plots on their own scale.
+                let pins_panel = indicator == IndicatorKind::Rsi
+                    && matches!(source, IndicatorSource::Price(_));
+                if enabled && pins_panel && scripts.has_panel_scripts() {
+                    return Err("RSI not added: scripts are drawn in the indicator panel.".into());
+                }
+                if enabled
+                    && pins_panel
+                    && chart.rsi_panel_has_plots()
+                    && !chart.rsi_panel_has_data()
+                {
+                    return Err(
+                        "RSI not added: the indicator panel holds other oscillators.".into(),
+                    );
+                }
+                let own_scale = matches!(indicator, IndicatorKind::Macd | IndicatorKind::Obv);
+                if enabled && own_scale && chart.rsi_panel_has_data() {
+                    return Err(format!(
+                        "{} not added: the indicator panel is showing RSI.",
+                        indicator.label()
+                    ));
+                }
+                let mut chain = indicator_chain.borrow_mut();
+                let mut removed = Vec::new();
+                if enabled {
+                    if let Err(err) = chain.add(indicator, study, panel_id, source) {
+                        return Err(format!("{} not added: {err}.", indicator.label()));
+                    }
+                    indicator_state
+                        .borrow_mut()
+                        .entry(panel_id)
+                        .or_insert_with(HashSet::new)
+                        .insert(indicator);
+                } else {
+                    let mut state = indicator_state.borrow_mut();
+                    removed = chain.remove(panel_id, indicator);
+                    if removed.is_empty() {
+                        return Err(format!("{} is not on this panel.", indicator.label()));
+                    }
+                    for entry in &removed {
+                        if let Some(set) = state.get_mut(&entry.origin) {
+                            set.remove(&entry.kind);
+                        }
+                    }
+                }
+                let store_ref = store.borrow();
+                chain.refresh(&store_ref.candles, &store_ref.volumes);
+                Ok(removed)
+            })
+        };
+
+        // Adds removed indicators back and returns the new node of the first
+        // one, which feeds the others.
+        let restore_indicators: IndicatorRestore = {
+            let indicator_state = indicator_state.clone();
+            let indicator_chain = indicator_chain.clone();
+            let store = store.clone();
+            let drawing_area = widgets.drawing_area.clone();
+            std::rc::Rc::new(move |removed| {
+                let mut chain = indicator_chain.borrow_mut();
+                let mut state = indicator_state.borrow_mut();
+                let restored = chain.restore(removed);
+                for (origin, kind, _) in &restored {
+                    state.entry(*origin).or_default().insert(*kind);
+                }
+                let store_ref = store.borrow();
+                chain.refresh(&store_ref.candles, &store_ref.volumes);
+                drawing_area.queue_draw();
+                restored.first().map(|(_, _, node)| *node)
+            })
+        };
+
+        // Indicator changes made from the modal, recorded as undo steps.
+        // Changes that did not happen are not recorded; the modal shows why.
+        let undoable_toggle: IndicatorChange = {
+            let toggle_indicator = toggle_indicator.clone();
+            let restore_indicators = restore_indicators.clone();
+            let chart = chart.clone();
+            let drawing_area = widgets.drawing_area.clone();
+            std::rc::Rc::new(move |panel_id, indicator, enabled, source, variant| {
+                let removed = toggle_indicator(panel_id, indicator, enabled, source, variant)?;
+                let toggle = |on: bool| -> std::rc::Rc<dyn Fn()> {
+                    let toggle_indicator = toggle_indicator.clone();
+                    let drawing_area = drawing_area.clone();
+                    std::rc::Rc::new(move || {
+                        let _ = toggle_indicator(panel_id, indicator, on, source, variant);
+                        drawing_area.queue_draw();
+                    })
+                };
+                let undo: std::rc::Rc<dyn Fn()> = if removed.is_empty() {
+                    toggle(!enabled)
+                } else {
+                    let restore_indicators = restore_indicators.clone();
+                    std::rc::Rc::new(move || {
+                        restore_indicators(&removed);
+                    })
+                };
+                chart.record_edit(undo, toggle(enabled));
+                Ok(())
+            })
+        };
+
+        let open_indicator_modal: std::rc::Rc<dyn Fn(PanelId)> = {
+            let indicator_modal = indicator_modal.clone();
+            let indicator_state = indicator_state.clone();
+            let indicator_chain = indicator_chain.clone();
+            let undoable_toggle = undoable_toggle.clone();
+            let scripts = scripts.clone();
+            let store = store.clone();
+            std::rc::Rc::new(move |panel| {
+                let active = indicator_state
+                    .borrow()
+                    .get(&panel)
+                    .cloned()
+                    .unwrap_or_default();
+                let sources = indicator_chain.borrow().sources();
+                configure_indicator_modal(&indicator_modal, panel, &active, &sources, {
+                    let undoable_toggle = undoable_toggle.clone();
+                    move |panel_id, indicator, enabled, source, variant| {
+                        undoable_toggle(panel_id, indicator, enabled, source, variant)
+                    }
+                });
+                configure_script_section(
+                    &indicator_modal,
+                    &scripts.summaries(),
+                    {
+                        let scripts = scripts.clone();
+                        let store = store.clone();
+                        move |source| {
+                            let store_ref = store.borrow();
+                            scripts
+                                .add(&source, &store_ref.candles, &store_ref.volumes)
+                                .map_err(|err| err.to_string())
+                        }
+                    },
+                    {
+                        let scripts = scripts.clone();
+                        move |id| scripts.remove(id)
+                    },
+                );
+                indicator_modal.window.present();
+            })
+        };
 
         let panel_menu = gtk::Popover::new();
         panel_menu.set_parent(&widgets.drawing_area);
@@ -337,67 +645,135 @@ impl SimpleComponent for AppModel {
         });
 
         menu_indicators.connect_clicked({
-            let indicator_modal = indicator_modal.clone();
-            let indicator_state = indicator_state.clone();
-            let chart = chart.clone();
-            let store = store.clone();
+            let open_indicator_modal = open_indicator_modal.clone();
             let panel_menu = panel_menu.clone();
             let panel_menu_state = panel_menu_state.clone();
             move |_| {
-                let panel = panel_menu_state.get();
-                let active = indicator_state
-                    .borrow()
-                    .get(&panel)
-                    .cloned()
-                    .unwrap_or_default();
-                configure_indicator_modal(&indicator_modal, panel, &active, {
-                    let indicator_state = indicator_state.clone();
-                    let chart = chart.clone();
-                    let store = store.clone();
-                    move |panel_id, indicator, enabled| {
-                        match indicator {
-                            IndicatorKind::Rsi => {
-                                if enabled {
-                                    let data = store.borrow().rsi.clone();
-                                    chart.set_rsi_panel("RSI".to_string(), data);
-                                    indicator_state
-                                        .borrow_mut()
-                                        .entry(panel_id)
-                                        .or_insert_with(HashSet::new)
-                                        .insert(IndicatorKind::Rsi);
-                                } else {
-                                    chart.clear_rsi_panel();
-                                    if let Some(set) = indicator_state.borrow_mut().get_mut(&panel_id) {
-                                        set.remove(&IndicatorKind::Rsi);
-                                    }
-                                }
-                            }
-                            _ => {
-                                eprintln!("Indicator {indicator:?} not wired yet.");
-                            }
-                        }
-                    }
-                });
-                indicator_modal.window.present();
+                open_indicator_modal(panel_menu_state.get());
                 panel_menu.popdown();
             }
         });
 
+        let alerts = PriceAlerts::new(
+            &chart,
+            &candle_series,
+            &widgets.main_window,
+            store.borrow().candles.last().map(|candle| candle.close),
+        );
         let hist_follow = settings.series.hist_follow_candle_colors.clone();
         let (sender, receiver) = std::sync::mpsc::channel::<DataEvent>();
         let receiver = std::rc::Rc::new(std::cell::RefCell::new(receiver));
         let lazy_loader = std::rc::Rc::new(std::cell::RefCell::new(LazyLoader::new(500, 80)));
 
-        glib::timeout_add_local(Duration::from_millis(50), {
-            let store = store.clone();
+        let reload_series: ReplayReload = {
             let candle_series = candle_series.clone();
             let line_series = line_series.clone();
             let hist_series = hist_series.clone();
+            let ichimoku = ichimoku.clone();
+            let pivots = pivots.clone();
+            let zigzag = zigzag.clone();
+            let auto_fib = auto_fib.clone();
+            let indicator_chain = indicator_chain.clone();
+            let scripts = scripts.clone();
             let chart = chart.clone();
             let drawing_area = widgets.drawing_area.clone();
-            let lazy_loader = lazy_loader.clone();
             let hist_follow = hist_follow.clone();
+            let alerts = alerts.clone();
+            std::rc::Rc::new(move |store_ref: &MarketStore| {
+                alerts.rebase(store_ref.candles.last().map(|candle| candle.close));
+                let candles = store_ref.candles.clone();
+                candle_series.set_data(candles.clone());
+                let line_points: Vec<LinePoint> = candles
+                    .iter()
+                    .map(|candle| LinePoint {
+                        time: candle.time,
+                        value: candle.close,
+                    })
+                    .collect();
+                line_series.set_data(line_points);
+                let volumes = histogram_points_for_chart(
+                    &store_ref.candles,
+                    &store_ref.volumes,
+                    hist_follow.state(),
+                    chart.style(),
+                );
+                hist_series.set_data(volumes);
+                ichimoku.refresh(&store_ref.candles);
+                pivots.refresh(&store_ref.candles);
+                zigzag.refresh(&store_ref.candles);
+                auto_fib.refresh(&store_ref.candles);
+                indicator_chain
+                    .borrow_mut()
+                    .refresh(&store_ref.candles, &store_ref.volumes);
+                scripts.refresh(&store_ref.candles, &store_ref.volumes);
+                drawing_area.queue_draw();
+            })
+        };
+        let update_bar: ReplayBar = {
+            let candle_series = candle_series.clone();
+            let line_series = line_series.clone();
+            let hist_series = hist_series.clone();
+            let drawing_area = widgets.drawing_area.clone();
+            let hist_follow = hist_follow.clone();
+            let alerts = alerts.clone();
+            std::rc::Rc::new(move |store_ref: &MarketStore, update: &KlineApplyResult| {
+                candle_series.update(update.candle.clone());
+                // Replayed bars are history; only live prices trigger alerts.
+                if !store_ref.is_replaying() {
+                    alerts.check(update.candle.close);
+                }
+                line_series.update(LinePoint {
+                    time: update.candle.time,
+                    value: update.candle.close,
+                });
+                let mut volume = update.volume.clone();
+                if !hist_follow.state() {
+                    volume.color = None;
+                }
+                hist_series.update(volume);
+                drawing_area.queue_draw();
+            })
+        };
+        let refresh_indicators: ReplayRefresh = {
+            let ichimoku = ichimoku.clone();
+            let pivots = pivots.clone();
+            let zigzag = zigzag.clone();
+            let auto_fib = auto_fib.clone();
+            let indicator_chain = indicator_chain.clone();
+            let scripts = scripts.clone();
+            let drawing_area = widgets.drawing_area.clone();
+            std::rc::Rc::new(move |store_ref: &MarketStore| {
+                ichimoku.refresh(&store_ref.candles);
+                pivots.refresh(&store_ref.candles);
+                zigzag.refresh(&store_ref.candles);
+                auto_fib.refresh(&store_ref.candles);
+                indicator_chain
+                    .borrow_mut()
+                    .refresh(&store_ref.candles, &store_ref.volumes);
+                scripts.refresh(&store_ref.candles, &store_ref.volumes);
+                drawing_area.queue_draw();
+            })
+        };
+        let replay = Replay::new(
+            store.clone(),
+            &chart,
+            reload_series.clone(),
+            update_bar.clone(),
+            refresh_indicators.clone(),
+        );
+        replay.set_detail_fetcher({
+            let sender = sender.clone();
+            move |store_ref: &MarketStore| spawn_replay_detail_fetch(store_ref, sender.clone())
+        });
+        widgets.header_bar.pack_end(&build_replay_menu(&replay));
+
+        glib::timeout_add_local(Duration::from_millis(50), {
+            let store = store.clone();
+            let chart = chart.clone();
+            let drawing_area = widgets.drawing_area.clone();
+            let lazy_loader = lazy_loader.clone();
             let receiver = receiver.clone();
+            let replay = replay.clone();
             move || {
                 let mut drained = false;
                 while let Ok(event) = receiver.borrow_mut().try_recv() {
@@ -407,24 +783,7 @@ impl SimpleComponent for AppModel {
                             let mut store_ref = store.borrow_mut();
                             let loaded_any = store_ref.prepend_batch(batch);
                             if loaded_any {
-                                let candles = store_ref.candles.clone();
-                                candle_series.set_data(candles.clone());
-                                let line_points: Vec<LinePoint> = candles
-                                    .iter()
-                                    .map(|candle| LinePoint {
-                                        time: candle.time,
-                                        value: candle.close,
-                                    })
-                                    .collect();
-                                line_series.set_data(line_points);
-                                let volumes = histogram_points_for_chart(
-                                    &store_ref.candles,
-                                    &store_ref.volumes,
-                                    hist_follow.state(),
-                                    chart.style(),
-                                );
-                                hist_series.set_data(volumes);
-                                chart.set_rsi_panel_data(store_ref.rsi.clone());
+                                reload_series(&store_ref);
                             }
                             lazy_loader.borrow_mut().finish_success(loaded_any);
                             drawing_area.queue_draw();
@@ -432,24 +791,20 @@ impl SimpleComponent for AppModel {
                         DataEvent::Kline(event) => {
                             let style = chart.style();
                             let mut store_ref = store.borrow_mut();
-                            let update = store_ref.apply_kline(event, &style);
-                            candle_series.update(update.candle.clone());
-                            line_series.update(LinePoint {
-                                time: update.candle.time,
-                                value: update.candle.close,
-                            });
-                            let mut volume = update.volume.clone();
-                            if !hist_follow.state() {
-                                volume.color = None;
+                            if let Some(update) = store_ref.apply_kline(event, &style) {
+                                update_bar(&store_ref, &update);
+                                refresh_indicators(&store_ref);
                             }
-                            hist_series.update(volume);
-                            chart.set_rsi_panel_data(store_ref.rsi.clone());
-                            drawing_area.queue_draw();
                         }
                         DataEvent::LoadFailed(err) => {
                             lazy_loader.borrow_mut().finish_failure();
                             eprintln!("Lazy load failed: {err}");
                         }
+                        DataEvent::ReplayDetail(batch) => {
+                            if store.borrow_mut().add_replay_detail(batch) {
+                                replay.retime();
+                            }
+                        }
                     }
                 }
                 if drained {
@@ -476,25 +831,21 @@ impl SimpleComponent for AppModel {
         let auto_scale_handler = {
             let left_button = widgets.auto_scale_left.clone();
             let right_button = widgets.auto_scale_right.clone();
-            let left_switch = settings.price_scale.left.auto_scale.clone();
-            let right_switch = settings.price_scale.right.auto_scale.clone();
-            std::rc::Rc::new(move |side: PriceScale| match side {
-                PriceScale::Left => {
-                    if left_button.is_active() {
-                        left_button.set_active(false);
-                    }
-                    if left_switch.state() {
-                        left_switch.set_state(false);
-                    }
-                }
-                PriceScale::Right => {
-                    if right_button.is_active() {
-                        right_button.set_active(false);
+            let settings = settings.clone();
+            std::rc::Rc::new(move |side: PriceScale| {
+                let (button, switch) = match side {
+                    PriceScale::Left => (&left_button, &settings.price_scale.left.auto_scale),
+                    PriceScale::Right => (&right_button, &settings.price_scale.right.auto_scale),
+                };
+                // Turned off by the chart, not edited in the settings window.
+                without_settings_history(&settings, || {
+                    if button.is_active() {
+                        button.set_active(false);
                     }
-                    if right_switch.state() {
-                        right_switch.set_state(false);
+                    if switch.state() {
+                        switch.set_state(false);
                     }
-                }
+                });
             })
         };
 
@@ -509,51 +860,74 @@ impl SimpleComponent for AppModel {
             })
         };
 
-        let panel_control_handler = {
-            let indicator_modal = indicator_modal.clone();
+        let remove_indicator_panel: IndicatorPanelRemoval = {
             let indicator_state = indicator_state.clone();
+            let indicator_chain = indicator_chain.clone();
+            let scripts = scripts.clone();
             let chart = chart.clone();
+            let drawing_area = widgets.drawing_area.clone();
+            std::rc::Rc::new(move |panel| {
+                let removed = indicator_chain.borrow_mut().remove_panel(panel);
+                let removed_scripts = scripts.remove_panel_scripts();
+                let moved_series = chart.panel_series(panel);
+                chart.clear_rsi_panel();
+                let mut state = indicator_state.borrow_mut();
+                for entry in &removed {
+                    if let Some(set) = state.get_mut(&entry.origin) {
+                        set.remove(&entry.kind);
+                    }
+                }
+                drawing_area.queue_draw();
+                (removed, removed_scripts, moved_series)
+            })
+        };
+
+        // Puts an indicator panel closed by mistake back. The panel comes
+        // back with a new id, so indicators added from it move to the new one.
+        let restore_indicator_panel: IndicatorPanelRestore = {
+            let restore_indicators = restore_indicators.clone();
+            let scripts = scripts.clone();
             let store = store.clone();
+            let chart = chart.clone();
+            std::rc::Rc::new(move |panel: PanelId, content: &RemovedPanelContent| {
+                let (removed, removed_scripts, moved_series) = content;
+                if (!removed.is_empty() || !moved_series.is_empty()) && !chart.has_rsi_panel() {
+                    chart.set_rsi_panel("RSI".to_string(), Vec::new());
+                }
+                let new_panel = chart.rsi_panel_id().unwrap_or(panel);
+                for &series in moved_series {
+                    chart.move_series_to_panel(series, new_panel);
+                }
+                let removed: Vec<RemovedIndicator> = removed
+                    .iter()
+                    .cloned()
+                    .map(|mut entry| {
+                        if entry.origin == panel {
+                            entry.origin = new_panel;
+                        }
+                        entry
+                    })
+                    .collect();
+                restore_indicators(&removed);
+                let store_ref = store.borrow();
+                for source in removed_scripts {
+                    if let Err(err) = scripts.add(source, &store_ref.candles, &store_ref.volumes) {
+                        eprintln!("Script not restored: {err}");
+                    }
+                }
+            })
+        };
+
+        let panel_control_handler = {
+            let open_indicator_modal = open_indicator_modal.clone();
+            let remove_indicator_panel = remove_indicator_panel.clone();
+            let restore_indicator_panel = restore_indicator_panel.clone();
+            let chart = chart.clone();
             let drawing_area = widgets.drawing_area.clone();
             std::rc::Rc::new(move |panel: PanelId, action: PanelControlAction| {
                 match action {
                     PanelControlAction::AddAbove | PanelControlAction::AddBelow => {
-                        let active = indicator_state
-                            .borrow()
-                            .get(&panel)
-                            .cloned()
-                            .unwrap_or_default();
-                        configure_indicator_modal(&indicator_modal, panel, &active, {
-                            let indicator_state = indicator_state.clone();
-                            let chart = chart.clone();
-                            let store = store.clone();
-                            move |panel_id, indicator, enabled| {
-                                match indicator {
-                                    IndicatorKind::Rsi => {
-                                        if enabled {
-                                            let data = store.borrow().rsi.clone();
-                                            chart.set_rsi_panel("RSI".to_string(), data);
-                                            indicator_state
-                                                .borrow_mut()
-                                                .entry(panel_id)
-                                                .or_insert_with(HashSet::new)
-                                                .insert(IndicatorKind::Rsi);
-                                        } else {
-                                            chart.clear_rsi_panel();
-                                            if let Some(set) =
-                                                indicator_state.borrow_mut().get_mut(&panel_id)
-                                            {
-                                                set.remove(&IndicatorKind::Rsi);
-                                            }
-                                        }
-                                    }
-                                    _ => {
-                                        eprintln!("Indicator {indicator:?} not wired yet.");
-                                    }
-                                }
-                            }
-                        });
-                        indicator_modal.window.present();
+                        open_indicator_modal(panel);
                     }
                     PanelControlAction::ToggleVisible => {
                         chart.toggle_panel_visibility(panel);
@@ -565,10 +939,18 @@ impl SimpleComponent for AppModel {
                     }
                     PanelControlAction::Remove => {
                         if matches!(chart.panel_role(panel), Some(PanelRole::Indicator)) {
-                            chart.clear_rsi_panel();
-                            if let Some(set) = indicator_state.borrow_mut().get_mut(&panel) {
-                                set.remove(&IndicatorKind::Rsi);
-                            }
+                            let removed = remove_indicator_panel(panel);
+                            let restore_indicator_panel = restore_indicator_panel.clone();
+                            let remove_indicator_panel = remove_indicator_panel.clone();
+                            let redo_chart = chart.clone();
+                            chart.record_edit(
+                                std::rc::Rc::new(move || restore_indicator_panel(panel, &removed)),
+                                std::rc::Rc::new(move || {
+                                    if let Some(current) = redo_chart.rsi_panel_id() {
+                                        remove_indicator_panel(current);
+                                    }
+                                }),
+                            );
                         } else {
                             chart.remove_panel(panel);
                         }
@@ -578,12 +960,184 @@ impl SimpleComponent for AppModel {
             })
         };
 
+        // Series are removed through the indicator, overlay or script that
+        // draws them, so the indicator list and the undo history stay in step.
+        let remove_series: SeriesRemoval = {
+            let undoable_toggle = undoable_toggle.clone();
+            let restore_indicators = restore_indicators.clone();
+            let indicator_state = indicator_state.clone();
+            let indicator_chain = indicator_chain.clone();
+            let scripts = scripts.clone();
+            let ichimoku = ichimoku.clone();
+            let pivots = pivots.clone();
+            let zigzag = zigzag.clone();
+            let auto_fib = auto_fib.clone();
+            let chart = chart.clone();
+            let store = store.clone();
+            let drawing_area = widgets.drawing_area.clone();
+            std::rc::Rc::new(move |series| -> Option<std::rc::Rc<dyn Fn()>> {
+                let overlay = if ichimoku.owns_series(series) {
+                    Some((IndicatorKind::Ichimoku, 0))
+                } else if pivots.owns_series(series) {
+                    pivots
+                        .variant()
+                        .map(|variant| (IndicatorKind::Pivots, variant))
+                } else if zigzag.owns_series(series) {
+                    zigzag
+                        .variant()
+                        .map(|variant| (IndicatorKind::ZigZag, variant))
+                } else if auto_fib.owns_series(series) {
+                    auto_fib
+                        .variant()
+                        .map(|variant| (IndicatorKind::AutoFib, variant))
+                } else {
+                    None
+                };
+                if let Some((kind, variant)) = overlay {
+                    let panel = indicator_state
+                        .borrow()
+                        .iter()
+                        .find(|(_, kinds)| kinds.contains(&kind))
+                        .map(|(panel, _)| *panel)
+                        .unwrap_or_else(|| chart.main_panel_id());
+                    let undoable_toggle = undoable_toggle.clone();
+                    let source = IndicatorSource::Price(PriceField::Close);
+                    return Some(std::rc::Rc::new(move || {
+                        let _ = undoable_toggle(panel, kind, false, source, variant);
+                    }));
+                }
+                if indicator_chain.borrow().owns_series(series) {
+                    let restore_indicators = restore_indicators.clone();
+                    let indicator_state = indicator_state.clone();
+                    let indicator_chain = indicator_chain.clone();
+                    let chart = chart.clone();
+                    let store = store.clone();
+                    let drawing_area = drawing_area.clone();
+                    // Takes `removed` off the indicator list and redraws the chain.
+                    let forget = {
+                        let indicator_chain = indicator_chain.clone();
+                        move |removed: &[RemovedIndicator]| {
+                            {
+                                let mut state = indicator_state.borrow_mut();
+                                for entry in removed {
+                                    if let Some(set) = state.get_mut(&entry.origin) {
+                                        set.remove(&entry.kind);
+                                    }
+                                }
+                            }
+                            let store_ref = store.borrow();
+                            indicator_chain
+                                .borrow_mut()
+                                .refresh(&store_ref.candles, &store_ref.volumes);
+                        }
+                    };
+                    return Some(std::rc::Rc::new(move || {
+                        let removed = indicator_chain.borrow_mut().remove_series(series);
+                        if removed.is_empty() {
+                            return;
+                        }
+                        forget(&removed);
+                        // Restoring gives the indicators new nodes, so redo
+                        // removes the node the last undo added back, along
+                        // with the indicators fed by it.
+                        let current = std::rc::Rc::new(std::cell::RefCell::new(removed));
+                        let node = std::rc::Rc::new(std::cell::Cell::new(None));
+                        let undo: std::rc::Rc<dyn Fn()> = {
+                            let restore_indicators = restore_indicators.clone();
+                            let current = current.clone();
+                            let node = node.clone();
+                            std::rc::Rc::new(move || {
+                                node.set(restore_indicators(&current.borrow()));
+                            })
+                        };
+                        let redo: std::rc::Rc<dyn Fn()> = {
+                            let indicator_chain = indicator_chain.clone();
+                            let forget = forget.clone();
+                            let drawing_area = drawing_area.clone();
+                            std::rc::Rc::new(move || {
+                                let Some(restored) = node.take() else {
+                                    return;
+                                };
+                                let removed = indicator_chain.borrow_mut().remove_node(restored);
+                                forget(&removed);
+                                *current.borrow_mut() = removed;
+                                drawing_area.queue_draw();
+                            })
+                        };
+                        chart.record_edit(undo, redo);
+                    }));
+                }
+                let (id, source) = scripts.script_for_series(series)?;
+                let scripts = scripts.clone();
+                let chart = chart.clone();
+                let store = store.clone();
+                let drawing_area = drawing_area.clone();
+                Some(std::rc::Rc::new(move || {
+                    scripts.remove(id);
+                    // Adding the script back gives it a new id.
+                    let current = std::rc::Rc::new(std::cell::Cell::new(id));
+                    let undo: std::rc::Rc<dyn Fn()> = {
+                        let scripts = scripts.clone();
+                        let store = store.clone();
+                        let current = current.clone();
+                        let source = source.clone();
+                        let drawing_area = drawing_area.clone();
+                        std::rc::Rc::new(move || {
+                            let store_ref = store.borrow();
+                            match scripts.add(&source, &store_ref.candles, &store_ref.volumes) {
+                                Ok(summary) => current.set(summary.id),
+                                Err(err) => eprintln!("Script not restored: {err}"),
+                            }
+                            drawing_area.queue_draw();
+                        })
+                    };
+                    let redo: std::rc::Rc<dyn Fn()> = {
+                        let scripts = scripts.clone();
+                        let drawing_area = drawing_area.clone();
+                        std::rc::Rc::new(move || {
+                            scripts.remove(current.get());
+                            drawing_area.queue_draw();
+                        })
+                    };
+                    chart.record_edit(undo, redo);
+                }))
+            })
+        };
+        let context_menu = ChartContextMenu::new(
+            &chart,
+            &widgets.drawing_area,
+            &drawing_tools,
+            &alerts,
+            &settings,
+            &widgets.settings_window,
+            &widgets.settings_stack,
+            remove_series,
+        );
         install_interactions(
             &widgets.drawing_area,
             chart.clone(),
             Some(auto_scale_handler),
             Some(panel_menu_handler),
             Some(panel_control_handler),
+            Some(drawing_tools.clone()),
+            Some(std::rc::Rc::new(move |x, y| context_menu.open(x, y))),
+        );
+        install_undo_shortcuts(&widgets.main_window, &widgets.drawing_area, chart.clone());
+        let key_bindings = std::rc::Rc::new(std::cell::RefCell::new(KeyBindings::default()));
+        let autoscale_reset_handler = {
+            let left_button = widgets.auto_scale_left.clone();
+            let right_button = widgets.auto_scale_right.clone();
+            std::rc::Rc::new(move |side: PriceScale| match side {
+                PriceScale::Left => left_button.set_active(true),
+                PriceScale::Right => right_button.set_active(true),
+            })
+        };
+        install_keyboard_navigation(
+            &widgets.drawing_area,
+            chart.clone(),
+            key_bindings.clone(),
+            Some(drawing_tools),
+            Some(autoscale_reset_handler),
         );
         wire_settings_panel(
             &widgets.drawing_area,
@@ -593,9 +1147,14 @@ impl SimpleComponent for AppModel {
             hist_series.clone(),
             settings,
             store.clone(),
+            scripts.clone(),
+            key_bindings,
             widgets.auto_scale_left.clone(),
             widgets.auto_scale_right.clone(),
         );
+        drawing_files.load_and_autosave();
+        // Setup and loading saved drawings are not edits to undo.
+        chart.clear_history();
 
         ComponentParts { model, widgets }
     }
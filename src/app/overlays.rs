@@ -0,0 +1,419 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use crate::chart::{
+    Candle, CandlestickSeriesApi, ChartApi, CloudSeriesApi, Color, HistogramSeriesApi, LinePoint,
+    LineSeriesApi, PriceLineOptions, PriceScale, PriceSegment, VolumeProfileApi,
+    VolumeProfileOptions, VolumeProfileRange,
+};
+use crate::indicators::ichimoku::compute_ichimoku;
+use crate::indicators::pivots::{compute_pivots, PivotKind, PivotPeriod};
+use crate::indicators::zigzag::{compute_zigzag, fib_retracement, ZigZagThreshold};
+use time::{Duration, OffsetDateTime};
+
+const TENKAN_PERIOD: usize = 9;
+const KIJUN_PERIOD: usize = 26;
+const SENKOU_B_PERIOD: usize = 52;
+const DISPLACEMENT: usize = 26;
+
+#[derive(Clone)]
+pub struct IchimokuOverlay {
+    cloud: CloudSeriesApi,
+    tenkan: LineSeriesApi,
+    kijun: LineSeriesApi,
+    senkou_a: LineSeriesApi,
+    senkou_b: LineSeriesApi,
+    chikou: LineSeriesApi,
+    enabled: Rc<Cell<bool>>,
+}
+
+impl IchimokuOverlay {
+    pub fn new(chart: &ChartApi) -> Self {
+        let cloud = chart.add_cloud_series();
+        cloud.set_price_scale(PriceScale::Right);
+        cloud.set_fill_colors(Color::new(0.26, 0.63, 0.28), Color::new(0.96, 0.26, 0.21));
+        cloud.set_fill_alpha(0.18);
+
+        let line = |color: Color, width: f64| {
+            let series = chart.add_line_series();
+            series.set_price_scale(PriceScale::Right);
+            series.set_color(color);
+            series.set_line_width(width);
+            series.set_price_line_visible(false);
+            series.set_last_value_visible(false);
+            series
+        };
+
+        Self {
+            cloud,
+            tenkan: line(Color::new(0.16, 0.38, 1.0), 1.5),
+            kijun: line(Color::new(0.72, 0.11, 0.11), 1.5),
+            senkou_a: line(Color::new(0.65, 0.84, 0.65), 1.0),
+            senkou_b: line(Color::new(0.94, 0.6, 0.6), 1.0),
+            chikou: line(Color::new(0.26, 0.63, 0.28), 1.0),
+            enabled: Rc::new(Cell::new(false)),
+        }
+    }
+
+    pub fn enable(&self, candles: &[Candle]) {
+        self.enabled.set(true);
+        self.refresh(candles);
+    }
+
+    pub fn disable(&self) {
+        self.enabled.set(false);
+        self.cloud.set_data(Vec::new());
+        self.tenkan.set_data(Vec::new());
+        self.kijun.set_data(Vec::new());
+        self.senkou_a.set_data(Vec::new());
+        self.senkou_b.set_data(Vec::new());
+        self.chikou.set_data(Vec::new());
+    }
+
+    /// Ids of the cloud and the lines.
+    pub fn series_ids(&self) -> [usize; 6] {
+        [
+            self.cloud.series_id(),
+            self.tenkan.series_id(),
+            self.kijun.series_id(),
+            self.senkou_a.series_id(),
+            self.senkou_b.series_id(),
+            self.chikou.series_id(),
+        ]
+    }
+
+    /// Whether series `series` is one of the lines or the cloud drawn while
+    /// enabled.
+    pub fn owns_series(&self, series: usize) -> bool {
+        self.enabled.get() && self.series_ids().contains(&series)
+    }
+
+    pub fn refresh(&self, candles: &[Candle]) {
+        if !self.enabled.get() {
+            return;
+        }
+        let series = compute_ichimoku(
+            candles,
+            TENKAN_PERIOD,
+            KIJUN_PERIOD,
+            SENKOU_B_PERIOD,
+            DISPLACEMENT,
+        );
+        self.cloud.set_data(series.cloud);
+        self.tenkan.set_data(series.tenkan);
+        self.kijun.set_data(series.kijun);
+        self.senkou_a.set_data(series.senkou_a);
+        self.senkou_b.set_data(series.senkou_b);
+        self.chikou.set_data(series.chikou);
+    }
+}
+
+pub const PIVOT_VARIANTS: &[(&str, PivotKind, PivotPeriod)] = &[
+    ("Classic, daily", PivotKind::Classic, PivotPeriod::Day),
+    ("Classic, weekly", PivotKind::Classic, PivotPeriod::Week),
+    ("Classic, monthly", PivotKind::Classic, PivotPeriod::Month),
+    ("Fibonacci, daily", PivotKind::Fibonacci, PivotPeriod::Day),
+    ("Fibonacci, weekly", PivotKind::Fibonacci, PivotPeriod::Week),
+    (
+        "Fibonacci, monthly",
+        PivotKind::Fibonacci,
+        PivotPeriod::Month,
+    ),
+    ("Camarilla, daily", PivotKind::Camarilla, PivotPeriod::Day),
+    ("Camarilla, weekly", PivotKind::Camarilla, PivotPeriod::Week),
+    (
+        "Camarilla, monthly",
+        PivotKind::Camarilla,
+        PivotPeriod::Month,
+    ),
+    ("Woodie, daily", PivotKind::Woodie, PivotPeriod::Day),
+    ("Woodie, weekly", PivotKind::Woodie, PivotPeriod::Week),
+    ("Woodie, monthly", PivotKind::Woodie, PivotPeriod::Month),
+];
+
+pub const ZIGZAG_VARIANTS: &[(&str, ZigZagThreshold)] = &[
+    ("5%", ZigZagThreshold::Percent(5.0)),
+    ("2%", ZigZagThreshold::Percent(2.0)),
+    ("10%", ZigZagThreshold::Percent(10.0)),
+    (
+        "ATR(14) x 3",
+        ZigZagThreshold::Atr {
+            period: 14,
+            multiplier: 3.0,
+        },
+    ),
+];
+
+const LEVEL_LABEL_ALPHA: f64 = 0.7;
+
+/// Segments need a series to hang off; this one carries no data of its own.
+fn segment_carrier(chart: &ChartApi) -> LineSeriesApi {
+    let series = chart.add_line_series();
+    series.set_price_scale(PriceScale::Right);
+    series.set_price_line_visible(false);
+    series.set_last_value_visible(false);
+    series
+}
+
+fn level_segment(
+    start: OffsetDateTime,
+    end: OffsetDateTime,
+    price: f64,
+    color: Color,
+    title: String,
+) -> PriceSegment {
+    PriceSegment {
+        start,
+        end,
+        options: PriceLineOptions {
+            price,
+            color,
+            line_opacity: 0.8,
+            axis_label_background_alpha: LEVEL_LABEL_ALPHA,
+            axis_label_padding: 2.0,
+            title: Some(title),
+            ..PriceLineOptions::default()
+        },
+    }
+}
+
+#[derive(Clone)]
+pub struct PivotOverlay {
+    carrier: LineSeriesApi,
+    variant: Rc<Cell<Option<usize>>>,
+}
+
+impl PivotOverlay {
+    pub fn new(chart: &ChartApi) -> Self {
+        Self {
+            carrier: segment_carrier(chart),
+            variant: Rc::new(Cell::new(None)),
+        }
+    }
+
+    pub fn enable(&self, candles: &[Candle], variant: usize) {
+        self.variant
+            .set(Some(variant.min(PIVOT_VARIANTS.len() - 1)));
+        self.refresh(candles);
+    }
+
+    pub fn disable(&self) {
+        self.variant.set(None);
+        self.carrier.set_price_segments(Vec::new());
+    }
+
+    pub fn variant(&self) -> Option<usize> {
+        self.variant.get()
+    }
+
+    /// Whether series `series` carries the levels drawn while enabled.
+    pub fn owns_series(&self, series: usize) -> bool {
+        self.variant.get().is_some() && self.carrier.series_id() == series
+    }
+
+    pub fn refresh(&self, candles: &[Candle]) {
+        let Some(variant) = self.variant.get() else {
+            return;
+        };
+        let (_, kind, period) = PIVOT_VARIANTS[variant];
+        let segments = compute_pivots(candles, kind, period)
+            .into_iter()
+            .flat_map(|set| {
+                set.levels.into_iter().map(move |level| {
+                    let color = match level.name.as_bytes()[0] {
+                        b'R' => Color::new(0.94, 0.33, 0.31),
+                        b'S' => Color::new(0.26, 0.7, 0.4),
+                        _ => Color::new(0.96, 0.65, 0.14),
+                    };
+                    level_segment(
+                        set.start,
+                        set.end,
+                        level.price,
+                        color,
+                        level.name.to_string(),
+                    )
+                })
+            })
+            .collect();
+        self.carrier.set_price_segments(segments);
+    }
+}
+
+#[derive(Clone)]
+pub struct ZigZagOverlay {
+    line: LineSeriesApi,
+    variant: Rc<Cell<Option<usize>>>,
+}
+
+impl ZigZagOverlay {
+    pub fn new(chart: &ChartApi) -> Self {
+        let line = chart.add_line_series();
+        line.set_price_scale(PriceScale::Right);
+        line.set_color(Color::new(0.98, 0.76, 0.18));
+        line.set_line_width(1.5);
+        line.set_price_line_visible(false);
+        line.set_last_value_visible(false);
+        Self {
+            line,
+            variant: Rc::new(Cell::new(None)),
+        }
+    }
+
+    pub fn enable(&self, candles: &[Candle], variant: usize) {
+        self.variant
+            .set(Some(variant.min(ZIGZAG_VARIANTS.len() - 1)));
+        self.refresh(candles);
+    }
+
+    pub fn disable(&self) {
+        self.variant.set(None);
+        self.line.set_data(Vec::new());
+    }
+
+    pub fn variant(&self) -> Option<usize> {
+        self.variant.get()
+    }
+
+    /// Whether series `series` is the line drawn while enabled.
+    pub fn owns_series(&self, series: usize) -> bool {
+        self.variant.get().is_some() && self.line.series_id() == series
+    }
+
+    pub fn refresh(&self, candles: &[Candle]) {
+        let Some(variant) = self.variant.get() else {
+            return;
+        };
+        let points = compute_zigzag(candles, ZIGZAG_VARIANTS[variant].1)
+            .into_iter()
+            .map(|swing| LinePoint {
+                time: swing.time,
+                value: swing.price,
+            })
+            .collect();
+        self.line.set_data(points);
+    }
+}
+
+/// Fibonacci retracement of the latest ZigZag leg, redrawn as new swings form.
+#[derive(Clone)]
+pub struct AutoFibOverlay {
+    carrier: LineSeriesApi,
+    variant: Rc<Cell<Option<usize>>>,
+}
+
+impl AutoFibOverlay {
+    pub fn new(chart: &ChartApi) -> Self {
+        Self {
+            carrier: segment_carrier(chart),
+            variant: Rc::new(Cell::new(None)),
+        }
+    }
+
+    pub fn enable(&self, candles: &[Candle], variant: usize) {
+        self.variant
+            .set(Some(variant.min(ZIGZAG_VARIANTS.len() - 1)));
+        self.refresh(candles);
+    }
+
+    pub fn disable(&self) {
+        self.variant.set(None);
+        self.carrier.set_price_segments(Vec::new());
+    }
+
+    pub fn variant(&self) -> Option<usize> {
+        self.variant.get()
+    }
+
+    /// Whether series `series` carries the levels drawn while enabled.
+    pub fn owns_series(&self, series: usize) -> bool {
+        self.variant.get().is_some() && self.carrier.series_id() == series
+    }
+
+    pub fn refresh(&self, candles: &[Candle]) {
+        let Some(variant) = self.variant.get() else {
+            return;
+        };
+        let swings = compute_zigzag(candles, ZIGZAG_VARIANTS[variant].1);
+        let (Some(fib), Some(last)) = (fib_retracement(&swings), candles.last()) else {
+            self.carrier.set_price_segments(Vec::new());
+            return;
+        };
+        let color = Color::new(0.47, 0.56, 0.98);
+        let segments = fib
+            .levels
+            .iter()
+            .map(|(ratio, price)| {
+                level_segment(fib.from.time, last.time, *price, color, format!("{ratio}"))
+            })
+            .collect();
+        self.carrier.set_price_segments(segments);
+    }
+}
+
+const FIXED_PROFILE_BARS: usize = 200;
+
+pub const VOLUME_PROFILE_VARIANTS: &[&str] = &[
+    "Visible range",
+    "Daily sessions",
+    "Weekly sessions",
+    "Fixed, last 200 bars",
+];
+
+#[derive(Clone)]
+pub struct VolumeProfileOverlay {
+    candle_series: CandlestickSeriesApi,
+    volume_series: HistogramSeriesApi,
+    profile: Rc<RefCell<Option<VolumeProfileApi>>>,
+}
+
+impl VolumeProfileOverlay {
+    pub fn new(candle_series: &CandlestickSeriesApi, volume_series: &HistogramSeriesApi) -> Self {
+        Self {
+            candle_series: candle_series.clone(),
+            volume_series: volume_series.clone(),
+            profile: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    pub fn enable(&self, candles: &[Candle], variant: usize) {
+        let range = match variant {
+            1 => VolumeProfileRange::Session {
+                length: Duration::days(1),
+            },
+            2 => VolumeProfileRange::Session {
+                length: Duration::weeks(1),
+            },
+            3 => match (
+                candles.get(candles.len().saturating_sub(FIXED_PROFILE_BARS)),
+                candles.last(),
+            ) {
+                (Some(first), Some(last)) => VolumeProfileRange::Fixed {
+                    start: first.time,
+                    end: last.time,
+                },
+                _ => VolumeProfileRange::Visible,
+            },
+            _ => VolumeProfileRange::Visible,
+        };
+        let options = VolumeProfileOptions {
+            range,
+            ..VolumeProfileOptions::default()
+        };
+        let mut profile = self.profile.borrow_mut();
+        match profile.as_ref() {
+            Some(existing) => existing.apply_options(options),
+            None => {
+                *profile = Some(
+                    self.candle_series
+                        .create_volume_profile(&self.volume_series, options),
+                )
+            }
+        }
+    }
+
+    pub fn disable(&self) {
+        if let Some(profile) = self.profile.borrow_mut().take() {
+            profile.remove();
+        }
+    }
+}
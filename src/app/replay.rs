@@ -0,0 +1,398 @@
+use crate::chart::ChartApi;
+use relm4::gtk;
+use relm4::gtk::glib;
+use relm4::gtk::prelude::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use super::go_to_date::parse_date;
+use super::market_data::{KlineApplyResult, MarketStore};
+
+/// Redraws the series and indicators from the whole store, after bars were
+/// hidden or put back.
+pub type ReplayReload = Rc<dyn Fn(&MarketStore)>;
+/// Streams one revealed bar into the series, like a live kline.
+pub type ReplayBar = Rc<dyn Fn(&MarketStore, &KlineApplyResult)>;
+/// Recomputes the indicators from the store after bars were revealed.
+pub type ReplayRefresh = Rc<dyn Fn(&MarketStore)>;
+/// Starts loading lower-timeframe bars for the bars replay just hid.
+pub type ReplayDetailFetch = Rc<dyn Fn(&MarketStore)>;
+type ReplayChange = Rc<dyn Fn(&Replay)>;
+
+/// Shortest time between playback ticks. Faster playback reveals several
+/// updates per tick instead.
+const MIN_TICK_MS: f64 = 16.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReplaySpeed {
+    Normal,
+    Double,
+    Fast,
+}
+
+impl ReplaySpeed {
+    const ALL: [ReplaySpeed; 3] = [ReplaySpeed::Normal, ReplaySpeed::Double, ReplaySpeed::Fast];
+
+    fn label(self) -> &'static str {
+        match self {
+            ReplaySpeed::Normal => "1x",
+            ReplaySpeed::Double => "2x",
+            ReplaySpeed::Fast => "10x",
+        }
+    }
+
+    /// How long each bar stays the latest one during playback.
+    fn bar_duration(self) -> Duration {
+        match self {
+            ReplaySpeed::Normal => Duration::from_millis(1000),
+            ReplaySpeed::Double => Duration::from_millis(500),
+            ReplaySpeed::Fast => Duration::from_millis(100),
+        }
+    }
+}
+
+struct ReplayState {
+    store: Rc<RefCell<MarketStore>>,
+    chart: ChartApi,
+    reload: ReplayReload,
+    apply_bar: ReplayBar,
+    refresh: ReplayRefresh,
+    fetch_detail: Option<ReplayDetailFetch>,
+    intrabar: bool,
+    speed: ReplaySpeed,
+    timer: Option<glib::SourceId>,
+    on_change: Option<ReplayChange>,
+}
+
+/// Bar replay: hides the bars after a chosen time and reveals them again one
+/// at a time, by hand or on a timer, through the same update path as live
+/// data. Live klines arriving meanwhile wait behind the hidden bars. With
+/// intrabar replay each bar forms from lower-timeframe data once loaded.
+#[derive(Clone)]
+pub struct Replay {
+    state: Rc<RefCell<ReplayState>>,
+}
+
+impl Replay {
+    pub fn new(
+        store: Rc<RefCell<MarketStore>>,
+        chart: &ChartApi,
+        reload: ReplayReload,
+        apply_bar: ReplayBar,
+        refresh: ReplayRefresh,
+    ) -> Self {
+        Self {
+            state: Rc::new(RefCell::new(ReplayState {
+                store,
+                chart: chart.clone(),
+                reload,
+                apply_bar,
+                refresh,
+                fetch_detail: None,
+                intrabar: true,
+                speed: ReplaySpeed::Normal,
+                timer: None,
+                on_change: None,
+            })),
+        }
+    }
+
+    /// Called after every start, step, stop or playback change.
+    pub fn set_change_handler(&self, handler: impl Fn(&Replay) + 'static) {
+        self.state.borrow_mut().on_change = Some(Rc::new(handler));
+    }
+
+    /// Sets how lower-timeframe data is loaded for intrabar replay.
+    pub fn set_detail_fetcher(&self, fetch: impl Fn(&MarketStore) + 'static) {
+        self.state.borrow_mut().fetch_detail = Some(Rc::new(fetch));
+    }
+
+    pub fn intrabar(&self) -> bool {
+        self.state.borrow().intrabar
+    }
+
+    /// Whether the next replay started forms bars from lower-timeframe data.
+    pub fn set_intrabar(&self, intrabar: bool) {
+        self.state.borrow_mut().intrabar = intrabar;
+    }
+
+    fn notify(&self) {
+        let handler = self.state.borrow().on_change.clone();
+        if let Some(handler) = handler {
+            handler(self);
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.state.borrow().store.borrow().is_replaying()
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.state.borrow().timer.is_some()
+    }
+
+    /// Number of bars still hidden.
+    pub fn remaining(&self) -> usize {
+        self.state.borrow().store.borrow().replay_remaining()
+    }
+
+    pub fn speed(&self) -> ReplaySpeed {
+        self.state.borrow().speed
+    }
+
+    /// Starts replay from `time` in unix seconds: later bars are hidden and
+    /// the chart scrolls to the last bar left. Returns false when `time` is
+    /// outside the loaded data.
+    pub fn start(&self, time: f64) -> bool {
+        self.stop_timer();
+        let (store, chart, reload) = {
+            let state = self.state.borrow();
+            (
+                state.store.clone(),
+                state.chart.clone(),
+                state.reload.clone(),
+            )
+        };
+        let was_active = store.borrow().is_replaying();
+        let started = store
+            .borrow_mut()
+            .start_replay((time * 1000.0) as i64, &chart.style());
+        if started || was_active {
+            reload(&store.borrow());
+            chart.scroll_to_real_time();
+        }
+        let fetch_detail = {
+            let state = self.state.borrow();
+            state.fetch_detail.clone().filter(|_| state.intrabar)
+        };
+        if let (true, Some(fetch_detail)) = (started, fetch_detail) {
+            fetch_detail(&store.borrow());
+        }
+        self.notify();
+        started
+    }
+
+    /// Reveals the next update, a whole bar or one step of a forming one.
+    /// Returns false when there is none.
+    pub fn step(&self) -> bool {
+        if self.reveal(1) == 0 {
+            return false;
+        }
+        self.notify();
+        true
+    }
+
+    /// Reveals up to `count` updates and then recomputes the indicators
+    /// once. Returns how many were revealed.
+    fn reveal(&self, count: usize) -> usize {
+        let (store, chart, apply_bar, refresh) = {
+            let state = self.state.borrow();
+            (
+                state.store.clone(),
+                state.chart.clone(),
+                state.apply_bar.clone(),
+                state.refresh.clone(),
+            )
+        };
+        let mut store = store.borrow_mut();
+        let style = chart.style();
+        let mut revealed = 0;
+        while revealed < count {
+            let Some(update) = store.replay_step(&style) else {
+                break;
+            };
+            apply_bar(&store, &update);
+            revealed += 1;
+        }
+        if revealed > 0 {
+            refresh(&store);
+        }
+        revealed
+    }
+
+    /// Steps automatically at the current speed until paused or caught up.
+    /// The speed counts whole bars, however many steps each takes to form.
+    pub fn play(&self) {
+        if self.is_playing() || !self.is_active() {
+            return;
+        }
+        let steps = self.state.borrow().store.borrow().replay_steps_per_bar();
+        let step_ms = self.speed().bar_duration().as_secs_f64() * 1000.0 / steps as f64;
+        let per_tick = (MIN_TICK_MS / step_ms).ceil().max(1.0) as usize;
+        let tick = Duration::from_secs_f64(step_ms * per_tick as f64 / 1000.0);
+        let replay = self.clone();
+        let timer = glib::timeout_add_local(tick, move || {
+            if replay.reveal(per_tick) == per_tick {
+                replay.notify();
+                return glib::ControlFlow::Continue;
+            }
+            // Returning Break removes the source, so only forget its id.
+            replay.state.borrow_mut().timer = None;
+            replay.notify();
+            glib::ControlFlow::Break
+        });
+        self.state.borrow_mut().timer = Some(timer);
+        self.notify();
+    }
+
+    pub fn pause(&self) {
+        if self.stop_timer() {
+            self.notify();
+        }
+    }
+
+    pub fn set_speed(&self, speed: ReplaySpeed) {
+        self.state.borrow_mut().speed = speed;
+        self.retime();
+    }
+
+    /// Restarts playback so its pace follows the current steps per bar,
+    /// after lower-timeframe data arrived.
+    pub fn retime(&self) {
+        if self.stop_timer() {
+            self.play();
+        }
+    }
+
+    /// Leaves replay mode and shows every bar again.
+    pub fn stop(&self) {
+        self.stop_timer();
+        let (store, chart, reload) = {
+            let state = self.state.borrow();
+            (
+                state.store.clone(),
+                state.chart.clone(),
+                state.reload.clone(),
+            )
+        };
+        if store.borrow_mut().stop_replay(&chart.style()) {
+            reload(&store.borrow());
+            chart.scroll_to_real_time();
+        }
+        self.notify();
+    }
+
+    fn stop_timer(&self) -> bool {
+        let timer = self.state.borrow_mut().timer.take();
+        match timer {
+            Some(timer) => {
+                timer.remove();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Header menu to start replay from a date and to step, play, pause or leave
+/// it.
+pub fn build_replay_menu(replay: &Replay) -> gtk::MenuButton {
+    let entry = gtk::Entry::new();
+    entry.set_placeholder_text(Some("YYYY-MM-DD HH:MM"));
+    entry.set_width_chars(18);
+    let start = gtk::Button::with_label("Start");
+    let start_row = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+    start_row.append(&entry);
+    start_row.append(&start);
+    let intrabar = gtk::CheckButton::with_label("Form bars from lower timeframe data");
+    intrabar.set_active(replay.intrabar());
+
+    let play = gtk::Button::with_label("Play");
+    let step = gtk::Button::with_label("Step");
+    let labels: Vec<&str> = ReplaySpeed::ALL.iter().map(|speed| speed.label()).collect();
+    let speed = gtk::DropDown::from_strings(&labels);
+    let exit = gtk::Button::with_label("Exit");
+    let controls = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+    controls.append(&play);
+    controls.append(&step);
+    controls.append(&speed);
+    controls.append(&exit);
+    let status = gtk::Label::new(None);
+    status.add_css_class("dim-label");
+    status.set_xalign(0.0);
+
+    let content = gtk::Box::new(gtk::Orientation::Vertical, 6);
+    content.append(&start_row);
+    content.append(&intrabar);
+    content.append(&controls);
+    content.append(&status);
+    let popover = gtk::Popover::new();
+    popover.set_child(Some(&content));
+    let menu = gtk::MenuButton::new();
+    menu.set_label("Replay");
+    menu.set_tooltip_text(Some("Bar replay"));
+    menu.set_popover(Some(&popover));
+
+    let refresh = {
+        let menu = menu.clone();
+        let controls = controls.clone();
+        let play = play.clone();
+        let status = status.clone();
+        move |replay: &Replay| {
+            let active = replay.is_active();
+            controls.set_sensitive(active);
+            play.set_label(if replay.is_playing() { "Pause" } else { "Play" });
+            menu.set_label(if active { "Replaying" } else { "Replay" });
+            status.set_text(&if active {
+                format!("{} bars hidden", replay.remaining())
+            } else {
+                "Pick a date to replay from".to_string()
+            });
+        }
+    };
+    refresh(replay);
+    replay.set_change_handler(refresh);
+
+    let begin = {
+        let replay = replay.clone();
+        let entry = entry.clone();
+        move || {
+            let started = parse_date(entry.text().as_str())
+                .is_some_and(|date| replay.start(date.unix_timestamp() as f64));
+            if started {
+                entry.remove_css_class("error");
+            } else {
+                entry.add_css_class("error");
+            }
+        }
+    };
+    entry.connect_activate({
+        let begin = begin.clone();
+        move |_| begin()
+    });
+    start.connect_clicked(move |_| begin());
+    intrabar.connect_toggled({
+        let replay = replay.clone();
+        move |check| replay.set_intrabar(check.is_active())
+    });
+    play.connect_clicked({
+        let replay = replay.clone();
+        move |_| {
+            if replay.is_playing() {
+                replay.pause();
+            } else {
+                replay.play();
+            }
+        }
+    });
+    step.connect_clicked({
+        let replay = replay.clone();
+        move |_| {
+            replay.step();
+        }
+    });
+    speed.connect_selected_notify({
+        let replay = replay.clone();
+        move |speed| {
+            if let Some(choice) = ReplaySpeed::ALL.get(speed.selected() as usize) {
+                replay.set_speed(*choice);
+            }
+        }
+    });
+    exit.connect_clicked({
+        let replay = replay.clone();
+        move |_| replay.stop()
+    });
+    menu
+}
@@ -0,0 +1,392 @@
+use crate::chart::{
+    Candle, CandlestickSeriesApi, ChartApi, Color, HistogramPoint, HistogramSeriesApi, LinePoint,
+    LineSeriesApi, Marker, MarkerPosition, MarkerShape, PriceScale,
+};
+use crate::indicators::script::{PlotKind, ScriptBar, ScriptError, ScriptIndicator, ScriptPanel};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const DEFAULT_PLOT_COLOR: Color = Color::new(0.33, 0.62, 0.98);
+
+#[derive(Clone, Debug)]
+pub struct ScriptSummary {
+    pub id: usize,
+    pub name: String,
+    pub error: Option<String>,
+}
+
+enum PlotTarget {
+    Line(LineSeriesApi),
+    Histogram(HistogramSeriesApi),
+    Markers,
+    Panel,
+}
+
+struct ScriptEntry {
+    id: usize,
+    source: String,
+    script: ScriptIndicator,
+    error: Option<ScriptError>,
+    targets: Vec<PlotTarget>,
+}
+
+struct ScriptState {
+    chart: ChartApi,
+    candle_series: CandlestickSeriesApi,
+    base_markers: Vec<Marker>,
+    entries: Vec<ScriptEntry>,
+    next_id: usize,
+    spare_lines: Vec<LineSeriesApi>,
+    spare_histograms: Vec<HistogramSeriesApi>,
+}
+
+/// User scripts plotted on the chart. Each script is compiled once and then
+/// advanced incrementally as candles arrive.
+#[derive(Clone)]
+pub struct ScriptIndicators {
+    state: Rc<RefCell<ScriptState>>,
+}
+
+impl ScriptIndicators {
+    pub fn new(
+        chart: &ChartApi,
+        candle_series: &CandlestickSeriesApi,
+        base_markers: Vec<Marker>,
+    ) -> Self {
+        Self {
+            state: Rc::new(RefCell::new(ScriptState {
+                chart: chart.clone(),
+                candle_series: candle_series.clone(),
+                base_markers,
+                entries: Vec::new(),
+                next_id: 1,
+                spare_lines: Vec::new(),
+                spare_histograms: Vec::new(),
+            })),
+        }
+    }
+
+    pub fn add(
+        &self,
+        source: &str,
+        candles: &[Candle],
+        volumes: &[HistogramPoint],
+    ) -> Result<ScriptSummary, ScriptError> {
+        let mut script = ScriptIndicator::compile(source)?;
+        // The RSI line pins the shared panel to 0-100, which would flatten or
+        // clip anything else plotted there.
+        if script.panel() == ScriptPanel::Indicator
+            && self.state.borrow().chart.rsi_panel_has_data()
+        {
+            return Err(ScriptError::new(
+                0,
+                0,
+                "the indicator panel is showing RSI; remove RSI or use `panel main`",
+            ));
+        }
+        script.update(&script_bars(candles, volumes))?;
+
+        let mut state = self.state.borrow_mut();
+        let id = state.next_id;
+        state.next_id += 1;
+        if script.panel() == ScriptPanel::Indicator && !state.chart.has_rsi_panel() {
+            state
+                .chart
+                .set_rsi_panel(script.name().to_string(), Vec::new());
+        }
+        let targets = script
+            .plots()
+            .iter()
+            .map(|plot| state.target_for(plot.kind, script.panel()))
+            .collect();
+        state.entries.push(ScriptEntry {
+            id,
+            source: source.to_string(),
+            script,
+            error: None,
+            targets,
+        });
+        state.publish();
+        Ok(summary(
+            state.entries.last().expect("entry was just pushed"),
+        ))
+    }
+
+    pub fn remove(&self, id: usize) {
+        let mut state = self.state.borrow_mut();
+        let Some(idx) = state.entries.iter().position(|entry| entry.id == id) else {
+            return;
+        };
+        let entry = state.entries.remove(idx);
+        state.release(entry);
+        state.publish();
+    }
+
+    /// Drops every script drawn in the indicator panel, used when that panel
+    /// is closed. Returns their sources so they can be added back.
+    pub fn remove_panel_scripts(&self) -> Vec<String> {
+        let entries: Vec<(usize, String)> = self
+            .state
+            .borrow()
+            .entries
+            .iter()
+            .filter(|entry| entry.script.panel() == ScriptPanel::Indicator)
+            .map(|entry| (entry.id, entry.source.clone()))
+            .collect();
+        entries
+            .into_iter()
+            .map(|(id, source)| {
+                self.remove(id);
+                source
+            })
+            .collect()
+    }
+
+    /// Id and source of the script plotting series `series`, if any.
+    pub fn script_for_series(&self, series: usize) -> Option<(usize, String)> {
+        self.state
+            .borrow()
+            .entries
+            .iter()
+            .find(|entry| {
+                entry.targets.iter().any(|target| match target {
+                    PlotTarget::Line(line) => line.series_id() == series,
+                    PlotTarget::Histogram(histogram) => histogram.series_id() == series,
+                    PlotTarget::Markers | PlotTarget::Panel => false,
+                })
+            })
+            .map(|entry| (entry.id, entry.source.clone()))
+    }
+
+    /// Whether any script is drawn in the indicator panel.
+    pub fn has_panel_scripts(&self) -> bool {
+        self.state
+            .borrow()
+            .entries
+            .iter()
+            .any(|entry| entry.script.panel() == ScriptPanel::Indicator)
+    }
+
+    pub fn summaries(&self) -> Vec<ScriptSummary> {
+        self.state.borrow().entries.iter().map(summary).collect()
+    }
+
+    pub fn sources(&self) -> Vec<String> {
+        self.state
+            .borrow()
+            .entries
+            .iter()
+            .map(|entry| entry.source.clone())
+            .collect()
+    }
+
+    /// Replaces all scripts, e.g. when a settings profile is loaded. Scripts
+    /// that fail to compile are reported and skipped.
+    pub fn replace_all(&self, sources: &[String], candles: &[Candle], volumes: &[HistogramPoint]) {
+        let ids: Vec<usize> = self.summaries().iter().map(|summary| summary.id).collect();
+        for id in ids {
+            self.remove(id);
+        }
+        for source in sources {
+            if let Err(err) = self.add(source, candles, volumes) {
+                eprintln!("Script not loaded: {err}");
+            }
+        }
+    }
+
+    pub fn refresh(&self, candles: &[Candle], volumes: &[HistogramPoint]) {
+        let mut state = self.state.borrow_mut();
+        if state.entries.is_empty() {
+            return;
+        }
+        let bars = script_bars(candles, volumes);
+        for entry in &mut state.entries {
+            if entry.error.is_some() {
+                continue;
+            }
+            if let Err(err) = entry.script.update(&bars) {
+                eprintln!("Script \"{}\" stopped: {err}", entry.script.name());
+                entry.error = Some(err);
+            }
+        }
+        state.publish();
+    }
+}
+
+impl ScriptState {
+    fn target_for(&mut self, kind: PlotKind, panel: ScriptPanel) -> PlotTarget {
+        match (kind, panel) {
+            (PlotKind::Markers, _) => PlotTarget::Markers,
+            (_, ScriptPanel::Indicator) => PlotTarget::Panel,
+            (PlotKind::Line, ScriptPanel::Main) => {
+                let series = self
+                    .spare_lines
+                    .pop()
+                    .unwrap_or_else(|| self.chart.add_line_series());
+                series.set_price_scale(PriceScale::Right);
+                series.set_line_width(1.5);
+                series.set_price_line_visible(false);
+                series.set_last_value_visible(false);
+                PlotTarget::Line(series)
+            }
+            (PlotKind::Histogram, ScriptPanel::Main) => {
+                let series = self
+                    .spare_histograms
+                    .pop()
+                    .unwrap_or_else(|| self.chart.add_histogram_series());
+                series.set_price_scale(PriceScale::Right);
+                series.set_price_line_visible(false);
+                series.set_last_value_visible(false);
+                PlotTarget::Histogram(series)
+            }
+        }
+    }
+
+    fn release(&mut self, entry: ScriptEntry) {
+        for target in entry.targets {
+            match target {
+                PlotTarget::Line(series) => {
+                    series.set_data(Vec::new());
+                    self.spare_lines.push(series);
+                }
+                PlotTarget::Histogram(series) => {
+                    series.set_data(Vec::new());
+                    self.spare_histograms.push(series);
+                }
+                PlotTarget::Markers | PlotTarget::Panel => {}
+            }
+        }
+        if entry.script.panel() == ScriptPanel::Indicator {
+            self.chart
+                .set_rsi_panel_plots(&panel_key(entry.id), Vec::new(), Vec::new());
+            if self.chart.has_rsi_panel() && !self.chart.rsi_panel_has_plots() {
+                self.chart.clear_rsi_panel();
+            }
+        }
+    }
+
+    fn publish(&self) {
+        let mut markers = self.base_markers.clone();
+        for entry in &self.entries {
+            let failed = entry.error.is_some();
+            let mut panel_lines: Vec<(Vec<LinePoint>, Color)> = Vec::new();
+            let mut panel_histograms: Vec<(Vec<HistogramPoint>, Color)> = Vec::new();
+            for (idx, (plot, target)) in entry.script.plots().iter().zip(&entry.targets).enumerate()
+            {
+                let color = plot.color.unwrap_or(DEFAULT_PLOT_COLOR);
+                match target {
+                    PlotTarget::Line(series) => {
+                        series.set_color(color);
+                        series.set_data(if failed {
+                            Vec::new()
+                        } else {
+                            entry.script.line(idx)
+                        });
+                    }
+                    PlotTarget::Histogram(series) => {
+                        series.set_data(if failed {
+                            Vec::new()
+                        } else {
+                            colored(entry.script.histogram(idx), color)
+                        });
+                    }
+                    PlotTarget::Markers if !failed => {
+                        markers.extend(
+                            entry
+                                .script
+                                .signals(idx)
+                                .into_iter()
+                                .map(|time| signal_marker(time, color, &plot.title)),
+                        );
+                    }
+                    PlotTarget::Panel if !failed => match plot.kind {
+                        PlotKind::Histogram => {
+                            panel_histograms.push((entry.script.histogram(idx), color));
+                        }
+                        _ => panel_lines.push((entry.script.line(idx), color)),
+                    },
+                    PlotTarget::Markers | PlotTarget::Panel => {}
+                }
+            }
+            if entry.script.panel() == ScriptPanel::Indicator {
+                self.chart
+                    .set_rsi_panel_plots(&panel_key(entry.id), panel_lines, panel_histograms);
+            }
+        }
+        markers.sort_by_key(|marker| marker.time);
+        self.candle_series.set_markers(markers);
+    }
+}
+
+fn summary(entry: &ScriptEntry) -> ScriptSummary {
+    ScriptSummary {
+        id: entry.id,
+        name: entry.script.name().to_string(),
+        error: entry.error.as_ref().map(|err| err.to_string()),
+    }
+}
+
+fn panel_key(id: usize) -> String {
+    format!("script-{id}")
+}
+
+fn script_bars(candles: &[Candle], volumes: &[HistogramPoint]) -> Vec<ScriptBar> {
+    candles
+        .iter()
+        .enumerate()
+        .map(|(idx, candle)| {
+            let volume = volumes
+                .get(idx)
+                .filter(|point| point.time == candle.time)
+                .map(|point| point.value)
+                .unwrap_or(0.0);
+            ScriptBar {
+                time: candle.time,
+                open: candle.open,
+                high: candle.high,
+                low: candle.low,
+                close: candle.close,
+                volume,
+            }
+        })
+        .collect()
+}
+
+fn colored(points: Vec<HistogramPoint>, color: Color) -> Vec<HistogramPoint> {
+    points
+        .into_iter()
+        .map(|point| HistogramPoint {
+            color: Some(color),
+            ..point
+        })
+        .collect()
+}
+
+fn signal_marker(time: time::OffsetDateTime, color: Color, title: &str) -> Marker {
+    Marker {
+        time,
+        position: MarkerPosition::Below,
+        price: None,
+        shape: MarkerShape::ArrowUp,
+        color,
+        size: 8.0,
+        icon_text: None,
+        icon_text_color: None,
+        icon_font_size: 0.0,
+        icon_background: None,
+        icon_padding: 2.0,
+        icon_border_color: None,
+        icon_border_width: 0.0,
+        text: (!title.is_empty()).then(|| title.to_string()),
+        label_background: None,
+        label_text: Some(color),
+        label_padding: 2.0,
+        label_radius: 2.0,
+        label_background_alpha: 0.0,
+        label_border_color: None,
+        label_border_width: 0.0,
+        label_text_size: 0.0,
+        label_offset_x: 0.0,
+        label_offset_y: 0.0,
+    }
+}
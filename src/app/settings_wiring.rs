@@ -1,9 +1,10 @@
 use crate::chart::{
-    Candle, CandlestickSeriesApi, ChartApi, ChartStyle, Color, CrosshairCenter, CrosshairOptions,
-    HandleScaleOptions, HandleScrollOptions, HistogramPoint, HistogramSeriesApi,
-    InteractionSensitivityOptions, KineticScrollOptions, LineSeriesApi, LineStyle, MarkerZOrder,
-    PriceLineApi, PriceLineOptions, PriceScale, PriceScaleOptions, SeriesMarkersOptions,
-    TimeLabelMode, TimeScaleOptions, TooltipOptions, TooltipPosition, TrackingModeOptions,
+    AnimationOptions, Candle, CandlestickSeriesApi, ChartApi, ChartStyle, Color, CrosshairCenter,
+    CrosshairOptions, Easing, HandleScaleOptions, HandleScrollOptions, HistogramPoint,
+    HistogramSeriesApi, InteractionSensitivityOptions, KineticScrollOptions, LineSeriesApi,
+    LineStyle, MarkerZOrder, PriceLineApi, PriceLineOptions, PriceScale, PriceScaleOptions,
+    SeriesMarkersOptions, TimeLabelMode, TimeScaleOptions, TooltipOptions, TooltipPosition,
+    TrackingModeOptions,
 };
 use crate::settings_ui::{
     PriceScaleSideControls, SeriesFormatControls, SeriesLastValueControls, SeriesMarkerControls,
@@ -16,12 +17,14 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use super::keyboard::KeyBindings;
 use super::helpers::{
     color_from_rgba, crosshair_mode_from_combo, crosshair_mode_to_index, line_style_from_combo,
     price_format_from_controls, price_scale_mode_to_index, price_scale_options_from_controls,
     rgba_from_color,
 };
 use super::market_data::MarketStore;
+use super::script_indicators::ScriptIndicators;
 
 #[derive(Clone)]
 struct PriceLineEntry {
@@ -191,6 +194,26 @@ struct InteractionProfile {
     axis_drag_price: f64,
     wheel_zoom: f64,
     pinch_zoom: f64,
+    #[serde(default)]
+    animation: AnimationProfile,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct AnimationProfile {
+    enabled: bool,
+    duration_ms: f64,
+    easing_index: i32,
+}
+
+impl Default for AnimationProfile {
+    fn default() -> Self {
+        let defaults = AnimationOptions::default();
+        Self {
+            enabled: defaults.enabled,
+            duration_ms: defaults.duration.as_millis() as f64,
+            easing_index: easing_to_index(defaults.easing) as i32,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -215,6 +238,10 @@ struct SettingsProfile {
     time_scale: TimeScaleProfile,
     interaction: InteractionProfile,
     tooltip: TooltipProfile,
+    #[serde(default)]
+    scripts: Vec<String>,
+    #[serde(default)]
+    keyboard: KeyBindings,
 }
 
 fn preset_color(color: Color) -> ColorPreset {
@@ -469,6 +496,11 @@ fn settings_profile_from_controls(controls: &SettingsControls) -> SettingsProfil
             axis_drag_price: controls.interaction.axis_drag_price.value(),
             wheel_zoom: controls.interaction.wheel_zoom.value(),
             pinch_zoom: controls.interaction.pinch_zoom.value(),
+            animation: AnimationProfile {
+                enabled: controls.interaction.animations.state(),
+                duration_ms: controls.interaction.animation_duration.value(),
+                easing_index: combo_index(&controls.interaction.animation_easing),
+            },
         },
         tooltip: TooltipProfile {
             enabled: controls.tooltip.enabled.state(),
@@ -479,6 +511,8 @@ fn settings_profile_from_controls(controls: &SettingsControls) -> SettingsProfil
             background: color_preset_from_button(&controls.tooltip.background),
             text: color_preset_from_button(&controls.tooltip.text),
         },
+        scripts: Vec::new(),
+        keyboard: KeyBindings::default(),
     }
 }
 
@@ -639,6 +673,13 @@ fn apply_settings_profile(controls: &SettingsControls, profile: &SettingsProfile
         .interaction
         .pinch_zoom
         .set_value(profile.interaction.pinch_zoom);
+    let animation = &profile.interaction.animation;
+    controls.interaction.animations.set_state(animation.enabled);
+    controls
+        .interaction
+        .animation_duration
+        .set_value(animation.duration_ms);
+    set_combo_index(&controls.interaction.animation_easing, animation.easing_index);
 
     controls.tooltip.enabled.set_state(profile.tooltip.enabled);
     set_combo_index(&controls.tooltip.position, profile.tooltip.position_index);
@@ -655,7 +696,7 @@ fn apply_settings_profile(controls: &SettingsControls, profile: &SettingsProfile
     apply_color_preset(&controls.tooltip.text, &profile.tooltip.text);
 }
 
-fn profiles_folder(controls: &SettingsControls) -> PathBuf {
+pub(super) fn profiles_folder(controls: &SettingsControls) -> PathBuf {
     let text = controls.profiles.folder_entry.text().to_string();
     if text.trim().is_empty() {
         PathBuf::from("profiles")
@@ -691,6 +732,22 @@ fn refresh_profiles_list(controls: &SettingsControls) {
     }
 }
 
+fn easing_from_combo(combo: &gtk::ComboBoxText) -> Easing {
+    match combo.active() {
+        Some(0) => Easing::Linear,
+        Some(2) => Easing::EaseInOut,
+        _ => Easing::EaseOut,
+    }
+}
+
+fn easing_to_index(easing: Easing) -> u32 {
+    match easing {
+        Easing::Linear => 0,
+        Easing::EaseOut => 1,
+        Easing::EaseInOut => 2,
+    }
+}
+
 fn marker_z_order_from_combo(combo: &gtk::ComboBoxText) -> MarkerZOrder {
     match combo.active() {
         Some(1) => MarkerZOrder::Top,
@@ -862,11 +919,28 @@ fn set_price_lines_controls_sensitive(controls: &SeriesPriceLinesControls, enabl
     controls.title.set_sensitive(enabled);
 }
 
+/// Draws the chart, and keeps redrawing on every frame clock tick while one
+/// of its animations runs.
 pub fn wire_chart_draw(drawing_area: &gtk::DrawingArea, chart: ChartApi) {
+    let ticking = std::rc::Rc::new(std::cell::Cell::new(false));
     drawing_area.set_draw_func({
         let chart = chart.clone();
-        move |_, cr, width, height| {
+        move |area, cr, width, height| {
             chart.draw(cr, width as f64, height as f64);
+            if !chart.is_animating() || ticking.replace(true) {
+                return;
+            }
+            let chart = chart.clone();
+            let ticking = ticking.clone();
+            area.add_tick_callback(move |area, _| {
+                // One more frame after the end draws the final state.
+                area.queue_draw();
+                if chart.is_animating() {
+                    return gtk::glib::ControlFlow::Continue;
+                }
+                ticking.set(false);
+                gtk::glib::ControlFlow::Break
+            });
         }
     });
 }
@@ -874,7 +948,8 @@ pub fn wire_chart_draw(drawing_area: &gtk::DrawingArea, chart: ChartApi) {
 fn wire_price_lines_controls(
     drawing_area: &gtk::DrawingArea,
     controls: &SeriesPriceLinesControls,
-    create_line: std::rc::Rc<dyn Fn(PriceLineOptions) -> PriceLineApi>,
+    chart: &ChartApi,
+    series: usize,
 ) {
     let lines = std::rc::Rc::new(std::cell::RefCell::new(Vec::<PriceLineEntry>::new()));
     controls.style.set_active(Some(0));
@@ -885,12 +960,13 @@ fn wire_price_lines_controls(
         let selector = controls.selector.clone();
         let controls = controls.clone();
         let drawing_area = drawing_area.clone();
-        let create_line = create_line.clone();
+        let chart = chart.clone();
         move |_| {
             let mut options = PriceLineOptions::default();
             let index = lines.borrow().len();
             options.title = Some(format!("Line {}", index + 1));
-            let api = (create_line)(options.clone());
+            chart.record_price_lines(series);
+            let api = chart.create_price_line(series, options.clone());
             lines.borrow_mut().push(PriceLineEntry { api, options: options.clone() });
             refresh_price_line_selector(&selector, &lines.borrow());
             selector.set_active(Some(index as u32));
@@ -920,13 +996,13 @@ fn wire_price_lines_controls(
         let selector = controls.selector.clone();
         let controls = controls.clone();
         let drawing_area = drawing_area.clone();
-        let create_line = create_line.clone();
+        let chart = chart.clone();
         move |_| {
             let lines = lines.clone();
             let selector = selector.clone();
             let controls = controls.clone();
             let drawing_area = drawing_area.clone();
-            let create_line = create_line.clone();
+            let chart = chart.clone();
             let clipboard = selector.clipboard();
             clipboard.read_text_async(None::<&gtk::gio::Cancellable>, move |result| {
                 let Ok(Some(text)) = result else {
@@ -937,13 +1013,14 @@ fn wire_price_lines_controls(
                 };
 
                 let mut lines_mut = lines.borrow_mut();
+                chart.record_price_lines(series);
                 for entry in lines_mut.drain(..) {
                     entry.api.remove();
                 }
 
                 for preset in presets {
                     let options = options_from_preset(preset);
-                    let api = (create_line)(options.clone());
+                    let api = chart.create_price_line(series, options.clone());
                     lines_mut.push(PriceLineEntry { api, options });
                 }
 
@@ -966,6 +1043,7 @@ fn wire_price_lines_controls(
         let selector = controls.selector.clone();
         let controls = controls.clone();
         let drawing_area = drawing_area.clone();
+        let chart = chart.clone();
         move |_| {
             let index = match selector.active() {
                 Some(index) => index as usize,
@@ -976,6 +1054,7 @@ fn wire_price_lines_controls(
                 return;
             }
             let entry = lines_mut.remove(index);
+            chart.record_price_lines(series);
             entry.api.remove();
             refresh_price_line_selector(&selector, &lines_mut);
             if lines_mut.is_empty() {
@@ -1010,6 +1089,7 @@ fn wire_price_lines_controls(
         let lines = lines.clone();
         let controls = controls.clone();
         let drawing_area = drawing_area.clone();
+        let chart = chart.clone();
         move || {
             let index = match controls.selector.active() {
                 Some(index) => index as usize,
@@ -1018,6 +1098,11 @@ fn wire_price_lines_controls(
             let mut lines_mut = lines.borrow_mut();
             if let Some(entry) = lines_mut.get_mut(index) {
                 let options = price_line_options_from_controls(&controls, &entry.options);
+                // Filling the controls for another line lands here too.
+                if options == entry.options {
+                    return;
+                }
+                chart.record_price_lines(series);
                 entry.options = options.clone();
                 entry.api.apply_options(options);
                 refresh_price_line_selector(&controls.selector, &lines_mut);
@@ -1089,6 +1174,98 @@ fn wire_price_lines_controls(
     });
 }
 
+/// Runs `change` without recording the settings it changes as an undo step.
+pub(crate) fn without_settings_history(settings: &SettingsControls, change: impl FnOnce()) {
+    let paused = settings.history_paused.replace(true);
+    change();
+    settings.history_paused.set(paused);
+}
+
+/// Records settings changes as undo steps, driven by the controls' change
+/// signals. A run of quick edits, such as typing in an entry, is debounced
+/// and pushed as one step once it settles. Changes made while history is
+/// paused, such as applying a profile or the chart turning auto scale on or
+/// off, are taken as the new baseline instead.
+fn wire_settings_history(settings: &SettingsControls, chart: &ChartApi) {
+    let snapshot = |settings: &SettingsControls| {
+        toml::to_string(&settings_profile_from_controls(settings)).unwrap_or_default()
+    };
+    let last = std::rc::Rc::new(std::cell::RefCell::new(snapshot(settings)));
+    let restore = {
+        let settings = settings.clone();
+        let last = last.clone();
+        move |text: &str| {
+            if let Ok(profile) = toml::from_str::<SettingsProfile>(text) {
+                without_settings_history(&settings, || apply_settings_profile(&settings, &profile));
+            }
+            *last.borrow_mut() = snapshot(&settings);
+        }
+    };
+    let pending = std::rc::Rc::new(std::cell::RefCell::new(None::<gtk::glib::SourceId>));
+    let on_change: std::rc::Rc<dyn Fn()> = std::rc::Rc::new({
+        let settings = settings.clone();
+        let chart = chart.clone();
+        move || {
+            if settings.history_paused.get() {
+                if pending.borrow().is_none() {
+                    *last.borrow_mut() = snapshot(&settings);
+                }
+                return;
+            }
+            if let Some(source) = pending.borrow_mut().take() {
+                source.remove();
+            }
+            let settings = settings.clone();
+            let chart = chart.clone();
+            let last = last.clone();
+            let restore = restore.clone();
+            let finished = pending.clone();
+            let source = gtk::glib::timeout_add_local_once(
+                std::time::Duration::from_millis(400),
+                move || {
+                    finished.borrow_mut().take();
+                    let current = snapshot(&settings);
+                    if *last.borrow() != current {
+                        let before = last.replace(current.clone());
+                        chart.record_edit(
+                            std::rc::Rc::new({
+                                let restore = restore.clone();
+                                move || restore(&before)
+                            }),
+                            std::rc::Rc::new(move || restore(&current)),
+                        );
+                    }
+                },
+            );
+            *pending.borrow_mut() = Some(source);
+        }
+    });
+    if let Some(root) = settings.chart.background_color.root() {
+        connect_control_changes(root.upcast_ref(), &on_change);
+    }
+}
+
+/// Calls `on_change` when a control under `widget` is edited.
+fn connect_control_changes(widget: &gtk::Widget, on_change: &std::rc::Rc<dyn Fn()>) {
+    let notify = on_change.clone();
+    if let Some(spin) = widget.downcast_ref::<gtk::SpinButton>() {
+        spin.connect_value_changed(move |_| notify());
+    } else if let Some(entry) = widget.downcast_ref::<gtk::Entry>() {
+        entry.connect_changed(move |_| notify());
+    } else if let Some(switch) = widget.downcast_ref::<gtk::Switch>() {
+        switch.connect_state_notify(move |_| notify());
+    } else if let Some(combo) = widget.downcast_ref::<gtk::ComboBoxText>() {
+        combo.connect_changed(move |_| notify());
+    } else if let Some(button) = widget.downcast_ref::<gtk::ColorButton>() {
+        button.connect_color_set(move |_| notify());
+    }
+    let mut child = widget.first_child();
+    while let Some(current) = child {
+        connect_control_changes(&current, on_change);
+        child = current.next_sibling();
+    }
+}
+
 pub fn wire_settings_panel(
     drawing_area: &gtk::DrawingArea,
     chart: ChartApi,
@@ -1097,6 +1274,8 @@ pub fn wire_settings_panel(
     hist_series: HistogramSeriesApi,
     settings: SettingsControls,
     store: std::rc::Rc<std::cell::RefCell<MarketStore>>,
+    scripts: ScriptIndicators,
+    key_bindings: std::rc::Rc<std::cell::RefCell<KeyBindings>>,
     auto_scale_left_button: gtk::ToggleButton,
     auto_scale_right_button: gtk::ToggleButton,
 ) {
@@ -1107,6 +1286,7 @@ pub fn wire_settings_panel(
     let handle_defaults = HandleScaleOptions::default();
     let handle_scroll_defaults = HandleScrollOptions::default();
     let kinetic_defaults = KineticScrollOptions::default();
+    let animation_defaults = AnimationOptions::default();
     let tracking_defaults = TrackingModeOptions::default();
     let sensitivity_defaults = InteractionSensitivityOptions::default();
     let left_price_defaults = PriceScaleOptions {
@@ -1516,6 +1696,27 @@ pub fn wire_settings_panel(
         .tracking_mode
         .set_state(tracking_defaults.enabled);
 
+    // Follow the desktop's reduced-motion setting unless a profile says
+    // otherwise.
+    let desktop_animations = gtk::Settings::default()
+        .map_or(true, |settings| settings.is_gtk_enable_animations());
+    interaction_controls
+        .animations
+        .set_state(animation_defaults.enabled && desktop_animations);
+    interaction_controls.animation_duration.set_range(50.0, 2000.0);
+    interaction_controls
+        .animation_duration
+        .set_increments(50.0, 100.0);
+    interaction_controls
+        .animation_duration
+        .set_value(animation_defaults.duration.as_millis() as f64);
+    for label in ["Linear", "Ease out", "Ease in-out"] {
+        interaction_controls.animation_easing.append_text(label);
+    }
+    interaction_controls
+        .animation_easing
+        .set_active(Some(easing_to_index(animation_defaults.easing)));
+
     interaction_controls.axis_drag_time.set_range(0.0005, 0.02);
     interaction_controls.axis_drag_time.set_increments(0.0005, 0.001);
     interaction_controls
@@ -1621,6 +1822,9 @@ pub fn wire_settings_panel(
 
     profiles_controls.open_file.connect_clicked({
         let settings = settings.clone();
+        let scripts = scripts.clone();
+        let key_bindings = key_bindings.clone();
+        let store = store.clone();
         move |_| {
             let parent = settings
                 .profiles
@@ -1636,6 +1840,9 @@ pub fn wire_settings_panel(
             );
             dialog.connect_response({
                 let settings = settings.clone();
+                let scripts = scripts.clone();
+                let key_bindings = key_bindings.clone();
+                let store = store.clone();
                 move |dialog, response| {
                     if response == gtk::ResponseType::Accept {
                         if let Some(file) = dialog.file() {
@@ -1651,7 +1858,16 @@ pub fn wire_settings_panel(
                                 }
                                 if let Ok(text) = fs::read_to_string(&path) {
                                     if let Ok(profile) = toml::from_str::<SettingsProfile>(&text) {
-                                        apply_settings_profile(&settings, &profile);
+                                        without_settings_history(&settings, || {
+                                            apply_settings_profile(&settings, &profile)
+                                        });
+                                        *key_bindings.borrow_mut() = profile.keyboard.clone();
+                                        let store_ref = store.borrow();
+                                        scripts.replace_all(
+                                            &profile.scripts,
+                                            &store_ref.candles,
+                                            &store_ref.volumes,
+                                        );
                                     }
                                 }
                                 refresh_profiles_list(&settings);
@@ -1667,6 +1883,8 @@ pub fn wire_settings_panel(
 
     profiles_controls.save.connect_clicked({
         let settings = settings.clone();
+        let scripts = scripts.clone();
+        let key_bindings = key_bindings.clone();
         move |_| {
             let name = settings
                 .profiles
@@ -1681,7 +1899,9 @@ pub fn wire_settings_panel(
             let folder = profiles_folder(&settings);
             let _ = fs::create_dir_all(&folder);
             let path = profile_path(&folder, &name);
-            let profile = settings_profile_from_controls(&settings);
+            let mut profile = settings_profile_from_controls(&settings);
+            profile.scripts = scripts.sources();
+            profile.keyboard = key_bindings.borrow().clone();
             if let Ok(text) = toml::to_string_pretty(&profile) {
                 if let Err(err) = fs::write(&path, text) {
                     eprintln!("Failed to save profile: {err}");
@@ -1693,6 +1913,9 @@ pub fn wire_settings_panel(
 
     profiles_controls.load.connect_clicked({
         let settings = settings.clone();
+        let scripts = scripts.clone();
+        let key_bindings = key_bindings.clone();
+        let store = store.clone();
         move |_| {
             let mut name = settings
                 .profiles
@@ -1713,7 +1936,12 @@ pub fn wire_settings_panel(
             let path = profile_path(&folder, &name);
             if let Ok(text) = fs::read_to_string(&path) {
                 if let Ok(profile) = toml::from_str::<SettingsProfile>(&text) {
-                    apply_settings_profile(&settings, &profile);
+                    without_settings_history(&settings, || {
+                        apply_settings_profile(&settings, &profile)
+                    });
+                    *key_bindings.borrow_mut() = profile.keyboard.clone();
+                    let store_ref = store.borrow();
+                    scripts.replace_all(&profile.scripts, &store_ref.candles, &store_ref.volumes);
                 }
             }
         }
@@ -2080,12 +2308,15 @@ pub fn wire_settings_panel(
             update_left_scale();
         }
     });
+    // The floating button is driven by the chart as well as clicked, e.g.
+    // when a drag turns auto scale off, so it does not record settings steps.
     auto_scale_left_button.connect_toggled({
         let switch = price_scale_controls.left.auto_scale.clone();
+        let settings = settings.clone();
         move |button: &gtk::ToggleButton| {
             let state = button.is_active();
             if switch.state() != state {
-                switch.set_state(state);
+                without_settings_history(&settings, || switch.set_state(state));
             }
         }
     });
@@ -2158,10 +2389,11 @@ pub fn wire_settings_panel(
     });
     auto_scale_right_button.connect_toggled({
         let switch = price_scale_controls.right.auto_scale.clone();
+        let settings = settings.clone();
         move |button: &gtk::ToggleButton| {
             let state = button.is_active();
             if switch.state() != state {
-                switch.set_state(state);
+                without_settings_history(&settings, || switch.set_state(state));
             }
         }
     });
@@ -2548,6 +2780,38 @@ pub fn wire_settings_panel(
 
     update_kinetic_scroll();
 
+    let update_animation = {
+        let chart = chart.clone();
+        let drawing_area = drawing_area.clone();
+        let interaction_controls = interaction_controls.clone();
+        move || {
+            let options = AnimationOptions {
+                enabled: interaction_controls.animations.state(),
+                duration: std::time::Duration::from_millis(
+                    interaction_controls.animation_duration.value() as u64,
+                ),
+                easing: easing_from_combo(&interaction_controls.animation_easing),
+            };
+            chart.apply_animation_options(options);
+            drawing_area.queue_draw();
+        }
+    };
+
+    interaction_controls.animations.connect_state_notify({
+        let update_animation = update_animation.clone();
+        move |_| update_animation()
+    });
+    interaction_controls.animation_duration.connect_value_changed({
+        let update_animation = update_animation.clone();
+        move |_| update_animation()
+    });
+    interaction_controls.animation_easing.connect_changed({
+        let update_animation = update_animation.clone();
+        move |_| update_animation()
+    });
+
+    update_animation();
+
     let update_handle_scale = {
         let chart = chart.clone();
         let drawing_area = drawing_area.clone();
@@ -3027,26 +3291,20 @@ pub fn wire_settings_panel(
     wire_price_lines_controls(
         drawing_area,
         &series_controls.candles_lines,
-        std::rc::Rc::new({
-            let candle_series = candle_series.clone();
-            move |options| candle_series.create_price_line(options)
-        }),
+        &chart,
+        candle_series.series_id(),
     );
     wire_price_lines_controls(
         drawing_area,
         &series_controls.line_lines,
-        std::rc::Rc::new({
-            let line_series = line_series.clone();
-            move |options| line_series.create_price_line(options)
-        }),
+        &chart,
+        line_series.series_id(),
     );
     wire_price_lines_controls(
         drawing_area,
         &series_controls.hist_lines,
-        std::rc::Rc::new({
-            let hist_series = hist_series.clone();
-            move |options| hist_series.create_price_line(options)
-        }),
+        &chart,
+        hist_series.series_id(),
     );
 
     update_candles_format();
@@ -3055,4 +3313,5 @@ pub fn wire_settings_panel(
     update_candles_markers();
     update_line_markers();
     update_hist_markers();
+    wire_settings_history(&settings, &chart);
 }
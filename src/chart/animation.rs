@@ -0,0 +1,58 @@
+use std::time::Instant;
+
+use super::options::AnimationOptions;
+
+/// A range such as the visible time range or a price scale's view moving
+/// from one value to another. It is sampled by wall-clock time, so it ends
+/// on schedule however often the chart is drawn.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Transition {
+    from: (f64, f64),
+    to: (f64, f64),
+    started: Instant,
+    options: AnimationOptions,
+}
+
+impl Transition {
+    /// A transition from `from` to `to`, or `None` when animations are off
+    /// or there is nothing to move.
+    pub(crate) fn new(from: (f64, f64), to: (f64, f64), options: AnimationOptions) -> Option<Self> {
+        let moves = (from.0 - to.0).abs() > f64::EPSILON || (from.1 - to.1).abs() > f64::EPSILON;
+        let valid = [from.0, from.1, to.0, to.1]
+            .iter()
+            .all(|value| value.is_finite());
+        (options.enabled && !options.duration.is_zero() && moves && valid).then(|| Self {
+            from,
+            to,
+            started: Instant::now(),
+            options,
+        })
+    }
+
+    pub(crate) fn target(&self) -> (f64, f64) {
+        self.to
+    }
+
+    /// Moves both ends of the transition by `by`, keeping its timing.
+    pub(crate) fn shift(&mut self, by: f64) {
+        self.from = (self.from.0 + by, self.from.1 + by);
+        self.to = (self.to.0 + by, self.to.1 + by);
+    }
+
+    fn progress(&self) -> f64 {
+        self.started.elapsed().as_secs_f64() / self.options.duration.as_secs_f64()
+    }
+
+    pub(crate) fn finished(&self) -> bool {
+        self.progress() >= 1.0
+    }
+
+    /// The range at this moment.
+    pub(crate) fn value(&self) -> (f64, f64) {
+        let eased = self.options.easing.apply(self.progress());
+        (
+            self.from.0 + (self.to.0 - self.from.0) * eased,
+            self.from.1 + (self.to.1 - self.from.1) * eased,
+        )
+    }
+}
@@ -1,13 +1,20 @@
 use super::core::ChartCore;
+use super::drawings::{Drawing, DrawingAnchor, DrawingHit, DrawingId};
+use super::events::{
+    EventCalls, LogicalRange, MouseEventKind, MouseEventParams, PriceLineDragParams, SubscriptionId,
+};
+use super::hit_test::HitTarget;
 use super::options::{
-    ChartStyle, HandleScaleOptions, HandleScrollOptions, InteractionSensitivityOptions,
-    KineticScrollOptions, PriceScaleOptions, TimeScaleOptions, TrackingModeOptions,
+    AnimationOptions, ChartStyle, HandleScaleOptions, HandleScrollOptions,
+    InteractionSensitivityOptions, KineticScrollOptions, PriceScaleOptions, TimeScaleOptions,
+    TrackingModeOptions,
 };
 use super::types::{
-    Bar, BarConversionError, Candle, Color, CrosshairCenter, CrosshairMode, HistogramPoint,
-    LinePoint, LineStyle, Marker, PanResult, PanelControlAction, PanelId, PanelResizeHandle,
-    PanelRole, PriceFormat, PriceLineOptions, PriceScale, PriceScaleMode, ScaleMargins,
-    SeriesMarkersOptions, TimeLabelMode, TooltipPosition,
+    Bar, BarConversionError, Candle, CloudPoint, Color, CrosshairCenter, CrosshairMode,
+    HistogramPoint, LinePoint, LineStyle, Marker, OrderLineOptions, PanResult, PanelControlAction,
+    PanelId, PanelResizeHandle, PanelRole, PositionLineOptions, PriceFormat, PriceLineOptions,
+    PriceScale, PriceScaleMode, PriceSegment, ScaleMargins, SeriesMarkersOptions, TimeLabelMode,
+    TooltipPosition, VolumeProfileLevels, VolumeProfileOptions,
 };
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -52,10 +59,27 @@ pub struct HistogramSeriesApi {
     id: usize,
 }
 
+/// API for managing cloud (filled band) series on a chart.
+///
+/// A cloud fills the area between two edges, switching between an up and
+/// a down color wherever the edges cross. Used for Ichimoku clouds and
+/// other band-style overlays.
+#[derive(Clone, Debug)]
+pub struct CloudSeriesApi {
+    inner: Rc<RefCell<ChartCore>>,
+    id: usize,
+}
+
 /// API for managing price lines on a series.
 ///
 /// Price lines are horizontal lines that can be added to any series
 /// to mark specific price levels with optional labels and styling.
+///
+/// Changes made through this API, including `set_price` and `remove`, are
+/// not recorded in the undo history, so price feeds and fired alerts stay
+/// out of it. Call `ChartApi::record_price_lines` before a change the user
+/// should be able to undo. Drags of draggable lines are recorded by the
+/// chart.
 #[derive(Clone, Debug)]
 pub struct PriceLineApi {
     inner: Rc<RefCell<ChartCore>>,
@@ -63,6 +87,29 @@ pub struct PriceLineApi {
     line_id: usize,
 }
 
+/// API for managing a position line on a candlestick series.
+#[derive(Clone, Debug)]
+pub struct PositionLineApi {
+    inner: Rc<RefCell<ChartCore>>,
+    series_id: usize,
+    line_id: usize,
+}
+
+/// API for managing an order line on a candlestick series.
+#[derive(Clone, Debug)]
+pub struct OrderLineApi {
+    inner: Rc<RefCell<ChartCore>>,
+    series_id: usize,
+    line_id: usize,
+}
+
+/// API for managing a volume profile attached to a candlestick series.
+#[derive(Clone, Debug)]
+pub struct VolumeProfileApi {
+    inner: Rc<RefCell<ChartCore>>,
+    profile_id: usize,
+}
+
 /// Creates a new chart instance.
 ///
 /// # Returns
@@ -83,6 +130,14 @@ pub fn create_chart() -> ChartApi {
     }
 }
 
+/// Runs event handlers once the chart is no longer borrowed, so they can
+/// call back into it.
+fn emit(calls: EventCalls) {
+    for call in calls {
+        call();
+    }
+}
+
 impl ChartApi {
     /// Adds a new candlestick series to the chart.
     ///
@@ -150,6 +205,31 @@ impl ChartApi {
         }
     }
 
+    /// Adds a new cloud series to the chart.
+    ///
+    /// Cloud series are drawn in the order they are added, so add the cloud
+    /// before any lines that should be drawn on top of it.
+    ///
+    /// # Returns
+    ///
+    /// A `CloudSeriesApi` instance for managing the new series.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lightweight_charts_rs::create_chart;
+    ///
+    /// let chart = create_chart();
+    /// let cloud = chart.add_cloud_series();
+    /// ```
+    pub fn add_cloud_series(&self) -> CloudSeriesApi {
+        let id = self.inner.borrow_mut().add_cloud_series();
+        CloudSeriesApi {
+            inner: self.inner.clone(),
+            id,
+        }
+    }
+
     /// Sets up an RSI (Relative Strength Index) panel with the given title and data.
     ///
     /// # Arguments
@@ -186,6 +266,50 @@ impl ChartApi {
         self.inner.borrow_mut().set_rsi_panel_data(data);
     }
 
+    /// Sets a group of extra plots drawn in the RSI panel alongside the RSI
+    /// line, such as a signal line derived from the RSI values.
+    ///
+    /// Each caller owns the group stored under its `key`, so independent
+    /// features can share the panel without overwriting each other.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Identifies the group; passing no lines and no histograms removes it
+    /// * `lines` - The points and color of each line
+    /// * `histograms` - The bars and color of each histogram, drawn from zero
+    pub fn set_rsi_panel_plots(
+        &self,
+        key: &str,
+        lines: Vec<(Vec<LinePoint>, Color)>,
+        histograms: Vec<(Vec<HistogramPoint>, Color)>,
+    ) {
+        self.inner
+            .borrow_mut()
+            .set_rsi_panel_plots(key, lines, histograms);
+    }
+
+    /// Checks whether the RSI panel has anything to draw, either RSI data or
+    /// extra plot groups.
+    pub fn rsi_panel_has_plots(&self) -> bool {
+        self.inner.borrow().rsi_panel_has_plots()
+    }
+
+    /// Checks whether the RSI panel holds the RSI line itself, which fixes
+    /// the panel scale to 0-100.
+    pub fn rsi_panel_has_data(&self) -> bool {
+        self.inner.borrow().rsi_panel_has_data()
+    }
+
+    /// Gets the id of the main price panel.
+    pub fn main_panel_id(&self) -> PanelId {
+        self.inner.borrow().main_panel_id()
+    }
+
+    /// Gets the id of the panel that hosts the RSI line, if one exists.
+    pub fn rsi_panel_id(&self) -> Option<PanelId> {
+        self.inner.borrow().rsi_panel_id()
+    }
+
     /// Clears and removes the RSI panel from the chart.
     pub fn clear_rsi_panel(&self) {
         self.inner.borrow_mut().clear_rsi_panel();
@@ -288,6 +412,7 @@ impl ChartApi {
     /// ```
     pub fn draw(&self, cr: &cairo::Context, width: f64, height: f64) {
         self.inner.borrow_mut().draw(cr, width, height);
+        self.emit_view_changes();
     }
 
     /// Gets the currently visible time range on the chart.
@@ -299,6 +424,328 @@ impl ChartApi {
         self.inner.borrow().visible_time_range()
     }
 
+    /// Gets the visible range as bar indexes of the primary candlestick
+    /// series, fractional at the edges.
+    pub fn visible_logical_range(&self) -> Option<LogicalRange> {
+        self.inner.borrow().visible_logical_range()
+    }
+
+    /// Shows the period from `from` to `to`, unix timestamps in seconds as
+    /// returned by `visible_time_range`. The bar spacing limits and fixed
+    /// edges still apply, so the range shown may be wider, narrower or
+    /// shifted.
+    pub fn set_visible_time_range(&self, from: f64, to: f64) {
+        self.inner.borrow_mut().set_visible_time_range(from, to);
+        self.emit_view_changes();
+    }
+
+    /// Like `set_visible_time_range`, with the range in bar indexes of the
+    /// primary candlestick series. Indexes past either end continue at the
+    /// spacing of the nearest bars. Returns `false` when there are no
+    /// candles.
+    pub fn set_visible_logical_range(&self, range: LogicalRange) -> bool {
+        let applied = self
+            .inner
+            .borrow_mut()
+            .set_visible_logical_range(range.from, range.to);
+        self.emit_view_changes();
+        applied
+    }
+
+    /// Scrolls `time` (unix seconds) to the middle of the view, keeping the
+    /// zoom. With `animate`, the scroll eases in over the next draws; keep
+    /// redrawing while `is_animating` is `true`.
+    pub fn scroll_to_time(&self, time: f64, animate: bool) {
+        self.inner.borrow_mut().scroll_to_time(time, animate);
+        self.emit_view_changes();
+    }
+
+    /// Scrolls to the latest bar, keeping the right offset. The scroll is
+    /// animated unless animations are off.
+    pub fn scroll_to_real_time(&self) {
+        self.inner.borrow_mut().scroll_to_real_time();
+        self.emit_view_changes();
+    }
+
+    /// Whether an animated scroll, zoom or auto scale is still under way.
+    /// Hosts keep redrawing until it is `false`, ideally once per frame.
+    pub fn is_animating(&self) -> bool {
+        self.inner.borrow().is_animating()
+    }
+
+    /// Calls `handler` whenever the crosshair moves or leaves the chart.
+    pub fn subscribe_crosshair_move(
+        &self,
+        handler: impl Fn(&MouseEventParams) + 'static,
+    ) -> SubscriptionId {
+        self.inner
+            .borrow_mut()
+            .subscribe_mouse(MouseEventKind::CrosshairMove, Rc::new(handler))
+    }
+
+    /// Calls `handler` on every `handle_click`.
+    pub fn subscribe_click(&self, handler: impl Fn(&MouseEventParams) + 'static) -> SubscriptionId {
+        self.inner
+            .borrow_mut()
+            .subscribe_mouse(MouseEventKind::Click, Rc::new(handler))
+    }
+
+    /// Calls `handler` on every `handle_double_click`.
+    pub fn subscribe_double_click(
+        &self,
+        handler: impl Fn(&MouseEventParams) + 'static,
+    ) -> SubscriptionId {
+        self.inner
+            .borrow_mut()
+            .subscribe_mouse(MouseEventKind::DoubleClick, Rc::new(handler))
+    }
+
+    /// Calls `handler` with the new start and end time whenever the visible
+    /// time range changes. Changes are noticed after drawing and after pan
+    /// and zoom calls.
+    pub fn subscribe_visible_time_range_change(
+        &self,
+        handler: impl Fn(f64, f64) + 'static,
+    ) -> SubscriptionId {
+        self.inner
+            .borrow_mut()
+            .subscribe_visible_time_range(Rc::new(handler))
+    }
+
+    /// Like `subscribe_visible_time_range_change`, with the range in bar
+    /// indexes. `None` means there are no bars to count.
+    pub fn subscribe_visible_logical_range_change(
+        &self,
+        handler: impl Fn(Option<LogicalRange>) + 'static,
+    ) -> SubscriptionId {
+        self.inner
+            .borrow_mut()
+            .subscribe_visible_logical_range(Rc::new(handler))
+    }
+
+    /// Calls `handler` with the new width and height when the chart is
+    /// drawn at a different size.
+    pub fn subscribe_size_change(&self, handler: impl Fn(f64, f64) + 'static) -> SubscriptionId {
+        self.inner.borrow_mut().subscribe_size(Rc::new(handler))
+    }
+
+    pub fn unsubscribe(&self, id: SubscriptionId) {
+        self.inner.borrow_mut().unsubscribe(id);
+    }
+
+    /// Reports a click at `(x, y)` to click subscribers. A click on an
+    /// order line's cancel button goes to cancel subscribers instead.
+    pub fn handle_click(&self, x: f64, y: f64) {
+        let calls = {
+            let mut inner = self.inner.borrow_mut();
+            match inner.hit_test(x, y) {
+                Some(HitTarget::OrderCancel { series, line }) => {
+                    inner.order_cancel_calls(series, line)
+                }
+                _ => inner.mouse_event_calls(MouseEventKind::Click, Some((x, y))),
+            }
+        };
+        emit(calls);
+    }
+
+    /// Calls `handler` with the series and line id when the cancel button
+    /// of an order line is clicked. The line stays until removed.
+    pub fn subscribe_order_cancel(
+        &self,
+        handler: impl Fn(usize, usize) + 'static,
+    ) -> SubscriptionId {
+        self.inner
+            .borrow_mut()
+            .subscribe_order_cancel(Rc::new(handler))
+    }
+
+    /// Finds the marker, price line, candle, line or histogram bar under
+    /// `(x, y)`, within a few pixels. Uses the layout of the last draw.
+    pub fn hit_test(&self, x: f64, y: f64) -> Option<HitTarget> {
+        self.inner.borrow_mut().hit_test(x, y)
+    }
+
+    /// The main pane's price axis under `(x, y)`, if any. Uses the layout of
+    /// the last draw.
+    pub fn price_scale_at(&self, x: f64, y: f64) -> Option<PriceScale> {
+        self.inner.borrow().price_scale_at(x, y)
+    }
+
+    /// A handle to price line `line` of series `series`, as reported by
+    /// `hit_test`.
+    pub fn price_line(&self, series: usize, line: usize) -> Option<PriceLineApi> {
+        self.inner.borrow().price_line_options(series, line)?;
+        Some(PriceLineApi {
+            inner: self.inner.clone(),
+            series_id: series,
+            line_id: line,
+        })
+    }
+
+    /// Adds a price line to series `series`, such as a copy of one found by
+    /// `hit_test`.
+    pub fn create_price_line(&self, series: usize, options: PriceLineOptions) -> PriceLineApi {
+        let line_id = self.inner.borrow_mut().create_price_line(series, options);
+        PriceLineApi {
+            inner: self.inner.clone(),
+            series_id: series,
+            line_id,
+        }
+    }
+
+    /// Shows or hides series `series` along with its markers and lines.
+    /// Hidden series are left out of auto scaling and hit testing.
+    pub fn set_series_visible(&self, series: usize, visible: bool) {
+        self.inner.borrow_mut().set_series_visible(series, visible);
+    }
+
+    pub fn series_visible(&self, series: usize) -> bool {
+        self.inner.borrow().series_visible(series)
+    }
+
+    /// Moves series `series` to panel `panel_id`. On the main panel it is
+    /// drawn against its left or right price scale; on the indicator panel,
+    /// against that panel's scale. Removing a panel moves its series back to
+    /// the main panel. Not recorded in the undo history.
+    ///
+    /// # Returns
+    ///
+    /// `false` when there is no such series or panel.
+    pub fn move_series_to_panel(&self, series: usize, panel_id: PanelId) -> bool {
+        self.inner
+            .borrow_mut()
+            .move_series_to_panel(series, panel_id)
+    }
+
+    /// Panel series `series` is drawn on.
+    pub fn series_panel(&self, series: usize) -> Option<PanelId> {
+        self.inner.borrow().series_panel(series)
+    }
+
+    /// Ids of the series drawn on panel `panel_id`.
+    pub fn panel_series(&self, panel_id: PanelId) -> Vec<usize> {
+        self.inner.borrow().panel_series(panel_id)
+    }
+
+    /// Ids of series hidden with `set_series_visible`.
+    pub fn hidden_series(&self) -> Vec<usize> {
+        self.inner.borrow().hidden_series()
+    }
+
+    /// Whether a draggable price line or its axis label is under `(x, y)`.
+    pub fn price_line_drag_at(&self, x: f64, y: f64) -> bool {
+        self.inner
+            .borrow_mut()
+            .draggable_price_line_at(x, y)
+            .is_some()
+    }
+
+    /// Grabs the draggable price line under `(x, y)`.
+    ///
+    /// # Returns
+    ///
+    /// `false` when there is no draggable line there or a veto rejects the
+    /// drag.
+    pub fn begin_price_line_drag(&self, x: f64, y: f64) -> bool {
+        let Some(params) = self.inner.borrow_mut().start_price_line_drag(x, y) else {
+            return false;
+        };
+        if self.price_line_drag_vetoed(&params) {
+            self.inner.borrow_mut().cancel_price_line_drag();
+            return false;
+        }
+        let calls = {
+            let mut inner = self.inner.borrow_mut();
+            // The whole drag undoes as one step.
+            inner.begin_history_group();
+            inner.price_line_drag_calls(params)
+        };
+        emit(calls);
+        true
+    }
+
+    pub fn is_dragging_price_line(&self) -> bool {
+        self.inner.borrow().is_dragging_price_line()
+    }
+
+    /// Moves the held price line to the pointer at `y`, snapped to the
+    /// series' `min_move`, unless a veto rejects the price.
+    pub fn drag_price_line(&self, y: f64) {
+        let Some(params) = self.inner.borrow_mut().price_line_drag_proposal(y) else {
+            return;
+        };
+        let current = self
+            .inner
+            .borrow()
+            .price_line_options(params.series, params.line)
+            .map(|options| options.price);
+        if current == Some(params.price) || self.price_line_drag_vetoed(&params) {
+            return;
+        }
+        let calls = {
+            let mut inner = self.inner.borrow_mut();
+            inner.record_price_lines(params.series);
+            inner.set_price_line_price(params.series, params.line, params.price);
+            inner.price_line_drag_calls(params)
+        };
+        emit(calls);
+    }
+
+    /// Drops the held price line. If a veto rejects the final price the
+    /// line goes back to where the drag started.
+    pub fn end_price_line_drag(&self) {
+        let Some(mut params) = self.inner.borrow_mut().finish_price_line_drag() else {
+            return;
+        };
+        if params.price != params.start_price && self.price_line_drag_vetoed(&params) {
+            self.inner.borrow_mut().set_price_line_price(
+                params.series,
+                params.line,
+                params.start_price,
+            );
+            params.price = params.start_price;
+        }
+        let calls = {
+            let mut inner = self.inner.borrow_mut();
+            inner.end_history_group();
+            inner.price_line_drag_calls(params)
+        };
+        emit(calls);
+    }
+
+    /// Calls `handler` when a draggable price line is grabbed, moved and
+    /// dropped.
+    pub fn subscribe_price_line_drag(
+        &self,
+        handler: impl Fn(&PriceLineDragParams) + 'static,
+    ) -> SubscriptionId {
+        self.inner
+            .borrow_mut()
+            .subscribe_price_line_drag(Rc::new(handler))
+    }
+
+    /// Asks `veto` before each step of a price line drag; returning `true`
+    /// rejects it. A rejected start cancels the drag, a rejected move leaves
+    /// the line where it is and a rejected end puts it back.
+    pub fn subscribe_price_line_drag_veto(
+        &self,
+        veto: impl Fn(&PriceLineDragParams) -> bool + 'static,
+    ) -> SubscriptionId {
+        self.inner
+            .borrow_mut()
+            .subscribe_price_line_drag_veto(Rc::new(veto))
+    }
+
+    fn price_line_drag_vetoed(&self, params: &PriceLineDragParams) -> bool {
+        let vetoes = self.inner.borrow().price_line_drag_vetoes();
+        vetoes.iter().any(|veto| veto(params))
+    }
+
+    fn emit_view_changes(&self) {
+        let calls = self.inner.borrow_mut().view_change_calls();
+        emit(calls);
+    }
+
     /// Gets the current chart style configuration.
     ///
     /// # Returns
@@ -457,9 +904,12 @@ impl ChartApi {
         x: f64,
         y: f64,
     ) -> PanResult {
-        self.inner
+        let result = self
+            .inner
             .borrow_mut()
-            .pan_by_pixels(dx, dy, width, height, x, y)
+            .pan_by_pixels(dx, dy, width, height, x, y);
+        self.emit_view_changes();
+        result
     }
 
     pub fn pan_by_pixels_touch(
@@ -471,9 +921,12 @@ impl ChartApi {
         x: f64,
         y: f64,
     ) -> bool {
-        self.inner
+        let panned = self
+            .inner
             .borrow_mut()
-            .pan_by_pixels_touch(dx, dy, width, height, x, y)
+            .pan_by_pixels_touch(dx, dy, width, height, x, y);
+        self.emit_view_changes();
+        panned
     }
 
     pub fn zoom_by_delta(
@@ -484,29 +937,301 @@ impl ChartApi {
         width: f64,
         height: f64,
     ) -> Option<PriceScale> {
-        self.inner
+        let zoomed = self
+            .inner
             .borrow_mut()
-            .zoom_by_delta(delta, x, y, width, height)
+            .zoom_by_delta(delta, x, y, width, height);
+        self.emit_view_changes();
+        zoomed
     }
 
     pub fn zoom_by_delta_pinch(&self, delta: f64, x: f64, y: f64, width: f64, height: f64) {
         self.inner
             .borrow_mut()
             .zoom_by_delta_pinch(delta, x, y, width, height);
+        self.emit_view_changes();
     }
 
     pub fn handle_double_click(&self, x: f64, y: f64, width: f64, height: f64) {
+        let calls = self
+            .inner
+            .borrow()
+            .mouse_event_calls(MouseEventKind::DoubleClick, Some((x, y)));
+        emit(calls);
         self.inner
             .borrow_mut()
             .handle_double_click(x, y, width, height);
+        self.emit_view_changes();
+    }
+
+    /// Adds a user drawing to the main panel.
+    ///
+    /// Anchors are in (time, price) space on the primary price scale, so the
+    /// drawing follows its bars and prices through pan, zoom and scale mode
+    /// changes.
+    ///
+    /// # Returns
+    ///
+    /// The id used to update or remove the drawing later.
+    pub fn add_drawing(&self, drawing: Drawing) -> DrawingId {
+        let id = self.inner.borrow_mut().add_drawing(drawing);
+        self.emit_drawing_changes();
+        id
+    }
+
+    pub fn update_drawing(&self, id: DrawingId, drawing: Drawing) {
+        self.inner.borrow_mut().update_drawing(id, drawing);
+        self.emit_drawing_changes();
+    }
+
+    /// Removes a drawing, returning it if it existed.
+    pub fn remove_drawing(&self, id: DrawingId) -> Option<Drawing> {
+        let removed = self.inner.borrow_mut().remove_drawing(id);
+        self.emit_drawing_changes();
+        removed
+    }
+
+    pub fn drawing(&self, id: DrawingId) -> Option<Drawing> {
+        self.inner.borrow().drawing(id)
+    }
+
+    pub fn drawings(&self) -> Vec<(DrawingId, Drawing)> {
+        self.inner.borrow().drawings()
+    }
+
+    pub fn clear_drawings(&self) {
+        self.inner.borrow_mut().clear_drawings();
+        self.emit_drawing_changes();
+    }
+
+    /// Calls `handler` after drawings were added, changed or removed,
+    /// including by undo and redo.
+    pub fn subscribe_drawings_change(&self, handler: impl Fn() + 'static) -> SubscriptionId {
+        self.inner.borrow_mut().subscribe_drawings(Rc::new(handler))
+    }
+
+    fn emit_drawing_changes(&self) {
+        let calls = self.inner.borrow_mut().drawing_change_calls();
+        emit(calls);
+    }
+
+    /// Shows a drawing that is still being placed, or hides it with `None`.
+    /// The preview is not part of `drawings()`.
+    pub fn set_drawing_preview(&self, drawing: Option<Drawing>) {
+        self.inner.borrow_mut().set_drawing_preview(drawing);
+    }
+
+    /// Converts a pointer position to a drawing anchor.
+    ///
+    /// # Returns
+    ///
+    /// The (time, price) under the pointer, or `None` outside the main plot.
+    pub fn drawing_anchor_at(
+        &self,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    ) -> Option<DrawingAnchor> {
+        self.inner
+            .borrow_mut()
+            .drawing_anchor_at(x, y, width, height)
+    }
+
+    /// The price under a pointer position and the scale it is read from,
+    /// unlike `drawing_anchor_at` never snapped to a bar.
+    pub fn price_at(&self, x: f64, y: f64, width: f64, height: f64) -> Option<(PriceScale, f64)> {
+        self.inner.borrow_mut().price_at(x, y, width, height)
+    }
+
+    /// Converts a pointer position to the fractions of the main plot stored in
+    /// `Drawing::pinned`, or `None` outside the main plot.
+    pub fn pinned_position_at(
+        &self,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    ) -> Option<(f64, f64)> {
+        self.inner
+            .borrow_mut()
+            .pinned_position_at(x, y, width, height)
+    }
+
+    /// Finds the drawing, and the part of it, under a pointer position.
+    pub fn drawing_hit_test(&self, x: f64, y: f64, width: f64, height: f64) -> Option<DrawingHit> {
+        self.inner.borrow_mut().drawing_hit_at(x, y, width, height)
+    }
+
+    /// Counter bumped by every drawing change, for noticing edits without
+    /// comparing drawings.
+    pub fn drawing_revision(&self) -> u64 {
+        self.inner.borrow().drawing_revision()
+    }
+
+    /// Undoes the last step: a drawing, price line or panel edit, or an
+    /// edit recorded with `record_edit`.
+    ///
+    /// # Returns
+    ///
+    /// `false` when there was nothing to undo.
+    pub fn undo(&self) -> bool {
+        let pending = self.inner.borrow_mut().undo();
+        let undone = pending.map(|pending| self.replay(pending)).is_some();
+        self.emit_drawing_changes();
+        undone
+    }
+
+    /// Redoes the last undone step.
+    ///
+    /// # Returns
+    ///
+    /// `false` when there was nothing to redo.
+    pub fn redo(&self) -> bool {
+        let pending = self.inner.borrow_mut().redo();
+        let redone = pending.map(|pending| self.replay(pending)).is_some();
+        self.emit_drawing_changes();
+        redone
+    }
+
+    fn replay(&self, pending: Vec<Rc<dyn Fn()>>) {
+        self.inner.borrow_mut().set_history_replaying(true);
+        for action in pending {
+            action();
+        }
+        self.inner.borrow_mut().set_history_replaying(false);
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.inner.borrow().can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.inner.borrow().can_redo()
+    }
+
+    /// Records an edit made outside the chart as an undo step. The callbacks
+    /// may call back into the chart; changes they make are not recorded.
+    pub fn record_edit(&self, undo: Rc<dyn Fn()>, redo: Rc<dyn Fn()>) {
+        self.inner.borrow_mut().record_custom_edit(undo, redo);
+    }
+
+    /// Records the price lines of series `series` as an undo step, to call
+    /// before the user edits them. Creating, moving and removing price lines
+    /// through the API, e.g. `PriceLineApi::set_price` and
+    /// `PriceLineApi::remove`, is not recorded on its own, so feeds and
+    /// alerts stay out of the history.
+    pub fn record_price_lines(&self, series: usize) {
+        self.inner.borrow_mut().record_price_lines(series);
+    }
+
+    /// Collects the edits made until the matching `end_edit_group` into one
+    /// undo step, e.g. for a drag.
+    pub fn begin_edit_group(&self) {
+        self.inner.borrow_mut().begin_history_group();
+    }
+
+    pub fn end_edit_group(&self) {
+        self.inner.borrow_mut().end_history_group();
+    }
+
+    /// Forgets all undo and redo steps, e.g. after loading saved state.
+    pub fn clear_history(&self) {
+        self.inner.borrow_mut().clear_history();
+    }
+
+    /// Shows a transient measure box, or hides it with `None`. A measurement
+    /// is drawn like a `DrawingTool::Measure` drawing but is not one until
+    /// pinned.
+    pub fn set_measurement(&self, measurement: Option<Drawing>) {
+        self.inner.borrow_mut().set_measurement(measurement);
+    }
+
+    pub fn measurement(&self) -> Option<Drawing> {
+        self.inner.borrow().measurement()
+    }
+
+    /// Turns the current measurement into a drawing and returns its id.
+    pub fn pin_measurement(&self) -> Option<DrawingId> {
+        let id = self.inner.borrow_mut().pin_measurement();
+        self.emit_drawing_changes();
+        id
+    }
+
+    pub fn measurement_contains(&self, x: f64, y: f64, width: f64, height: f64) -> bool {
+        self.inner
+            .borrow_mut()
+            .measurement_contains(x, y, width, height)
+    }
+
+    /// Marks a drawing as selected so its anchor handles are shown.
+    pub fn select_drawing(&self, id: Option<DrawingId>) {
+        self.inner.borrow_mut().set_selected_drawing(id);
+    }
+
+    pub fn selected_drawing(&self) -> Option<DrawingId> {
+        self.inner.borrow().selected_drawing()
+    }
+
+    /// Snaps anchors from `drawing_anchor_at` to the nearest candle's open,
+    /// high, low or close, like `CrosshairMode::MagnetOhlc`.
+    pub fn set_drawing_magnet(&self, enabled: bool) {
+        self.inner.borrow_mut().set_drawing_magnet(enabled);
+    }
+
+    pub fn drawing_magnet(&self) -> bool {
+        self.inner.borrow().drawing_magnet()
+    }
+
+    /// Returns a copy of `drawing` moved by a pixel offset.
+    pub fn translate_drawing(
+        &self,
+        drawing: &Drawing,
+        dx: f64,
+        dy: f64,
+        width: f64,
+        height: f64,
+    ) -> Option<Drawing> {
+        self.inner
+            .borrow_mut()
+            .translate_drawing(drawing, dx, dy, width, height)
     }
 
     pub fn set_crosshair(&self, x: f64, y: f64) {
         self.inner.borrow_mut().set_crosshair(x, y);
+        let calls = self
+            .inner
+            .borrow()
+            .mouse_event_calls(MouseEventKind::CrosshairMove, Some((x, y)));
+        emit(calls);
     }
 
     pub fn clear_crosshair(&self) {
         self.inner.borrow_mut().clear_crosshair();
+        let calls = self
+            .inner
+            .borrow()
+            .mouse_event_calls(MouseEventKind::CrosshairMove, None);
+        emit(calls);
+    }
+
+    /// Puts the crosshair on the bar of the main candlestick series that
+    /// contains `time` (unix seconds), at its close. On a longer interval
+    /// this is the enclosing bar. Hides the crosshair and returns `false`
+    /// when that bar is out of view or the chart has not been drawn yet.
+    pub fn set_crosshair_time(&self, time: f64) -> bool {
+        let point = self.inner.borrow_mut().set_crosshair_time(time);
+        let calls = self
+            .inner
+            .borrow()
+            .mouse_event_calls(MouseEventKind::CrosshairMove, point);
+        emit(calls);
+        point.is_some()
+    }
+
+    /// Whether `self` and `other` are handles to the same chart.
+    pub fn same_chart(&self, other: &ChartApi) -> bool {
+        Rc::ptr_eq(&self.inner, &other.inner)
     }
 
     pub fn set_candle_colors(
@@ -618,6 +1343,92 @@ impl ChartApi {
         self.inner.borrow_mut().fit_content();
     }
 
+    /// Shows the zoom tool's rectangle between two corners while it is
+    /// being dragged.
+    pub fn preview_box_zoom(&self, x0: f64, y0: f64, x1: f64, y1: f64) {
+        self.inner.borrow_mut().set_zoom_box(Some((x0, y0, x1, y1)));
+    }
+
+    /// Hides the zoom tool's rectangle without zooming.
+    pub fn cancel_box_zoom(&self) {
+        self.inner.borrow_mut().set_zoom_box(None);
+    }
+
+    /// Zooms to the box between two corners: its width becomes the visible
+    /// time range and its height a manual price range for the pane it starts
+    /// in, turning auto scale off. The previous view is saved for
+    /// `zoom_out`. Returns the main price scales that were made manual, or
+    /// `None` when the box was too small to zoom.
+    pub fn box_zoom(&self, x0: f64, y0: f64, x1: f64, y1: f64) -> Option<Vec<PriceScale>> {
+        let sides = self.inner.borrow_mut().box_zoom(x0, y0, x1, y1);
+        if sides.is_some() {
+            self.emit_view_changes();
+        }
+        sides
+    }
+
+    /// Whether a box zoom can be undone with `zoom_out`.
+    pub fn can_zoom_out(&self) -> bool {
+        self.inner.borrow().can_zoom_out()
+    }
+
+    /// Returns to the view before the last box zoom, including its price
+    /// ranges and auto scale settings. Returns `false` when there is none.
+    pub fn zoom_out(&self) -> bool {
+        let restored = self.inner.borrow_mut().zoom_out();
+        if restored {
+            self.emit_view_changes();
+        }
+        restored
+    }
+
+    /// Scrolls by `bars` bars; positive moves towards newer data.
+    pub fn pan_by_bars(&self, bars: f64) {
+        self.inner.borrow_mut().pan_by_bars(bars);
+        self.emit_view_changes();
+    }
+
+    /// Scrolls by `pages` widths of the visible range.
+    pub fn pan_by_pages(&self, pages: f64) {
+        self.inner.borrow_mut().pan_by_pages(pages);
+        self.emit_view_changes();
+    }
+
+    /// Zooms the time scale around the last bar. `factor` below one zooms in.
+    pub fn zoom_at_last_bar(&self, factor: f64) {
+        self.inner.borrow_mut().zoom_at_last_bar(factor);
+        self.emit_view_changes();
+    }
+
+    /// Scrolls the first data point to the left edge.
+    pub fn scroll_to_first_bar(&self) {
+        self.inner.borrow_mut().scroll_to_first_bar();
+        self.emit_view_changes();
+    }
+
+    /// Scrolls the last data point to the right edge, keeping the right offset.
+    pub fn scroll_to_last_bar(&self) {
+        self.inner.borrow_mut().scroll_to_last_bar();
+        self.emit_view_changes();
+    }
+
+    /// Moves the crosshair `bars` bars along the main candlestick series and
+    /// onto the close, scrolling when the bar is out of view. A hidden
+    /// crosshair appears on the last visible bar. Returns `false` when there
+    /// is nothing to step through or the chart has not been drawn yet.
+    pub fn step_crosshair(&self, bars: i64) -> bool {
+        let Some((x, y)) = self.inner.borrow_mut().step_crosshair(bars) else {
+            return false;
+        };
+        self.emit_view_changes();
+        let calls = self
+            .inner
+            .borrow()
+            .mouse_event_calls(MouseEventKind::CrosshairMove, Some((x, y)));
+        emit(calls);
+        true
+    }
+
     pub fn set_time_scale_right_offset(&self, offset: f64) {
         self.inner.borrow_mut().set_time_scale_right_offset(offset);
     }
@@ -750,6 +1561,15 @@ impl ChartApi {
         self.inner.borrow_mut().set_kinetic_scroll_options(options);
     }
 
+    /// Configures animated view changes, or turns them off.
+    pub fn apply_animation_options(&self, options: AnimationOptions) {
+        self.inner.borrow_mut().set_animation_options(options);
+    }
+
+    pub fn animation_options(&self) -> AnimationOptions {
+        self.inner.borrow().options.animation
+    }
+
     pub fn apply_tracking_mode_options(&self, options: TrackingModeOptions) {
         self.inner.borrow_mut().set_tracking_mode_options(options);
     }
@@ -798,6 +1618,16 @@ impl ChartApi {
             .set_price_scale_auto_scale(side, enabled);
     }
 
+    pub fn price_scale_auto_scale(&self, side: PriceScale) -> bool {
+        self.inner.borrow().price_scale_auto_scale(side)
+    }
+
+    /// `price` formatted like the series on the `side` scale, e.g. for
+    /// copying.
+    pub fn format_price(&self, side: PriceScale, price: f64) -> String {
+        self.inner.borrow().format_price(side, price)
+    }
+
     pub fn set_price_scale_visible(&self, side: PriceScale, visible: bool) {
         self.inner
             .borrow_mut()
@@ -862,6 +1692,16 @@ impl ChartApi {
 }
 
 impl CandlestickSeriesApi {
+    /// Id of this series in `MouseEventParams::series_values`.
+    pub fn series_id(&self) -> usize {
+        self.id
+    }
+
+    /// Shows or hides this series with its markers and lines.
+    pub fn set_visible(&self, visible: bool) {
+        self.inner.borrow_mut().set_series_visible(self.id, visible);
+    }
+
     /// Sets the candlestick data for this series.
     ///
     /// This replaces all existing data in the series.
@@ -1015,6 +1855,12 @@ impl CandlestickSeriesApi {
             .set_series_price_format(self.id, format);
     }
 
+    /// `price` formatted with the series' price format, as on its price
+    /// line labels.
+    pub fn format_price(&self, price: f64) -> String {
+        self.inner.borrow().format_series_price(self.id, price)
+    }
+
     /// Creates a new price line on this series.
     ///
     /// # Arguments
@@ -1048,9 +1894,78 @@ impl CandlestickSeriesApi {
             line_id,
         }
     }
+
+    /// Adds a line for an open position, showing its size and unrealized
+    /// profit or loss against this series' last close.
+    pub fn create_position_line(&self, options: PositionLineOptions) -> PositionLineApi {
+        let line_id = self
+            .inner
+            .borrow_mut()
+            .create_position_line(self.id, options);
+        PositionLineApi {
+            inner: self.inner.clone(),
+            series_id: self.id,
+            line_id,
+        }
+    }
+
+    /// Adds a line for a working order with side, type and quantity badges.
+    pub fn create_order_line(&self, options: OrderLineOptions) -> OrderLineApi {
+        let line_id = self.inner.borrow_mut().create_order_line(self.id, options);
+        OrderLineApi {
+            inner: self.inner.clone(),
+            series_id: self.id,
+            line_id,
+        }
+    }
+
+    /// Adds a volume-by-price profile built from this series and `volume`.
+    ///
+    /// Bars are matched to volume points by time. The profile is recomputed
+    /// only when the bars it covers or the data change, so panning a
+    /// visible-range profile stays cheap.
+    ///
+    /// # Arguments
+    ///
+    /// * `volume` - Histogram series holding the volume of each bar
+    /// * `options` - Range, row and styling options for the profile
+    pub fn create_volume_profile(
+        &self,
+        volume: &HistogramSeriesApi,
+        options: VolumeProfileOptions,
+    ) -> VolumeProfileApi {
+        let profile_id = self
+            .inner
+            .borrow_mut()
+            .create_volume_profile(self.id, volume.id, options);
+        VolumeProfileApi {
+            inner: self.inner.clone(),
+            profile_id,
+        }
+    }
+
+    /// Replaces the series' price segments.
+    ///
+    /// Segments are horizontal levels drawn only across their own time span,
+    /// e.g. one set of pivot levels per session. Pass an empty vector to clear.
+    pub fn set_price_segments(&self, segments: Vec<PriceSegment>) {
+        self.inner
+            .borrow_mut()
+            .set_price_segments(self.id, segments);
+    }
 }
 
 impl LineSeriesApi {
+    /// Id of this series in `MouseEventParams::series_values`.
+    pub fn series_id(&self) -> usize {
+        self.id
+    }
+
+    /// Shows or hides this series with its markers and lines.
+    pub fn set_visible(&self, visible: bool) {
+        self.inner.borrow_mut().set_series_visible(self.id, visible);
+    }
+
     pub fn set_data(&self, points: Vec<LinePoint>) {
         self.inner.borrow_mut().set_line_points(self.id, points);
     }
@@ -1063,6 +1978,16 @@ impl LineSeriesApi {
         self.inner.borrow_mut().set_series_scale(self.id, scale);
     }
 
+    pub fn set_color(&self, color: Color) {
+        self.inner.borrow_mut().set_series_color(self.id, color);
+    }
+
+    pub fn set_line_width(&self, width: f64) {
+        self.inner
+            .borrow_mut()
+            .set_series_line_width(self.id, width);
+    }
+
     pub fn set_price_line_visible(&self, visible: bool) {
         self.inner
             .borrow_mut()
@@ -1129,9 +2054,63 @@ impl LineSeriesApi {
             line_id,
         }
     }
+
+    pub fn set_price_segments(&self, segments: Vec<PriceSegment>) {
+        self.inner
+            .borrow_mut()
+            .set_price_segments(self.id, segments);
+    }
+}
+
+impl CloudSeriesApi {
+    /// Id of this series in `MouseEventParams::series_values`.
+    pub fn series_id(&self) -> usize {
+        self.id
+    }
+
+    /// Shows or hides this series with its markers and lines.
+    pub fn set_visible(&self, visible: bool) {
+        self.inner.borrow_mut().set_series_visible(self.id, visible);
+    }
+
+    pub fn set_data(&self, points: Vec<CloudPoint>) {
+        self.inner.borrow_mut().set_cloud_points(self.id, points);
+    }
+
+    pub fn update(&self, point: CloudPoint) {
+        self.inner.borrow_mut().update_cloud_point(self.id, point);
+    }
+
+    pub fn set_price_scale(&self, scale: PriceScale) {
+        self.inner.borrow_mut().set_series_scale(self.id, scale);
+    }
+
+    /// Sets the fill colors used while `value_a >= value_b` (`up`) and
+    /// while `value_a < value_b` (`down`).
+    pub fn set_fill_colors(&self, up: Color, down: Color) {
+        self.inner
+            .borrow_mut()
+            .set_series_fill_colors(self.id, up, down);
+    }
+
+    pub fn set_fill_alpha(&self, alpha: f64) {
+        self.inner
+            .borrow_mut()
+            .set_series_fill_alpha(self.id, alpha);
+    }
 }
 
 impl HistogramSeriesApi {
+    /// Id of this series in `MouseEventParams::series_values`.
+    pub fn series_id(&self) -> usize {
+        self.id
+    }
+
+    /// Shows or hides this series with its markers and lines.
+    pub fn set_visible(&self, visible: bool) {
+        self.inner.borrow_mut().set_series_visible(self.id, visible);
+    }
+
     pub fn set_data(&self, points: Vec<HistogramPoint>) {
         self.inner
             .borrow_mut()
@@ -1214,9 +2193,28 @@ impl HistogramSeriesApi {
             line_id,
         }
     }
+
+    pub fn set_price_segments(&self, segments: Vec<PriceSegment>) {
+        self.inner
+            .borrow_mut()
+            .set_price_segments(self.id, segments);
+    }
 }
 
 impl PriceLineApi {
+    /// Id of this price line in `HitTarget::PriceLine`.
+    pub fn line_id(&self) -> usize {
+        self.line_id
+    }
+
+    /// Current options of this price line, `None` once it was removed.
+    pub fn options(&self) -> Option<PriceLineOptions> {
+        self.inner
+            .borrow()
+            .price_line_options(self.series_id, self.line_id)
+            .cloned()
+    }
+
     /// Applies new options to this price line.
     ///
     /// This updates all configurable aspects of the price line.
@@ -1230,7 +2228,8 @@ impl PriceLineApi {
             .update_price_line(self.series_id, self.line_id, options);
     }
 
-    /// Updates the price level of this price line.
+    /// Updates the price level of this price line. Not recorded in the undo
+    /// history; see `ChartApi::record_price_lines`.
     ///
     /// # Arguments
     ///
@@ -1244,10 +2243,127 @@ impl PriceLineApi {
     /// Removes this price line from the chart.
     ///
     /// After calling this method, the price line will no longer be visible
-    /// and the PriceLineApi instance should be discarded.
+    /// and the PriceLineApi instance should be discarded. Not recorded in
+    /// the undo history; see `ChartApi::record_price_lines`.
     pub fn remove(&self) {
         self.inner
             .borrow_mut()
             .remove_price_line(self.series_id, self.line_id);
     }
 }
+
+impl PositionLineApi {
+    /// Id of this line in `HitTarget::Position`.
+    pub fn line_id(&self) -> usize {
+        self.line_id
+    }
+
+    pub fn apply_options(&self, options: PositionLineOptions) {
+        if let Some(current) = self
+            .inner
+            .borrow_mut()
+            .position_line_mut(self.series_id, self.line_id)
+        {
+            *current = options;
+        }
+    }
+
+    /// Updates the average entry price.
+    pub fn set_price(&self, price: f64) {
+        if let Some(current) = self
+            .inner
+            .borrow_mut()
+            .position_line_mut(self.series_id, self.line_id)
+        {
+            current.price = price;
+        }
+    }
+
+    /// Updates the position size; negative for a short position.
+    pub fn set_quantity(&self, quantity: f64) {
+        if let Some(current) = self
+            .inner
+            .borrow_mut()
+            .position_line_mut(self.series_id, self.line_id)
+        {
+            current.quantity = quantity;
+        }
+    }
+
+    /// Unrealized profit or loss against the series' last close, or `None`
+    /// when the series has no data or the line was removed.
+    pub fn unrealized_pnl(&self) -> Option<f64> {
+        self.inner
+            .borrow()
+            .position_pnl(self.series_id, self.line_id)
+    }
+
+    pub fn remove(&self) {
+        self.inner
+            .borrow_mut()
+            .remove_position_line(self.series_id, self.line_id);
+    }
+}
+
+impl OrderLineApi {
+    /// Id of this line in `HitTarget::Order` and cancel events.
+    pub fn line_id(&self) -> usize {
+        self.line_id
+    }
+
+    pub fn apply_options(&self, options: OrderLineOptions) {
+        if let Some(current) = self
+            .inner
+            .borrow_mut()
+            .order_line_mut(self.series_id, self.line_id)
+        {
+            *current = options;
+        }
+    }
+
+    pub fn set_price(&self, price: f64) {
+        if let Some(current) = self
+            .inner
+            .borrow_mut()
+            .order_line_mut(self.series_id, self.line_id)
+        {
+            current.price = price;
+        }
+    }
+
+    pub fn set_quantity(&self, quantity: f64) {
+        if let Some(current) = self
+            .inner
+            .borrow_mut()
+            .order_line_mut(self.series_id, self.line_id)
+        {
+            current.quantity = quantity;
+        }
+    }
+
+    pub fn remove(&self) {
+        self.inner
+            .borrow_mut()
+            .remove_order_line(self.series_id, self.line_id);
+    }
+}
+
+impl VolumeProfileApi {
+    pub fn apply_options(&self, options: VolumeProfileOptions) {
+        self.inner
+            .borrow_mut()
+            .update_volume_profile(self.profile_id, options);
+    }
+
+    /// Returns POC, value area and volume totals for the profiles in the
+    /// current view; session profiles yield one entry per session.
+    pub fn levels(&self) -> Vec<VolumeProfileLevels> {
+        self.inner.borrow().volume_profile_levels(self.profile_id)
+    }
+
+    pub fn remove(&self) {
+        self.inner
+            .borrow_mut()
+            .remove_volume_profile(self.profile_id);
+    }
+}
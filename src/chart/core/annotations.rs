@@ -0,0 +1,90 @@
+use cairo::{Context, FontSlant, FontWeight, Format, ImageSurface};
+
+use super::super::drawings::{Drawing, DrawingTool};
+use super::drawings::AnchorMapper;
+use crate::icons::is_marker_icon;
+
+/// Space between a text box's border and its text, in pixels.
+pub(super) const TEXT_PADDING: f64 = 6.0;
+/// Line height as a multiple of the font size.
+const LINE_SPACING: f64 = 1.3;
+
+/// Layout of the box of a text, callout or note drawing.
+pub(super) struct TextBox {
+    pub(super) left: f64,
+    pub(super) top: f64,
+    pub(super) width: f64,
+    pub(super) height: f64,
+    pub(super) line_height: f64,
+    pub(super) lines: Vec<String>,
+}
+
+impl TextBox {
+    pub(super) fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.left
+            && x <= self.left + self.width
+            && y >= self.top
+            && y <= self.top + self.height
+    }
+
+    pub(super) fn center(&self) -> (f64, f64) {
+        (self.left + self.width / 2.0, self.top + self.height / 2.0)
+    }
+}
+
+/// Scratch context for measuring text outside of rendering, e.g. when
+/// hit-testing.
+pub(super) fn measure_context() -> Option<Context> {
+    let surface = ImageSurface::create(Format::ARgb32, 1, 1).ok()?;
+    Context::new(&surface).ok()
+}
+
+pub(super) fn set_text_font(cr: &Context, drawing: &Drawing) {
+    cr.select_font_face("Sans", FontSlant::Normal, FontWeight::Normal);
+    cr.set_font_size(drawing.style.font_size.max(4.0));
+}
+
+/// Lays out a drawing's text box, or returns `None` for tools without one.
+/// The box's top-left corner sits at the anchor; callouts use their second
+/// anchor, the first being what they point at. Empty text shows the tool's
+/// name so the box can still be found and edited. Lines naming a marker icon
+/// are drawn as that icon, one font size square.
+pub(super) fn text_box(cr: &Context, mapper: &AnchorMapper, drawing: &Drawing) -> Option<TextBox> {
+    if !drawing.tool.has_text_box() {
+        return None;
+    }
+    let points = mapper.points(drawing);
+    let (left, top) = match drawing.tool {
+        DrawingTool::Callout => *points.get(1)?,
+        _ => *points.first()?,
+    };
+    set_text_font(cr, drawing);
+    let size = drawing.style.font_size.max(4.0);
+    let text = if drawing.text.trim().is_empty() {
+        drawing.tool.label()
+    } else {
+        drawing.text.as_str()
+    };
+    let lines: Vec<String> = text.lines().map(str::to_string).collect();
+    let text_width = lines
+        .iter()
+        .map(|line| {
+            if is_marker_icon(line) {
+                size
+            } else {
+                cr.text_extents(line)
+                    .map(|extents| extents.x_advance())
+                    .unwrap_or(0.0)
+            }
+        })
+        .fold(0.0, f64::max);
+    let line_height = size * LINE_SPACING;
+    Some(TextBox {
+        left,
+        top,
+        width: text_width + TEXT_PADDING * 2.0,
+        height: line_height * lines.len().max(1) as f64 + TEXT_PADDING * 2.0,
+        line_height,
+        lines,
+    })
+}
@@ -0,0 +1,210 @@
+use cairo::Context;
+
+use super::super::data::{PriceScaleState, SeriesScale};
+use super::super::layout::ChartLayout;
+use super::super::types::{PriceScale, Rect};
+use super::super::util::map_y_to_price_scaled;
+use super::ChartCore;
+
+/// Smallest box, in pixels along either side, that zooms rather than being
+/// taken for a click.
+const MIN_BOX_SIZE: f64 = 4.0;
+
+/// Maximum number of views kept for `zoom_out`.
+const ZOOM_HISTORY_LIMIT: usize = 50;
+
+/// A view saved before a box zoom, restored by `zoom_out`.
+#[derive(Clone, Debug)]
+pub(crate) struct ZoomView {
+    start: f64,
+    end: f64,
+    bar_spacing: f64,
+    left: (PriceScaleState, bool),
+    right: (PriceScaleState, bool),
+    rsi: Option<(PriceScaleState, bool)>,
+}
+
+/// Prices at the top and bottom of a box spanning `top..bottom`, lowest
+/// first.
+fn price_span(
+    scale: SeriesScale,
+    top: f64,
+    bottom: f64,
+    area_top: f64,
+    area_height: f64,
+) -> (f64, f64) {
+    let price_at = |y: f64| {
+        map_y_to_price_scaled(
+            y,
+            scale.min,
+            scale.max,
+            area_top,
+            area_height,
+            scale.margins,
+            scale.invert,
+            scale.mode,
+            scale.base,
+        )
+    };
+    let (a, b) = (price_at(top), price_at(bottom));
+    (a.min(b), a.max(b))
+}
+
+impl ChartCore {
+    /// Shows the zoom rectangle from `(x0, y0)` to `(x1, y1)`, or hides it.
+    pub(crate) fn set_zoom_box(&mut self, corners: Option<(f64, f64, f64, f64)>) {
+        self.zoom_box = corners.map(|(x0, y0, x1, y1)| Rect {
+            x: x0.min(x1),
+            y: y0.min(y1),
+            width: (x1 - x0).abs(),
+            height: (y1 - y0).abs(),
+        });
+    }
+
+    fn zoom_view(&self) -> ZoomView {
+        ZoomView {
+            start: self.time_scale.start,
+            end: self.time_scale.end,
+            bar_spacing: self.time_scale.bar_spacing,
+            left: (
+                self.left_scale.clone(),
+                self.options.left_price_scale.auto_scale,
+            ),
+            right: (
+                self.right_scale.clone(),
+                self.options.right_price_scale.auto_scale,
+            ),
+            rsi: self
+                .rsi_panel
+                .as_ref()
+                .map(|panel| (panel.scale.clone(), panel.options.auto_scale)),
+        }
+    }
+
+    /// Zooms to the box from `(x0, y0)` to `(x1, y1)`: its horizontal extent
+    /// becomes the visible time range and its vertical extent a manual price
+    /// range for the scales of the pane it was started in. Returns the main
+    /// price scales switched to manual, or `None` when the box is too small
+    /// or outside the plot.
+    pub(crate) fn box_zoom(
+        &mut self,
+        x0: f64,
+        y0: f64,
+        x1: f64,
+        y1: f64,
+    ) -> Option<Vec<PriceScale>> {
+        self.zoom_box = None;
+        let (width, height) = self.events.drawn_size?;
+        let layout = ChartLayout::new(self, width, height);
+        if layout.plot_width <= 0.0 {
+            return None;
+        }
+        let in_rsi = layout.rsi_height > 0.0 && layout.in_rsi_plot(y0);
+        if !in_rsi && !layout.in_main_plot(y0) {
+            return None;
+        }
+        let (area_top, area_height) = if in_rsi {
+            (layout.rsi_top, layout.rsi_height)
+        } else {
+            (layout.plot_top, layout.main_height)
+        };
+        let left = x0.min(x1).max(layout.plot_left);
+        let right = x0.max(x1).min(layout.plot_right);
+        let top = y0.min(y1).max(area_top);
+        let bottom = y0.max(y1).min(area_top + area_height);
+        if right - left < MIN_BOX_SIZE || bottom - top < MIN_BOX_SIZE {
+            return None;
+        }
+
+        let saved = self.zoom_view();
+        let start = self.time_scale.start;
+        let end = self.time_scale.end;
+        let mut manual = Vec::new();
+        if in_rsi {
+            let scale = self.scale_for_rsi(start, end)?;
+            let (low, high) = price_span(scale, top, bottom, area_top, area_height);
+            let panel = self.rsi_panel.as_mut()?;
+            panel.scale.view_min = low;
+            panel.scale.view_max = high;
+            panel.scale.transition = None;
+            panel.scale.auto = false;
+            panel.options.auto_scale = false;
+        } else {
+            for side in [PriceScale::Left, PriceScale::Right] {
+                let Some(scale) = self.scale_for_side(side, start, end) else {
+                    continue;
+                };
+                let (low, high) = price_span(scale, top, bottom, area_top, area_height);
+                let (state, options) = match side {
+                    PriceScale::Left => (&mut self.left_scale, &mut self.options.left_price_scale),
+                    PriceScale::Right => {
+                        (&mut self.right_scale, &mut self.options.right_price_scale)
+                    }
+                };
+                state.view_min = low;
+                state.view_max = high;
+                // A running auto scale animation would pull the range back.
+                state.transition = None;
+                state.auto = false;
+                options.auto_scale = false;
+                manual.push(side);
+            }
+        }
+
+        let range = end - start;
+        let time_at = |x: f64| start + (x - layout.plot_left) / layout.plot_width * range;
+        self.set_visible_time_range(time_at(left), time_at(right));
+
+        self.zoom_history.push(saved);
+        if self.zoom_history.len() > ZOOM_HISTORY_LIMIT {
+            self.zoom_history.remove(0);
+        }
+        Some(manual)
+    }
+
+    /// Forgets the views saved by box zoom. Their time and price ranges
+    /// belong to data that has been replaced.
+    pub(crate) fn clear_zoom_history(&mut self) {
+        self.zoom_history.clear();
+    }
+
+    /// Whether `zoom_out` has a view to return to.
+    pub(crate) fn can_zoom_out(&self) -> bool {
+        !self.zoom_history.is_empty()
+    }
+
+    /// Returns to the view before the last box zoom.
+    pub(crate) fn zoom_out(&mut self) -> bool {
+        let Some(view) = self.zoom_history.pop() else {
+            return false;
+        };
+        self.time_scale.set_bar_spacing(view.bar_spacing);
+        self.time_scale.start = view.start;
+        self.time_scale.end = view.end;
+        (self.left_scale, self.options.left_price_scale.auto_scale) = view.left;
+        (self.right_scale, self.options.right_price_scale.auto_scale) = view.right;
+        self.left_scale.transition = None;
+        self.right_scale.transition = None;
+        if let (Some(panel), Some((scale, auto_scale))) = (self.rsi_panel.as_mut(), view.rsi) {
+            panel.scale = scale;
+            panel.scale.transition = None;
+            panel.options.auto_scale = auto_scale;
+        }
+        true
+    }
+
+    pub(super) fn draw_zoom_box(&self, cr: &Context) {
+        let Some(rect) = self.zoom_box else {
+            return;
+        };
+        let color = self.style.crosshair;
+        cr.set_source_rgba(color.r, color.g, color.b, 0.12);
+        cr.rectangle(rect.x, rect.y, rect.width, rect.height);
+        let _ = cr.fill_preserve();
+        cr.set_source_rgba(color.r, color.g, color.b, 0.8);
+        cr.set_line_width(1.0);
+        cr.set_dash(&[4.0, 3.0], 0.0);
+        let _ = cr.stroke();
+        cr.set_dash(&[], 0.0);
+    }
+}
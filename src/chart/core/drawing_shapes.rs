@@ -0,0 +1,212 @@
+use super::super::drawings::{Drawing, DrawingTool};
+use super::super::types::Candle;
+use super::super::util::candle_time;
+use super::drawings::{extend_line, extend_styled, AnchorMapper, PlotRect, Segment};
+
+type Point = (f64, f64);
+
+const ELLIPSE_STEPS: usize = 48;
+
+fn anchor_points(mapper: &AnchorMapper, drawing: &Drawing) -> Vec<Point> {
+    drawing
+        .anchors
+        .iter()
+        .map(|anchor| mapper.point(anchor))
+        .collect()
+}
+
+/// Main line, parallel line and middle line of a parallel channel. The
+/// parallel is the main line shifted to pass through the third anchor.
+pub(super) fn parallel_channel(
+    mapper: &AnchorMapper,
+    rect: PlotRect,
+    drawing: &Drawing,
+) -> Option<[Segment; 3]> {
+    let &[a, b, c, ..] = anchor_points(mapper, drawing).as_slice() else {
+        return None;
+    };
+    let shift = if (b.0 - a.0).abs() > f64::EPSILON {
+        (0.0, c.1 - (a.1 + (b.1 - a.1) * (c.0 - a.0) / (b.0 - a.0)))
+    } else {
+        (c.0 - a.0, 0.0)
+    };
+    let line = |t: f64| {
+        let moved = |p: Point| (p.0 + shift.0 * t, p.1 + shift.1 * t);
+        extend_styled(moved(a), moved(b), rect, &drawing.style, false, false)
+    };
+    Some([line(0.0), line(1.0), line(0.5)])
+}
+
+pub(super) struct RegressionChannel {
+    pub(super) center: Segment,
+    /// Upper and lower band for each visible level, with the level index
+    pub(super) bands: Vec<(usize, Segment, Segment)>,
+}
+
+/// Least-squares fit of closes against bar index over the candles between
+/// the two anchors. Bands sit the level's number of standard deviations of
+/// the residuals above and below the fit.
+pub(super) fn regression_channel(
+    mapper: &AnchorMapper,
+    rect: PlotRect,
+    drawing: &Drawing,
+    candles: &[Candle],
+) -> Option<RegressionChannel> {
+    let [a, b, ..] = drawing.anchors.as_slice() else {
+        return None;
+    };
+    let (start, end) = (
+        candle_time(a.time.min(b.time)),
+        candle_time(a.time.max(b.time)),
+    );
+    let first = candles.partition_point(|candle| candle_time(candle.time) < start);
+    let last = candles.partition_point(|candle| candle_time(candle.time) <= end);
+    let bars = candles.get(first..last).filter(|bars| bars.len() >= 2)?;
+
+    let n = bars.len() as f64;
+    let mean_x = (n - 1.0) / 2.0;
+    let mean_y = bars.iter().map(|bar| bar.close).sum::<f64>() / n;
+    let (sxy, sxx) = bars
+        .iter()
+        .enumerate()
+        .fold((0.0, 0.0), |(sxy, sxx), (idx, bar)| {
+            let dx = idx as f64 - mean_x;
+            (sxy + dx * (bar.close - mean_y), sxx + dx * dx)
+        });
+    let slope = sxy / sxx;
+    let intercept = mean_y - slope * mean_x;
+    let variance = bars
+        .iter()
+        .enumerate()
+        .map(|(idx, bar)| (bar.close - (intercept + slope * idx as f64)).powi(2))
+        .sum::<f64>()
+        / n;
+    let deviation = variance.sqrt();
+
+    let x0 = mapper.time_x(candle_time(bars[0].time));
+    let x1 = mapper.time_x(candle_time(bars[bars.len() - 1].time));
+    let end_price = intercept + slope * (n - 1.0);
+    let line = |offset: f64| {
+        extend_styled(
+            (x0, mapper.y(intercept + offset)),
+            (x1, mapper.y(end_price + offset)),
+            rect,
+            &drawing.style,
+            false,
+            false,
+        )
+    };
+    let bands = drawing
+        .levels
+        .iter()
+        .enumerate()
+        .filter(|(_, level)| level.visible)
+        .map(|(idx, level)| {
+            let offset = level.value * deviation;
+            (idx, line(offset), line(-offset))
+        })
+        .collect();
+    Some(RegressionChannel {
+        center: line(0.0),
+        bands,
+    })
+}
+
+/// Median line, upper tine, lower tine and the segment joining the tines'
+/// anchors. The median runs from the handle through the midpoint of the
+/// second and third anchors; the tines are parallel to it.
+pub(super) fn pitchfork(
+    mapper: &AnchorMapper,
+    rect: PlotRect,
+    drawing: &Drawing,
+) -> Option<[Segment; 4]> {
+    let &[a, b, c, ..] = anchor_points(mapper, drawing).as_slice() else {
+        return None;
+    };
+    let handle = match drawing.tool {
+        DrawingTool::Pitchfork => a,
+        DrawingTool::SchiffPitchfork => (a.0, (a.1 + b.1) / 2.0),
+        DrawingTool::ModifiedSchiffPitchfork => ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0),
+        _ => return None,
+    };
+    let mid = ((b.0 + c.0) / 2.0, (b.1 + c.1) / 2.0);
+    let direction = (mid.0 - handle.0, mid.1 - handle.1);
+    if direction.0.abs() < f64::EPSILON && direction.1.abs() < f64::EPSILON {
+        return None;
+    }
+    let tine = |p: Point| extend_line(p, (p.0 + direction.0, p.1 + direction.1), rect, false, true);
+    Some([
+        extend_line(handle, mid, rect, false, true),
+        tine(b),
+        tine(c),
+        (b, c),
+    ])
+}
+
+/// Corner points of a rectangle, ellipse, triangle or risk/reward box, in
+/// drawing order.
+pub(super) fn shape_outline(mapper: &AnchorMapper, drawing: &Drawing) -> Vec<Point> {
+    let points = anchor_points(mapper, drawing);
+    match (drawing.tool, points.as_slice()) {
+        (DrawingTool::Rectangle | DrawingTool::Measure, &[a, b, ..]) => {
+            vec![a, (b.0, a.1), b, (a.0, b.1)]
+        }
+        (DrawingTool::Ellipse, &[a, b, ..]) => {
+            let center = ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+            let radii = ((b.0 - a.0).abs() / 2.0, (b.1 - a.1).abs() / 2.0);
+            (0..ELLIPSE_STEPS)
+                .map(|step| {
+                    let angle = step as f64 / ELLIPSE_STEPS as f64 * std::f64::consts::TAU;
+                    (
+                        center.0 + radii.0 * angle.cos(),
+                        center.1 + radii.1 * angle.sin(),
+                    )
+                })
+                .collect()
+        }
+        (DrawingTool::Triangle, &[a, b, c, ..]) => vec![a, b, c],
+        (DrawingTool::RiskReward, &[a, b, c, ..]) => {
+            let (left, right) = risk_reward_span(a, b, c);
+            let top = a.1.min(b.1).min(c.1);
+            let bottom = a.1.max(b.1).max(c.1);
+            vec![(left, top), (right, top), (right, bottom), (left, bottom)]
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Left and right edge of a risk/reward box: from the entry to the later of
+/// the target and stop anchors.
+pub(super) fn risk_reward_span(entry: Point, target: Point, stop: Point) -> (f64, f64) {
+    let end = target.0.max(stop.0);
+    (entry.0.min(end), entry.0.max(end))
+}
+
+/// Edges of the closed polygon through `points`.
+pub(super) fn closed_edges(points: &[Point]) -> Vec<Segment> {
+    if points.len() < 2 {
+        return Vec::new();
+    }
+    points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(from, to)| (*from, *to))
+        .collect()
+}
+
+/// Neckline of a head and shoulders, through the two neckline anchors and
+/// running from the pattern's start to its end.
+pub(super) fn neckline(mapper: &AnchorMapper, drawing: &Drawing) -> Option<Segment> {
+    if drawing.tool != DrawingTool::HeadAndShoulders {
+        return None;
+    }
+    let &[start, _, left, _, right, _, end, ..] = anchor_points(mapper, drawing).as_slice() else {
+        return None;
+    };
+    if (right.0 - left.0).abs() < f64::EPSILON {
+        return Some((left, right));
+    }
+    let slope = (right.1 - left.1) / (right.0 - left.0);
+    let at = |x: f64| (x, left.1 + slope * (x - left.0));
+    Some((at(start.0.min(end.0)), at(start.0.max(end.0))))
+}
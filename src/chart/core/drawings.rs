@@ -0,0 +1,727 @@
+use time::OffsetDateTime;
+
+use super::super::data::SeriesScale;
+use super::super::drawings::{
+    Drawing, DrawingAnchor, DrawingHit, DrawingHitTarget, DrawingId, DrawingStyle, DrawingTool,
+};
+use super::super::layout::ChartLayout;
+use super::super::types::{Candle, PriceScale};
+use super::super::util::{
+    candle_time, map_price_to_y_scaled, map_time_to_x, map_y_to_price_scaled,
+};
+use super::annotations::{measure_context, text_box};
+use super::drawing_shapes::{
+    closed_edges, neckline, parallel_channel, pitchfork, regression_channel, shape_outline,
+};
+use super::render_helpers::{nearest_ohlc, primary_candle_side, primary_candles};
+use super::ChartCore;
+
+/// Pick radius around anchor handles, in pixels.
+const HANDLE_HIT_RADIUS: f64 = 6.0;
+/// Maximum distance from a line that still counts as a hit, in pixels.
+const LINE_HIT_TOLERANCE: f64 = 5.0;
+
+/// Plot rectangle drawings are clipped to: `(left, top, right, bottom)`.
+pub(super) type PlotRect = (f64, f64, f64, f64);
+
+pub(super) type Segment = ((f64, f64), (f64, f64));
+
+pub(super) fn plot_rect(layout: &ChartLayout) -> PlotRect {
+    (
+        layout.plot_left,
+        layout.plot_top,
+        layout.plot_right,
+        layout.main_bottom,
+    )
+}
+
+/// Converts drawing anchors to pixels for one frame. Drawings always use the
+/// primary price scale: the right one when present, otherwise the left.
+#[derive(Clone, Copy)]
+pub(super) struct AnchorMapper {
+    pub(super) scale: SeriesScale,
+    pub(super) side: PriceScale,
+    pub(super) start_time: f64,
+    pub(super) end_time: f64,
+    pub(super) plot_left: f64,
+    pub(super) plot_width: f64,
+    pub(super) plot_top: f64,
+    pub(super) main_height: f64,
+}
+
+impl AnchorMapper {
+    pub(super) fn new(
+        layout: &ChartLayout,
+        left_scale: Option<SeriesScale>,
+        right_scale: Option<SeriesScale>,
+        start_time: f64,
+        end_time: f64,
+    ) -> Option<Self> {
+        let (scale, side) = match (right_scale, left_scale) {
+            (Some(scale), _) => (scale, PriceScale::Right),
+            (None, Some(scale)) => (scale, PriceScale::Left),
+            (None, None) => return None,
+        };
+        Some(Self {
+            scale,
+            side,
+            start_time,
+            end_time,
+            plot_left: layout.plot_left,
+            plot_width: layout.plot_width,
+            plot_top: layout.plot_top,
+            main_height: layout.main_height,
+        })
+    }
+
+    pub(super) fn x(&self, time: OffsetDateTime) -> f64 {
+        self.time_x(candle_time(time))
+    }
+
+    pub(super) fn time_x(&self, time: f64) -> f64 {
+        map_time_to_x(
+            time,
+            self.start_time,
+            self.end_time,
+            self.plot_left,
+            self.plot_width,
+        )
+    }
+
+    pub(super) fn y(&self, price: f64) -> f64 {
+        map_price_to_y_scaled(
+            price,
+            self.scale.min,
+            self.scale.max,
+            self.plot_top,
+            self.main_height,
+            self.scale.margins,
+            self.scale.invert,
+            self.scale.mode,
+            self.scale.base,
+        )
+    }
+
+    pub(super) fn point(&self, anchor: &DrawingAnchor) -> (f64, f64) {
+        (self.x(anchor.time), self.y(anchor.price))
+    }
+
+    /// Screen positions of a drawing's anchors. A pinned drawing has a single
+    /// point at its pinned position.
+    pub(super) fn points(&self, drawing: &Drawing) -> Vec<(f64, f64)> {
+        match drawing.pinned {
+            Some(pinned) => vec![self.pinned_point(pinned)],
+            None => drawing
+                .anchors
+                .iter()
+                .map(|anchor| self.point(anchor))
+                .collect(),
+        }
+    }
+
+    fn pinned_point(&self, (fx, fy): (f64, f64)) -> (f64, f64) {
+        (
+            self.plot_left + fx * self.plot_width,
+            self.plot_top + fy * self.main_height,
+        )
+    }
+
+    pub(super) fn pinned_position(&self, x: f64, y: f64) -> (f64, f64) {
+        (
+            ((x - self.plot_left) / self.plot_width).clamp(0.0, 1.0),
+            ((y - self.plot_top) / self.main_height).clamp(0.0, 1.0),
+        )
+    }
+
+    pub(super) fn anchor(&self, x: f64, y: f64) -> DrawingAnchor {
+        let time = self.start_time
+            + (x - self.plot_left) / self.plot_width * (self.end_time - self.start_time);
+        let price = map_y_to_price_scaled(
+            y,
+            self.scale.min,
+            self.scale.max,
+            self.plot_top,
+            self.main_height,
+            self.scale.margins,
+            self.scale.invert,
+            self.scale.mode,
+            self.scale.base,
+        );
+        DrawingAnchor {
+            time: OffsetDateTime::from_unix_timestamp(time.round() as i64)
+                .unwrap_or(OffsetDateTime::UNIX_EPOCH),
+            price,
+        }
+    }
+}
+
+impl ChartCore {
+    pub(crate) fn add_drawing(&mut self, drawing: Drawing) -> DrawingId {
+        self.record_drawings();
+        let id = DrawingId(self.next_drawing_id);
+        self.next_drawing_id += 1;
+        self.drawings.push((id, drawing));
+        self.drawing_revision = self.drawing_revision.wrapping_add(1);
+        id
+    }
+
+    pub(crate) fn update_drawing(&mut self, id: DrawingId, drawing: Drawing) {
+        if !self.drawings.iter().any(|(key, _)| *key == id) {
+            return;
+        }
+        self.record_drawings();
+        if let Some((_, existing)) = self.drawings.iter_mut().find(|(key, _)| *key == id) {
+            *existing = drawing;
+            self.drawing_revision = self.drawing_revision.wrapping_add(1);
+        }
+    }
+
+    pub(crate) fn remove_drawing(&mut self, id: DrawingId) -> Option<Drawing> {
+        let idx = self.drawings.iter().position(|(key, _)| *key == id)?;
+        self.record_drawings();
+        if self.selected_drawing == Some(id) {
+            self.selected_drawing = None;
+        }
+        self.drawing_revision = self.drawing_revision.wrapping_add(1);
+        Some(self.drawings.remove(idx).1)
+    }
+
+    pub(crate) fn drawing(&self, id: DrawingId) -> Option<Drawing> {
+        self.drawings
+            .iter()
+            .find(|(key, _)| *key == id)
+            .map(|(_, drawing)| drawing.clone())
+    }
+
+    pub(crate) fn drawings(&self) -> Vec<(DrawingId, Drawing)> {
+        self.drawings.clone()
+    }
+
+    pub(crate) fn clear_drawings(&mut self) {
+        if !self.drawings.is_empty() {
+            self.record_drawings();
+        }
+        self.drawings.clear();
+        self.selected_drawing = None;
+        self.drawing_revision = self.drawing_revision.wrapping_add(1);
+    }
+
+    pub(crate) fn drawing_revision(&self) -> u64 {
+        self.drawing_revision
+    }
+
+    pub(crate) fn set_drawing_preview(&mut self, drawing: Option<Drawing>) {
+        self.drawing_preview = drawing;
+    }
+
+    pub(crate) fn set_measurement(&mut self, measurement: Option<Drawing>) {
+        self.measurement = measurement;
+    }
+
+    pub(crate) fn measurement(&self) -> Option<Drawing> {
+        self.measurement.clone()
+    }
+
+    /// Keeps the current measurement as a regular drawing.
+    pub(crate) fn pin_measurement(&mut self) -> Option<DrawingId> {
+        let measurement = self.measurement.take()?;
+        Some(self.add_drawing(measurement))
+    }
+
+    /// Whether `(x, y)` lies inside the current measurement's box.
+    pub(crate) fn measurement_contains(&mut self, x: f64, y: f64, width: f64, height: f64) -> bool {
+        let Some(mapper) = self.anchor_mapper(width, height) else {
+            return false;
+        };
+        let Some([a, b, ..]) = self
+            .measurement
+            .as_ref()
+            .map(|measurement| measurement.anchors.as_slice())
+        else {
+            return false;
+        };
+        let ((ax, ay), (bx, by)) = (mapper.point(a), mapper.point(b));
+        x >= ax.min(bx) && x <= ax.max(bx) && y >= ay.min(by) && y <= ay.max(by)
+    }
+
+    pub(crate) fn set_selected_drawing(&mut self, id: Option<DrawingId>) {
+        self.selected_drawing = id.filter(|id| self.drawings.iter().any(|(key, _)| key == id));
+    }
+
+    pub(crate) fn selected_drawing(&self) -> Option<DrawingId> {
+        self.selected_drawing
+    }
+
+    pub(crate) fn set_drawing_magnet(&mut self, enabled: bool) {
+        self.drawing_magnet = enabled;
+    }
+
+    pub(crate) fn drawing_magnet(&self) -> bool {
+        self.drawing_magnet
+    }
+
+    /// Frame-independent mapper for pointer input, built the same way the
+    /// renderer builds its own.
+    pub(super) fn anchor_mapper(&mut self, width: f64, height: f64) -> Option<AnchorMapper> {
+        let layout = ChartLayout::new(self, width, height);
+        if layout.plot_width <= 0.0 || layout.main_height <= 0.0 {
+            return None;
+        }
+        let start_time = self.time_scale.start;
+        let end_time = self.time_scale.end;
+        let right_scale = self.scale_for_side(PriceScale::Right, start_time, end_time);
+        let left_scale = if right_scale.is_some() {
+            None
+        } else {
+            self.scale_for_side(PriceScale::Left, start_time, end_time)
+        };
+        AnchorMapper::new(&layout, left_scale, right_scale, start_time, end_time)
+    }
+
+    /// The price under `(x, y)` on the scale drawings use, without magnet
+    /// snapping, if the point lies in the main plot.
+    pub(crate) fn price_at(
+        &mut self,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    ) -> Option<(PriceScale, f64)> {
+        let layout = ChartLayout::new(self, width, height);
+        if !layout.in_main_plot(y) || x < layout.plot_left || x > layout.plot_right {
+            return None;
+        }
+        let mapper = self.anchor_mapper(width, height)?;
+        Some((mapper.side, mapper.anchor(x, y).price))
+    }
+
+    /// Returns the data-space point under `(x, y)` if it lies in the main plot.
+    pub(crate) fn drawing_anchor_at(
+        &mut self,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    ) -> Option<DrawingAnchor> {
+        let layout = ChartLayout::new(self, width, height);
+        if !layout.in_main_plot(y) || x < layout.plot_left || x > layout.plot_right {
+            return None;
+        }
+        let mapper = self.anchor_mapper(width, height)?;
+        let anchor = mapper.anchor(x, y);
+        if !self.drawing_magnet
+            || primary_candle_side(self.primary_candles, &self.series) != Some(mapper.side)
+        {
+            return Some(anchor);
+        }
+        // Same snapping as `CrosshairMode::MagnetOhlc`.
+        let snapped = primary_candles(self.primary_candles, &self.series)
+            .and_then(|candles| nearest_ohlc(candles, candle_time(anchor.time), anchor.price));
+        Some(match snapped {
+            Some((candle, price)) => DrawingAnchor {
+                time: candle.time,
+                price,
+            },
+            None => anchor,
+        })
+    }
+
+    /// Returns `(x, y)` as fractions of the main plot: the position a drawing
+    /// pinned there keeps.
+    pub(crate) fn pinned_position_at(
+        &mut self,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    ) -> Option<(f64, f64)> {
+        let layout = ChartLayout::new(self, width, height);
+        if !layout.in_main_plot(y) || x < layout.plot_left || x > layout.plot_right {
+            return None;
+        }
+        let mapper = self.anchor_mapper(width, height)?;
+        Some(mapper.pinned_position(x, y))
+    }
+
+    /// Finds the topmost drawing under `(x, y)`. Anchor handles take
+    /// precedence over lines so a handle can be grabbed where lines cross.
+    pub(crate) fn drawing_hit_at(
+        &mut self,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    ) -> Option<DrawingHit> {
+        let layout = ChartLayout::new(self, width, height);
+        if !layout.in_main_plot(y) || x < layout.plot_left || x > layout.plot_right {
+            return None;
+        }
+        let mapper = self.anchor_mapper(width, height)?;
+        let rect = plot_rect(&layout);
+        let candles = primary_candles(self.primary_candles, &self.series).unwrap_or(&[]);
+        for (id, drawing) in self.drawings.iter().rev() {
+            // Pinned drawings have no anchor to drag, only a body to move.
+            if drawing.pinned.is_some() {
+                continue;
+            }
+            let handle = drawing.anchors.iter().position(|anchor| {
+                let (ax, ay) = mapper.point(anchor);
+                (ax - x).hypot(ay - y) <= HANDLE_HIT_RADIUS
+            });
+            if let Some(index) = handle {
+                return Some(DrawingHit {
+                    id: *id,
+                    target: DrawingHitTarget::Anchor(index),
+                });
+            }
+        }
+        let cr = measure_context();
+        self.drawings
+            .iter()
+            .rev()
+            .find(|(_, drawing)| {
+                let in_box = cr
+                    .as_ref()
+                    .and_then(|cr| text_box(cr, &mapper, drawing))
+                    .is_some_and(|text_box| text_box.contains(x, y));
+                in_box
+                    || drawing_segments(&mapper, rect, drawing, candles)
+                        .into_iter()
+                        .any(|(from, to)| {
+                            distance_to_segment((x, y), from, to) <= LINE_HIT_TOLERANCE
+                        })
+            })
+            .map(|(id, _)| DrawingHit {
+                id: *id,
+                target: DrawingHitTarget::Body,
+            })
+    }
+
+    /// Returns `drawing` moved by a pixel offset. The move happens in screen
+    /// space so it looks the same on log and percentage scales.
+    pub(crate) fn translate_drawing(
+        &mut self,
+        drawing: &Drawing,
+        dx: f64,
+        dy: f64,
+        width: f64,
+        height: f64,
+    ) -> Option<Drawing> {
+        let mapper = self.anchor_mapper(width, height)?;
+        let mut moved = drawing.clone();
+        if let Some(pinned) = drawing.pinned {
+            let (x, y) = mapper.pinned_point(pinned);
+            moved.pinned = Some(mapper.pinned_position(x + dx, y + dy));
+            return Some(moved);
+        }
+        for anchor in &mut moved.anchors {
+            let (x, y) = mapper.point(anchor);
+            *anchor = mapper.anchor(x + dx, y + dy);
+        }
+        Some(moved)
+    }
+}
+
+/// The on-screen line of a line tool, or `None` for other tools and while a
+/// two-anchor line only has its first anchor.
+pub(super) fn line_segment(
+    mapper: &AnchorMapper,
+    rect: PlotRect,
+    drawing: &Drawing,
+) -> Option<Segment> {
+    let mut points = drawing.anchors.iter().map(|anchor| mapper.point(anchor));
+    let first = points.next()?;
+    let (left, top, right, bottom) = rect;
+    let style = &drawing.style;
+    match drawing.tool {
+        DrawingTool::HorizontalRay => {
+            let start = if style.extend_left { left } else { first.0 };
+            Some(((start, first.1), (right, first.1)))
+        }
+        DrawingTool::HorizontalLine => Some(((left, first.1), (right, first.1))),
+        DrawingTool::VerticalLine => Some(((first.0, top), (first.0, bottom))),
+        DrawingTool::TrendLine | DrawingTool::ExtendedLine | DrawingTool::Ray => {
+            let second = points.next()?;
+            let (backward, forward) = match drawing.tool {
+                DrawingTool::TrendLine => (false, false),
+                DrawingTool::Ray => (false, true),
+                _ => (true, true),
+            };
+            Some(extend_styled(first, second, rect, style, backward, forward))
+        }
+        _ => None,
+    }
+}
+
+/// Extends `from`-`to` backwards and/or forwards, and additionally towards
+/// whichever plot edges the style's left/right extension asks for.
+pub(super) fn extend_styled(
+    from: (f64, f64),
+    to: (f64, f64),
+    rect: PlotRect,
+    style: &DrawingStyle,
+    mut backward: bool,
+    mut forward: bool,
+) -> Segment {
+    if to.0 >= from.0 {
+        backward |= style.extend_left;
+        forward |= style.extend_right;
+    } else {
+        backward |= style.extend_right;
+        forward |= style.extend_left;
+    }
+    extend_line(from, to, rect, backward, forward)
+}
+
+/// Every line segment a drawing puts on screen, used for hit-testing.
+pub(super) fn drawing_segments(
+    mapper: &AnchorMapper,
+    rect: PlotRect,
+    drawing: &Drawing,
+    candles: &[Candle],
+) -> Vec<Segment> {
+    let (_, top, _, bottom) = rect;
+    match drawing.tool {
+        DrawingTool::FibRetracement | DrawingTool::FibExtension => {
+            let mut segments: Vec<Segment> = level_lines(mapper, rect, drawing)
+                .into_iter()
+                .map(|line| line.segment)
+                .collect();
+            segments.extend(anchor_path(mapper, drawing));
+            segments
+        }
+        DrawingTool::FibFan => fan_rays(mapper, rect, drawing)
+            .into_iter()
+            .map(|(_, segment)| segment)
+            .collect(),
+        DrawingTool::FibArcs => anchor_path(mapper, drawing),
+        DrawingTool::FibTimeZones => time_zone_xs(mapper, drawing, candles)
+            .into_iter()
+            .map(|(_, x)| ((x, top), (x, bottom)))
+            .collect(),
+        DrawingTool::ParallelChannel => parallel_channel(mapper, rect, drawing)
+            .map(Vec::from)
+            .unwrap_or_default(),
+        DrawingTool::RegressionChannel => regression_channel(mapper, rect, drawing, candles)
+            .map(|channel| {
+                let mut segments = vec![channel.center];
+                for (_, upper, lower) in channel.bands {
+                    segments.extend([upper, lower]);
+                }
+                segments
+            })
+            .unwrap_or_default(),
+        DrawingTool::Pitchfork
+        | DrawingTool::SchiffPitchfork
+        | DrawingTool::ModifiedSchiffPitchfork => pitchfork(mapper, rect, drawing)
+            .map(Vec::from)
+            .unwrap_or_default(),
+        DrawingTool::Rectangle
+        | DrawingTool::Ellipse
+        | DrawingTool::Triangle
+        | DrawingTool::Measure
+        | DrawingTool::RiskReward => closed_edges(&shape_outline(mapper, drawing)),
+        DrawingTool::Xabcd => anchor_path(mapper, drawing),
+        DrawingTool::HeadAndShoulders => {
+            let mut segments = anchor_path(mapper, drawing);
+            segments.extend(neckline(mapper, drawing));
+            segments
+        }
+        DrawingTool::Callout | DrawingTool::Arrow => anchor_path(mapper, drawing),
+        _ => line_segment(mapper, rect, drawing).into_iter().collect(),
+    }
+}
+
+/// Segments joining consecutive anchors.
+pub(super) fn anchor_path(mapper: &AnchorMapper, drawing: &Drawing) -> Vec<Segment> {
+    let points: Vec<(f64, f64)> = drawing
+        .anchors
+        .iter()
+        .map(|anchor| mapper.point(anchor))
+        .collect();
+    points.windows(2).map(|pair| (pair[0], pair[1])).collect()
+}
+
+/// A horizontal level of a Fibonacci retracement or extension.
+pub(super) struct LevelLine {
+    /// Index into `Drawing::levels`
+    pub(super) level: usize,
+    pub(super) price: f64,
+    pub(super) segment: Segment,
+}
+
+/// Visible level lines of a retracement or extension, in level order.
+/// Retracement levels run from 0 at the second anchor to 1 at the first;
+/// extension levels project the first-to-second move from the third anchor.
+pub(super) fn level_lines(
+    mapper: &AnchorMapper,
+    rect: PlotRect,
+    drawing: &Drawing,
+) -> Vec<LevelLine> {
+    let points: Vec<(f64, f64)> = drawing
+        .anchors
+        .iter()
+        .map(|anchor| mapper.point(anchor))
+        .collect();
+    let (base, range, span) = match (drawing.tool, drawing.anchors.as_slice()) {
+        (DrawingTool::FibRetracement, [a, b, ..]) => (
+            b.price,
+            a.price - b.price,
+            (points[0].0.min(points[1].0), points[0].0.max(points[1].0)),
+        ),
+        (DrawingTool::FibExtension, [a, b, c, ..]) => {
+            let width = (points[1].0 - points[0].0).abs().max(40.0);
+            (
+                c.price,
+                b.price - a.price,
+                (points[2].0, points[2].0 + width),
+            )
+        }
+        _ => return Vec::new(),
+    };
+    let (left, _, right, _) = rect;
+    let start = if drawing.style.extend_left {
+        left
+    } else {
+        span.0
+    };
+    let end = if drawing.style.extend_right {
+        right
+    } else {
+        span.1
+    };
+    drawing
+        .levels
+        .iter()
+        .enumerate()
+        .filter(|(_, level)| level.visible)
+        .map(|(idx, level)| {
+            let price = base + range * level.value;
+            let y = mapper.y(price);
+            LevelLine {
+                level: idx,
+                price,
+                segment: ((start, y), (end, y)),
+            }
+        })
+        .collect()
+}
+
+/// Visible fan rays with their level index. Level 0 runs through the second
+/// anchor and level 1 is horizontal at the first anchor's price.
+pub(super) fn fan_rays(
+    mapper: &AnchorMapper,
+    rect: PlotRect,
+    drawing: &Drawing,
+) -> Vec<(usize, Segment)> {
+    let [a, b, ..] = drawing.anchors.as_slice() else {
+        return Vec::new();
+    };
+    let origin = mapper.point(a);
+    let x = mapper.x(b.time);
+    drawing
+        .levels
+        .iter()
+        .enumerate()
+        .filter(|(_, level)| level.visible)
+        .map(|(idx, level)| {
+            let through = (x, mapper.y(b.price + (a.price - b.price) * level.value));
+            (idx, extend_line(origin, through, rect, false, true))
+        })
+        .collect()
+}
+
+/// Visible time zone lines as `(level index, x)`. Levels count bars from the
+/// anchor's bar, continuing past the last candle at its spacing.
+pub(super) fn time_zone_xs(
+    mapper: &AnchorMapper,
+    drawing: &Drawing,
+    candles: &[Candle],
+) -> Vec<(usize, f64)> {
+    let Some(anchor) = drawing.anchors.first() else {
+        return Vec::new();
+    };
+    let Some(start) = bar_index(candles, candle_time(anchor.time)) else {
+        return Vec::new();
+    };
+    drawing
+        .levels
+        .iter()
+        .enumerate()
+        .filter(|(_, level)| level.visible && level.value >= 0.0)
+        .filter_map(|(idx, level)| {
+            let time = bar_time(candles, start + level.value.round() as usize)?;
+            Some((idx, mapper.time_x(time)))
+        })
+        .collect()
+}
+
+/// Spacing of the last two candles, used to extend bar positions past the end
+/// of the data.
+fn bar_step(candles: &[Candle]) -> Option<f64> {
+    let [.., prev, last] = candles else {
+        return None;
+    };
+    Some(candle_time(last.time) - candle_time(prev.time)).filter(|step| *step > 0.0)
+}
+
+pub(super) fn bar_index(candles: &[Candle], time: f64) -> Option<usize> {
+    let last = candles.last()?;
+    let idx = candles.partition_point(|candle| candle_time(candle.time) < time);
+    if idx < candles.len() {
+        return Some(idx);
+    }
+    let past = match bar_step(candles) {
+        Some(step) => ((time - candle_time(last.time)) / step).round().max(0.0) as usize,
+        None => 0,
+    };
+    Some(candles.len() - 1 + past)
+}
+
+fn bar_time(candles: &[Candle], index: usize) -> Option<f64> {
+    if let Some(candle) = candles.get(index) {
+        return Some(candle_time(candle.time));
+    }
+    let last = candle_time(candles.last()?.time);
+    Some(last + bar_step(candles)? * (index + 1 - candles.len()) as f64)
+}
+
+/// Extends the line through `from` and `to` to the plot edges, backwards
+/// past `from` and/or forwards past `to`.
+pub(super) fn extend_line(
+    from: (f64, f64),
+    to: (f64, f64),
+    rect: PlotRect,
+    backward: bool,
+    forward: bool,
+) -> Segment {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    if dx.abs() < f64::EPSILON && dy.abs() < f64::EPSILON {
+        return (from, to);
+    }
+    let (left, top, right, bottom) = rect;
+    let (mut t_min, mut t_max) = (f64::NEG_INFINITY, f64::INFINITY);
+    for (origin, delta, low, high) in [(from.0, dx, left, right), (from.1, dy, top, bottom)] {
+        if delta.abs() < f64::EPSILON {
+            continue;
+        }
+        let (a, b) = ((low - origin) / delta, (high - origin) / delta);
+        t_min = t_min.max(a.min(b));
+        t_max = t_max.min(a.max(b));
+    }
+    let at = |t: f64| (from.0 + dx * t, from.1 + dy * t);
+    let start = if backward { at(t_min.min(0.0)) } else { from };
+    let end = if forward { at(t_max.max(1.0)) } else { to };
+    (start, end)
+}
+
+pub(super) fn distance_to_segment(point: (f64, f64), from: (f64, f64), to: (f64, f64)) -> f64 {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let length_sq = dx * dx + dy * dy;
+    let t = if length_sq <= f64::EPSILON {
+        0.0
+    } else {
+        (((point.0 - from.0) * dx + (point.1 - from.1) * dy) / length_sq).clamp(0.0, 1.0)
+    };
+    (point.0 - (from.0 + dx * t)).hypot(point.1 - (from.1 + dy * t))
+}
@@ -0,0 +1,234 @@
+use super::super::data::{HasTime, SeriesData};
+use super::super::events::{
+    DrawingsHandler, EventCalls, LogicalRange, LogicalRangeHandler, MouseEventKind,
+    MouseEventParams, MouseHandler, SeriesValue, SizeHandler, SubscriptionId, TimeRangeHandler,
+};
+use super::super::layout::ChartLayout;
+use super::super::types::Candle;
+use super::super::util::candle_time;
+use super::render_helpers::primary_candles;
+use super::ChartCore;
+
+/// Point of `data` exactly at `time`.
+fn point_at<T: HasTime>(data: &[T], time: f64) -> Option<&T> {
+    let idx = data.partition_point(|point| candle_time(point.time()) < time);
+    data.get(idx)
+        .filter(|point| candle_time(point.time()) == time)
+}
+
+/// Fractional bar index of `time` among `candles`. Outside the data the
+/// spacing of the two nearest bars carries the count on.
+fn logical_at(candles: &[Candle], time: f64) -> Option<f64> {
+    let count = candles.len();
+    if count < 2 {
+        return (count == 1).then_some(0.0);
+    }
+    let bar_time = |idx: usize| candle_time(candles[idx].time);
+    let (lower, upper) = match candles.partition_point(|candle| candle_time(candle.time) <= time) {
+        0 => (0, 1),
+        idx if idx >= count => (count - 2, count - 1),
+        idx => (idx - 1, idx),
+    };
+    let span = bar_time(upper) - bar_time(lower);
+    if span <= f64::EPSILON {
+        return Some(lower as f64);
+    }
+    Some(lower as f64 + (time - bar_time(lower)) / span)
+}
+
+/// Time at the fractional bar index `logical` among `candles`, the inverse
+/// of `logical_at`.
+pub(super) fn time_at_logical(candles: &[Candle], logical: f64) -> Option<f64> {
+    let bar_time = |idx: usize| candle_time(candles[idx].time);
+    match candles.len() {
+        0 => None,
+        1 => Some(bar_time(0)),
+        count => {
+            let lower = (logical.floor().max(0.0) as usize).min(count - 2);
+            let span = bar_time(lower + 1) - bar_time(lower);
+            Some(bar_time(lower) + (logical - lower as f64) * span)
+        }
+    }
+}
+
+impl ChartCore {
+    pub(crate) fn subscribe_mouse(
+        &mut self,
+        kind: MouseEventKind,
+        handler: MouseHandler,
+    ) -> SubscriptionId {
+        let id = self.events.next_id();
+        self.events.mouse_handlers(kind).push((id, handler));
+        id
+    }
+
+    pub(crate) fn subscribe_visible_time_range(
+        &mut self,
+        handler: TimeRangeHandler,
+    ) -> SubscriptionId {
+        let id = self.events.next_id();
+        self.events.visible_time_range.push((id, handler));
+        id
+    }
+
+    pub(crate) fn subscribe_visible_logical_range(
+        &mut self,
+        handler: LogicalRangeHandler,
+    ) -> SubscriptionId {
+        let id = self.events.next_id();
+        self.events.visible_logical_range.push((id, handler));
+        id
+    }
+
+    pub(crate) fn subscribe_size(&mut self, handler: SizeHandler) -> SubscriptionId {
+        let id = self.events.next_id();
+        self.events.size.push((id, handler));
+        id
+    }
+
+    pub(crate) fn subscribe_drawings(&mut self, handler: DrawingsHandler) -> SubscriptionId {
+        let id = self.events.next_id();
+        self.events.drawings.push((id, handler));
+        id
+    }
+
+    pub(crate) fn unsubscribe(&mut self, id: SubscriptionId) {
+        self.events.unsubscribe(id);
+    }
+
+    pub(crate) fn visible_logical_range(&self) -> Option<LogicalRange> {
+        let candles = primary_candles(self.primary_candles, &self.series)?;
+        let (start, end) = self.visible_time_range();
+        Some(LogicalRange {
+            from: logical_at(candles, start)?,
+            to: logical_at(candles, end)?,
+        })
+    }
+
+    /// Describes the bar, panel and series values under `point`, laid out
+    /// at the size of the last draw.
+    pub(crate) fn mouse_event_params(&self, point: Option<(f64, f64)>) -> MouseEventParams {
+        let mut params = MouseEventParams {
+            point,
+            time: None,
+            logical: None,
+            panel: None,
+            series_values: Vec::new(),
+        };
+        let (Some((x, y)), Some((width, height))) = (point, self.events.drawn_size) else {
+            return params;
+        };
+        let layout = ChartLayout::new(self, width, height);
+        if layout.plot_width <= 0.0 || x < layout.plot_left || x > layout.plot_right {
+            return params;
+        }
+        params.panel = self.panel_at(x, y, width, height);
+        let (start, end) = self.visible_time_range();
+        let target = start + (x - layout.plot_left) / layout.plot_width * (end - start);
+        let candles = primary_candles(self.primary_candles, &self.series).unwrap_or(&[]);
+        params.logical = logical_at(candles, target).map(|logical| logical.round() as i64);
+        let bar = params
+            .logical
+            .and_then(|logical| usize::try_from(logical).ok())
+            .and_then(|idx| candles.get(idx));
+        let Some(bar) = bar else {
+            return params;
+        };
+        let time = candle_time(bar.time);
+        params.time = Some(bar.time);
+        params.series_values =
+            self.series
+                .iter()
+                .enumerate()
+                .filter_map(|(id, series)| {
+                    let value =
+                        match &series.data {
+                            SeriesData::Candlestick { data } => point_at(data, time)
+                                .map(|candle| SeriesValue::Candle(candle.clone())),
+                            SeriesData::Line { data } => {
+                                point_at(data, time).map(|point| SeriesValue::Line(point.value))
+                            }
+                            SeriesData::Histogram { data } => point_at(data, time)
+                                .map(|point| SeriesValue::Histogram(point.value)),
+                            SeriesData::Cloud { data } => point_at(data, time)
+                                .map(|point| SeriesValue::Cloud(point.value_a, point.value_b)),
+                        }?;
+                    Some((id, value))
+                })
+                .collect();
+        params
+    }
+
+    /// Binds the handlers of a pointer event to what lies under `point`.
+    pub(crate) fn mouse_event_calls(
+        &self,
+        kind: MouseEventKind,
+        point: Option<(f64, f64)>,
+    ) -> EventCalls {
+        let handlers = match kind {
+            MouseEventKind::CrosshairMove => &self.events.crosshair_move,
+            MouseEventKind::Click => &self.events.click,
+            MouseEventKind::DoubleClick => &self.events.double_click,
+        };
+        if handlers.is_empty() {
+            return Vec::new();
+        }
+        let params = std::rc::Rc::new(self.mouse_event_params(point));
+        handlers
+            .iter()
+            .map(|(_, handler)| {
+                let handler = handler.clone();
+                let params = params.clone();
+                Box::new(move || handler(&params)) as Box<dyn FnOnce()>
+            })
+            .collect()
+    }
+
+    /// Binds range and size handlers to the values that changed since they
+    /// were last delivered.
+    pub(crate) fn view_change_calls(&mut self) -> EventCalls {
+        let mut calls: EventCalls = Vec::new();
+        let range = self.visible_time_range();
+        if self.events.last_time_range != Some(range) {
+            self.events.last_time_range = Some(range);
+            for (_, handler) in &self.events.visible_time_range {
+                let handler = handler.clone();
+                calls.push(Box::new(move || handler(range.0, range.1)));
+            }
+        }
+        let logical = self.visible_logical_range();
+        if self.events.last_logical_range != logical {
+            self.events.last_logical_range = logical;
+            for (_, handler) in &self.events.visible_logical_range {
+                let handler = handler.clone();
+                calls.push(Box::new(move || handler(logical)));
+            }
+        }
+        let size = self.events.drawn_size;
+        if let Some((width, height)) = size.filter(|_| self.events.last_size != size) {
+            self.events.last_size = size;
+            for (_, handler) in &self.events.size {
+                let handler = handler.clone();
+                calls.push(Box::new(move || handler(width, height)));
+            }
+        }
+        calls
+    }
+
+    /// Calls for drawing subscribers when drawings changed since the last
+    /// delivery.
+    pub(crate) fn drawing_change_calls(&mut self) -> EventCalls {
+        if self.events.last_drawing_revision == self.drawing_revision {
+            return Vec::new();
+        }
+        self.events.last_drawing_revision = self.drawing_revision;
+        self.events
+            .drawings
+            .iter()
+            .map(|(_, handler)| {
+                let handler = handler.clone();
+                Box::new(move || handler()) as Box<dyn FnOnce()>
+            })
+            .collect()
+    }
+}
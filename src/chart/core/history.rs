@@ -0,0 +1,302 @@
+use std::fmt;
+use std::rc::Rc;
+
+use super::super::data::{Panel, PriceLine, TimeScaleGroup};
+use super::super::drawings::{Drawing, DrawingId};
+use super::super::types::PanelId;
+use super::ChartCore;
+
+/// Maximum number of undo steps kept.
+const HISTORY_LIMIT: usize = 100;
+
+/// Custom edit callbacks an undo or redo leaves to run.
+pub(crate) type PendingEdits = Vec<Rc<dyn Fn()>>;
+
+/// Edit defined outside the chart, e.g. by the application around its own
+/// state. Undoing runs `undo`; the redo entry runs `redo`.
+#[derive(Clone)]
+pub(crate) struct CustomEdit {
+    undo: Rc<dyn Fn()>,
+    redo: Rc<dyn Fn()>,
+}
+
+impl fmt::Debug for CustomEdit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CustomEdit")
+    }
+}
+
+/// Panels and time scale groups as they were before a panel was added or
+/// removed, with the series that sit on a panel only this side has. The
+/// other side keeps those series on the main panel. Other series and the
+/// RSI panel's data are left as they are.
+#[derive(Clone, Debug)]
+pub(crate) struct PanelLayout {
+    panels: Vec<Panel>,
+    time_scales: Vec<TimeScaleGroup>,
+    rsi_panel_id: Option<PanelId>,
+    /// Ids of the series on panels missing from the other side, with their
+    /// panel
+    series: Vec<(usize, PanelId)>,
+}
+
+/// State from before an edit. Undoing swaps it with the current state,
+/// which becomes the matching redo step.
+#[derive(Clone, Debug)]
+pub(crate) enum Snapshot {
+    Drawings(Vec<(DrawingId, Drawing)>),
+    PriceLines {
+        series: usize,
+        lines: Vec<PriceLine>,
+    },
+    PanelHeights(Vec<(PanelId, f64)>),
+    Layout(Box<PanelLayout>),
+    Custom(CustomEdit),
+}
+
+impl Snapshot {
+    /// Snapshots of the same state collapse within a group, keeping the
+    /// oldest, so a drag undoes in one step.
+    fn same_subject(&self, other: &Snapshot) -> bool {
+        match (self, other) {
+            (Snapshot::Drawings(_), Snapshot::Drawings(_))
+            | (Snapshot::PanelHeights(_), Snapshot::PanelHeights(_))
+            | (Snapshot::Layout(_), Snapshot::Layout(_)) => true,
+            (Snapshot::PriceLines { series: a, .. }, Snapshot::PriceLines { series: b, .. }) => {
+                a == b
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct EditHistory {
+    undo: Vec<Vec<Snapshot>>,
+    redo: Vec<Vec<Snapshot>>,
+    group: Vec<Snapshot>,
+    group_depth: usize,
+    /// Set while undo or redo runs custom edits, so the changes they make
+    /// are not recorded again.
+    replaying: bool,
+}
+
+impl EditHistory {
+    fn record(&mut self, snapshot: Snapshot) {
+        if self.replaying {
+            return;
+        }
+        if self.group_depth > 0 {
+            if !self
+                .group
+                .iter()
+                .any(|existing| existing.same_subject(&snapshot))
+            {
+                self.group.push(snapshot);
+            }
+            return;
+        }
+        self.push(vec![snapshot]);
+    }
+
+    fn push(&mut self, step: Vec<Snapshot>) {
+        self.undo.push(step);
+        if self.undo.len() > HISTORY_LIMIT {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+}
+
+impl ChartCore {
+    pub(super) fn record_drawings(&mut self) {
+        let snapshot = Snapshot::Drawings(self.drawings.clone());
+        self.history.record(snapshot);
+    }
+
+    pub(crate) fn record_price_lines(&mut self, series: usize) {
+        let Some(lines) = self.series.get(series).map(|s| s.price_lines.clone()) else {
+            return;
+        };
+        self.history.record(Snapshot::PriceLines { series, lines });
+    }
+
+    pub(super) fn record_panel_heights(&mut self) {
+        let heights = self
+            .panels
+            .iter()
+            .map(|panel| (panel.id, panel.height_weight))
+            .collect();
+        self.history.record(Snapshot::PanelHeights(heights));
+    }
+
+    /// Records the panel layout before panel `removed` is taken off, along
+    /// with the series it holds.
+    pub(super) fn record_layout(&mut self, removed: PanelId) {
+        let series = self
+            .series
+            .iter()
+            .enumerate()
+            .filter(|(_, series)| series.panel_id == removed)
+            .map(|(id, _)| (id, removed))
+            .collect();
+        let layout = self.panel_layout(series);
+        self.history.record(Snapshot::Layout(Box::new(layout)));
+    }
+
+    pub(crate) fn record_custom_edit(&mut self, undo: Rc<dyn Fn()>, redo: Rc<dyn Fn()>) {
+        self.history
+            .record(Snapshot::Custom(CustomEdit { undo, redo }));
+    }
+
+    /// Starts collecting edits into a single undo step. Groups nest; the
+    /// step is pushed when the outermost group ends.
+    pub(crate) fn begin_history_group(&mut self) {
+        self.history.group_depth += 1;
+    }
+
+    pub(crate) fn end_history_group(&mut self) {
+        let history = &mut self.history;
+        if history.group_depth == 0 {
+            return;
+        }
+        history.group_depth -= 1;
+        if history.group_depth == 0 && !history.group.is_empty() {
+            let step = std::mem::take(&mut history.group);
+            history.push(step);
+        }
+    }
+
+    pub(crate) fn can_undo(&self) -> bool {
+        !self.history.undo.is_empty()
+    }
+
+    pub(crate) fn can_redo(&self) -> bool {
+        !self.history.redo.is_empty()
+    }
+
+    pub(crate) fn clear_history(&mut self) {
+        self.history = EditHistory::default();
+    }
+
+    pub(crate) fn set_history_replaying(&mut self, replaying: bool) {
+        self.history.replaying = replaying;
+    }
+
+    /// Restores the state from before the last step and moves the step to
+    /// the redo stack.
+    ///
+    /// # Returns
+    ///
+    /// Custom edit callbacks still to run, in order, or `None` when there is
+    /// nothing to undo. They must run outside the chart borrow.
+    pub(crate) fn undo(&mut self) -> Option<PendingEdits> {
+        let step = self.history.undo.pop()?;
+        let (inverse, pending) = self.apply_step(step);
+        self.history.redo.push(inverse);
+        Some(pending)
+    }
+
+    pub(crate) fn redo(&mut self) -> Option<PendingEdits> {
+        let step = self.history.redo.pop()?;
+        let (inverse, pending) = self.apply_step(step);
+        self.history.undo.push(inverse);
+        Some(pending)
+    }
+
+    fn apply_step(&mut self, step: Vec<Snapshot>) -> (Vec<Snapshot>, PendingEdits) {
+        let mut inverse = Vec::with_capacity(step.len());
+        let mut pending = Vec::new();
+        for snapshot in step.into_iter().rev() {
+            inverse.push(self.swap_snapshot(snapshot, &mut pending));
+        }
+        inverse.reverse();
+        (inverse, pending)
+    }
+
+    fn swap_snapshot(&mut self, snapshot: Snapshot, pending: &mut PendingEdits) -> Snapshot {
+        match snapshot {
+            Snapshot::Drawings(drawings) => {
+                let current = std::mem::replace(&mut self.drawings, drawings);
+                if let Some(selected) = self.selected_drawing {
+                    if !self.drawings.iter().any(|(id, _)| *id == selected) {
+                        self.selected_drawing = None;
+                    }
+                }
+                self.drawing_revision = self.drawing_revision.wrapping_add(1);
+                Snapshot::Drawings(current)
+            }
+            Snapshot::PriceLines { series, lines } => {
+                let current = match self.series.get_mut(series) {
+                    Some(target) => std::mem::replace(&mut target.price_lines, lines),
+                    None => lines,
+                };
+                Snapshot::PriceLines {
+                    series,
+                    lines: current,
+                }
+            }
+            Snapshot::PanelHeights(heights) => {
+                let current = self
+                    .panels
+                    .iter()
+                    .map(|panel| (panel.id, panel.height_weight))
+                    .collect();
+                for (id, weight) in heights {
+                    if let Some(panel) = self.panels.iter_mut().find(|panel| panel.id == id) {
+                        panel.height_weight = weight;
+                    }
+                }
+                Snapshot::PanelHeights(current)
+            }
+            Snapshot::Layout(layout) => {
+                let restored: Vec<PanelId> = layout.panels.iter().map(|panel| panel.id).collect();
+                let detached = self
+                    .series
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, series)| !restored.contains(&series.panel_id))
+                    .map(|(id, series)| (id, series.panel_id))
+                    .collect();
+                let current = self.panel_layout(detached);
+                self.restore_panel_layout(*layout);
+                Snapshot::Layout(Box::new(current))
+            }
+            Snapshot::Custom(edit) => {
+                pending.push(edit.undo.clone());
+                Snapshot::Custom(CustomEdit {
+                    undo: edit.redo,
+                    redo: edit.undo,
+                })
+            }
+        }
+    }
+
+    fn panel_layout(&self, series: Vec<(usize, PanelId)>) -> PanelLayout {
+        PanelLayout {
+            panels: self.panels.clone(),
+            time_scales: self.time_scales.clone(),
+            rsi_panel_id: self.rsi_panel_id,
+            series,
+        }
+    }
+
+    /// Puts back the panels of `layout` and its series on them. Series on
+    /// panels that `layout` lacks go to its main panel.
+    fn restore_panel_layout(&mut self, layout: PanelLayout) {
+        self.panels = layout.panels;
+        self.time_scales = layout.time_scales;
+        self.rsi_panel_id = layout.rsi_panel_id;
+        let main_panel = self.main_panel_id();
+        for series in &mut self.series {
+            if !self.panels.iter().any(|panel| panel.id == series.panel_id) {
+                series.panel_id = main_panel;
+            }
+        }
+        for (id, panel_id) in layout.series {
+            if let Some(series) = self.series.get_mut(id) {
+                series.panel_id = panel_id;
+            }
+        }
+    }
+}
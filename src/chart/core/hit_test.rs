@@ -0,0 +1,280 @@
+use super::super::data::{SeriesData, SeriesScale};
+use super::super::hit_test::{HitTarget, MarkerHit, PriceLineLabelHit};
+use super::super::layout::ChartLayout;
+use super::super::types::{PriceScale, Rect};
+use super::super::util::{
+    candle_time, histogram_range, map_price_to_y, map_price_to_y_scaled, map_time_to_x,
+    series_bar_width_times, visible_candles, visible_histogram_points, visible_line_points,
+};
+use super::drawings::distance_to_segment;
+use super::ChartCore;
+
+/// Maximum distance from a line, wick or price line that still counts as a
+/// hit, in pixels.
+const HIT_TOLERANCE: f64 = 4.0;
+
+impl ChartCore {
+    pub(super) fn clear_hit_boxes(&self) {
+        self.marker_hits.borrow_mut().clear();
+        self.price_line_labels.borrow_mut().clear();
+        self.badges.borrow_mut().clear();
+    }
+
+    pub(super) fn push_marker_hit(&self, series: usize, index: usize, rect: Rect) {
+        self.marker_hits.borrow_mut().push(MarkerHit {
+            series,
+            index,
+            rect,
+        });
+    }
+
+    pub(super) fn push_price_line_label(&self, series: usize, line: usize, rect: Rect) {
+        self.price_line_labels
+            .borrow_mut()
+            .push(PriceLineLabelHit { series, line, rect });
+    }
+
+    /// The main pane's price axis under `(x, y)`, laid out at the size of
+    /// the last draw.
+    pub(crate) fn price_scale_at(&self, x: f64, y: f64) -> Option<PriceScale> {
+        let (width, height) = self.events.drawn_size?;
+        let layout = ChartLayout::new(self, width, height);
+        if !layout.in_main_plot(y) || layout.in_time_axis(y) {
+            return None;
+        }
+        if layout.in_left_axis(x) {
+            Some(PriceScale::Left)
+        } else if layout.in_right_axis(x) {
+            Some(PriceScale::Right)
+        } else {
+            None
+        }
+    }
+
+    /// Finds what lies under `(x, y)`, laid out at the size of the last draw.
+    /// Position and order badges and price line labels on the axis come
+    /// first, then markers, price lines, position and order lines and series
+    /// from the topmost down, matching the order they are painted in.
+    pub(crate) fn hit_test(&mut self, x: f64, y: f64) -> Option<HitTarget> {
+        let (width, height) = self.events.drawn_size?;
+        let badge = self
+            .badges
+            .borrow()
+            .iter()
+            .rev()
+            .find(|hit| hit.rect.contains(x, y))
+            .map(|hit| hit.target);
+        if badge.is_some() {
+            return badge;
+        }
+        let label = self
+            .price_line_labels
+            .borrow()
+            .iter()
+            .rev()
+            .find(|hit| hit.rect.contains(x, y))
+            .map(|hit| HitTarget::PriceLine {
+                series: hit.series,
+                line: hit.line,
+            });
+        if label.is_some() {
+            return label;
+        }
+
+        let layout = ChartLayout::new(self, width, height);
+        if layout.plot_width <= 0.0 || x < layout.plot_left || x > layout.plot_right {
+            return None;
+        }
+
+        let marker = self
+            .marker_hits
+            .borrow()
+            .iter()
+            .rev()
+            .find(|hit| hit.rect.contains(x, y))
+            .map(|hit| HitTarget::Marker {
+                series: hit.series,
+                index: hit.index,
+            });
+        if marker.is_some() {
+            return marker;
+        }
+
+        let start_time = self.time_scale.start;
+        let end_time = self.time_scale.end;
+        let left_scale = self.scale_for_side(PriceScale::Left, start_time, end_time);
+        let right_scale = self.scale_for_side(PriceScale::Right, start_time, end_time);
+        let scale_of = |side: PriceScale| match side {
+            PriceScale::Left => left_scale,
+            PriceScale::Right => right_scale,
+        };
+
+        for (series_index, series) in self.series.iter().enumerate().rev() {
+            // Price lines are drawn for series on the main panel only.
+            if !series.options.visible || series.panel_id != self.main_panel_id() {
+                continue;
+            }
+            let Some(scale) = scale_of(series.scale) else {
+                continue;
+            };
+            let line = series.price_lines.iter().rev().find(|line| {
+                line.options.line_visible
+                    && (price_y(line.options.price, scale, &layout) - y).abs() <= HIT_TOLERANCE
+            });
+            if let Some(line) = line {
+                return Some(HitTarget::PriceLine {
+                    series: series_index,
+                    line: line.id,
+                });
+            }
+        }
+
+        let trading = self.trading_line_at(y, &layout, scale_of, HIT_TOLERANCE);
+        if trading.is_some() {
+            return trading;
+        }
+
+        let x_of = |time: f64| {
+            map_time_to_x(
+                time,
+                start_time,
+                end_time,
+                layout.plot_left,
+                layout.plot_width,
+            )
+        };
+        let rsi_scale = self.scale_for_rsi(start_time, end_time);
+        let main_layout = &layout;
+        let rsi_layout = layout.on_rsi_panel();
+        for (series_index, series) in self.series.iter().enumerate().rev() {
+            let Some((scale, layout)) = self.series_area(
+                series,
+                left_scale,
+                right_scale,
+                rsi_scale,
+                main_layout,
+                &rsi_layout,
+            ) else {
+                continue;
+            };
+            match &series.data {
+                SeriesData::Candlestick { data } => {
+                    let visible = visible_candles(data, start_time, end_time);
+                    let body_width = series_bar_width_times(
+                        visible.iter().map(|candle| candle_time(candle.time)),
+                        start_time,
+                        end_time,
+                        layout.plot_width,
+                    );
+                    let hit = visible.iter().rev().find(|candle| {
+                        let x_center = x_of(candle_time(candle.time));
+                        let high_y = price_y(candle.high, scale, &layout);
+                        let low_y = price_y(candle.low, scale, &layout);
+                        let open_y = price_y(candle.open, scale, &layout);
+                        let close_y = price_y(candle.close, scale, &layout);
+                        let in_body = (x - x_center).abs() <= body_width / 2.0
+                            && y >= open_y.min(close_y) - 1.0
+                            && y <= open_y.max(close_y) + 1.0;
+                        let on_wick =
+                            distance_to_segment((x, y), (x_center, high_y), (x_center, low_y))
+                                <= HIT_TOLERANCE;
+                        in_body || on_wick
+                    });
+                    if let Some(candle) = hit {
+                        return Some(HitTarget::Candle {
+                            series: series_index,
+                            time: candle.time,
+                        });
+                    }
+                }
+                SeriesData::Line { data } => {
+                    let visible = visible_line_points(data, start_time, end_time);
+                    let points: Vec<(f64, f64)> = visible
+                        .iter()
+                        .map(|point| {
+                            (
+                                x_of(candle_time(point.time)),
+                                price_y(point.value, scale, &layout),
+                            )
+                        })
+                        .collect();
+                    let tolerance = HIT_TOLERANCE + series.options.line_width / 2.0;
+                    let single = points.len() == 1
+                        && distance_to_segment((x, y), points[0], points[0]) <= tolerance;
+                    let hit = if single {
+                        Some(0)
+                    } else {
+                        points
+                            .windows(2)
+                            .position(|pair| {
+                                distance_to_segment((x, y), pair[0], pair[1]) <= tolerance
+                            })
+                            .map(|idx| {
+                                if (x - points[idx].0).abs() <= (x - points[idx + 1].0).abs() {
+                                    idx
+                                } else {
+                                    idx + 1
+                                }
+                            })
+                    };
+                    if let Some(idx) = hit {
+                        return Some(HitTarget::Line {
+                            series: series_index,
+                            time: visible[idx].time,
+                        });
+                    }
+                }
+                SeriesData::Histogram { data } => {
+                    if layout.hist_height <= 0.0 {
+                        continue;
+                    }
+                    let visible = visible_histogram_points(data, start_time, end_time);
+                    let Some((hist_min, hist_max)) = histogram_range(&visible) else {
+                        continue;
+                    };
+                    let bar_width = series_bar_width_times(
+                        visible.iter().map(|point| candle_time(point.time)),
+                        start_time,
+                        end_time,
+                        layout.plot_width,
+                    );
+                    let hit = visible.iter().rev().find(|point| {
+                        let x_center = x_of(candle_time(point.time));
+                        let top = map_price_to_y(
+                            point.value,
+                            hist_min,
+                            hist_max,
+                            layout.hist_top,
+                            layout.hist_height,
+                        );
+                        (x - x_center).abs() <= bar_width / 2.0
+                            && y >= top.min(layout.hist_bottom - 1.0)
+                            && y <= layout.hist_bottom
+                    });
+                    if let Some(point) = hit {
+                        return Some(HitTarget::Histogram {
+                            series: series_index,
+                            time: point.time,
+                        });
+                    }
+                }
+                SeriesData::Cloud { .. } => {}
+            }
+        }
+        None
+    }
+}
+
+pub(super) fn price_y(price: f64, scale: SeriesScale, layout: &ChartLayout) -> f64 {
+    map_price_to_y_scaled(
+        price,
+        scale.min,
+        scale.max,
+        layout.plot_top,
+        layout.main_height,
+        scale.margins,
+        scale.invert,
+        scale.mode,
+        scale.base,
+    )
+}
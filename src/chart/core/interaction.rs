@@ -375,6 +375,7 @@ impl ChartCore {
         if upper_index == lower_index {
             return;
         }
+        self.record_panel_heights();
         if upper_index < lower_index {
             let (left, right) = self.panels.split_at_mut(lower_index);
             let upper = &mut left[upper_index];
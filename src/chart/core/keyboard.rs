@@ -0,0 +1,155 @@
+use super::super::data::SeriesData;
+use super::super::layout::ChartLayout;
+use super::super::util::{candle_time, map_time_to_x};
+use super::hit_test::price_y;
+use super::ChartCore;
+
+impl ChartCore {
+    /// Scrolls the time scale by `bars` bars; positive moves towards newer
+    /// data.
+    pub(crate) fn pan_by_bars(&mut self, bars: f64) {
+        let delta = bars * self.time_scale.bar_time();
+        self.time_scale.pan_by(delta);
+        self.clamp_time_scale_right_edge();
+    }
+
+    /// Scrolls the time scale by `pages` visible ranges.
+    pub(crate) fn pan_by_pages(&mut self, pages: f64) {
+        let delta = pages * self.time_scale.visible_range();
+        self.time_scale.pan_by(delta);
+        self.clamp_time_scale_right_edge();
+    }
+
+    /// Zooms the time scale around the last bar, or around the right edge
+    /// when the last bar is out of view. `factor` below one zooms in.
+    pub(crate) fn zoom_at_last_bar(&mut self, factor: f64) {
+        let range = self.time_scale.visible_range().max(1.0);
+        let anchor = ((self.time_scale.max - self.time_scale.start) / range).clamp(0.0, 1.0);
+        self.zoom_time_by_factor(factor, anchor);
+        self.clamp_time_scale_right_edge();
+    }
+
+    /// Scrolls the first data point to the left edge.
+    pub(crate) fn scroll_to_first_bar(&mut self) {
+        let delta = self.time_scale.min - self.time_scale.start;
+        self.time_scale.pan_by(delta);
+    }
+
+    /// Scrolls the last data point, plus the right offset, to the right edge.
+    pub(crate) fn scroll_to_last_bar(&mut self) {
+        let delta = self.time_scale.max_end() - self.time_scale.end;
+        self.time_scale.pan_by(delta);
+    }
+
+    /// Moves the crosshair `bars` bars along the primary candlestick series,
+    /// resting it on the close and scrolling the bar into view. A hidden
+    /// crosshair appears on the last visible bar. Returns the new position.
+    pub(crate) fn step_crosshair(&mut self, bars: i64) -> Option<(f64, f64)> {
+        let (width, height) = self.events.drawn_size?;
+        let layout = ChartLayout::new(self, width, height);
+        if layout.plot_width <= 0.0 {
+            return None;
+        }
+        let series_index = self.crosshair_series()?;
+        let SeriesData::Candlestick { data } = &self.series[series_index].data else {
+            return None;
+        };
+        if data.is_empty() {
+            return None;
+        }
+        let start = self.time_scale.start;
+        let end = self.time_scale.end;
+        let index = match self.crosshair {
+            Some((x, _)) => {
+                let time = start + (x - layout.plot_left) / layout.plot_width * (end - start);
+                let next = data.partition_point(|candle| candle_time(candle.time) < time);
+                let nearest = if next == 0 {
+                    0
+                } else if next >= data.len() {
+                    data.len() - 1
+                } else if candle_time(data[next].time) - time
+                    < time - candle_time(data[next - 1].time)
+                {
+                    next
+                } else {
+                    next - 1
+                };
+                (nearest as i64 + bars).clamp(0, data.len() as i64 - 1) as usize
+            }
+            None => data
+                .partition_point(|candle| candle_time(candle.time) <= end)
+                .saturating_sub(1),
+        };
+        let time = candle_time(data[index].time);
+        let margin = self.time_scale.bar_time() / 2.0;
+        if time < start + margin {
+            self.time_scale.pan_by(time - start - margin);
+        } else if time > end - margin {
+            self.time_scale.pan_by(time - end + margin);
+        }
+        self.place_crosshair_on_bar(series_index, index, &layout)
+    }
+
+    /// Puts the crosshair on the bar containing `time`, the last one starting
+    /// at or before it, so a chart on a longer interval shows the enclosing
+    /// bar. The crosshair is hidden when that bar is out of view.
+    pub(crate) fn set_crosshair_time(&mut self, time: f64) -> Option<(f64, f64)> {
+        self.crosshair = None;
+        let (width, height) = self.events.drawn_size?;
+        let layout = ChartLayout::new(self, width, height);
+        if layout.plot_width <= 0.0 {
+            return None;
+        }
+        let series_index = self.crosshair_series()?;
+        let SeriesData::Candlestick { data } = &self.series[series_index].data else {
+            return None;
+        };
+        let index = data
+            .partition_point(|candle| candle_time(candle.time) <= time)
+            .checked_sub(1)?;
+        let bar_time = candle_time(data[index].time);
+        if bar_time < self.time_scale.start || bar_time > self.time_scale.end {
+            return None;
+        }
+        self.place_crosshair_on_bar(series_index, index, &layout)
+    }
+
+    /// The candlestick series the crosshair steps through.
+    fn crosshair_series(&self) -> Option<usize> {
+        self.primary_candles
+            .filter(|id| {
+                matches!(
+                    self.series.get(*id).map(|series| &series.data),
+                    Some(SeriesData::Candlestick { .. })
+                )
+            })
+            .or_else(|| {
+                self.series
+                    .iter()
+                    .position(|series| matches!(series.data, SeriesData::Candlestick { .. }))
+            })
+    }
+
+    /// Puts the crosshair on the close of bar `index` of `series_index`.
+    fn place_crosshair_on_bar(
+        &mut self,
+        series_index: usize,
+        index: usize,
+        layout: &ChartLayout,
+    ) -> Option<(f64, f64)> {
+        let series = &self.series[series_index];
+        let side = series.scale;
+        let SeriesData::Candlestick { data } = &series.data else {
+            return None;
+        };
+        let candle = data.get(index)?;
+        let (time, close) = (candle_time(candle.time), candle.close);
+        let start = self.time_scale.start;
+        let end = self.time_scale.end;
+        let scale = self.scale_for_side(side, start, end)?;
+        let x = map_time_to_x(time, start, end, layout.plot_left, layout.plot_width);
+        let y = price_y(close, scale, layout);
+        self.crosshair = Some((x, y));
+        Some((x, y))
+    }
+}
@@ -1,21 +1,46 @@
+mod annotations;
+mod box_zoom;
+mod drawing_shapes;
+mod drawings;
+mod events;
+mod history;
+mod hit_test;
 mod interaction;
+mod keyboard;
 mod options;
+mod price_line_drag;
 mod pricescale;
 mod render;
+mod render_annotations;
 mod render_axes;
+mod render_cloud;
 mod render_crosshair;
+mod render_drawings;
+mod render_fibonacci;
 mod render_helpers;
 mod render_markers;
+mod render_measure;
 mod render_overlays;
+mod render_shapes;
 mod scale;
 mod series;
 mod timescale;
+mod trading;
+mod volume_profile;
 
 use super::data::{IndicatorPanel, Panel, PriceScaleState, Series, TimeScaleGroup};
+use super::drawings::{Drawing, DrawingId};
+use super::events::EventHandlers;
+use super::hit_test::{BadgeHit, MarkerHit, PriceLineLabelHit};
 use super::options::{ChartOptions, ChartStyle};
 use super::types::{PanelControlHit, PanelId, PanelRole, Rect, TimeScaleId};
+use super::volume_profile::VolumeProfileState;
+use box_zoom::ZoomView;
+use history::EditHistory;
+use price_line_drag::PriceLineDrag;
 use std::cell::{Cell, RefCell};
 use std::rc::Rc;
+use timescale::TimeScaleAnimation;
 
 #[derive(Clone, Debug)]
 pub(crate) struct ChartCore {
@@ -34,9 +59,37 @@ pub(crate) struct ChartCore {
     rsi_panel: Option<IndicatorPanel>,
     tooltip_icon: Cell<Option<(PanelId, Rect)>>,
     panel_controls: Rc<RefCell<Vec<PanelControlHit>>>,
+    marker_hits: Rc<RefCell<Vec<MarkerHit>>>,
+    price_line_labels: Rc<RefCell<Vec<PriceLineLabelHit>>>,
+    badges: Rc<RefCell<Vec<BadgeHit>>>,
+    price_line_drag: Option<PriceLineDrag>,
     next_panel_id: usize,
     next_time_scale_id: usize,
     rsi_panel_id: Option<PanelId>,
+    drawings: Vec<(DrawingId, Drawing)>,
+    next_drawing_id: usize,
+    drawing_preview: Option<Drawing>,
+    /// Transient measure box, shown until dismissed or pinned
+    measurement: Option<Drawing>,
+    /// Rectangle being dragged out with the zoom tool
+    zoom_box: Option<Rect>,
+    /// Views saved by box zooms, newest last
+    zoom_history: Vec<ZoomView>,
+    time_animation: Option<TimeScaleAnimation>,
+    selected_drawing: Option<DrawingId>,
+    drawing_magnet: bool,
+    /// Bumped whenever a drawing is added, changed or removed.
+    drawing_revision: u64,
+    /// Undo and redo steps for drawings, price lines, panels and edits
+    /// recorded by the application.
+    history: EditHistory,
+    /// Event subscribers and the values last delivered to them.
+    events: EventHandlers,
+    volume_profiles: Vec<VolumeProfileState>,
+    next_volume_profile_id: usize,
+    /// Bumped on every series data change so render caches can tell when
+    /// they are stale.
+    data_revision: u64,
 }
 
 impl ChartCore {
@@ -80,9 +133,28 @@ impl ChartCore {
             rsi_panel: None,
             tooltip_icon: Cell::new(None),
             panel_controls: Rc::new(RefCell::new(Vec::new())),
+            marker_hits: Rc::new(RefCell::new(Vec::new())),
+            price_line_labels: Rc::new(RefCell::new(Vec::new())),
+            badges: Rc::new(RefCell::new(Vec::new())),
+            price_line_drag: None,
             next_panel_id: 2,
             next_time_scale_id: 2,
             rsi_panel_id: None,
+            drawings: Vec::new(),
+            next_drawing_id: 1,
+            drawing_preview: None,
+            measurement: None,
+            zoom_box: None,
+            zoom_history: Vec::new(),
+            time_animation: None,
+            selected_drawing: None,
+            drawing_magnet: false,
+            drawing_revision: 0,
+            history: EditHistory::default(),
+            events: EventHandlers::default(),
+            volume_profiles: Vec::new(),
+            next_volume_profile_id: 0,
+            data_revision: 0,
         }
     }
 }
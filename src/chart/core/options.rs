@@ -1,7 +1,7 @@
 use super::super::layout::ChartLayout;
 use super::super::options::{
-    ChartStyle, HandleScaleOptions, HandleScrollOptions, InteractionSensitivityOptions,
-    KineticScrollOptions, TrackingModeOptions,
+    AnimationOptions, ChartStyle, HandleScaleOptions, HandleScrollOptions,
+    InteractionSensitivityOptions, KineticScrollOptions, TrackingModeOptions,
 };
 use super::super::types::{
     Color, CrosshairCenter, CrosshairMode, LineStyle, PanelControlAction, PanelControlHit, PanelId,
@@ -86,6 +86,23 @@ impl ChartCore {
         self.options.kinetic_scroll = options;
     }
 
+    /// Replaces the animation options. Turning animations off finishes any
+    /// that are running.
+    pub(crate) fn set_animation_options(&mut self, options: AnimationOptions) {
+        self.options.animation = options;
+        if options.enabled {
+            return;
+        }
+        if let Some(animation) = self.time_animation.take() {
+            (self.time_scale.start, self.time_scale.end) = animation.target();
+        }
+        self.left_scale.transition = None;
+        self.right_scale.transition = None;
+        if let Some(panel) = self.rsi_panel.as_mut() {
+            panel.scale.transition = None;
+        }
+    }
+
     pub(crate) fn set_tracking_mode_options(&mut self, options: TrackingModeOptions) {
         self.options.tracking_mode = options;
     }
@@ -157,10 +174,7 @@ impl ChartCore {
     }
 
     pub(crate) fn visible_time_range(&self) -> (f64, f64) {
-        self.time_scales
-            .first()
-            .map(|group| (group.time_scale.start, group.time_scale.end))
-            .unwrap_or((0.0, 0.0))
+        (self.time_scale.start, self.time_scale.end)
     }
 
     pub(crate) fn style(&self) -> ChartStyle {
@@ -0,0 +1,154 @@
+use super::super::events::{
+    EventCalls, PriceLineDragHandler, PriceLineDragParams, PriceLineDragPhase, PriceLineDragVeto,
+    SubscriptionId,
+};
+use super::super::hit_test::HitTarget;
+use super::super::layout::ChartLayout;
+use super::super::types::PriceFormat;
+use super::super::util::map_y_to_price_scaled;
+use super::ChartCore;
+
+/// A draggable price line held by the pointer.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct PriceLineDrag {
+    series: usize,
+    line: usize,
+    start_price: f64,
+}
+
+impl ChartCore {
+    pub(crate) fn subscribe_price_line_drag(
+        &mut self,
+        handler: PriceLineDragHandler,
+    ) -> SubscriptionId {
+        let id = self.events.next_id();
+        self.events.price_line_drag.push((id, handler));
+        id
+    }
+
+    pub(crate) fn subscribe_price_line_drag_veto(
+        &mut self,
+        veto: PriceLineDragVeto,
+    ) -> SubscriptionId {
+        let id = self.events.next_id();
+        self.events.price_line_drag_veto.push((id, veto));
+        id
+    }
+
+    pub(crate) fn price_line_drag_vetoes(&self) -> Vec<PriceLineDragVeto> {
+        self.events
+            .price_line_drag_veto
+            .iter()
+            .map(|(_, veto)| veto.clone())
+            .collect()
+    }
+
+    pub(crate) fn price_line_drag_calls(&self, params: PriceLineDragParams) -> EventCalls {
+        self.events
+            .price_line_drag
+            .iter()
+            .map(|(_, handler)| {
+                let handler = handler.clone();
+                Box::new(move || handler(&params)) as Box<dyn FnOnce()>
+            })
+            .collect()
+    }
+
+    /// Series and line id of the draggable price line under `(x, y)`.
+    pub(crate) fn draggable_price_line_at(&mut self, x: f64, y: f64) -> Option<(usize, usize)> {
+        let Some(HitTarget::PriceLine { series, line }) = self.hit_test(x, y) else {
+            return None;
+        };
+        let draggable = self.price_line_options(series, line)?.draggable;
+        draggable.then_some((series, line))
+    }
+
+    /// Grabs the draggable price line under `(x, y)`.
+    pub(crate) fn start_price_line_drag(&mut self, x: f64, y: f64) -> Option<PriceLineDragParams> {
+        let (series, line) = self.draggable_price_line_at(x, y)?;
+        let start_price = self.price_line_options(series, line)?.price;
+        self.price_line_drag = Some(PriceLineDrag {
+            series,
+            line,
+            start_price,
+        });
+        Some(PriceLineDragParams {
+            series,
+            line,
+            phase: PriceLineDragPhase::Start,
+            price: start_price,
+            start_price,
+        })
+    }
+
+    pub(crate) fn is_dragging_price_line(&self) -> bool {
+        self.price_line_drag.is_some()
+    }
+
+    /// Price the held line would move to with the pointer at `y`, snapped to
+    /// the series' `min_move` and rounded to its precision, so the price is
+    /// exactly the one its label shows.
+    pub(crate) fn price_line_drag_proposal(&mut self, y: f64) -> Option<PriceLineDragParams> {
+        let drag = self.price_line_drag?;
+        let (width, height) = self.events.drawn_size?;
+        let layout = ChartLayout::new(self, width, height);
+        let series = self.series.get(drag.series)?;
+        let side = series.scale;
+        let snap = match series.options.price_format {
+            PriceFormat::Price {
+                precision,
+                min_move,
+            } if min_move > 0.0 => Some((min_move, precision)),
+            _ => None,
+        };
+        let start_time = self.time_scale.start;
+        let end_time = self.time_scale.end;
+        let scale = self.scale_for_side(side, start_time, end_time)?;
+        let y = y.clamp(layout.plot_top, layout.main_bottom);
+        let price = map_y_to_price_scaled(
+            y,
+            scale.min,
+            scale.max,
+            layout.plot_top,
+            layout.main_height,
+            scale.margins,
+            scale.invert,
+            scale.mode,
+            scale.base,
+        );
+        let price = match snap {
+            Some((step, precision)) => {
+                // `0.1 * 3` is `0.30000000000000004`; drop the float noise.
+                let scale = 10_f64.powi(precision.min(15) as i32);
+                ((price / step).round() * step * scale).round() / scale
+            }
+            None => price,
+        };
+        Some(PriceLineDragParams {
+            series: drag.series,
+            line: drag.line,
+            phase: PriceLineDragPhase::Move,
+            price,
+            start_price: drag.start_price,
+        })
+    }
+
+    /// Releases the held line, describing where it was dropped.
+    pub(crate) fn finish_price_line_drag(&mut self) -> Option<PriceLineDragParams> {
+        let drag = self.price_line_drag.take()?;
+        let price = self
+            .price_line_options(drag.series, drag.line)
+            .map_or(drag.start_price, |options| options.price);
+        Some(PriceLineDragParams {
+            series: drag.series,
+            line: drag.line,
+            phase: PriceLineDragPhase::End,
+            price,
+            start_price: drag.start_price,
+        })
+    }
+
+    pub(crate) fn cancel_price_line_drag(&mut self) {
+        self.price_line_drag = None;
+    }
+}
@@ -1,3 +1,4 @@
+use super::super::format::format_price_with_format;
 use super::super::options::PriceScaleOptions;
 use super::super::types::{Color, PriceScale, PriceScaleMode, ScaleMargins};
 use super::ChartCore;
@@ -32,6 +33,17 @@ impl ChartCore {
         }
     }
 
+    pub(crate) fn price_scale_auto_scale(&self, side: PriceScale) -> bool {
+        self.price_scale_options(side).auto_scale
+    }
+
+    /// `price` in the format of the series on `side`, without any percentage
+    /// mode of the scale.
+    pub(crate) fn format_price(&self, side: PriceScale, price: f64) -> String {
+        let format = self.price_format_for_side(side);
+        format_price_with_format(price, &format, 2, PriceScaleMode::Normal)
+    }
+
     pub(crate) fn set_price_scale_visible(&mut self, side: PriceScale, visible: bool) {
         match side {
             PriceScale::Left => self.options.left_price_scale.visible = visible,
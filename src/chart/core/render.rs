@@ -6,7 +6,8 @@ use super::super::ticks::build_time_ticks;
 use super::super::types::{MarkerZOrder, PriceFormat, PriceScale};
 use super::super::util::{
     candle_time, histogram_range, map_price_to_y, map_price_to_y_scaled, map_time_to_x,
-    series_bar_width_times, visible_candles, visible_histogram_points, visible_line_points,
+    series_bar_width_times, visible_candles, visible_cloud_points, visible_histogram_points,
+    visible_line_points,
 };
 use super::render_helpers::{
     aligned_price_ticks, build_ticks_for_scale, primary_candle_scale, primary_candles,
@@ -15,8 +16,11 @@ use super::ChartCore;
 
 impl ChartCore {
     pub(crate) fn draw(&mut self, cr: &Context, width: f64, height: f64) {
+        self.events.drawn_size = Some((width, height));
         self.draw_background(cr, width, height);
         self.set_panel_controls(Vec::new());
+        self.clear_hit_boxes();
+        self.advance_time_animation();
 
         if !self.has_data() {
             return;
@@ -64,18 +68,20 @@ impl ChartCore {
         );
 
         let mut top_marker_series: Vec<usize> = Vec::new();
+        let main_layout = &layout;
+        let rsi_layout = layout.on_rsi_panel();
 
         for (series_index, series) in self.series.iter().enumerate() {
-            if !self.panel_content_visible(series.panel_id) {
+            // Series moved to the indicator panel draw there, on its scale.
+            let Some((scale, layout)) = self.series_area(
+                series,
+                left_scale,
+                right_scale,
+                rsi_scale,
+                main_layout,
+                &rsi_layout,
+            ) else {
                 continue;
-            }
-            let scale = match series.scale {
-                PriceScale::Left => left_scale,
-                PriceScale::Right => right_scale,
-            };
-            let scale = match scale {
-                Some(scale) => scale,
-                None => continue,
             };
 
             match &series.data {
@@ -89,6 +95,7 @@ impl ChartCore {
                     if !series.markers.is_empty() && z_order == MarkerZOrder::Bottom {
                         self.draw_markers(
                             cr,
+                            series_index,
                             &series.markers,
                             data,
                             scale,
@@ -198,6 +205,7 @@ impl ChartCore {
                     if !series.markers.is_empty() && z_order == MarkerZOrder::Normal {
                         self.draw_markers(
                             cr,
+                            series_index,
                             &series.markers,
                             data,
                             scale,
@@ -222,6 +230,7 @@ impl ChartCore {
                     if !series.markers.is_empty() && z_order == MarkerZOrder::Bottom {
                         self.draw_markers(
                             cr,
+                            series_index,
                             &series.markers,
                             data,
                             scale,
@@ -233,8 +242,9 @@ impl ChartCore {
                         );
                     }
 
-                    cr.set_source_rgb(self.style.line.r, self.style.line.g, self.style.line.b);
-                    cr.set_line_width(2.0);
+                    let color = series.options.color.unwrap_or(self.style.line);
+                    cr.set_source_rgb(color.r, color.g, color.b);
+                    cr.set_line_width(series.options.line_width);
                     let mut first = true;
                     for point in &visible {
                         let time = candle_time(point.time);
@@ -268,6 +278,7 @@ impl ChartCore {
                     if !series.markers.is_empty() && z_order == MarkerZOrder::Normal {
                         self.draw_markers(
                             cr,
+                            series_index,
                             &series.markers,
                             data,
                             scale,
@@ -293,6 +304,7 @@ impl ChartCore {
                     if !series.markers.is_empty() && z_order == MarkerZOrder::Bottom {
                         self.draw_markers(
                             cr,
+                            series_index,
                             &series.markers,
                             data,
                             scale,
@@ -347,6 +359,7 @@ impl ChartCore {
                     if !series.markers.is_empty() && z_order == MarkerZOrder::Normal {
                         self.draw_markers(
                             cr,
+                            series_index,
                             &series.markers,
                             data,
                             scale,
@@ -362,6 +375,18 @@ impl ChartCore {
                         top_marker_series.push(series_index);
                     }
                 }
+                SeriesData::Cloud { data } => {
+                    let visible = visible_cloud_points(data, start_time, end_time);
+                    self.draw_cloud(
+                        cr,
+                        &visible,
+                        scale,
+                        &layout,
+                        start_time,
+                        end_time,
+                        &series.options,
+                    );
+                }
             }
         }
 
@@ -372,10 +397,64 @@ impl ChartCore {
         if rsi_visible {
             if let (Some(panel), Some(scale)) = (self.rsi_panel.as_ref(), rsi_scale) {
                 if layout.rsi_height > 0.0 {
-                    let visible = visible_line_points(&panel.data, start_time, end_time);
-                    if !visible.is_empty() {
-                        cr.set_source_rgb(panel.color.r, panel.color.g, panel.color.b);
-                        cr.set_line_width(1.5);
+                    let map_y = |value: f64| {
+                        map_price_to_y_scaled(
+                            value,
+                            scale.min,
+                            scale.max,
+                            layout.rsi_top,
+                            layout.rsi_height,
+                            scale.margins,
+                            scale.invert,
+                            scale.mode,
+                            scale.base,
+                        )
+                    };
+                    let zero_y =
+                        map_y(0.0).clamp(layout.rsi_top, layout.rsi_top + layout.rsi_height);
+                    for (data, color) in panel.groups.iter().flat_map(|group| &group.histograms) {
+                        let visible = visible_histogram_points(data, start_time, end_time);
+                        let bar_width = series_bar_width_times(
+                            visible.iter().map(|point| candle_time(point.time)),
+                            start_time,
+                            end_time,
+                            layout.plot_width,
+                        );
+                        for point in &visible {
+                            let color = point.color.unwrap_or(*color);
+                            cr.set_source_rgb(color.r, color.g, color.b);
+                            let x_center = map_time_to_x(
+                                candle_time(point.time),
+                                start_time,
+                                end_time,
+                                layout.plot_left,
+                                layout.plot_width,
+                            );
+                            let y = map_y(point.value);
+                            cr.rectangle(
+                                x_center - bar_width / 2.0,
+                                y.min(zero_y),
+                                bar_width,
+                                (zero_y - y).abs().max(1.0),
+                            );
+                            let _ = cr.fill();
+                        }
+                    }
+
+                    let lines = std::iter::once((&panel.data, panel.color, 1.5)).chain(
+                        panel
+                            .groups
+                            .iter()
+                            .flat_map(|group| &group.lines)
+                            .map(|(data, color)| (data, *color, 1.0)),
+                    );
+                    for (data, color, width) in lines {
+                        let visible = visible_line_points(data, start_time, end_time);
+                        if visible.is_empty() {
+                            continue;
+                        }
+                        cr.set_source_rgb(color.r, color.g, color.b);
+                        cr.set_line_width(width);
                         let mut first = true;
                         for point in &visible {
                             let time = candle_time(point.time);
@@ -386,17 +465,7 @@ impl ChartCore {
                                 layout.plot_left,
                                 layout.plot_width,
                             );
-                            let y = map_price_to_y_scaled(
-                                point.value,
-                                scale.min,
-                                scale.max,
-                                layout.rsi_top,
-                                layout.rsi_height,
-                                scale.margins,
-                                scale.invert,
-                                scale.mode,
-                                scale.base,
-                            );
+                            let y = map_y(point.value);
                             if first {
                                 cr.move_to(x, y);
                                 first = false;
@@ -463,6 +532,8 @@ impl ChartCore {
             }
         }
 
+        self.draw_volume_profiles(cr, &layout, left_scale, right_scale, start_time, end_time);
+
         self.draw_time_axis(
             cr,
             layout.plot_left,
@@ -555,7 +626,9 @@ impl ChartCore {
             }
         }
 
-        self.draw_series_overlays(cr, &layout, left_scale, right_scale);
+        self.draw_series_overlays(cr, &layout, left_scale, right_scale, start_time, end_time);
+        self.draw_trading_lines(cr, &layout, left_scale, right_scale);
+        self.draw_drawings(cr, &layout, left_scale, right_scale, start_time, end_time);
         self.draw_panel_controls(cr, &layout);
         self.draw_main_header(cr, &layout);
 
@@ -565,13 +638,15 @@ impl ChartCore {
                     Some(series) => series,
                     None => continue,
                 };
-                let scale = match series.scale {
-                    PriceScale::Left => left_scale,
-                    PriceScale::Right => right_scale,
-                };
-                let scale = match scale {
-                    Some(scale) => scale,
-                    None => continue,
+                let Some((scale, layout)) = self.series_area(
+                    series,
+                    left_scale,
+                    right_scale,
+                    rsi_scale,
+                    &layout,
+                    &rsi_layout,
+                ) else {
+                    continue;
                 };
                 match &series.data {
                     SeriesData::Candlestick { data } => {
@@ -580,6 +655,7 @@ impl ChartCore {
                         }
                         self.draw_markers(
                             cr,
+                            series_index,
                             &series.markers,
                             data,
                             scale,
@@ -596,6 +672,7 @@ impl ChartCore {
                         }
                         self.draw_markers(
                             cr,
+                            series_index,
                             &series.markers,
                             data,
                             scale,
@@ -612,6 +689,7 @@ impl ChartCore {
                         }
                         self.draw_markers(
                             cr,
+                            series_index,
                             &series.markers,
                             data,
                             scale,
@@ -622,6 +700,7 @@ impl ChartCore {
                             &series.options.markers_options,
                         );
                     }
+                    SeriesData::Cloud { .. } => {}
                 }
             }
         }
@@ -636,6 +715,8 @@ impl ChartCore {
         );
         let _ = cr.stroke();
 
+        self.draw_zoom_box(cr);
+
         let primary_scale =
             primary_candle_scale(self.primary_candles, &self.series, left_scale, right_scale)
                 .or_else(|| match primary_side {
@@ -0,0 +1,120 @@
+use cairo::Context;
+
+use super::super::drawings::{Drawing, DrawingTool};
+use super::annotations::{set_text_font, text_box, TextBox, TEXT_PADDING};
+use super::render_drawings::{set_drawing_stroke, stroke_segment, DrawingFrame};
+use super::render_helpers::draw_rounded_rect;
+use crate::icons::draw_marker_svg_icon;
+
+const BOX_RADIUS: f64 = 4.0;
+/// Half the width of a callout pointer where it leaves the box.
+const POINTER_HALF_WIDTH: f64 = 6.0;
+
+/// Text, callout and note boxes: background, border and multi-line text,
+/// with a callout's pointer drawn underneath its box.
+pub(super) fn draw_text_drawing(cr: &Context, frame: &DrawingFrame, drawing: &Drawing) {
+    let Some(text_box) = text_box(cr, &frame.mapper, drawing) else {
+        return;
+    };
+    let style = &drawing.style;
+    if drawing.tool == DrawingTool::Callout {
+        if let Some(target) = drawing.anchors.first() {
+            draw_callout_pointer(cr, drawing, &text_box, frame.mapper.point(target));
+        }
+    }
+    if let Some(background) = style.text_background {
+        cr.set_source_rgb(background.r, background.g, background.b);
+        draw_rounded_rect(
+            cr,
+            text_box.left,
+            text_box.top,
+            text_box.width,
+            text_box.height,
+            BOX_RADIUS,
+        );
+        let _ = cr.fill();
+    }
+    if style.text_border {
+        set_drawing_stroke(cr, drawing, style.color);
+        draw_rounded_rect(
+            cr,
+            text_box.left,
+            text_box.top,
+            text_box.width,
+            text_box.height,
+            BOX_RADIUS,
+        );
+        let _ = cr.stroke();
+        cr.set_dash(&[], 0.0);
+    }
+
+    set_text_font(cr, drawing);
+    let size = style.font_size.max(4.0);
+    let x = text_box.left + TEXT_PADDING;
+    for (idx, line) in text_box.lines.iter().enumerate() {
+        let top = text_box.top + TEXT_PADDING + text_box.line_height * idx as f64;
+        if draw_marker_svg_icon(cr, line, x, top, size, style.color) {
+            continue;
+        }
+        cr.set_source_rgb(style.color.r, style.color.g, style.color.b);
+        cr.move_to(x, top + size);
+        let _ = cr.show_text(line);
+    }
+}
+
+/// Wedge from the middle of the box to the callout's target.
+fn draw_callout_pointer(cr: &Context, drawing: &Drawing, text_box: &TextBox, target: (f64, f64)) {
+    let center = text_box.center();
+    let (dx, dy) = (target.0 - center.0, target.1 - center.1);
+    let length = dx.hypot(dy);
+    if length < f64::EPSILON {
+        return;
+    }
+    let (nx, ny) = (
+        -dy / length * POINTER_HALF_WIDTH,
+        dx / length * POINTER_HALF_WIDTH,
+    );
+    let (left, right) = (
+        (center.0 + nx, center.1 + ny),
+        (center.0 - nx, center.1 - ny),
+    );
+    if let Some(background) = drawing.style.text_background {
+        cr.set_source_rgb(background.r, background.g, background.b);
+        cr.move_to(left.0, left.1);
+        cr.line_to(target.0, target.1);
+        cr.line_to(right.0, right.1);
+        cr.close_path();
+        let _ = cr.fill();
+    }
+    set_drawing_stroke(cr, drawing, drawing.style.color);
+    cr.move_to(left.0, left.1);
+    cr.line_to(target.0, target.1);
+    cr.line_to(right.0, right.1);
+    let _ = cr.stroke();
+    cr.set_dash(&[], 0.0);
+}
+
+/// Line from the first anchor ending in a filled head at the second.
+pub(super) fn draw_arrow(cr: &Context, frame: &DrawingFrame, drawing: &Drawing) {
+    let &[from, to, ..] = frame.mapper.points(drawing).as_slice() else {
+        return;
+    };
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let length = dx.hypot(dy);
+    if length < f64::EPSILON {
+        return;
+    }
+    let (ux, uy) = (dx / length, dy / length);
+    let head_length = (drawing.style.width * 4.0 + 8.0).min(length);
+    let half_width = head_length * 0.5;
+    let base = (to.0 - ux * head_length, to.1 - uy * head_length);
+
+    set_drawing_stroke(cr, drawing, drawing.style.color);
+    stroke_segment(cr, (from, base));
+    cr.set_dash(&[], 0.0);
+    cr.move_to(to.0, to.1);
+    cr.line_to(base.0 - uy * half_width, base.1 + ux * half_width);
+    cr.line_to(base.0 + uy * half_width, base.1 - ux * half_width);
+    cr.close_path();
+    let _ = cr.fill();
+}
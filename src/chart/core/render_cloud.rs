@@ -0,0 +1,113 @@
+use cairo::Context;
+
+use super::super::data::{SeriesOptions, SeriesScale};
+use super::super::layout::ChartLayout;
+use super::super::types::{CloudPoint, Color};
+use super::super::util::{candle_time, map_price_to_y_scaled, map_time_to_x};
+use super::ChartCore;
+
+impl ChartCore {
+    pub(super) fn draw_cloud(
+        &self,
+        cr: &Context,
+        visible: &[&CloudPoint],
+        scale: SeriesScale,
+        layout: &ChartLayout,
+        start_time: f64,
+        end_time: f64,
+        options: &SeriesOptions,
+    ) {
+        if visible.len() < 2 {
+            return;
+        }
+
+        let up = options.fill_up_color.unwrap_or(self.style.up);
+        let down = options.fill_down_color.unwrap_or(self.style.down);
+        let alpha = options.fill_alpha.clamp(0.0, 1.0);
+        let to_y = |value: f64| {
+            map_price_to_y_scaled(
+                value,
+                scale.min,
+                scale.max,
+                layout.plot_top,
+                layout.main_height,
+                scale.margins,
+                scale.invert,
+                scale.mode,
+                scale.base,
+            )
+        };
+
+        // Screen-space vertices of each edge, split into runs of a single
+        // polarity. A run ends where the edges cross; the crossing point is
+        // shared by the run that ends there and the one that starts there.
+        let mut run_a: Vec<(f64, f64)> = Vec::new();
+        let mut run_b: Vec<(f64, f64)> = Vec::new();
+        let mut run_up: Option<bool> = None;
+        let mut prev: Option<(f64, f64, f64)> = None;
+
+        for point in visible {
+            let x = map_time_to_x(
+                candle_time(point.time),
+                start_time,
+                end_time,
+                layout.plot_left,
+                layout.plot_width,
+            );
+            let ya = to_y(point.value_a);
+            let yb = to_y(point.value_b);
+            let diff = point.value_a - point.value_b;
+            let is_up = diff >= 0.0;
+
+            if let (Some(current), Some((px, pya, pyb))) = (run_up, prev) {
+                if current != is_up {
+                    let prev_gap = pya - pyb;
+                    let gap = ya - yb;
+                    let t = if (prev_gap - gap).abs() > f64::EPSILON {
+                        (prev_gap / (prev_gap - gap)).clamp(0.0, 1.0)
+                    } else {
+                        0.5
+                    };
+                    let cross_x = px + (x - px) * t;
+                    let cross_y = pya + (ya - pya) * t;
+                    run_a.push((cross_x, cross_y));
+                    run_b.push((cross_x, cross_y));
+                    fill_run(cr, &run_a, &run_b, if current { up } else { down }, alpha);
+                    run_a.clear();
+                    run_b.clear();
+                    run_a.push((cross_x, cross_y));
+                    run_b.push((cross_x, cross_y));
+                }
+            }
+
+            run_a.push((x, ya));
+            run_b.push((x, yb));
+            run_up = Some(is_up);
+            prev = Some((x, ya, yb));
+        }
+
+        if let Some(current) = run_up {
+            fill_run(cr, &run_a, &run_b, if current { up } else { down }, alpha);
+        }
+    }
+}
+
+fn fill_run(cr: &Context, edge_a: &[(f64, f64)], edge_b: &[(f64, f64)], color: Color, alpha: f64) {
+    if edge_a.len() < 2 {
+        return;
+    }
+    cr.new_path();
+    for (index, (x, y)) in edge_a.iter().enumerate() {
+        if index == 0 {
+            cr.move_to(*x, *y);
+        } else {
+            cr.line_to(*x, *y);
+        }
+    }
+    for (x, y) in edge_b.iter().rev() {
+        cr.line_to(*x, *y);
+    }
+    cr.close_path();
+    cr.set_source_rgba(color.r, color.g, color.b, alpha);
+    let _ = cr.fill();
+}
@@ -13,7 +13,9 @@ use super::super::util::{
     apply_line_style, candle_time, map_price_to_y_scaled, map_time_to_x, map_y_to_price_scaled,
     nearest_by_time, transform_price,
 };
-use super::render_helpers::{build_ticks_for_scale, primary_candle_side, tooltip_position};
+use super::render_helpers::{
+    build_ticks_for_scale, nearest_ohlc, primary_candle_side, tooltip_position,
+};
 use super::ChartCore;
 use crate::icons::{draw_svg_icon, IconName};
 
@@ -93,29 +95,21 @@ impl ChartCore {
                         * (end_time - start_time);
                 let mut best_dist = f64::INFINITY;
 
-                if snap_to_ohlc {
-                    if let Some(candles) = primary_candles {
-                        if let Some(candle) = nearest_by_time(candles, target_time) {
-                            let candle_side =
-                                primary_candle_side(self.primary_candles, &self.series);
-                            if candle_side == Some(side) {
-                                let values = [candle.open, candle.high, candle.low, candle.close];
-                                for value in values {
-                                    let dist = (cursor_price - value).abs();
-                                    if dist < best_dist {
-                                        best_dist = dist;
-                                        snapped_time = Some(candle_time(candle.time));
-                                        snapped_price = Some(value);
-                                    }
-                                }
-                            }
-                        }
+                if snap_to_ohlc
+                    && primary_candle_side(self.primary_candles, &self.series) == Some(side)
+                {
+                    if let Some((candle, value)) = primary_candles
+                        .and_then(|candles| nearest_ohlc(candles, target_time, cursor_price))
+                    {
+                        best_dist = (cursor_price - value).abs();
+                        snapped_time = Some(candle_time(candle.time));
+                        snapped_price = Some(value);
                     }
                 }
 
                 if snap_to_series {
                     for series in &self.series {
-                        if !self.panel_content_visible(series.panel_id) {
+                        if !self.series_on_main_panel(series) {
                             continue;
                         }
                         if series.scale != side {
@@ -517,7 +511,7 @@ impl ChartCore {
             } else if in_hist {
                 let mut hist_index = 1;
                 for series in &self.series {
-                    if !self.panel_content_visible(series.panel_id) {
+                    if !self.series_shown(series) {
                         continue;
                     }
                     if let SeriesData::Histogram { data } = &series.data {
@@ -618,7 +612,7 @@ impl ChartCore {
                 let mut line_index = 1;
                 let mut hist_index = 1;
                 for series in &self.series {
-                    if !self.panel_content_visible(series.panel_id) {
+                    if !self.series_shown(series) {
                         continue;
                     }
                     match &series.data {
@@ -0,0 +1,231 @@
+use cairo::{Context, FontSlant, FontWeight};
+
+use super::super::data::SeriesScale;
+use super::super::drawings::{Drawing, DrawingTool};
+use super::super::layout::ChartLayout;
+use super::super::types::{Candle, Color, PriceFormat, PriceLineOptions, PriceScale};
+use super::super::util::apply_line_style;
+use super::drawings::{line_segment, plot_rect, AnchorMapper, PlotRect, Segment};
+use super::render_annotations::{draw_arrow, draw_text_drawing};
+use super::render_fibonacci::{draw_fib_arcs, draw_fib_fan, draw_fib_levels, draw_fib_time_zones};
+use super::render_helpers::primary_candles;
+use super::render_measure::{draw_measure, draw_risk_reward};
+use super::render_overlays::{draw_price_level_label, price_level_text};
+use super::render_shapes::{
+    draw_head_and_shoulders, draw_parallel_channel, draw_pitchfork, draw_regression_channel,
+    draw_shape, draw_xabcd,
+};
+use super::ChartCore;
+
+impl ChartCore {
+    pub(super) fn draw_drawings(
+        &self,
+        cr: &Context,
+        layout: &ChartLayout,
+        left_scale: Option<SeriesScale>,
+        right_scale: Option<SeriesScale>,
+        start_time: f64,
+        end_time: f64,
+    ) {
+        if self.drawings.is_empty() && self.drawing_preview.is_none() && self.measurement.is_none()
+        {
+            return;
+        }
+        let Some(mapper) = AnchorMapper::new(layout, left_scale, right_scale, start_time, end_time)
+        else {
+            return;
+        };
+        let frame = DrawingFrame {
+            mapper,
+            rect: plot_rect(layout),
+            candles: primary_candles(self.primary_candles, &self.series).unwrap_or(&[]),
+            format: self.price_format_for_side(mapper.side),
+            up: self.style.up,
+            down: self.style.down,
+        };
+
+        cr.save().ok();
+        cr.rectangle(
+            layout.plot_left,
+            layout.plot_top,
+            layout.plot_width,
+            layout.main_height,
+        );
+        cr.clip();
+        let drawings = self
+            .drawings
+            .iter()
+            .map(|(_, drawing)| drawing)
+            .chain(self.drawing_preview.as_ref())
+            .chain(self.measurement.as_ref());
+        for drawing in drawings {
+            draw_drawing(cr, &frame, drawing);
+        }
+        if let Some((_, drawing)) = self
+            .drawings
+            .iter()
+            .find(|(id, _)| Some(*id) == self.selected_drawing)
+        {
+            draw_handles(cr, &mapper, drawing);
+        }
+        cr.restore().ok();
+
+        // Axis labels sit outside the plot clip.
+        for drawing in self.drawings.iter().map(|(_, drawing)| drawing) {
+            if drawing.tool != DrawingTool::HorizontalLine {
+                continue;
+            }
+            let Some(anchor) = drawing.anchors.first() else {
+                continue;
+            };
+            let options = PriceLineOptions {
+                price: anchor.price,
+                color: drawing.style.color,
+                ..PriceLineOptions::default()
+            };
+            let text = price_level_text(&options, &frame.format, mapper.scale, 2);
+            draw_price_level_label(
+                cr,
+                layout,
+                &options,
+                &text,
+                mapper.y(anchor.price),
+                |box_width| match mapper.side {
+                    PriceScale::Left => layout.axis_left + 4.0,
+                    PriceScale::Right => layout.axis_right - box_width - 4.0,
+                },
+            );
+        }
+    }
+}
+
+/// Per-frame state shared by the drawing renderers.
+pub(super) struct DrawingFrame<'a> {
+    pub(super) mapper: AnchorMapper,
+    pub(super) rect: PlotRect,
+    /// Candles of the primary series, for tools measured in bars
+    pub(super) candles: &'a [Candle],
+    /// Price format of the drawings' price scale
+    pub(super) format: PriceFormat,
+    /// Candle colors, for tools colored by direction
+    pub(super) up: Color,
+    pub(super) down: Color,
+}
+
+fn draw_drawing(cr: &Context, frame: &DrawingFrame, drawing: &Drawing) {
+    let mapper = &frame.mapper;
+    let points = mapper.points(drawing);
+    let Some(&first) = points.first() else {
+        return;
+    };
+
+    let style = &drawing.style;
+    set_drawing_stroke(cr, drawing, style.color);
+    if drawing.pinned.is_none() && drawing.anchors.len() < drawing.tool.anchor_count() {
+        // Still being placed: join the anchors so far and mark each one.
+        for pair in points.windows(2) {
+            cr.move_to(pair[0].0, pair[0].1);
+            cr.line_to(pair[1].0, pair[1].1);
+        }
+        let _ = cr.stroke();
+        cr.set_dash(&[], 0.0);
+        for (x, y) in &points {
+            cr.arc(*x, *y, 3.0, 0.0, std::f64::consts::TAU);
+            let _ = cr.fill();
+        }
+        return;
+    }
+    match drawing.tool {
+        DrawingTool::FibRetracement | DrawingTool::FibExtension => {
+            draw_fib_levels(cr, frame, drawing)
+        }
+        DrawingTool::FibFan => draw_fib_fan(cr, frame, drawing),
+        DrawingTool::FibArcs => draw_fib_arcs(cr, frame, drawing),
+        DrawingTool::FibTimeZones => draw_fib_time_zones(cr, frame, drawing),
+        DrawingTool::ParallelChannel => draw_parallel_channel(cr, frame, drawing),
+        DrawingTool::RegressionChannel => draw_regression_channel(cr, frame, drawing),
+        DrawingTool::Pitchfork
+        | DrawingTool::SchiffPitchfork
+        | DrawingTool::ModifiedSchiffPitchfork => draw_pitchfork(cr, frame, drawing),
+        DrawingTool::Rectangle | DrawingTool::Ellipse | DrawingTool::Triangle => {
+            draw_shape(cr, frame, drawing)
+        }
+        DrawingTool::Xabcd => draw_xabcd(cr, frame, drawing),
+        DrawingTool::HeadAndShoulders => draw_head_and_shoulders(cr, frame, drawing),
+        DrawingTool::Text | DrawingTool::Callout | DrawingTool::Note => {
+            draw_text_drawing(cr, frame, drawing)
+        }
+        DrawingTool::Arrow => draw_arrow(cr, frame, drawing),
+        DrawingTool::Measure => draw_measure(cr, frame, drawing),
+        DrawingTool::RiskReward => draw_risk_reward(cr, frame, drawing),
+        _ => {
+            if let Some(segment) = line_segment(mapper, frame.rect, drawing) {
+                stroke_segment(cr, segment);
+            }
+        }
+    }
+    cr.set_dash(&[], 0.0);
+
+    if drawing.text.is_empty() || drawing.tool.has_text_box() {
+        return;
+    }
+    let (left, top, _, _) = frame.rect;
+    let (x, y) = match (drawing.tool, points.get(1)) {
+        (DrawingTool::HorizontalLine, _) => (left + 8.0, first.1 - 5.0),
+        (DrawingTool::HorizontalRay, _) => (first.0 + 4.0, first.1 - 5.0),
+        (DrawingTool::VerticalLine | DrawingTool::FibTimeZones, _) => (first.0 + 4.0, top + 28.0),
+        (_, Some(second)) => ((first.0 + second.0) / 2.0, (first.1 + second.1) / 2.0 - 5.0),
+        (_, None) => return,
+    };
+    cr.set_source_rgb(style.color.r, style.color.g, style.color.b);
+    cr.select_font_face("Sans", FontSlant::Normal, FontWeight::Normal);
+    cr.set_font_size(style.font_size.max(4.0));
+    // Extra lines stack upwards so the last one sits where a single line would.
+    let lines: Vec<&str> = drawing.text.lines().collect();
+    let line_height = style.font_size.max(4.0) * 1.3;
+    for (idx, line) in lines.iter().enumerate() {
+        cr.move_to(x, y - line_height * (lines.len() - 1 - idx) as f64);
+        let _ = cr.show_text(line);
+    }
+}
+
+/// Length rays are stretched to for fills; the plot clip trims them.
+const FAR: f64 = 1.0e5;
+
+/// A point far along the ray from the segment's start through its end.
+pub(super) fn far_point((from, to): Segment) -> (f64, f64) {
+    let length = (to.0 - from.0).hypot(to.1 - from.1).max(f64::EPSILON);
+    (
+        from.0 + (to.0 - from.0) / length * FAR,
+        from.1 + (to.1 - from.1) / length * FAR,
+    )
+}
+
+pub(super) fn set_drawing_stroke(cr: &Context, drawing: &Drawing, color: Color) {
+    cr.set_source_rgb(color.r, color.g, color.b);
+    cr.set_line_width(drawing.style.width.max(0.5));
+    apply_line_style(cr, drawing.style.line_style, drawing.style.width);
+}
+
+pub(super) fn set_drawing_fill(cr: &Context, drawing: &Drawing, color: Color) {
+    let alpha = drawing.style.fill_alpha.clamp(0.0, 1.0);
+    cr.set_source_rgba(color.r, color.g, color.b, alpha);
+}
+
+pub(super) fn stroke_segment(cr: &Context, (from, to): Segment) {
+    cr.move_to(from.0, from.1);
+    cr.line_to(to.0, to.1);
+    let _ = cr.stroke();
+}
+
+fn draw_handles(cr: &Context, mapper: &AnchorMapper, drawing: &Drawing) {
+    let color = drawing.style.color;
+    cr.set_line_width(1.5);
+    for (x, y) in mapper.points(drawing) {
+        cr.arc(x, y, 4.0, 0.0, std::f64::consts::TAU);
+        cr.set_source_rgb(1.0, 1.0, 1.0);
+        let _ = cr.fill_preserve();
+        cr.set_source_rgb(color.r, color.g, color.b);
+        let _ = cr.stroke();
+    }
+}
@@ -0,0 +1,180 @@
+use cairo::{Context, FontSlant, FontWeight};
+
+use super::super::drawings::{Drawing, DrawingLevel};
+use super::super::format::format_price_with_format;
+use super::super::types::{Color, PriceScaleMode};
+use super::super::util::transform_price;
+use super::drawings::{anchor_path, fan_rays, level_lines, time_zone_xs};
+use super::render_drawings::{
+    far_point, set_drawing_fill, set_drawing_stroke, stroke_segment, DrawingFrame,
+};
+
+/// Dashed line through the anchors, showing the move the levels are taken
+/// from.
+fn draw_trend(cr: &Context, frame: &DrawingFrame, drawing: &Drawing) {
+    let color = drawing.style.color;
+    cr.set_source_rgba(color.r, color.g, color.b, 0.7);
+    cr.set_line_width(1.0);
+    cr.set_dash(&[4.0, 4.0], 0.0);
+    for segment in anchor_path(&frame.mapper, drawing) {
+        stroke_segment(cr, segment);
+    }
+    cr.set_dash(&[], 0.0);
+}
+
+fn show_label(cr: &Context, color: Color, x: f64, y: f64, text: &str) {
+    cr.set_dash(&[], 0.0);
+    cr.set_source_rgb(color.r, color.g, color.b);
+    cr.select_font_face("Sans", FontSlant::Normal, FontWeight::Normal);
+    cr.set_font_size(10.0);
+    cr.move_to(x, y);
+    let _ = cr.show_text(text);
+}
+
+fn ratio_text(level: &DrawingLevel) -> String {
+    format!("{}", level.value)
+}
+
+fn price_text(frame: &DrawingFrame, price: f64) -> String {
+    let scale = frame.mapper.scale;
+    let shown = match scale.mode {
+        PriceScaleMode::Percentage | PriceScaleMode::IndexedTo100 => {
+            transform_price(price, scale.mode, scale.base)
+        }
+        _ => price,
+    };
+    format_price_with_format(shown, &frame.format, 0, scale.mode)
+}
+
+/// Retracement and extension levels: horizontal lines with the bands
+/// between them shaded in the upper level's color.
+pub(super) fn draw_fib_levels(cr: &Context, frame: &DrawingFrame, drawing: &Drawing) {
+    draw_trend(cr, frame, drawing);
+    let lines = level_lines(&frame.mapper, frame.rect, drawing);
+    if drawing.style.fill {
+        let mut ordered: Vec<_> = lines.iter().collect();
+        ordered.sort_by(|a, b| {
+            drawing.levels[a.level]
+                .value
+                .total_cmp(&drawing.levels[b.level].value)
+        });
+        for pair in ordered.windows(2) {
+            let ((left, top), (right, _)) = pair[0].segment;
+            let bottom = pair[1].segment.0 .1;
+            set_drawing_fill(cr, drawing, drawing.levels[pair[1].level].color);
+            cr.rectangle(left, top.min(bottom), right - left, (bottom - top).abs());
+            let _ = cr.fill();
+        }
+    }
+    for line in &lines {
+        let level = &drawing.levels[line.level];
+        set_drawing_stroke(cr, drawing, level.color);
+        stroke_segment(cr, line.segment);
+        let (start, y) = line.segment.0;
+        let text = format!("{} ({})", ratio_text(level), price_text(frame, line.price));
+        show_label(cr, level.color, start + 4.0, y - 3.0, &text);
+    }
+}
+
+/// Fan rays from the first anchor, with the wedges between them shaded.
+pub(super) fn draw_fib_fan(cr: &Context, frame: &DrawingFrame, drawing: &Drawing) {
+    draw_trend(cr, frame, drawing);
+    let rays = fan_rays(&frame.mapper, frame.rect, drawing);
+    if drawing.style.fill {
+        for pair in rays.windows(2) {
+            let (origin, _) = pair[0].1;
+            let (first, second) = (far_point(pair[0].1), far_point(pair[1].1));
+            set_drawing_fill(cr, drawing, drawing.levels[pair[1].0].color);
+            cr.move_to(origin.0, origin.1);
+            cr.line_to(first.0, first.1);
+            cr.line_to(second.0, second.1);
+            cr.close_path();
+            let _ = cr.fill();
+        }
+    }
+    let Some(b) = drawing.anchors.get(1) else {
+        return;
+    };
+    let label_x = frame.mapper.x(b.time) + 4.0;
+    for (idx, segment) in &rays {
+        let level = &drawing.levels[*idx];
+        set_drawing_stroke(cr, drawing, level.color);
+        stroke_segment(cr, *segment);
+        let (from, to) = *segment;
+        let t = if (to.0 - from.0).abs() > f64::EPSILON {
+            (label_x - from.0) / (to.0 - from.0)
+        } else {
+            0.0
+        };
+        let y = from.1 + (to.1 - from.1) * t.clamp(0.0, 1.0);
+        show_label(cr, level.color, label_x, y - 3.0, &ratio_text(level));
+    }
+}
+
+/// Half circles centred on the second anchor, opening away from the first.
+/// Radii are fractions of the distance between the anchors on screen.
+pub(super) fn draw_fib_arcs(cr: &Context, frame: &DrawingFrame, drawing: &Drawing) {
+    let [a, b, ..] = drawing.anchors.as_slice() else {
+        return;
+    };
+    draw_trend(cr, frame, drawing);
+    let (start, center) = (frame.mapper.point(a), frame.mapper.point(b));
+    let distance = (start.0 - center.0).hypot(start.1 - center.1);
+    // Cairo angles grow clockwise, so pi..2pi is the upper half.
+    let (from_angle, to_angle, up) = if start.1 < center.1 {
+        (std::f64::consts::PI, std::f64::consts::TAU, true)
+    } else {
+        (0.0, std::f64::consts::PI, false)
+    };
+    let mut arcs: Vec<(usize, f64)> = drawing
+        .levels
+        .iter()
+        .enumerate()
+        .filter(|(_, level)| level.visible && level.value > 0.0)
+        .map(|(idx, level)| (idx, level.value * distance))
+        .collect();
+    arcs.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    if drawing.style.fill {
+        for pair in arcs.windows(2) {
+            set_drawing_fill(cr, drawing, drawing.levels[pair[1].0].color);
+            cr.new_path();
+            cr.arc(center.0, center.1, pair[1].1, from_angle, to_angle);
+            cr.arc_negative(center.0, center.1, pair[0].1, to_angle, from_angle);
+            cr.close_path();
+            let _ = cr.fill();
+        }
+    }
+    for (idx, radius) in &arcs {
+        let level = &drawing.levels[*idx];
+        set_drawing_stroke(cr, drawing, level.color);
+        cr.new_path();
+        cr.arc(center.0, center.1, *radius, from_angle, to_angle);
+        let _ = cr.stroke();
+        let y = if up {
+            center.1 - radius - 3.0
+        } else {
+            center.1 + radius + 12.0
+        };
+        show_label(cr, level.color, center.0 + 3.0, y, &ratio_text(level));
+    }
+}
+
+/// Vertical lines at Fibonacci bar counts from the anchor.
+pub(super) fn draw_fib_time_zones(cr: &Context, frame: &DrawingFrame, drawing: &Drawing) {
+    let (_, top, _, bottom) = frame.rect;
+    let zones = time_zone_xs(&frame.mapper, drawing, frame.candles);
+    if drawing.style.fill {
+        for pair in zones.windows(2) {
+            set_drawing_fill(cr, drawing, drawing.levels[pair[1].0].color);
+            cr.rectangle(pair[0].1, top, pair[1].1 - pair[0].1, bottom - top);
+            let _ = cr.fill();
+        }
+    }
+    for (idx, x) in &zones {
+        let level = &drawing.levels[*idx];
+        set_drawing_stroke(cr, drawing, level.color);
+        stroke_segment(cr, ((*x, top), (*x, bottom)));
+        show_label(cr, level.color, x + 3.0, top + 12.0, &ratio_text(level));
+    }
+}
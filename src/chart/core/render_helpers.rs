@@ -5,8 +5,8 @@ use super::super::options::PriceScaleOptions;
 use super::super::ticks::{build_price_ticks, PriceTicks};
 use super::super::types::{Candle, Color, PriceScale, TooltipPosition};
 use super::super::util::{
-    inverse_transform_price, map_price_to_y_scaled, map_y_to_price_scaled, scale_area,
-    transform_price,
+    inverse_transform_price, map_price_to_y_scaled, map_y_to_price_scaled, nearest_by_time,
+    scale_area, transform_price,
 };
 use cairo::Context;
 
@@ -28,6 +28,20 @@ pub(super) fn primary_candles(primary: Option<usize>, series: &[Series]) -> Opti
     None
 }
 
+/// Returns the candle nearest to `target_time` together with whichever of its
+/// open, high, low and close is closest to `price`.
+pub(super) fn nearest_ohlc(
+    candles: &[Candle],
+    target_time: f64,
+    price: f64,
+) -> Option<(&Candle, f64)> {
+    let candle = nearest_by_time(candles, target_time)?;
+    let value = [candle.open, candle.high, candle.low, candle.close]
+        .into_iter()
+        .min_by(|a, b| (price - a).abs().total_cmp(&(price - b).abs()))?;
+    Some((candle, value))
+}
+
 pub(super) fn primary_candle_side(primary: Option<usize>, series: &[Series]) -> Option<PriceScale> {
     if let Some(id) = primary {
         if let Some(series) = series.get(id) {
@@ -174,13 +188,14 @@ pub(super) fn series_last_value(series: &Series, style: &ChartStyle) -> Option<(
         }
         SeriesData::Line { data } => {
             let point = data.last()?;
-            Some((point.value, style.line))
+            Some((point.value, series.options.color.unwrap_or(style.line)))
         }
         SeriesData::Histogram { data } => {
             let point = data.last()?;
             let color = point.color.unwrap_or(style.histogram);
             Some((point.value, color))
         }
+        SeriesData::Cloud { .. } => None,
     }
 }
 
@@ -2,7 +2,7 @@ use cairo::Context;
 
 use super::super::data::{HasTime, SeriesScale};
 use super::super::layout::ChartLayout;
-use super::super::types::{Color, Marker, MarkerPosition, MarkerShape, SeriesMarkersOptions};
+use super::super::types::{Color, Marker, MarkerPosition, MarkerShape, Rect, SeriesMarkersOptions};
 use super::super::util::{candle_time, map_price_to_y_scaled, map_time_to_x, nearest_by_time};
 use super::render_helpers::draw_rounded_rect;
 use super::ChartCore;
@@ -12,6 +12,7 @@ impl ChartCore {
     pub(super) fn draw_markers<T: HasTime>(
         &self,
         cr: &Context,
+        series: usize,
         markers: &[Marker],
         data: &[T],
         scale: SeriesScale,
@@ -28,7 +29,7 @@ impl ChartCore {
         let min_time = start_time.min(end_time);
         let max_time = start_time.max(end_time);
 
-        for marker in markers {
+        for (index, marker) in markers.iter().enumerate() {
             let time = candle_time(marker.time);
             if time < min_time || time > max_time {
                 continue;
@@ -109,6 +110,16 @@ impl ChartCore {
                     let _ = cr.fill();
                 }
             }
+            self.push_marker_hit(
+                series,
+                index,
+                Rect {
+                    x: x - half,
+                    y: y - half,
+                    width: size,
+                    height: size,
+                },
+            );
 
             draw_marker_icon(
                 cr,
@@ -149,6 +160,16 @@ impl ChartCore {
                 if label_y + label_height > layout.plot_bottom {
                     label_y = layout.plot_bottom - label_height;
                 }
+                self.push_marker_hit(
+                    series,
+                    index,
+                    Rect {
+                        x: label_x,
+                        y: label_y,
+                        width: label_width,
+                        height: label_height,
+                    },
+                );
 
                 let bg = marker.label_background.unwrap_or(Color::new(
                     marker.color.r,
@@ -0,0 +1,205 @@
+use cairo::{Context, FontSlant, FontWeight};
+
+use super::super::drawings::{Drawing, DrawingAnchor};
+use super::super::format::format_price_with_format;
+use super::super::types::{Color, PriceScaleMode};
+use super::super::util::{candle_time, transform_price};
+use super::drawing_shapes::risk_reward_span;
+use super::drawings::bar_index;
+use super::render_drawings::{set_drawing_fill, set_drawing_stroke, stroke_segment, DrawingFrame};
+use super::render_helpers::draw_rounded_rect;
+
+const ARROW_SIZE: f64 = 5.0;
+const LABEL_PADDING: f64 = 6.0;
+const LABEL_GAP: f64 = 6.0;
+
+/// Shaded box between the anchors with arrows along its centre lines and a
+/// label with the change, colored by direction like the candles.
+pub(super) fn draw_measure(cr: &Context, frame: &DrawingFrame, drawing: &Drawing) {
+    let [a, b, ..] = drawing.anchors.as_slice() else {
+        return;
+    };
+    let (start, end) = (frame.mapper.point(a), frame.mapper.point(b));
+    let rising = b.price >= a.price;
+    let color = if rising { frame.up } else { frame.down };
+    let (left, right) = (start.0.min(end.0), start.0.max(end.0));
+    let (top, bottom) = (start.1.min(end.1), start.1.max(end.1));
+
+    if drawing.style.fill {
+        set_drawing_fill(cr, drawing, color);
+        cr.rectangle(left, top, right - left, bottom - top);
+        let _ = cr.fill();
+    }
+    let mid = ((left + right) / 2.0, (top + bottom) / 2.0);
+    cr.set_source_rgb(color.r, color.g, color.b);
+    cr.set_line_width(1.0);
+    stroke_segment(cr, ((mid.0, start.1), (mid.0, end.1)));
+    stroke_segment(cr, ((start.0, mid.1), (end.0, mid.1)));
+    draw_arrow_head(cr, (mid.0, end.1), (0.0, (end.1 - start.1).signum()));
+    draw_arrow_head(cr, (end.0, mid.1), ((end.0 - start.0).signum(), 0.0));
+
+    let lines = measure_text(frame, a, b);
+    draw_label(
+        cr,
+        color,
+        &lines,
+        mid.0,
+        if rising { top } else { bottom },
+        rising,
+    );
+}
+
+/// Target zone shaded like up candles and stop zone like down candles,
+/// from the entry to the later of the other two anchors, labelled with the
+/// distance to each and the reward-to-risk multiple.
+pub(super) fn draw_risk_reward(cr: &Context, frame: &DrawingFrame, drawing: &Drawing) {
+    let [entry, target, stop, ..] = drawing.anchors.as_slice() else {
+        return;
+    };
+    let [entry_point, target_point, stop_point] =
+        [entry, target, stop].map(|anchor| frame.mapper.point(anchor));
+    let (left, right) = risk_reward_span(entry_point, target_point, stop_point);
+    let entry_y = entry_point.1;
+
+    if drawing.style.fill {
+        for (edge, color) in [(target_point.1, frame.up), (stop_point.1, frame.down)] {
+            set_drawing_fill(cr, drawing, color);
+            cr.rectangle(
+                left,
+                entry_y.min(edge),
+                right - left,
+                (edge - entry_y).abs(),
+            );
+            let _ = cr.fill();
+        }
+    }
+    set_drawing_stroke(cr, drawing, drawing.style.color);
+    stroke_segment(cr, ((left, entry_y), (right, entry_y)));
+    cr.set_dash(&[], 0.0);
+
+    let level_text = |name: &str, anchor: &DrawingAnchor| {
+        let price =
+            format_price_with_format(anchor.price, &frame.format, 0, PriceScaleMode::Normal);
+        let percent = if entry.price.abs() > f64::EPSILON {
+            (anchor.price / entry.price - 1.0) * 100.0
+        } else {
+            0.0
+        };
+        format!("{name} {price} ({percent:+.2}%)")
+    };
+    let risk = (entry.price - stop.price).abs();
+    let ratio = if risk > f64::EPSILON {
+        format!("{:.2}", (target.price - entry.price).abs() / risk)
+    } else {
+        "-".to_string()
+    };
+    let mid = (left + right) / 2.0;
+    draw_label(
+        cr,
+        frame.up,
+        &[level_text("Target", target), format!("Risk/Reward {ratio}")],
+        mid,
+        target_point.1,
+        target_point.1 <= entry_y,
+    );
+    draw_label(
+        cr,
+        frame.down,
+        &[level_text("Stop", stop)],
+        mid,
+        stop_point.1,
+        stop_point.1 < entry_y,
+    );
+}
+
+fn draw_arrow_head(cr: &Context, tip: (f64, f64), (ux, uy): (f64, f64)) {
+    if ux == 0.0 && uy == 0.0 {
+        return;
+    }
+    let base = (tip.0 - ux * ARROW_SIZE, tip.1 - uy * ARROW_SIZE);
+    cr.move_to(tip.0, tip.1);
+    cr.line_to(base.0 - uy * ARROW_SIZE, base.1 + ux * ARROW_SIZE);
+    cr.line_to(base.0 + uy * ARROW_SIZE, base.1 - ux * ARROW_SIZE);
+    cr.close_path();
+    let _ = cr.fill();
+}
+
+/// Label centred on `x`, just above `edge` when `above` and below it
+/// otherwise.
+fn draw_label(cr: &Context, color: Color, lines: &[String], x: f64, edge: f64, above: bool) {
+    cr.select_font_face("Sans", FontSlant::Normal, FontWeight::Normal);
+    cr.set_font_size(11.0);
+    let widths: Vec<f64> = lines
+        .iter()
+        .map(|line| {
+            cr.text_extents(line)
+                .map(|extents| extents.x_advance())
+                .unwrap_or(0.0)
+        })
+        .collect();
+    let line_height = 14.0;
+    let width = widths.iter().cloned().fold(0.0, f64::max) + LABEL_PADDING * 2.0;
+    let height = line_height * lines.len() as f64 + LABEL_PADDING * 2.0;
+    let top = if above {
+        edge - LABEL_GAP - height
+    } else {
+        edge + LABEL_GAP
+    };
+    let left = x - width / 2.0;
+    cr.set_source_rgb(color.r, color.g, color.b);
+    draw_rounded_rect(cr, left, top, width, height, 4.0);
+    let _ = cr.fill();
+    cr.set_source_rgb(1.0, 1.0, 1.0);
+    for (idx, (line, line_width)) in lines.iter().zip(&widths).enumerate() {
+        cr.move_to(
+            x - line_width / 2.0,
+            top + LABEL_PADDING + line_height * idx as f64 + 11.0,
+        );
+        let _ = cr.show_text(line);
+    }
+}
+
+/// Price change with percent change, then bars and elapsed time. On
+/// percentage and indexed scales the change is given in the scale's units,
+/// as read off the axis.
+fn measure_text(frame: &DrawingFrame, a: &DrawingAnchor, b: &DrawingAnchor) -> Vec<String> {
+    let scale = frame.mapper.scale;
+    let change = match scale.mode {
+        PriceScaleMode::Percentage | PriceScaleMode::IndexedTo100 => {
+            transform_price(b.price, scale.mode, scale.base)
+                - transform_price(a.price, scale.mode, scale.base)
+        }
+        PriceScaleMode::Normal | PriceScaleMode::Logarithmic => b.price - a.price,
+    };
+    let sign = if change < 0.0 { "-" } else { "+" };
+    let change = format_price_with_format(change.abs(), &frame.format, 0, scale.mode);
+    let percent = if a.price.abs() > f64::EPSILON {
+        (b.price / a.price - 1.0) * 100.0
+    } else {
+        0.0
+    };
+
+    let (from, to) = (candle_time(a.time), candle_time(b.time));
+    let bars = match (bar_index(frame.candles, from), bar_index(frame.candles, to)) {
+        (Some(from), Some(to)) => to as i64 - from as i64,
+        _ => 0,
+    };
+    vec![
+        format!("{sign}{change} ({percent:+.2}%)"),
+        format!("{bars} bars, {}", format_duration((to - from).abs())),
+    ]
+}
+
+fn format_duration(seconds: f64) -> String {
+    let total = seconds.round() as i64;
+    let (days, hours, minutes) = (total / 86_400, total % 86_400 / 3_600, total % 3_600 / 60);
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else if minutes > 0 {
+        format!("{minutes}m")
+    } else {
+        format!("{total}s")
+    }
+}
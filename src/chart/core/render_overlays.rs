@@ -4,10 +4,12 @@ use super::super::data::SeriesScale;
 use super::super::format::format_price_with_format;
 use super::super::layout::ChartLayout;
 use super::super::types::{
-    Color, PanelControlAction, PanelControlHit, PanelId, PanelRole, PriceScale, PriceScaleMode,
-    Rect,
+    Color, PanelControlAction, PanelControlHit, PanelId, PanelRole, PriceFormat, PriceLineOptions,
+    PriceScale, PriceScaleMode, Rect,
+};
+use super::super::util::{
+    apply_line_style, candle_time, map_price_to_y_scaled, map_time_to_x, transform_price,
 };
-use super::super::util::{apply_line_style, map_price_to_y_scaled, transform_price};
 use super::render_helpers::{build_ticks_for_scale, draw_rounded_rect, series_last_value};
 use super::ChartCore;
 use crate::icons::{draw_svg_icon, IconName};
@@ -55,6 +57,8 @@ impl ChartCore {
         layout: &ChartLayout,
         left_scale: Option<SeriesScale>,
         right_scale: Option<SeriesScale>,
+        start_time: f64,
+        end_time: f64,
     ) {
         let left_ticks = left_scale.map(|scale| {
             build_ticks_for_scale(
@@ -73,7 +77,11 @@ impl ChartCore {
             )
         });
 
-        for series in &self.series {
+        for (series_index, series) in self.series.iter().enumerate() {
+            // Labels and price lines follow the main panel's price scales.
+            if !series.options.visible || series.panel_id != self.main_panel_id() {
+                continue;
+            }
             let scale = match series.scale {
                 PriceScale::Left => left_scale,
                 PriceScale::Right => right_scale,
@@ -83,18 +91,15 @@ impl ChartCore {
                 None => continue,
             };
 
-            if !series.price_lines.is_empty() {
+            if !series.price_lines.is_empty() || !series.price_segments.is_empty() {
                 let ticks = match series.scale {
                     PriceScale::Left => left_ticks.as_ref(),
                     PriceScale::Right => right_ticks.as_ref(),
                 };
                 let precision = ticks.map(|ticks| ticks.precision).unwrap_or(2);
-
-                for price_line in &series.price_lines {
-                    let options = &price_line.options;
-                    let color = options.color;
-                    let y = map_price_to_y_scaled(
-                        options.price,
+                let level_y = |price: f64| {
+                    map_price_to_y_scaled(
+                        price,
                         scale.min,
                         scale.max,
                         layout.plot_top,
@@ -103,101 +108,65 @@ impl ChartCore {
                         scale.invert,
                         scale.mode,
                         scale.base,
-                    );
+                    )
+                };
 
+                for price_line in &series.price_lines {
+                    let options = &price_line.options;
+                    let y = level_y(options.price);
                     if options.line_visible {
-                        cr.set_source_rgba(
-                            color.r,
-                            color.g,
-                            color.b,
-                            options.line_opacity.clamp(0.0, 1.0),
-                        );
-                        cr.set_line_width(options.line_width.max(0.5));
-                        apply_line_style(cr, options.line_style, options.line_width);
-                        cr.move_to(layout.plot_left, y);
-                        cr.line_to(layout.plot_right, y);
-                        let _ = cr.stroke();
-                        cr.set_dash(&[], 0.0);
+                        stroke_price_level(cr, options, layout.plot_left, layout.plot_right, y);
                     }
-
                     if options.axis_label_visible {
-                        let label_value = if matches!(
-                            scale.mode,
-                            PriceScaleMode::Percentage | PriceScaleMode::IndexedTo100
-                        ) {
-                            transform_price(options.price, scale.mode, scale.base)
-                        } else {
-                            options.price
-                        };
-                        let price_label = format_price_with_format(
-                            label_value,
+                        let text = price_level_text(
+                            options,
                             &series.options.price_format,
+                            scale,
                             precision,
-                            scale.mode,
                         );
-                        let text = match &options.title {
-                            Some(title) if !title.is_empty() => format!("{title} {price_label}"),
-                            _ => price_label,
-                        };
-                        let extents = match cr.text_extents(&text) {
-                            Ok(extents) => extents,
-                            Err(_) => continue,
-                        };
-                        let padding = options.axis_label_padding.max(2.0);
-                        let box_width = extents.width() + padding * 2.0;
-                        let box_height = extents.height() + padding * 1.5;
-                        let mut box_y = y - box_height / 2.0;
-                        if box_y < layout.plot_top {
-                            box_y = layout.plot_top;
-                        }
-                        if box_y + box_height > layout.main_bottom {
-                            box_y = layout.main_bottom - box_height;
-                        }
-                        let box_x = match series.scale {
-                            PriceScale::Left => layout.axis_left + 4.0,
-                            PriceScale::Right => layout.axis_right - box_width - 4.0,
-                        };
-
-                        let bg = options.axis_label_color.unwrap_or(color);
-                        let text_color = options
-                            .axis_label_text_color
-                            .unwrap_or(Color::new(0.95, 0.96, 0.98));
-                        cr.set_source_rgba(
-                            bg.r,
-                            bg.g,
-                            bg.b,
-                            options.axis_label_background_alpha.clamp(0.0, 1.0),
-                        );
-                        draw_rounded_rect(
-                            cr,
-                            box_x,
-                            box_y,
-                            box_width,
-                            box_height,
-                            options.axis_label_radius,
-                        );
-                        let _ = cr.fill();
-
-                        if let Some(border) = options.axis_label_border_color {
-                            let width = options.axis_label_border_width.max(0.0);
-                            if width > 0.0 {
-                                cr.set_line_width(width);
-                                cr.set_source_rgb(border.r, border.g, border.b);
-                                draw_rounded_rect(
-                                    cr,
-                                    box_x,
-                                    box_y,
-                                    box_width,
-                                    box_height,
-                                    options.axis_label_radius,
-                                );
-                                let _ = cr.stroke();
-                            }
+                        let label =
+                            draw_price_level_label(cr, layout, options, &text, y, |box_width| {
+                                match series.scale {
+                                    PriceScale::Left => layout.axis_left + 4.0,
+                                    PriceScale::Right => layout.axis_right - box_width - 4.0,
+                                }
+                            });
+                        if let Some(rect) = label {
+                            self.push_price_line_label(series_index, price_line.id, rect);
                         }
+                    }
+                }
 
-                        cr.set_source_rgb(text_color.r, text_color.g, text_color.b);
-                        cr.move_to(box_x + padding, box_y + box_height - padding * 0.5);
-                        let _ = cr.show_text(&text);
+                for segment in &series.price_segments {
+                    let segment_x = |time| {
+                        map_time_to_x(
+                            candle_time(time),
+                            start_time,
+                            end_time,
+                            layout.plot_left,
+                            layout.plot_width,
+                        )
+                    };
+                    let left = segment_x(segment.start).max(layout.plot_left);
+                    let right = segment_x(segment.end).min(layout.plot_right);
+                    if right <= left {
+                        continue;
+                    }
+                    let options = &segment.options;
+                    let y = level_y(options.price);
+                    if options.line_visible {
+                        stroke_price_level(cr, options, left, right, y);
+                    }
+                    if options.axis_label_visible {
+                        let text = price_level_text(
+                            options,
+                            &series.options.price_format,
+                            scale,
+                            precision,
+                        );
+                        draw_price_level_label(cr, layout, options, &text, y, |box_width| {
+                            (right - box_width - 4.0).max(left)
+                        });
                     }
                 }
             }
@@ -407,3 +376,114 @@ impl ChartCore {
         self.set_panel_controls(hits);
     }
 }
+
+fn stroke_price_level(cr: &Context, options: &PriceLineOptions, left: f64, right: f64, y: f64) {
+    let color = options.color;
+    cr.set_source_rgba(
+        color.r,
+        color.g,
+        color.b,
+        options.line_opacity.clamp(0.0, 1.0),
+    );
+    cr.set_line_width(options.line_width.max(0.5));
+    apply_line_style(cr, options.line_style, options.line_width);
+    cr.move_to(left, y);
+    cr.line_to(right, y);
+    let _ = cr.stroke();
+    cr.set_dash(&[], 0.0);
+}
+
+pub(super) fn price_level_text(
+    options: &PriceLineOptions,
+    price_format: &PriceFormat,
+    scale: SeriesScale,
+    precision: usize,
+) -> String {
+    let label_value = if matches!(
+        scale.mode,
+        PriceScaleMode::Percentage | PriceScaleMode::IndexedTo100
+    ) {
+        transform_price(options.price, scale.mode, scale.base)
+    } else {
+        options.price
+    };
+    let price_label = format_price_with_format(label_value, price_format, precision, scale.mode);
+    match &options.title {
+        Some(title) if !title.is_empty() => format!("{title} {price_label}"),
+        _ => price_label,
+    }
+}
+
+/// Draws the boxed label of a price line or segment; `box_x` places the box
+/// horizontally given its width. Returns the box drawn.
+pub(super) fn draw_price_level_label(
+    cr: &Context,
+    layout: &ChartLayout,
+    options: &PriceLineOptions,
+    text: &str,
+    y: f64,
+    box_x: impl FnOnce(f64) -> f64,
+) -> Option<Rect> {
+    let extents = match cr.text_extents(text) {
+        Ok(extents) => extents,
+        Err(_) => return None,
+    };
+    let padding = options.axis_label_padding.max(2.0);
+    let box_width = extents.width() + padding * 2.0;
+    let box_height = extents.height() + padding * 1.5;
+    let mut box_y = y - box_height / 2.0;
+    if box_y < layout.plot_top {
+        box_y = layout.plot_top;
+    }
+    if box_y + box_height > layout.main_bottom {
+        box_y = layout.main_bottom - box_height;
+    }
+    let box_x = box_x(box_width);
+
+    let bg = options.axis_label_color.unwrap_or(options.color);
+    let text_color = options
+        .axis_label_text_color
+        .unwrap_or(Color::new(0.95, 0.96, 0.98));
+    cr.set_source_rgba(
+        bg.r,
+        bg.g,
+        bg.b,
+        options.axis_label_background_alpha.clamp(0.0, 1.0),
+    );
+    draw_rounded_rect(
+        cr,
+        box_x,
+        box_y,
+        box_width,
+        box_height,
+        options.axis_label_radius,
+    );
+    let _ = cr.fill();
+
+    if let Some(border) = options.axis_label_border_color {
+        let width = options.axis_label_border_width.max(0.0);
+        if width > 0.0 {
+            cr.set_line_width(width);
+            cr.set_source_rgb(border.r, border.g, border.b);
+            draw_rounded_rect(
+                cr,
+                box_x,
+                box_y,
+                box_width,
+                box_height,
+                options.axis_label_radius,
+            );
+            let _ = cr.stroke();
+        }
+    }
+
+    cr.set_source_rgb(text_color.r, text_color.g, text_color.b);
+    cr.move_to(box_x + padding, box_y + box_height - padding * 0.5);
+    let _ = cr.show_text(text);
+    Some(Rect {
+        x: box_x,
+        y: box_y,
+        width: box_width,
+        height: box_height,
+    })
+}
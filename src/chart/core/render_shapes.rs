@@ -0,0 +1,209 @@
+use cairo::{Context, FontSlant, FontWeight};
+
+use super::super::drawings::Drawing;
+use super::drawing_shapes::{
+    neckline, parallel_channel, pitchfork, regression_channel, shape_outline,
+};
+use super::drawings::anchor_path;
+use super::render_drawings::{
+    far_point, set_drawing_fill, set_drawing_stroke, stroke_segment, DrawingFrame,
+};
+
+type Point = (f64, f64);
+
+const XABCD_LABELS: [&str; 5] = ["X", "A", "B", "C", "D"];
+const HEAD_AND_SHOULDERS_LABELS: [&str; 7] =
+    ["", "Left Shoulder", "", "Head", "", "Right Shoulder", ""];
+
+fn fill_polygon(cr: &Context, drawing: &Drawing, points: &[Point]) {
+    let [first, rest @ ..] = points else {
+        return;
+    };
+    if !drawing.style.fill || rest.len() < 2 {
+        return;
+    }
+    set_drawing_fill(cr, drawing, drawing.style.color);
+    cr.move_to(first.0, first.1);
+    for point in rest {
+        cr.line_to(point.0, point.1);
+    }
+    cr.close_path();
+    let _ = cr.fill();
+}
+
+fn stroke_path(cr: &Context, points: &[Point], closed: bool) {
+    let [first, rest @ ..] = points else {
+        return;
+    };
+    cr.move_to(first.0, first.1);
+    for point in rest {
+        cr.line_to(point.0, point.1);
+    }
+    if closed {
+        cr.close_path();
+    }
+    let _ = cr.stroke();
+}
+
+fn anchor_points(frame: &DrawingFrame, drawing: &Drawing) -> Vec<Point> {
+    drawing
+        .anchors
+        .iter()
+        .map(|anchor| frame.mapper.point(anchor))
+        .collect()
+}
+
+/// Labels each anchor of a pattern, above the point when it is a peak and
+/// below it when it is a trough.
+fn draw_point_labels(cr: &Context, drawing: &Drawing, points: &[Point], labels: &[&str]) {
+    let color = drawing.style.color;
+    cr.set_source_rgb(color.r, color.g, color.b);
+    cr.select_font_face("Sans", FontSlant::Normal, FontWeight::Bold);
+    cr.set_font_size(11.0);
+    for (idx, (point, label)) in points.iter().zip(labels).enumerate() {
+        if label.is_empty() {
+            continue;
+        }
+        let neighbours: Vec<f64> = [idx.checked_sub(1), Some(idx + 1)]
+            .into_iter()
+            .flatten()
+            .filter_map(|neighbour| points.get(neighbour))
+            .map(|neighbour| neighbour.1)
+            .collect();
+        let peak = neighbours.is_empty()
+            || point.1 <= neighbours.iter().sum::<f64>() / neighbours.len() as f64;
+        let Ok(extents) = cr.text_extents(label) else {
+            continue;
+        };
+        let y = if peak {
+            point.1 - 8.0
+        } else {
+            point.1 + extents.height() + 8.0
+        };
+        cr.move_to(point.0 - extents.width() / 2.0, y);
+        let _ = cr.show_text(label);
+    }
+}
+
+pub(super) fn draw_parallel_channel(cr: &Context, frame: &DrawingFrame, drawing: &Drawing) {
+    let Some([main, parallel, middle]) = parallel_channel(&frame.mapper, frame.rect, drawing)
+    else {
+        return;
+    };
+    fill_polygon(cr, drawing, &[main.0, main.1, parallel.1, parallel.0]);
+    set_drawing_stroke(cr, drawing, drawing.style.color);
+    stroke_segment(cr, main);
+    stroke_segment(cr, parallel);
+    cr.set_dash(&[4.0, 4.0], 0.0);
+    stroke_segment(cr, middle);
+}
+
+pub(super) fn draw_regression_channel(cr: &Context, frame: &DrawingFrame, drawing: &Drawing) {
+    let Some(channel) = regression_channel(&frame.mapper, frame.rect, drawing, frame.candles)
+    else {
+        return;
+    };
+    let widest = channel.bands.iter().max_by(|a, b| {
+        drawing.levels[a.0]
+            .value
+            .abs()
+            .total_cmp(&drawing.levels[b.0].value.abs())
+    });
+    if let Some((_, upper, lower)) = widest {
+        fill_polygon(cr, drawing, &[upper.0, upper.1, lower.1, lower.0]);
+    }
+    set_drawing_stroke(cr, drawing, drawing.style.color);
+    stroke_segment(cr, channel.center);
+    cr.set_dash(&[4.0, 4.0], 0.0);
+    for (_, upper, lower) in &channel.bands {
+        stroke_segment(cr, *upper);
+        stroke_segment(cr, *lower);
+    }
+}
+
+pub(super) fn draw_pitchfork(cr: &Context, frame: &DrawingFrame, drawing: &Drawing) {
+    let Some([median, upper, lower, base]) = pitchfork(&frame.mapper, frame.rect, drawing) else {
+        return;
+    };
+    fill_polygon(
+        cr,
+        drawing,
+        &[upper.0, far_point(upper), far_point(lower), lower.0],
+    );
+    set_drawing_stroke(cr, drawing, drawing.style.color);
+    for segment in [median, upper, lower, base] {
+        stroke_segment(cr, segment);
+    }
+}
+
+/// Rectangles, ellipses and triangles.
+pub(super) fn draw_shape(cr: &Context, frame: &DrawingFrame, drawing: &Drawing) {
+    let outline = shape_outline(&frame.mapper, drawing);
+    fill_polygon(cr, drawing, &outline);
+    set_drawing_stroke(cr, drawing, drawing.style.color);
+    stroke_path(cr, &outline, true);
+}
+
+/// XABCD legs with the XAB and BCD triangles shaded, and the retracement
+/// ratio of each leg on the dashed line spanning it.
+pub(super) fn draw_xabcd(cr: &Context, frame: &DrawingFrame, drawing: &Drawing) {
+    let points = anchor_points(frame, drawing);
+    let &[x, a, b, c, d] = points.as_slice() else {
+        return;
+    };
+    fill_polygon(cr, drawing, &[x, a, b]);
+    fill_polygon(cr, drawing, &[b, c, d]);
+    set_drawing_stroke(cr, drawing, drawing.style.color);
+    stroke_path(cr, &points, false);
+
+    let prices: Vec<f64> = drawing.anchors.iter().map(|anchor| anchor.price).collect();
+    let leg = |from: usize, to: usize| (prices[to] - prices[from]).abs();
+    let ratios = [
+        (0, 2, leg(1, 2) / leg(0, 1)),
+        (1, 3, leg(2, 3) / leg(1, 2)),
+        (2, 4, leg(3, 4) / leg(2, 3)),
+        (0, 4, leg(1, 4) / leg(0, 1)),
+    ];
+    cr.select_font_face("Sans", FontSlant::Normal, FontWeight::Normal);
+    cr.set_font_size(10.0);
+    for (from, to, ratio) in ratios {
+        if !ratio.is_finite() {
+            continue;
+        }
+        let (start, end) = (points[from], points[to]);
+        cr.set_line_width(1.0);
+        cr.set_dash(&[4.0, 4.0], 0.0);
+        stroke_segment(cr, (start, end));
+        cr.set_dash(&[], 0.0);
+        let text = format!("{ratio:.3}");
+        if let Ok(extents) = cr.text_extents(&text) {
+            cr.move_to(
+                (start.0 + end.0 - extents.width()) / 2.0,
+                (start.1 + end.1) / 2.0 - 3.0,
+            );
+            let _ = cr.show_text(&text);
+        }
+    }
+    draw_point_labels(cr, drawing, &points, &XABCD_LABELS);
+}
+
+/// Head and shoulders outline with its three humps shaded, the neckline
+/// and the shoulders and head labelled.
+pub(super) fn draw_head_and_shoulders(cr: &Context, frame: &DrawingFrame, drawing: &Drawing) {
+    let points = anchor_points(frame, drawing);
+    if points.len() < HEAD_AND_SHOULDERS_LABELS.len() {
+        return;
+    }
+    for hump in [&points[0..3], &points[2..5], &points[4..7]] {
+        fill_polygon(cr, drawing, hump);
+    }
+    set_drawing_stroke(cr, drawing, drawing.style.color);
+    for segment in anchor_path(&frame.mapper, drawing) {
+        stroke_segment(cr, segment);
+    }
+    if let Some(segment) = neckline(&frame.mapper, drawing) {
+        stroke_segment(cr, segment);
+    }
+    cr.set_dash(&[], 0.0);
+    draw_point_labels(cr, drawing, &points, &HEAD_AND_SHOULDERS_LABELS);
+}
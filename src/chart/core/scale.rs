@@ -1,8 +1,8 @@
-use super::super::data::{SeriesData, SeriesScale};
+use super::super::data::{Series, SeriesData, SeriesScale};
 use super::super::layout::ChartLayout;
 use super::super::options::PriceScaleOptions;
 use super::super::scales::{
-    data_range_candles, data_range_line, merge_range, update_price_scale_state,
+    data_range_candles, data_range_cloud, data_range_line, merge_range, update_price_scale_state,
 };
 use super::super::types::{PriceFormat, PriceScale, PriceScaleMode};
 use super::super::util::{
@@ -17,6 +17,7 @@ impl ChartCore {
             SeriesData::Candlestick { data } => !data.is_empty(),
             SeriesData::Line { data } => !data.is_empty(),
             SeriesData::Histogram { data } => !data.is_empty(),
+            SeriesData::Cloud { data } => !data.is_empty(),
         })
     }
 
@@ -33,7 +34,16 @@ impl ChartCore {
             PriceScale::Left => &mut self.left_scale,
             PriceScale::Right => &mut self.right_scale,
         };
-        update_price_scale_state(state, min, max, options.auto_scale, options.mode, base);
+        update_price_scale_state(
+            state,
+            min,
+            max,
+            options.auto_scale,
+            options.mode,
+            base,
+            self.options.animation,
+            self.data_revision,
+        );
         Some(SeriesScale {
             min: state.view_min,
             max: state.view_max,
@@ -50,14 +60,34 @@ impl ChartCore {
                 return None;
             }
         }
-        let panel = self.rsi_panel.as_mut()?;
-        if panel.data.is_empty() {
-            return None;
+        let mut range = None;
+        for series in &self.series {
+            if self.series_on_rsi_panel(series) {
+                merge_range(&mut range, series_data_range(series, start, end));
+            }
         }
+        let panel = self.rsi_panel.as_mut()?;
         let base = 1.0;
-        let range = data_range_line(&panel.data, start, end)?;
-        let min = 0.0_f64.min(range.0);
-        let max = 100.0_f64.max(range.1);
+        merge_range(&mut range, data_range_line(&panel.data, start, end));
+        for group in &panel.groups {
+            for (data, _) in &group.lines {
+                merge_range(&mut range, data_range_line(data, start, end));
+            }
+            for (data, _) in &group.histograms {
+                merge_range(
+                    &mut range,
+                    histogram_range(&visible_histogram_points(data, start, end)),
+                );
+            }
+        }
+        let range = range?;
+        // The RSI line keeps its fixed 0-100 frame; panels holding only other
+        // plots scale to their own data.
+        let (min, max) = if panel.data.is_empty() {
+            range
+        } else {
+            (0.0_f64.min(range.0), 100.0_f64.max(range.1))
+        };
         update_price_scale_state(
             &mut panel.scale,
             min,
@@ -65,6 +95,8 @@ impl ChartCore {
             panel.options.auto_scale,
             panel.options.mode,
             base,
+            self.options.animation,
+            self.data_revision,
         );
         Some(SeriesScale {
             min: panel.scale.view_min,
@@ -76,6 +108,31 @@ impl ChartCore {
         })
     }
 
+    /// Scale and layout `series` is drawn with: its side's scale on the main
+    /// panel, or the indicator panel's scale and area. `None` when the series
+    /// is hidden or its scale has no range.
+    pub(super) fn series_area<'a>(
+        &self,
+        series: &Series,
+        left_scale: Option<SeriesScale>,
+        right_scale: Option<SeriesScale>,
+        rsi_scale: Option<SeriesScale>,
+        layout: &'a ChartLayout,
+        rsi_layout: &'a ChartLayout,
+    ) -> Option<(SeriesScale, &'a ChartLayout)> {
+        if self.series_on_rsi_panel(series) {
+            return rsi_scale.map(|scale| (scale, rsi_layout));
+        }
+        if !self.series_on_main_panel(series) {
+            return None;
+        }
+        let scale = match series.scale {
+            PriceScale::Left => left_scale,
+            PriceScale::Right => right_scale,
+        };
+        scale.map(|scale| (scale, layout))
+    }
+
     pub(super) fn data_range_for_side(
         &self,
         side: PriceScale,
@@ -85,27 +142,14 @@ impl ChartCore {
         let mut range: Option<(f64, f64)> = None;
 
         for series in &self.series {
-            if !self.panel_content_visible(series.panel_id) {
+            if !self.series_on_main_panel(series) {
                 continue;
             }
             if series.scale != side {
                 continue;
             }
 
-            match &series.data {
-                SeriesData::Candlestick { data } => {
-                    merge_range(&mut range, data_range_candles(data, start, end));
-                }
-                SeriesData::Line { data } => {
-                    merge_range(&mut range, data_range_line(data, start, end));
-                }
-                SeriesData::Histogram { data } => {
-                    merge_range(
-                        &mut range,
-                        histogram_range(&visible_histogram_points(data, start, end)),
-                    );
-                }
-            }
+            merge_range(&mut range, series_data_range(series, start, end));
 
             if series.options.markers_options.auto_scale {
                 for marker in &series.markers {
@@ -140,7 +184,7 @@ impl ChartCore {
         let mut best_value: Option<f64> = None;
 
         for series in &self.series {
-            if !self.panel_content_visible(series.panel_id) {
+            if !self.series_on_main_panel(series) {
                 continue;
             }
             if series.scale != side {
@@ -174,6 +218,7 @@ impl ChartCore {
                         }
                     }
                 }
+                SeriesData::Cloud { .. } => {}
             }
         }
 
@@ -182,7 +227,7 @@ impl ChartCore {
 
     pub(super) fn price_format_for_side(&self, side: PriceScale) -> PriceFormat {
         for series in &self.series {
-            if !self.panel_content_visible(series.panel_id) {
+            if !self.series_on_main_panel(series) {
                 continue;
             }
             if series.scale == side {
@@ -365,3 +410,15 @@ impl ChartCore {
         }
     }
 }
+
+/// Value range of the visible part of `series`.
+fn series_data_range(series: &Series, start: f64, end: f64) -> Option<(f64, f64)> {
+    match &series.data {
+        SeriesData::Candlestick { data } => data_range_candles(data, start, end),
+        SeriesData::Line { data } => data_range_line(data, start, end),
+        SeriesData::Histogram { data } => {
+            histogram_range(&visible_histogram_points(data, start, end))
+        }
+        SeriesData::Cloud { data } => data_range_cloud(data, start, end),
+    }
+}
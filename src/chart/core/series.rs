@@ -1,10 +1,12 @@
 use super::super::data::{
-    HasTime, IndicatorPanel, Panel, PanelSeries, PriceLine, PriceScaleState, Series, SeriesData,
-    SeriesKind, SeriesOptions,
+    HasTime, IndicatorPanel, Panel, PanelPlotGroup, PanelSeries, PriceLine, PriceScaleState,
+    Series, SeriesData, SeriesKind, SeriesOptions, TradingLines,
 };
+use super::super::format::format_price_with_format;
 use super::super::types::{
-    Candle, Color, HistogramPoint, LinePoint, LineStyle, Marker, PanelId, PanelRole, PriceFormat,
-    PriceLineOptions, PriceScale, SeriesMarkersOptions, TimeScaleId,
+    Candle, CloudPoint, Color, HistogramPoint, LinePoint, LineStyle, Marker, PanelId, PanelRole,
+    PriceFormat, PriceLineOptions, PriceScale, PriceScaleMode, PriceSegment, SeriesMarkersOptions,
+    TimeScaleId,
 };
 use super::ChartCore;
 
@@ -20,6 +22,8 @@ impl ChartCore {
             options: SeriesOptions::default(),
             markers: Vec::new(),
             price_lines: Vec::new(),
+            price_segments: Vec::new(),
+            trading: TradingLines::default(),
             next_price_line_id: 0,
         });
         self.attach_series_to_panel(panel_id, id, SeriesKind::Candlestick);
@@ -40,6 +44,8 @@ impl ChartCore {
             options: SeriesOptions::default(),
             markers: Vec::new(),
             price_lines: Vec::new(),
+            price_segments: Vec::new(),
+            trading: TradingLines::default(),
             next_price_line_id: 0,
         });
         self.attach_series_to_panel(panel_id, id, SeriesKind::Line);
@@ -57,12 +63,37 @@ impl ChartCore {
             options: SeriesOptions::default(),
             markers: Vec::new(),
             price_lines: Vec::new(),
+            price_segments: Vec::new(),
+            trading: TradingLines::default(),
             next_price_line_id: 0,
         });
         self.attach_series_to_panel(panel_id, id, SeriesKind::Histogram);
         id
     }
 
+    pub(crate) fn add_cloud_series(&mut self) -> usize {
+        let id = self.series.len();
+        let panel_id = self.main_panel_id();
+        self.series.push(Series {
+            kind: SeriesKind::Cloud,
+            scale: PriceScale::Right,
+            panel_id,
+            data: SeriesData::Cloud { data: Vec::new() },
+            options: SeriesOptions {
+                show_price_line: false,
+                show_last_value: false,
+                ..SeriesOptions::default()
+            },
+            markers: Vec::new(),
+            price_lines: Vec::new(),
+            price_segments: Vec::new(),
+            trading: TradingLines::default(),
+            next_price_line_id: 0,
+        });
+        self.attach_series_to_panel(panel_id, id, SeriesKind::Cloud);
+        id
+    }
+
     pub(crate) fn set_rsi_panel(&mut self, title: String, data: Vec<LinePoint>) {
         let options = super::super::options::PriceScaleOptions {
             visible: true,
@@ -75,6 +106,7 @@ impl ChartCore {
             scale: PriceScaleState::new(),
             options,
             color: self.style.line,
+            groups: Vec::new(),
         };
         self.rsi_panel = Some(panel);
         let parent_id = self.main_panel_id();
@@ -92,7 +124,7 @@ impl ChartCore {
     pub(crate) fn clear_rsi_panel(&mut self) {
         self.rsi_panel = None;
         if let Some(panel_id) = self.rsi_panel_id.take() {
-            self.remove_panel(panel_id);
+            self.detach_panel(panel_id);
         }
     }
 
@@ -103,6 +135,58 @@ impl ChartCore {
         }
     }
 
+    pub(crate) fn set_rsi_panel_plots(
+        &mut self,
+        key: &str,
+        lines: Vec<(Vec<LinePoint>, Color)>,
+        histograms: Vec<(Vec<HistogramPoint>, Color)>,
+    ) {
+        let Some(panel) = self.rsi_panel.as_mut() else {
+            return;
+        };
+        let existing = panel.groups.iter().position(|group| group.key == key);
+        if lines.is_empty() && histograms.is_empty() {
+            if let Some(idx) = existing {
+                panel.groups.remove(idx);
+            }
+            return;
+        }
+        let group = PanelPlotGroup {
+            key: key.to_string(),
+            lines,
+            histograms,
+        };
+        match existing {
+            Some(idx) => panel.groups[idx] = group,
+            None => panel.groups.push(group),
+        }
+    }
+
+    /// Whether the indicator panel draws anything: the RSI line, plot
+    /// groups, or a series moved there that has data.
+    pub(crate) fn rsi_panel_has_plots(&self) -> bool {
+        let has_series = self.series.iter().any(|series| {
+            Some(series.panel_id) == self.rsi_panel_id
+                && series.options.visible
+                && !series_is_empty(series)
+        });
+        self.rsi_panel
+            .as_ref()
+            .map(|panel| has_series || !panel.data.is_empty() || !panel.groups.is_empty())
+            .unwrap_or(false)
+    }
+
+    pub(crate) fn rsi_panel_has_data(&self) -> bool {
+        self.rsi_panel
+            .as_ref()
+            .map(|panel| !panel.data.is_empty())
+            .unwrap_or(false)
+    }
+
+    pub(crate) fn rsi_panel_id(&self) -> Option<PanelId> {
+        self.rsi_panel.as_ref().and(self.rsi_panel_id)
+    }
+
     pub(crate) fn has_rsi_panel(&self) -> bool {
         self.rsi_panel.is_some()
     }
@@ -139,7 +223,7 @@ impl ChartCore {
         self.rsi_panel.as_ref().map(|panel| panel.options.visible)
     }
 
-    fn main_panel_id(&self) -> PanelId {
+    pub(crate) fn main_panel_id(&self) -> PanelId {
         self.panels
             .first()
             .map(|panel| panel.id)
@@ -193,15 +277,67 @@ impl ChartCore {
     }
 
     pub(crate) fn remove_panel(&mut self, panel_id: PanelId) {
+        if panel_id == self.main_panel_id() || !self.panels.iter().any(|p| p.id == panel_id) {
+            return;
+        }
+        self.record_layout(panel_id);
+        self.detach_panel(panel_id);
+    }
+
+    /// Takes panel `panel_id` off the chart. Its series go back to the main
+    /// panel rather than away, so series ids stay valid.
+    fn detach_panel(&mut self, panel_id: PanelId) {
         let main_panel = self.main_panel_id();
         if panel_id == main_panel {
             return;
         }
+        for id in self.panel_series(panel_id) {
+            self.move_series_to_panel(id, main_panel);
+        }
         self.panels.retain(|panel| panel.id != panel_id);
         for group in &mut self.time_scales {
             group.panels.retain(|id| *id != panel_id);
         }
-        self.series.retain(|series| series.panel_id != panel_id);
+    }
+
+    /// Moves series `id` to panel `panel_id`. The series keeps its price
+    /// scale side, which applies on the main panel; on the indicator panel
+    /// it shares that panel's scale.
+    ///
+    /// # Returns
+    ///
+    /// `false` when there is no such series or panel.
+    pub(crate) fn move_series_to_panel(&mut self, id: usize, panel_id: PanelId) -> bool {
+        if !self.panels.iter().any(|panel| panel.id == panel_id) {
+            return false;
+        }
+        let Some(series) = self.series.get_mut(id) else {
+            return false;
+        };
+        let from = std::mem::replace(&mut series.panel_id, panel_id);
+        let kind = series.kind;
+        if let Some(panel) = self.panels.iter_mut().find(|panel| panel.id == from) {
+            panel.series.retain(|entry| entry.series_id != id);
+            panel.show_volume = panel
+                .series
+                .iter()
+                .any(|entry| matches!(entry.kind, SeriesKind::Histogram));
+        }
+        self.attach_series_to_panel(panel_id, id, kind);
+        true
+    }
+
+    pub(crate) fn series_panel(&self, id: usize) -> Option<PanelId> {
+        self.series.get(id).map(|series| series.panel_id)
+    }
+
+    pub(crate) fn panel_series(&self, panel_id: PanelId) -> Vec<usize> {
+        self.series
+            .iter()
+            .enumerate()
+            .filter(|(_, series)| series.panel_id == panel_id)
+            .map(|(id, _)| id)
+            .collect()
     }
 
     pub(crate) fn toggle_panel_visibility(&mut self, panel_id: PanelId) {
@@ -256,6 +392,8 @@ impl ChartCore {
                     options: SeriesOptions::default(),
                     markers: Vec::new(),
                     price_lines: Vec::new(),
+                    price_segments: Vec::new(),
+                    trading: TradingLines::default(),
                     next_price_line_id: 0,
                 });
                 self.attach_series_to_panel(panel_id, id, SeriesKind::Line);
@@ -325,6 +463,44 @@ impl ChartCore {
         }
     }
 
+    pub(crate) fn set_series_visible(&mut self, id: usize, visible: bool) {
+        if let Some(series) = self.series.get_mut(id) {
+            series.options.visible = visible;
+        }
+    }
+
+    pub(crate) fn series_visible(&self, id: usize) -> bool {
+        self.series
+            .get(id)
+            .is_some_and(|series| series.options.visible)
+    }
+
+    pub(crate) fn hidden_series(&self) -> Vec<usize> {
+        self.series
+            .iter()
+            .enumerate()
+            .filter(|(_, series)| !series.options.visible)
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    /// Whether `series` is drawn: shown itself, in a panel whose content
+    /// is visible.
+    pub(crate) fn series_shown(&self, series: &Series) -> bool {
+        series.options.visible && self.panel_content_visible(series.panel_id)
+    }
+
+    /// Whether `series` is drawn on the main panel, against the left or
+    /// right price scale.
+    pub(crate) fn series_on_main_panel(&self, series: &Series) -> bool {
+        self.series_shown(series) && series.panel_id == self.main_panel_id()
+    }
+
+    /// Whether `series` is drawn on the indicator panel, against its scale.
+    pub(crate) fn series_on_rsi_panel(&self, series: &Series) -> bool {
+        self.series_shown(series) && Some(series.panel_id) == self.rsi_panel_id()
+    }
+
     pub(crate) fn set_series_price_line(&mut self, id: usize, visible: bool) {
         if let Some(series) = self.series.get_mut(id) {
             series.options.show_price_line = visible;
@@ -367,6 +543,31 @@ impl ChartCore {
         }
     }
 
+    pub(crate) fn set_series_color(&mut self, id: usize, color: Color) {
+        if let Some(series) = self.series.get_mut(id) {
+            series.options.color = Some(color);
+        }
+    }
+
+    pub(crate) fn set_series_line_width(&mut self, id: usize, width: f64) {
+        if let Some(series) = self.series.get_mut(id) {
+            series.options.line_width = width.max(0.5);
+        }
+    }
+
+    pub(crate) fn set_series_fill_colors(&mut self, id: usize, up: Color, down: Color) {
+        if let Some(series) = self.series.get_mut(id) {
+            series.options.fill_up_color = Some(up);
+            series.options.fill_down_color = Some(down);
+        }
+    }
+
+    pub(crate) fn set_series_fill_alpha(&mut self, id: usize, alpha: f64) {
+        if let Some(series) = self.series.get_mut(id) {
+            series.options.fill_alpha = alpha.clamp(0.0, 1.0);
+        }
+    }
+
     pub(crate) fn set_series_markers(&mut self, id: usize, markers: Vec<Marker>) {
         if let Some(series) = self.series.get_mut(id) {
             series.markers = markers;
@@ -421,17 +622,37 @@ impl ChartCore {
         }
     }
 
+    pub(crate) fn price_line_options(
+        &self,
+        id: usize,
+        line_id: usize,
+    ) -> Option<&PriceLineOptions> {
+        self.series
+            .get(id)?
+            .price_lines
+            .iter()
+            .find(|line| line.id == line_id)
+            .map(|line| &line.options)
+    }
+
     pub(crate) fn remove_price_line(&mut self, id: usize, line_id: usize) {
         if let Some(series) = self.series.get_mut(id) {
             series.price_lines.retain(|line| line.id != line_id);
         }
     }
 
+    pub(crate) fn set_price_segments(&mut self, id: usize, segments: Vec<PriceSegment>) {
+        if let Some(series) = self.series.get_mut(id) {
+            series.price_segments = segments;
+        }
+    }
+
     pub(crate) fn set_candles(&mut self, id: usize, mut candles: Vec<Candle>) {
         candles.sort_by(|a, b| a.time.cmp(&b.time));
         if let Some(series) = self.series.get_mut(id) {
             series.data = SeriesData::Candlestick { data: candles };
         }
+        self.clear_zoom_history();
         self.recalculate_time_scale_after_data_update();
     }
 
@@ -451,6 +672,14 @@ impl ChartCore {
         self.recalculate_time_scale_after_data_update();
     }
 
+    pub(crate) fn set_cloud_points(&mut self, id: usize, mut points: Vec<CloudPoint>) {
+        points.sort_by(|a, b| a.time.cmp(&b.time));
+        if let Some(series) = self.series.get_mut(id) {
+            series.data = SeriesData::Cloud { data: points };
+        }
+        self.recalculate_time_scale_after_data_update();
+    }
+
     pub(crate) fn update_candle(&mut self, id: usize, candle: Candle) {
         if let Some(series) = self.series.get_mut(id) {
             if let SeriesData::Candlestick { data } = &mut series.data {
@@ -478,11 +707,29 @@ impl ChartCore {
         self.recalculate_time_scale_after_data_update();
     }
 
+    pub(crate) fn update_cloud_point(&mut self, id: usize, point: CloudPoint) {
+        if let Some(series) = self.series.get_mut(id) {
+            if let SeriesData::Cloud { data } = &mut series.data {
+                update_sorted_by_time(data, point);
+            }
+        }
+        self.recalculate_time_scale_after_data_update();
+    }
+
     pub(crate) fn set_series_price_format(&mut self, id: usize, format: PriceFormat) {
         if let Some(series) = self.series.get_mut(id) {
             series.options.price_format = format;
         }
     }
+
+    pub(crate) fn format_series_price(&self, id: usize, price: f64) -> String {
+        let format = self
+            .series
+            .get(id)
+            .map(|series| series.options.price_format.clone())
+            .unwrap_or_default();
+        format_price_with_format(price, &format, 0, PriceScaleMode::Normal)
+    }
 }
 
 fn update_sorted_by_time<T: HasTime>(data: &mut Vec<T>, item: T) {
@@ -499,3 +746,12 @@ fn update_sorted_by_time<T: HasTime>(data: &mut Vec<T>, item: T) {
         }
     }
 }
+
+fn series_is_empty(series: &Series) -> bool {
+    match &series.data {
+        SeriesData::Candlestick { data } => data.is_empty(),
+        SeriesData::Line { data } => data.is_empty(),
+        SeriesData::Histogram { data } => data.is_empty(),
+        SeriesData::Cloud { data } => data.is_empty(),
+    }
+}
@@ -1,13 +1,35 @@
+use super::super::animation::Transition;
 use super::super::options::TimeScaleOptions;
 use super::super::types::Color;
+use super::events::time_at_logical;
+use super::render_helpers::primary_candles;
 use super::ChartCore;
 
+/// A move of the visible time range in progress, advanced on each draw.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct TimeScaleAnimation {
+    transition: Transition,
+    /// Range as last placed by the animation. Any other change to the view,
+    /// such as the user panning, cancels the animation.
+    placed: (f64, f64),
+}
+
+impl TimeScaleAnimation {
+    pub(super) fn target(&self) -> (f64, f64) {
+        self.transition.target()
+    }
+}
+
 impl ChartCore {
+    /// Fits all data into view, animated when animations are on.
     pub(crate) fn fit_content(&mut self) {
+        self.time_animation = None;
+        let from = (self.time_scale.start, self.time_scale.end);
         self.time_scale.recalculate(&self.series);
         if self.last_plot_width > 0.0 {
             let bar_time = self.time_scale.bar_time();
-            let total_bars = ((self.time_scale.max - self.time_scale.min) / bar_time).max(1.0);
+            let last_time = self.time_scale.max.max(self.time_scale.projected_max);
+            let total_bars = ((last_time - self.time_scale.min) / bar_time).max(1.0);
             let spacing = (self.last_plot_width / total_bars).clamp(
                 self.time_scale.min_bar_spacing,
                 if self.time_scale.max_bar_spacing > 0.0 {
@@ -19,6 +41,7 @@ impl ChartCore {
             self.time_scale.set_bar_spacing(spacing);
         }
         self.apply_bar_spacing_with_anchor(1.0);
+        self.animate_time_range_from(from);
     }
 
     pub(crate) fn set_time_scale_right_offset(&mut self, offset: f64) {
@@ -130,11 +153,22 @@ impl ChartCore {
         self.apply_bar_spacing();
     }
 
+    /// Keeps the visible range in place, or on the latest bar, as data
+    /// changes. A running animation is measured at its target and shifted
+    /// by the same amount, so it still ends where the update would put it.
     pub(super) fn recalculate_time_scale_after_data_update(&mut self) {
+        self.data_revision = self.data_revision.wrapping_add(1);
+        let shown = (self.time_scale.start, self.time_scale.end);
+        let animation = self
+            .time_animation
+            .take()
+            .filter(|animation| animation.placed == shown);
+        if let Some(animation) = &animation {
+            (self.time_scale.start, self.time_scale.end) = animation.target();
+        }
         let prev_end = self.time_scale.end;
         let prev_range = self.time_scale.visible_range();
-        let prev_max_end = self.time_scale.max
-            + self.time_scale.effective_right_offset() * self.time_scale.bar_time();
+        let prev_max_end = self.time_scale.max_end();
         let was_at_right = (prev_end - prev_max_end).abs() <= self.time_scale.bar_time().max(1.0);
 
         self.time_scale.recalculate(&self.series);
@@ -143,8 +177,7 @@ impl ChartCore {
             return;
         }
 
-        let max_end = self.time_scale.max
-            + self.time_scale.effective_right_offset() * self.time_scale.bar_time();
+        let max_end = self.time_scale.max_end();
         let range = prev_range.max(1.0);
         let mut end = if was_at_right
             && self.options.time_scale.right_bar_stays_on_scroll
@@ -166,6 +199,14 @@ impl ChartCore {
         }
         self.time_scale.start = start;
         self.time_scale.end = end.max(start + 1.0);
+
+        if let Some(mut animation) = animation {
+            let shift = self.time_scale.end - animation.target().1;
+            animation.transition.shift(shift);
+            animation.placed = (shown.0 + shift, shown.1 + shift);
+            (self.time_scale.start, self.time_scale.end) = animation.placed;
+            self.time_animation = Some(animation);
+        }
     }
 
     pub(super) fn apply_bar_spacing(&mut self) {
@@ -182,8 +223,7 @@ impl ChartCore {
             .max(self.time_scale.min_bar_spacing);
         let visible_bars = (self.last_plot_width / bar_spacing).max(1.0);
         let range = self.time_scale.bar_time() * visible_bars;
-        let max_end = self.time_scale.max
-            + self.time_scale.effective_right_offset() * self.time_scale.bar_time();
+        let max_end = self.time_scale.max_end();
         let old_range = self.time_scale.visible_range();
         let anchor_time = self.time_scale.start + anchor.clamp(0.0, 1.0) * old_range;
         let mut start = anchor_time - anchor.clamp(0.0, 1.0) * range;
@@ -203,22 +243,148 @@ impl ChartCore {
 
     pub(super) fn zoom_time_by_factor(&mut self, factor: f64, anchor: f64) {
         let min_spacing = self.time_scale.min_bar_spacing.max(0.1);
-        let max_spacing = if self.time_scale.max_bar_spacing > 0.0 {
+        let max_spacing = self.max_bar_spacing_limit();
+        let new_spacing = (self.time_scale.bar_spacing / factor).clamp(min_spacing, max_spacing);
+        self.time_scale.set_bar_spacing(new_spacing);
+        self.apply_bar_spacing_with_anchor(anchor);
+    }
+
+    fn max_bar_spacing_limit(&self) -> f64 {
+        if self.time_scale.max_bar_spacing > 0.0 {
             self.time_scale.max_bar_spacing
         } else {
             200.0
+        }
+    }
+
+    /// Shows `from..to` as closely as the bar spacing limits and fixed edges
+    /// allow: a range needing spacing outside the limits is widened or
+    /// narrowed around its centre, then shifted inside the edges.
+    pub(crate) fn set_visible_time_range(&mut self, from: f64, to: f64) {
+        let (from, to) = (from.min(to), from.max(to));
+        let span = (to - from).max(1.0);
+        if self.last_plot_width <= 0.0 {
+            self.time_scale.start = from;
+            self.time_scale.end = from + span;
+            return;
+        }
+        let bar_time = self.time_scale.bar_time();
+        let spacing = (self.last_plot_width * bar_time / span).clamp(
+            self.time_scale.min_bar_spacing.max(0.1),
+            self.max_bar_spacing_limit(),
+        );
+        self.time_scale.set_bar_spacing(spacing);
+        let range = self.last_plot_width / self.time_scale.bar_spacing * bar_time;
+        let center = from + span / 2.0;
+        let mut start = center - range / 2.0;
+        let mut end = start + range;
+        let (min_limit, max_limit) = self.time_scale.pan_limits(range, self.time_scale.max_end());
+        if start < min_limit {
+            start = min_limit;
+            end = start + range;
+        }
+        if end > max_limit {
+            end = max_limit;
+            start = end - range;
+        }
+        self.time_scale.start = start;
+        self.time_scale.end = end.max(start + 1.0);
+    }
+
+    /// Like `set_visible_time_range`, with the range in bar indexes of the
+    /// primary candlestick series. Returns `false` when there are no candles.
+    pub(crate) fn set_visible_logical_range(&mut self, from: f64, to: f64) -> bool {
+        let Some(candles) = primary_candles(self.primary_candles, &self.series) else {
+            return false;
         };
-        let new_spacing = (self.time_scale.bar_spacing / factor).clamp(min_spacing, max_spacing);
-        self.time_scale.set_bar_spacing(new_spacing);
-        self.apply_bar_spacing_with_anchor(anchor);
+        let (Some(from), Some(to)) = (time_at_logical(candles, from), time_at_logical(candles, to))
+        else {
+            return false;
+        };
+        self.set_visible_time_range(from, to);
+        true
+    }
+
+    /// Scrolls `time` to the middle of the view, keeping the bar spacing. An
+    /// animated scroll moves a little further on each draw; see
+    /// `is_animating`.
+    pub(crate) fn scroll_to_time(&mut self, time: f64, animate: bool) {
+        self.time_animation = None;
+        let (start, end) = (self.time_scale.start, self.time_scale.end);
+        let center = (start + end) / 2.0;
+        self.time_scale.pan_by(time - center);
+        if animate {
+            self.animate_time_range_from((start, end));
+        }
+    }
+
+    /// Scrolls so the latest bar is at the right edge, keeping the right
+    /// offset. Animated when animations are on.
+    pub(crate) fn scroll_to_real_time(&mut self) {
+        self.time_animation = None;
+        let from = (self.time_scale.start, self.time_scale.end);
+        self.scroll_to_last_bar();
+        self.animate_time_range_from(from);
+    }
+
+    /// Whether the time range or an auto scaled price range is still moving.
+    pub(crate) fn is_animating(&self) -> bool {
+        self.time_animation.is_some()
+            || self.left_scale.is_animating()
+            || self.right_scale.is_animating()
+            || self
+                .rsi_panel
+                .as_ref()
+                .is_some_and(|panel| panel.scale.is_animating())
+    }
+
+    /// Puts the visible range back to `from` and animates it to where it was
+    /// just set, unless animations are off or the chart was never drawn.
+    fn animate_time_range_from(&mut self, from: (f64, f64)) {
+        if self.last_plot_width <= 0.0 {
+            return;
+        }
+        let to = (self.time_scale.start, self.time_scale.end);
+        let Some(transition) = Transition::new(from, to, self.options.animation) else {
+            return;
+        };
+        (self.time_scale.start, self.time_scale.end) = from;
+        self.time_animation = Some(TimeScaleAnimation {
+            transition,
+            placed: from,
+        });
+    }
+
+    pub(super) fn advance_time_animation(&mut self) {
+        let Some(animation) = self.time_animation.as_mut() else {
+            return;
+        };
+        let (start, end) = animation.placed;
+        if (self.time_scale.start - start).abs() > f64::EPSILON
+            || (self.time_scale.end - end).abs() > f64::EPSILON
+        {
+            self.time_animation = None;
+            return;
+        }
+        let finished = animation.transition.finished();
+        let (start, end) = if finished {
+            animation.transition.target()
+        } else {
+            animation.transition.value()
+        };
+        animation.placed = (start, end);
+        self.time_scale.start = start;
+        self.time_scale.end = end;
+        if finished {
+            self.time_animation = None;
+        }
     }
 
     pub(super) fn clamp_time_scale_right_edge(&mut self) {
         if !self.options.time_scale.right_bar_stays_on_scroll {
             return;
         }
-        let max_end = self.time_scale.max
-            + self.time_scale.effective_right_offset() * self.time_scale.bar_time();
+        let max_end = self.time_scale.max_end();
         let range = self.time_scale.visible_range();
         if self.time_scale.end > max_end {
             self.time_scale.end = max_end;
@@ -226,3 +392,54 @@ impl ChartCore {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    use time::OffsetDateTime;
+
+    use super::super::super::options::Easing;
+    use super::super::super::types::Candle;
+    use super::*;
+
+    const MINUTE: i64 = 60;
+
+    fn candle(index: i64) -> Candle {
+        Candle {
+            time: OffsetDateTime::from_unix_timestamp(index * MINUTE).unwrap(),
+            open: 10.0,
+            high: 11.0,
+            low: 9.0,
+            close: 10.5,
+        }
+    }
+
+    #[test]
+    fn data_update_keeps_the_animation_on_its_target() {
+        let mut chart = ChartCore::new();
+        chart.options.time_scale.right_bar_stays_on_scroll = true;
+        chart.options.animation.duration = Duration::from_millis(60);
+        chart.options.animation.easing = Easing::Linear;
+        chart.last_plot_width = 600.0;
+        let id = chart.add_candlestick_series();
+        chart.set_candles(id, (0..100).map(candle).collect());
+        chart.apply_bar_spacing();
+        chart.time_scale.pan_by(-50.0 * chart.time_scale.bar_time());
+
+        chart.scroll_to_real_time();
+        sleep(Duration::from_millis(20));
+        chart.advance_time_animation();
+        assert!(chart.time_animation.is_some());
+
+        chart.update_candle(id, candle(100));
+        assert!(chart.time_animation.is_some());
+
+        sleep(Duration::from_millis(80));
+        chart.advance_time_animation();
+        assert!(chart.time_animation.is_none());
+        let max_end = chart.time_scale.max_end();
+        assert!((chart.time_scale.end - max_end).abs() < 1e-6);
+    }
+}
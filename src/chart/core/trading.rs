@@ -0,0 +1,331 @@
+use cairo::{Context, FontSlant, FontWeight};
+
+use super::super::data::{SeriesData, SeriesScale};
+use super::super::events::{EventCalls, OrderCancelHandler, SubscriptionId};
+use super::super::hit_test::{BadgeHit, HitTarget};
+use super::super::layout::ChartLayout;
+use super::super::types::{
+    Color, LineStyle, OrderLineOptions, OrderSide, PositionLineOptions, PriceLineOptions,
+    PriceScale, Rect,
+};
+use super::super::util::apply_line_style;
+use super::hit_test::price_y;
+use super::render_helpers::draw_rounded_rect;
+use super::render_overlays::{draw_price_level_label, price_level_text};
+use super::ChartCore;
+
+const BADGE_PADDING: f64 = 5.0;
+const BADGE_HEIGHT: f64 = 18.0;
+const BADGE_GAP: f64 = 2.0;
+
+/// Unrealized profit or loss of `position` at `close`.
+fn position_pnl(position: &PositionLineOptions, close: f64) -> f64 {
+    (close - position.price) * position.quantity * position.point_value
+}
+
+impl ChartCore {
+    pub(crate) fn create_position_line(
+        &mut self,
+        series: usize,
+        options: PositionLineOptions,
+    ) -> usize {
+        let Some(series) = self.series.get_mut(series) else {
+            return 0;
+        };
+        let trading = &mut series.trading;
+        let id = trading.next_id;
+        trading.next_id += 1;
+        trading.positions.push((id, options));
+        id
+    }
+
+    pub(crate) fn position_line_mut(
+        &mut self,
+        series: usize,
+        line: usize,
+    ) -> Option<&mut PositionLineOptions> {
+        self.series
+            .get_mut(series)?
+            .trading
+            .positions
+            .iter_mut()
+            .find(|(id, _)| *id == line)
+            .map(|(_, options)| options)
+    }
+
+    pub(crate) fn remove_position_line(&mut self, series: usize, line: usize) {
+        if let Some(series) = self.series.get_mut(series) {
+            series.trading.positions.retain(|(id, _)| *id != line);
+        }
+    }
+
+    /// Profit or loss of a position line against its series' last close.
+    pub(crate) fn position_pnl(&self, series: usize, line: usize) -> Option<f64> {
+        let series = self.series.get(series)?;
+        let SeriesData::Candlestick { data } = &series.data else {
+            return None;
+        };
+        let close = data.last()?.close;
+        series
+            .trading
+            .positions
+            .iter()
+            .find(|(id, _)| *id == line)
+            .map(|(_, options)| position_pnl(options, close))
+    }
+
+    pub(crate) fn create_order_line(&mut self, series: usize, options: OrderLineOptions) -> usize {
+        let Some(series) = self.series.get_mut(series) else {
+            return 0;
+        };
+        let trading = &mut series.trading;
+        let id = trading.next_id;
+        trading.next_id += 1;
+        trading.orders.push((id, options));
+        id
+    }
+
+    pub(crate) fn order_line_mut(
+        &mut self,
+        series: usize,
+        line: usize,
+    ) -> Option<&mut OrderLineOptions> {
+        self.series
+            .get_mut(series)?
+            .trading
+            .orders
+            .iter_mut()
+            .find(|(id, _)| *id == line)
+            .map(|(_, options)| options)
+    }
+
+    pub(crate) fn remove_order_line(&mut self, series: usize, line: usize) {
+        if let Some(series) = self.series.get_mut(series) {
+            series.trading.orders.retain(|(id, _)| *id != line);
+        }
+    }
+
+    pub(crate) fn subscribe_order_cancel(&mut self, handler: OrderCancelHandler) -> SubscriptionId {
+        let id = self.events.next_id();
+        self.events.order_cancel.push((id, handler));
+        id
+    }
+
+    pub(crate) fn order_cancel_calls(&self, series: usize, line: usize) -> EventCalls {
+        self.events
+            .order_cancel
+            .iter()
+            .map(|(_, handler)| {
+                let handler = handler.clone();
+                Box::new(move || handler(series, line)) as Box<dyn FnOnce()>
+            })
+            .collect()
+    }
+
+    /// Position and order line under `(x, y)`, within `tolerance` pixels of
+    /// the line.
+    pub(super) fn trading_line_at(
+        &self,
+        y: f64,
+        layout: &ChartLayout,
+        scale_of: impl Fn(PriceScale) -> Option<SeriesScale>,
+        tolerance: f64,
+    ) -> Option<HitTarget> {
+        for (series_index, series) in self.series.iter().enumerate().rev() {
+            if !series.options.visible || series.panel_id != self.main_panel_id() {
+                continue;
+            }
+            let Some(scale) = scale_of(series.scale) else {
+                continue;
+            };
+            let near = |price: f64| (price_y(price, scale, layout) - y).abs() <= tolerance;
+            let trading = &series.trading;
+            if let Some((line, _)) = trading.orders.iter().rev().find(|(_, o)| near(o.price)) {
+                return Some(HitTarget::Order {
+                    series: series_index,
+                    line: *line,
+                });
+            }
+            if let Some((line, _)) = trading.positions.iter().rev().find(|(_, p)| near(p.price)) {
+                return Some(HitTarget::Position {
+                    series: series_index,
+                    line: *line,
+                });
+            }
+        }
+        None
+    }
+
+    pub(super) fn draw_trading_lines(
+        &self,
+        cr: &Context,
+        layout: &ChartLayout,
+        left_scale: Option<SeriesScale>,
+        right_scale: Option<SeriesScale>,
+    ) {
+        for (series_index, series) in self.series.iter().enumerate() {
+            let trading = &series.trading;
+            if trading.positions.is_empty() && trading.orders.is_empty() {
+                continue;
+            }
+            if !self.series_on_main_panel(series) {
+                continue;
+            }
+            let scale = match series.scale {
+                PriceScale::Left => left_scale,
+                PriceScale::Right => right_scale,
+            };
+            let Some(scale) = scale else {
+                continue;
+            };
+            let close = match &series.data {
+                SeriesData::Candlestick { data } => data.last().map(|candle| candle.close),
+                _ => None,
+            };
+            let axis_label = |price: f64, color: Color, y: f64| {
+                let options = PriceLineOptions {
+                    price,
+                    color,
+                    ..PriceLineOptions::default()
+                };
+                let text = price_level_text(&options, &series.options.price_format, scale, 2);
+                draw_price_level_label(cr, layout, &options, &text, y, |box_width| {
+                    match series.scale {
+                        PriceScale::Left => layout.axis_left + 4.0,
+                        PriceScale::Right => layout.axis_right - box_width - 4.0,
+                    }
+                });
+            };
+
+            for (line, position) in &trading.positions {
+                let y = price_y(position.price, scale, layout);
+                if !layout.in_main_plot(y) {
+                    continue;
+                }
+                stroke_level(
+                    cr,
+                    layout,
+                    y,
+                    position.color,
+                    position.line_width,
+                    position.line_style,
+                );
+                let target = HitTarget::Position {
+                    series: series_index,
+                    line: *line,
+                };
+                let side = if position.quantity < 0.0 {
+                    "SHORT"
+                } else {
+                    "LONG"
+                };
+                let size = match &position.title {
+                    Some(title) if !title.is_empty() => {
+                        format!("{title} {side} {}", position.quantity.abs())
+                    }
+                    _ => format!("{side} {}", position.quantity.abs()),
+                };
+                let mut x = layout.plot_left + 8.0;
+                x = self.draw_badge(cr, target, x, y, &size, position.color);
+                if let Some(close) = close {
+                    let pnl = position_pnl(position, close);
+                    let color = if pnl < 0.0 {
+                        self.style.down
+                    } else {
+                        self.style.up
+                    };
+                    self.draw_badge(cr, target, x, y, &format!("{pnl:+.2}"), color);
+                }
+                if position.axis_label_visible {
+                    axis_label(position.price, position.color, y);
+                }
+            }
+
+            for (line, order) in &trading.orders {
+                let y = price_y(order.price, scale, layout);
+                if !layout.in_main_plot(y) {
+                    continue;
+                }
+                let color = order.color.unwrap_or(match order.side {
+                    OrderSide::Buy => self.style.up,
+                    OrderSide::Sell => self.style.down,
+                });
+                stroke_level(cr, layout, y, color, order.line_width, order.line_style);
+                let target = HitTarget::Order {
+                    series: series_index,
+                    line: *line,
+                };
+                let side = match order.side {
+                    OrderSide::Buy => "BUY",
+                    OrderSide::Sell => "SELL",
+                };
+                let mut x = layout.plot_left + 8.0;
+                let kind = format!("{side} {}", order.order_type.badge());
+                x = self.draw_badge(cr, target, x, y, &kind, color);
+                x = self.draw_badge(cr, target, x, y, &order.quantity.to_string(), color);
+                if let Some(title) = order.title.as_ref().filter(|title| !title.is_empty()) {
+                    x = self.draw_badge(cr, target, x, y, title, color);
+                }
+                if order.cancellable {
+                    let cancel = HitTarget::OrderCancel {
+                        series: series_index,
+                        line: *line,
+                    };
+                    self.draw_badge(cr, cancel, x, y, "\u{2715}", color);
+                }
+                if order.axis_label_visible {
+                    axis_label(order.price, color, y);
+                }
+            }
+        }
+    }
+
+    /// Draws a badge with its left edge at `x`, centred on `y`, and records
+    /// it for hit-testing. Returns where the next badge starts.
+    fn draw_badge(
+        &self,
+        cr: &Context,
+        target: HitTarget,
+        x: f64,
+        y: f64,
+        text: &str,
+        color: Color,
+    ) -> f64 {
+        cr.select_font_face("Sans", FontSlant::Normal, FontWeight::Bold);
+        cr.set_font_size(11.0);
+        let Ok(extents) = cr.text_extents(text) else {
+            return x;
+        };
+        let rect = Rect {
+            x,
+            y: y - BADGE_HEIGHT / 2.0,
+            width: extents.x_advance() + BADGE_PADDING * 2.0,
+            height: BADGE_HEIGHT,
+        };
+        cr.set_source_rgb(color.r, color.g, color.b);
+        draw_rounded_rect(cr, rect.x, rect.y, rect.width, rect.height, 3.0);
+        let _ = cr.fill();
+        cr.set_source_rgb(1.0, 1.0, 1.0);
+        cr.move_to(x + BADGE_PADDING, y + extents.height() / 2.0);
+        let _ = cr.show_text(text);
+        self.badges.borrow_mut().push(BadgeHit { target, rect });
+        rect.x + rect.width + BADGE_GAP
+    }
+}
+
+fn stroke_level(
+    cr: &Context,
+    layout: &ChartLayout,
+    y: f64,
+    color: Color,
+    width: f64,
+    style: LineStyle,
+) {
+    cr.set_source_rgb(color.r, color.g, color.b);
+    cr.set_line_width(width.max(0.5));
+    apply_line_style(cr, style, width);
+    cr.move_to(layout.plot_left, y);
+    cr.line_to(layout.plot_right, y);
+    let _ = cr.stroke();
+    cr.set_dash(&[], 0.0);
+}
@@ -0,0 +1,287 @@
+use cairo::{Context, FontSlant, FontWeight};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::super::data::{SeriesData, SeriesScale};
+use super::super::format::format_price_with_format;
+use super::super::layout::ChartLayout;
+use super::super::types::{
+    LineStyle, PriceFormat, PriceScale, VolumeProfileLevels, VolumeProfileOptions,
+    VolumeProfileRange,
+};
+use super::super::util::{apply_line_style, map_price_to_y_scaled, map_time_to_x};
+use super::super::volume_profile::{
+    compute_profiles, profile_bar_range, Profile, ProfileKey, VolumeProfileState,
+};
+use super::ChartCore;
+
+impl ChartCore {
+    pub(crate) fn create_volume_profile(
+        &mut self,
+        candle_series: usize,
+        volume_series: usize,
+        options: VolumeProfileOptions,
+    ) -> usize {
+        let id = self.next_volume_profile_id;
+        self.next_volume_profile_id += 1;
+        self.volume_profiles.push(VolumeProfileState {
+            id,
+            candle_series,
+            volume_series,
+            options,
+            cache: RefCell::new(None),
+        });
+        id
+    }
+
+    pub(crate) fn update_volume_profile(&mut self, id: usize, options: VolumeProfileOptions) {
+        if let Some(state) = self.volume_profiles.iter_mut().find(|state| state.id == id) {
+            state.options = options;
+            state.cache.replace(None);
+        }
+    }
+
+    pub(crate) fn remove_volume_profile(&mut self, id: usize) {
+        self.volume_profiles.retain(|state| state.id != id);
+    }
+
+    pub(crate) fn volume_profile_levels(&self, id: usize) -> Vec<VolumeProfileLevels> {
+        self.volume_profiles
+            .iter()
+            .find(|state| state.id == id)
+            .and_then(|state| self.profiles_for(state, self.time_scale.start, self.time_scale.end))
+            .map(|profiles| profiles.iter().map(|profile| profile.levels).collect())
+            .unwrap_or_default()
+    }
+
+    fn profiles_for(
+        &self,
+        state: &VolumeProfileState,
+        start_time: f64,
+        end_time: f64,
+    ) -> Option<Rc<Vec<Profile>>> {
+        let candle_series = self.series.get(state.candle_series)?;
+        let SeriesData::Candlestick { data: candles } = &candle_series.data else {
+            return None;
+        };
+        let SeriesData::Histogram { data: volumes } = &self.series.get(state.volume_series)?.data
+        else {
+            return None;
+        };
+        let format = &candle_series.options.price_format;
+        let (first, last, session) =
+            profile_bar_range(candles, state.options.range, start_time, end_time);
+        let key = ProfileKey {
+            first,
+            last,
+            revision: self.data_revision,
+            min_move: match format {
+                PriceFormat::Price { min_move, .. } => Some(min_move.to_bits()),
+                _ => None,
+            },
+        };
+        if let Some((cached_key, profiles)) = state.cache.borrow().as_ref() {
+            if *cached_key == key {
+                return Some(profiles.clone());
+            }
+        }
+        let profiles = Rc::new(compute_profiles(
+            &candles[first..last.max(first)],
+            volumes,
+            &state.options,
+            format,
+            session,
+        ));
+        state.cache.replace(Some((key, profiles.clone())));
+        Some(profiles)
+    }
+
+    pub(super) fn draw_volume_profiles(
+        &self,
+        cr: &Context,
+        layout: &ChartLayout,
+        left_scale: Option<SeriesScale>,
+        right_scale: Option<SeriesScale>,
+        start_time: f64,
+        end_time: f64,
+    ) {
+        for state in &self.volume_profiles {
+            let Some(series) = self.series.get(state.candle_series) else {
+                continue;
+            };
+            if !self.series_on_main_panel(series) {
+                continue;
+            }
+            let scale = match series.scale {
+                PriceScale::Left => left_scale,
+                PriceScale::Right => right_scale,
+            };
+            let (Some(scale), Some(profiles)) =
+                (scale, self.profiles_for(state, start_time, end_time))
+            else {
+                continue;
+            };
+            for profile in profiles.iter() {
+                draw_profile(
+                    cr,
+                    layout,
+                    scale,
+                    &state.options,
+                    &series.options.price_format,
+                    profile,
+                    start_time,
+                    end_time,
+                );
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_profile(
+    cr: &Context,
+    layout: &ChartLayout,
+    scale: SeriesScale,
+    options: &VolumeProfileOptions,
+    format: &PriceFormat,
+    profile: &Profile,
+    start_time: f64,
+    end_time: f64,
+) {
+    let price_y = |price: f64| {
+        map_price_to_y_scaled(
+            price,
+            scale.min,
+            scale.max,
+            layout.plot_top,
+            layout.main_height,
+            scale.margins,
+            scale.invert,
+            scale.mode,
+            scale.base,
+        )
+    };
+    let time_x = |time: f64| {
+        map_time_to_x(
+            time,
+            start_time,
+            end_time,
+            layout.plot_left,
+            layout.plot_width,
+        )
+    };
+
+    // The visible-range profile hangs off the price axis; fixed and session
+    // profiles grow rightwards from the start of their own span.
+    let anchored_right = options.range == VolumeProfileRange::Visible;
+    let (left, right) = if anchored_right {
+        (layout.plot_left, layout.plot_right)
+    } else {
+        (
+            time_x(profile.span.0).max(layout.plot_left),
+            time_x(profile.span.1).min(layout.plot_right),
+        )
+    };
+    if right - left < 1.0 {
+        return;
+    }
+    let max_width = (right - left) * options.width.clamp(0.0, 1.0);
+    let max_volume = profile
+        .rows
+        .iter()
+        .map(|row| row.total())
+        .fold(0.0, f64::max);
+    if max_volume <= 0.0 {
+        return;
+    }
+
+    for (idx, row) in profile.rows.iter().enumerate() {
+        let top = price_y(row.high).max(layout.plot_top);
+        let bottom = price_y(row.low).min(layout.main_bottom);
+        if bottom <= top {
+            continue;
+        }
+        let height = (bottom - top - 1.0).max(1.0);
+        let alpha = if (profile.value_area.0..=profile.value_area.1).contains(&idx) {
+            options.value_area_alpha
+        } else {
+            options.alpha
+        };
+        let up_width = row.up / max_volume * max_width;
+        let down_width = row.down / max_volume * max_width;
+        for (color, offset, width) in [
+            (options.up_color, 0.0, up_width),
+            (options.down_color, up_width, down_width),
+        ] {
+            if width <= 0.0 {
+                continue;
+            }
+            let x = if anchored_right {
+                right - offset - width
+            } else {
+                left + offset
+            };
+            cr.set_source_rgba(color.r, color.g, color.b, alpha.clamp(0.0, 1.0));
+            cr.rectangle(x, top, width, height);
+            let _ = cr.fill();
+        }
+    }
+
+    let levels = &profile.levels;
+    let level_lines = [
+        (levels.poc, options.poc_color, LineStyle::Solid, "POC"),
+        (
+            levels.value_area_high,
+            options.value_area_line_color,
+            LineStyle::Dashed,
+            "VAH",
+        ),
+        (
+            levels.value_area_low,
+            options.value_area_line_color,
+            LineStyle::Dashed,
+            "VAL",
+        ),
+    ];
+    let precision = match format {
+        PriceFormat::Price { precision, .. }
+        | PriceFormat::Percent { precision }
+        | PriceFormat::Volume { precision } => *precision,
+    };
+    cr.select_font_face("Sans", FontSlant::Normal, FontWeight::Normal);
+    cr.set_font_size(10.0);
+    for (price, color, style, name) in level_lines {
+        let y = price_y(price);
+        if y < layout.plot_top || y > layout.main_bottom {
+            continue;
+        }
+        cr.set_source_rgb(color.r, color.g, color.b);
+        cr.set_line_width(1.0);
+        apply_line_style(cr, style, 1.0);
+        cr.move_to(left, y);
+        cr.line_to(right, y);
+        let _ = cr.stroke();
+        cr.set_dash(&[], 0.0);
+
+        if !options.labels_visible {
+            continue;
+        }
+        let mut text = format!(
+            "{name} {}",
+            format_price_with_format(price, format, precision, scale.mode)
+        );
+        if name == "VAH" {
+            text.push_str(&format!(" · VA {:.1}%", levels.value_area_percent));
+        }
+        let Ok(extents) = cr.text_extents(&text) else {
+            continue;
+        };
+        let x = if anchored_right {
+            right - max_width - extents.width() - 6.0
+        } else {
+            left + 4.0
+        };
+        cr.move_to(x.max(left), y - 3.0);
+        let _ = cr.show_text(&text);
+    }
+}
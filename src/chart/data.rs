@@ -1,7 +1,9 @@
-use super::options::PriceScaleOptions;
+use super::animation::Transition;
+use super::options::{AnimationOptions, PriceScaleOptions};
 use super::types::{
-    Candle, Color, HistogramPoint, LinePoint, Marker, PanelId, PanelRole, PriceFormat,
-    PriceLineOptions, PriceScale, SeriesMarkersOptions, TimeScaleId,
+    Candle, CloudPoint, Color, HistogramPoint, LinePoint, Marker, OrderLineOptions, PanelId,
+    PanelRole, PositionLineOptions, PriceFormat, PriceLineOptions, PriceScale, PriceSegment,
+    SeriesMarkersOptions, TimeScaleId,
 };
 use time::OffsetDateTime;
 
@@ -10,6 +12,7 @@ pub(crate) enum SeriesKind {
     Candlestick,
     Line,
     Histogram,
+    Cloud,
 }
 
 #[derive(Clone, Debug)]
@@ -17,6 +20,7 @@ pub(crate) enum SeriesData {
     Candlestick { data: Vec<Candle> },
     Line { data: Vec<LinePoint> },
     Histogram { data: Vec<HistogramPoint> },
+    Cloud { data: Vec<CloudPoint> },
 }
 
 #[derive(Clone, Debug)]
@@ -29,6 +33,8 @@ pub(crate) struct Series {
     pub(crate) markers: Vec<Marker>,
     pub(crate) price_lines: Vec<PriceLine>,
     pub(crate) next_price_line_id: usize,
+    pub(crate) price_segments: Vec<PriceSegment>,
+    pub(crate) trading: TradingLines,
 }
 
 #[derive(Clone, Debug)]
@@ -62,6 +68,14 @@ pub(crate) struct IndicatorPanel {
     pub(crate) scale: PriceScaleState,
     pub(crate) options: PriceScaleOptions,
     pub(crate) color: Color,
+    pub(crate) groups: Vec<PanelPlotGroup>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct PanelPlotGroup {
+    pub(crate) key: String,
+    pub(crate) lines: Vec<(Vec<LinePoint>, Color)>,
+    pub(crate) histograms: Vec<(Vec<HistogramPoint>, Color)>,
 }
 
 #[derive(Clone, Debug)]
@@ -82,6 +96,12 @@ pub(crate) struct SeriesOptions {
     pub(crate) last_value_text: Option<Color>,
     pub(crate) price_format: PriceFormat,
     pub(crate) markers_options: SeriesMarkersOptions,
+    pub(crate) color: Option<Color>,
+    pub(crate) line_width: f64,
+    pub(crate) fill_up_color: Option<Color>,
+    pub(crate) fill_down_color: Option<Color>,
+    pub(crate) fill_alpha: f64,
+    pub(crate) visible: bool,
 }
 
 impl Default for SeriesOptions {
@@ -96,6 +116,12 @@ impl Default for SeriesOptions {
             last_value_text: None,
             price_format: PriceFormat::default(),
             markers_options: SeriesMarkersOptions::default(),
+            color: None,
+            line_width: 2.0,
+            fill_up_color: None,
+            fill_down_color: None,
+            fill_alpha: 0.2,
+            visible: true,
         }
     }
 }
@@ -106,6 +132,15 @@ pub(crate) struct PriceLine {
     pub(crate) options: PriceLineOptions,
 }
 
+/// Position and order lines of a candlestick series, set by the embedding
+/// application and not part of the edit history.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TradingLines {
+    pub(crate) positions: Vec<(usize, PositionLineOptions)>,
+    pub(crate) orders: Vec<(usize, OrderLineOptions)>,
+    pub(crate) next_id: usize,
+}
+
 #[derive(Clone, Copy, Debug)]
 pub(crate) struct SeriesScale {
     pub(crate) min: f64,
@@ -123,6 +158,13 @@ pub(crate) struct PriceScaleState {
     pub(crate) view_min: f64,
     pub(crate) view_max: f64,
     pub(crate) auto: bool,
+    /// Auto scaled view on its way to a new range
+    pub(crate) transition: Option<Transition>,
+    /// Whether a range was ever shown, so the first one is not animated
+    pub(crate) shown: bool,
+    /// Data revision of the last auto scaled range, telling new data apart
+    /// from a pan or zoom
+    pub(crate) data_revision: u64,
 }
 
 impl PriceScaleState {
@@ -133,7 +175,48 @@ impl PriceScaleState {
             view_min: 0.0,
             view_max: 1.0,
             auto: true,
+            transition: None,
+            shown: false,
+            data_revision: 0,
+        }
+    }
+
+    /// Moves the auto scaled view to `target`. Eases from the range on
+    /// screen when new data arrived or auto scale was just switched back on;
+    /// a target moved by panning or zooming the time scale is set directly.
+    pub(crate) fn ease_to(
+        &mut self,
+        target: (f64, f64),
+        animation: AnimationOptions,
+        data_revision: u64,
+    ) {
+        let retarget = self
+            .transition
+            .map_or(true, |transition| transition.target() != target);
+        if retarget {
+            let animate = self.shown && (data_revision != self.data_revision || !self.auto);
+            self.transition = if animate {
+                Transition::new((self.view_min, self.view_max), target, animation)
+            } else {
+                None
+            };
         }
+        self.data_revision = data_revision;
+        let (min, max) = match self.transition {
+            Some(transition) if !transition.finished() => transition.value(),
+            _ => {
+                self.transition = None;
+                target
+            }
+        };
+        self.view_min = min;
+        self.view_max = max;
+        self.shown = true;
+    }
+
+    pub(crate) fn is_animating(&self) -> bool {
+        self.transition
+            .is_some_and(|transition| !transition.finished())
     }
 
     pub(crate) fn update_data(&mut self, min: f64, max: f64, auto_range: (f64, f64)) {
@@ -186,3 +269,9 @@ impl HasTime for HistogramPoint {
         self.time
     }
 }
+
+impl HasTime for CloudPoint {
+    fn time(&self) -> OffsetDateTime {
+        self.time
+    }
+}
@@ -0,0 +1,490 @@
+use time::OffsetDateTime;
+
+use super::types::{Color, LineStyle};
+
+/// Identifies a drawing added to a chart.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DrawingId(pub usize);
+
+/// A point in chart data space.
+///
+/// Drawings store anchors as time and price rather than pixels, so they stay
+/// attached to the same bars and prices through pan, zoom, scale mode changes
+/// and panel resizes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DrawingAnchor {
+    /// The time coordinate of the anchor
+    pub time: OffsetDateTime,
+    /// The price coordinate of the anchor
+    pub price: f64,
+}
+
+/// The kinds of drawings a user can place on the chart.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DrawingTool {
+    /// Segment between two anchors
+    TrendLine,
+    /// Line through two anchors, extended to both edges of the plot
+    ExtendedLine,
+    /// Line from the first anchor through the second, extended past it
+    Ray,
+    /// Horizontal line from an anchor to the right edge of the plot
+    HorizontalRay,
+    /// Horizontal line across the whole plot
+    HorizontalLine,
+    /// Vertical line across the whole plot
+    VerticalLine,
+    /// Fibonacci retracement levels between two anchors
+    FibRetracement,
+    /// Trend-based Fibonacci extension: the move from the first to the second
+    /// anchor projected from the third
+    FibExtension,
+    /// Fibonacci fan: rays from the first anchor through the retracement
+    /// levels at the second
+    FibFan,
+    /// Fibonacci arcs centred on the second anchor
+    FibArcs,
+    /// Vertical lines at Fibonacci bar counts from the anchor
+    FibTimeZones,
+    /// Line through the first two anchors and a parallel through the third
+    ParallelChannel,
+    /// Linear regression of closes between the anchors' bars, with
+    /// standard-deviation bands
+    RegressionChannel,
+    /// Andrews' pitchfork with its handle at the first anchor
+    Pitchfork,
+    /// Pitchfork whose handle is moved halfway to the second anchor's price
+    SchiffPitchfork,
+    /// Pitchfork whose handle is moved halfway to the second anchor in both
+    /// time and price
+    ModifiedSchiffPitchfork,
+    /// Rectangle with opposite corners at the anchors
+    Rectangle,
+    /// Ellipse inscribed in the rectangle spanned by the anchors
+    Ellipse,
+    /// Triangle through three anchors
+    Triangle,
+    /// XABCD harmonic pattern with its leg ratios
+    Xabcd,
+    /// Head and shoulders: start, left shoulder, neckline, head, neckline,
+    /// right shoulder and end
+    HeadAndShoulders,
+    /// Text box with its top-left corner at the anchor
+    Text,
+    /// Text box at the second anchor pointing at the first
+    Callout,
+    /// Text box that keeps its place on screen, see `Drawing::pinned`
+    Note,
+    /// Arrow from the first anchor to the second
+    Arrow,
+    /// Box between two anchors showing the price change, percent change,
+    /// bar count and time between them
+    Measure,
+    /// Trade plan from an entry at the first anchor: a target zone to the
+    /// second anchor's price and a stop zone to the third's, labelled with
+    /// the reward-to-risk multiple
+    RiskReward,
+}
+
+impl DrawingTool {
+    /// Number of anchors the user places to create this drawing.
+    pub fn anchor_count(self) -> usize {
+        match self {
+            DrawingTool::HeadAndShoulders => 7,
+            DrawingTool::Xabcd => 5,
+            DrawingTool::FibExtension
+            | DrawingTool::ParallelChannel
+            | DrawingTool::Pitchfork
+            | DrawingTool::SchiffPitchfork
+            | DrawingTool::ModifiedSchiffPitchfork
+            | DrawingTool::Triangle
+            | DrawingTool::RiskReward => 3,
+            DrawingTool::TrendLine
+            | DrawingTool::ExtendedLine
+            | DrawingTool::Ray
+            | DrawingTool::FibRetracement
+            | DrawingTool::FibFan
+            | DrawingTool::FibArcs
+            | DrawingTool::RegressionChannel
+            | DrawingTool::Rectangle
+            | DrawingTool::Ellipse
+            | DrawingTool::Callout
+            | DrawingTool::Arrow
+            | DrawingTool::Measure => 2,
+            DrawingTool::HorizontalRay
+            | DrawingTool::HorizontalLine
+            | DrawingTool::VerticalLine
+            | DrawingTool::FibTimeZones
+            | DrawingTool::Text
+            | DrawingTool::Note => 1,
+        }
+    }
+
+    /// Levels a new drawing of this kind starts with; empty for tools
+    /// without levels. Time zone levels are bar counts, regression channel
+    /// levels standard deviations and the others ratios.
+    pub fn default_levels(self) -> Vec<DrawingLevel> {
+        let values: &[(f64, bool)] = match self {
+            DrawingTool::FibRetracement => &[
+                (0.0, true),
+                (0.236, true),
+                (0.382, true),
+                (0.5, true),
+                (0.618, true),
+                (0.786, true),
+                (1.0, true),
+                (1.618, true),
+                (2.618, false),
+                (3.618, false),
+                (4.236, false),
+            ],
+            DrawingTool::FibExtension => &[
+                (0.0, true),
+                (0.236, false),
+                (0.382, true),
+                (0.5, true),
+                (0.618, true),
+                (0.786, false),
+                (1.0, true),
+                (1.618, true),
+                (2.618, true),
+                (3.618, false),
+                (4.236, false),
+            ],
+            DrawingTool::FibFan | DrawingTool::FibArcs => &[
+                (0.236, true),
+                (0.382, true),
+                (0.5, true),
+                (0.618, true),
+                (0.786, true),
+                (1.0, true),
+            ],
+            DrawingTool::FibTimeZones => &[
+                (0.0, true),
+                (1.0, true),
+                (2.0, true),
+                (3.0, true),
+                (5.0, true),
+                (8.0, true),
+                (13.0, true),
+                (21.0, true),
+                (34.0, true),
+                (55.0, true),
+                (89.0, true),
+                (144.0, true),
+            ],
+            DrawingTool::RegressionChannel => &[(1.0, false), (2.0, true), (3.0, false)],
+            _ => &[],
+        };
+        values
+            .iter()
+            .enumerate()
+            .map(|(idx, &(value, visible))| DrawingLevel {
+                value,
+                color: level_color(idx),
+                visible,
+            })
+            .collect()
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DrawingTool::TrendLine => "Trend Line",
+            DrawingTool::ExtendedLine => "Extended Line",
+            DrawingTool::Ray => "Ray",
+            DrawingTool::HorizontalRay => "Horizontal Ray",
+            DrawingTool::HorizontalLine => "Horizontal Line",
+            DrawingTool::VerticalLine => "Vertical Line",
+            DrawingTool::FibRetracement => "Fib Retracement",
+            DrawingTool::FibExtension => "Trend-Based Fib Extension",
+            DrawingTool::FibFan => "Fib Fan",
+            DrawingTool::FibArcs => "Fib Arcs",
+            DrawingTool::FibTimeZones => "Fib Time Zones",
+            DrawingTool::ParallelChannel => "Parallel Channel",
+            DrawingTool::RegressionChannel => "Regression Trend",
+            DrawingTool::Pitchfork => "Pitchfork",
+            DrawingTool::SchiffPitchfork => "Schiff Pitchfork",
+            DrawingTool::ModifiedSchiffPitchfork => "Modified Schiff Pitchfork",
+            DrawingTool::Rectangle => "Rectangle",
+            DrawingTool::Ellipse => "Ellipse",
+            DrawingTool::Triangle => "Triangle",
+            DrawingTool::Xabcd => "XABCD Pattern",
+            DrawingTool::HeadAndShoulders => "Head and Shoulders",
+            DrawingTool::Text => "Text",
+            DrawingTool::Callout => "Callout",
+            DrawingTool::Note => "Note",
+            DrawingTool::Arrow => "Arrow",
+            DrawingTool::Measure => "Measure",
+            DrawingTool::RiskReward => "Risk/Reward",
+        }
+    }
+
+    /// Toolbar group the tool is listed under.
+    pub fn category(self) -> &'static str {
+        match self {
+            DrawingTool::TrendLine
+            | DrawingTool::ExtendedLine
+            | DrawingTool::Ray
+            | DrawingTool::HorizontalRay
+            | DrawingTool::HorizontalLine
+            | DrawingTool::VerticalLine => "Lines",
+            DrawingTool::FibRetracement
+            | DrawingTool::FibExtension
+            | DrawingTool::FibFan
+            | DrawingTool::FibArcs
+            | DrawingTool::FibTimeZones => "Fibonacci",
+            DrawingTool::ParallelChannel
+            | DrawingTool::RegressionChannel
+            | DrawingTool::Pitchfork
+            | DrawingTool::SchiffPitchfork
+            | DrawingTool::ModifiedSchiffPitchfork => "Channels",
+            DrawingTool::Rectangle | DrawingTool::Ellipse | DrawingTool::Triangle => "Shapes",
+            DrawingTool::Xabcd | DrawingTool::HeadAndShoulders => "Patterns",
+            DrawingTool::Text | DrawingTool::Callout | DrawingTool::Note | DrawingTool::Arrow => {
+                "Annotations"
+            }
+            DrawingTool::Measure => "Measure",
+            DrawingTool::RiskReward => "Trading",
+        }
+    }
+
+    /// Whether the drawing's text is shown in a box rather than as a label
+    /// beside its lines.
+    pub fn has_text_box(self) -> bool {
+        matches!(
+            self,
+            DrawingTool::Text | DrawingTool::Callout | DrawingTool::Note
+        )
+    }
+
+    /// Whether new drawings of this kind start with their areas shaded.
+    fn fills_by_default(self) -> bool {
+        !matches!(
+            self,
+            DrawingTool::TrendLine
+                | DrawingTool::ExtendedLine
+                | DrawingTool::Ray
+                | DrawingTool::HorizontalRay
+                | DrawingTool::HorizontalLine
+                | DrawingTool::VerticalLine
+                | DrawingTool::FibTimeZones
+                | DrawingTool::Text
+                | DrawingTool::Callout
+                | DrawingTool::Note
+                | DrawingTool::Arrow
+        )
+    }
+
+    /// Name the tool is stored under in drawing files. It must not change
+    /// once released, even if the variant is renamed.
+    pub fn name(self) -> &'static str {
+        match self {
+            DrawingTool::TrendLine => "TrendLine",
+            DrawingTool::ExtendedLine => "ExtendedLine",
+            DrawingTool::Ray => "Ray",
+            DrawingTool::HorizontalRay => "HorizontalRay",
+            DrawingTool::HorizontalLine => "HorizontalLine",
+            DrawingTool::VerticalLine => "VerticalLine",
+            DrawingTool::FibRetracement => "FibRetracement",
+            DrawingTool::FibExtension => "FibExtension",
+            DrawingTool::FibFan => "FibFan",
+            DrawingTool::FibArcs => "FibArcs",
+            DrawingTool::FibTimeZones => "FibTimeZones",
+            DrawingTool::ParallelChannel => "ParallelChannel",
+            DrawingTool::RegressionChannel => "RegressionChannel",
+            DrawingTool::Pitchfork => "Pitchfork",
+            DrawingTool::SchiffPitchfork => "SchiffPitchfork",
+            DrawingTool::ModifiedSchiffPitchfork => "ModifiedSchiffPitchfork",
+            DrawingTool::Rectangle => "Rectangle",
+            DrawingTool::Ellipse => "Ellipse",
+            DrawingTool::Triangle => "Triangle",
+            DrawingTool::Xabcd => "Xabcd",
+            DrawingTool::HeadAndShoulders => "HeadAndShoulders",
+            DrawingTool::Text => "Text",
+            DrawingTool::Callout => "Callout",
+            DrawingTool::Note => "Note",
+            DrawingTool::Arrow => "Arrow",
+            DrawingTool::Measure => "Measure",
+            DrawingTool::RiskReward => "RiskReward",
+        }
+    }
+
+    /// The tool stored under `name`, see `name`.
+    pub fn from_name(name: &str) -> Option<DrawingTool> {
+        Self::all().iter().copied().find(|tool| tool.name() == name)
+    }
+
+    pub fn all() -> &'static [DrawingTool] {
+        &[
+            DrawingTool::TrendLine,
+            DrawingTool::ExtendedLine,
+            DrawingTool::Ray,
+            DrawingTool::HorizontalRay,
+            DrawingTool::HorizontalLine,
+            DrawingTool::VerticalLine,
+            DrawingTool::FibRetracement,
+            DrawingTool::FibExtension,
+            DrawingTool::FibFan,
+            DrawingTool::FibArcs,
+            DrawingTool::FibTimeZones,
+            DrawingTool::ParallelChannel,
+            DrawingTool::RegressionChannel,
+            DrawingTool::Pitchfork,
+            DrawingTool::SchiffPitchfork,
+            DrawingTool::ModifiedSchiffPitchfork,
+            DrawingTool::Rectangle,
+            DrawingTool::Ellipse,
+            DrawingTool::Triangle,
+            DrawingTool::Xabcd,
+            DrawingTool::HeadAndShoulders,
+            DrawingTool::Text,
+            DrawingTool::Callout,
+            DrawingTool::Note,
+            DrawingTool::Arrow,
+            DrawingTool::Measure,
+            DrawingTool::RiskReward,
+        ]
+    }
+}
+
+const LEVEL_COLORS: [Color; 8] = [
+    Color::new(0.47, 0.47, 0.47),
+    Color::new(0.95, 0.21, 0.21),
+    Color::new(1.0, 0.6, 0.0),
+    Color::new(0.3, 0.69, 0.31),
+    Color::new(0.03, 0.6, 0.51),
+    Color::new(0.0, 0.74, 0.83),
+    Color::new(0.16, 0.38, 1.0),
+    Color::new(0.61, 0.15, 0.69),
+];
+
+/// Palette color for the level at `index`, cycling when there are more
+/// levels than colors.
+pub fn level_color(index: usize) -> Color {
+    LEVEL_COLORS[index % LEVEL_COLORS.len()]
+}
+
+/// One level of a Fibonacci drawing or regression channel.
+#[derive(Clone, Copy, Debug)]
+pub struct DrawingLevel {
+    /// The level's ratio, bar count for time zones or standard deviations
+    /// for regression channels
+    pub value: f64,
+    /// The color of the level's line, label and fill
+    pub color: Color,
+    /// Whether the level is drawn
+    pub visible: bool,
+}
+
+/// Line and text styling shared by all drawings.
+#[derive(Clone, Debug)]
+pub struct DrawingStyle {
+    /// The color of the drawing
+    pub color: Color,
+    /// The width of the line in pixels
+    pub width: f64,
+    /// The style of the line (solid, dotted, dashed)
+    pub line_style: LineStyle,
+    /// Whether the line continues to the left edge of the plot
+    pub extend_left: bool,
+    /// Whether the line continues to the right edge of the plot
+    pub extend_right: bool,
+    /// Whether the areas between levels or shape outlines are filled
+    pub fill: bool,
+    /// Opacity of the fill (0.0 to 1.0)
+    pub fill_alpha: f64,
+    /// Font size of text boxes and labels
+    pub font_size: f64,
+    /// Background of text boxes; `None` for transparent
+    pub text_background: Option<Color>,
+    /// Whether text boxes get a border in the drawing's color
+    pub text_border: bool,
+}
+
+impl Default for DrawingStyle {
+    fn default() -> Self {
+        Self {
+            color: Color::new(0.16, 0.47, 0.98),
+            width: 1.5,
+            line_style: LineStyle::Solid,
+            extend_left: false,
+            extend_right: false,
+            fill: false,
+            fill_alpha: 0.15,
+            font_size: 12.0,
+            text_background: None,
+            text_border: false,
+        }
+    }
+}
+
+/// A user drawing anchored in (time, price) space.
+#[derive(Clone, Debug)]
+pub struct Drawing {
+    /// What kind of drawing this is
+    pub tool: DrawingTool,
+    /// Anchor points, `tool.anchor_count()` of them once complete
+    pub anchors: Vec<DrawingAnchor>,
+    /// Line styling
+    pub style: DrawingStyle,
+    /// Label drawn next to the drawing; empty for none
+    pub text: String,
+    /// Levels of Fibonacci tools and regression channels; empty for other
+    /// tools
+    pub levels: Vec<DrawingLevel>,
+    /// Screen position as fractions of the plot's width and height. When
+    /// set, the drawing stays there through pans and zooms instead of
+    /// following its first anchor. Sticky notes are placed this way.
+    pub pinned: Option<(f64, f64)>,
+    /// Locked drawings can be selected and styled but not moved, reshaped
+    /// or deleted with the keyboard
+    pub locked: bool,
+}
+
+impl Drawing {
+    pub fn new(tool: DrawingTool, anchors: Vec<DrawingAnchor>) -> Self {
+        let mut style = DrawingStyle {
+            fill: tool.fills_by_default(),
+            ..DrawingStyle::default()
+        };
+        match tool {
+            DrawingTool::Callout => {
+                style.text_background = Some(Color::new(0.89, 0.93, 1.0));
+                style.text_border = true;
+            }
+            DrawingTool::Note => {
+                style.color = Color::new(0.2, 0.2, 0.2);
+                style.text_background = Some(Color::new(1.0, 0.95, 0.6));
+                style.text_border = true;
+            }
+            _ => {}
+        }
+        Self {
+            tool,
+            anchors,
+            style,
+            text: String::new(),
+            levels: tool.default_levels(),
+            pinned: None,
+            locked: false,
+        }
+    }
+}
+
+/// The part of a drawing under the pointer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DrawingHitTarget {
+    /// One of the drawing's anchor handles, by index
+    Anchor(usize),
+    /// The drawing's line itself
+    Body,
+}
+
+/// Result of hit-testing the drawings layer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DrawingHit {
+    /// The drawing that was hit
+    pub id: DrawingId,
+    /// Which part of it was hit
+    pub target: DrawingHitTarget,
+}
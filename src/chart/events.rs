@@ -0,0 +1,157 @@
+use std::fmt;
+use std::rc::Rc;
+
+use time::OffsetDateTime;
+
+use super::types::{Candle, PanelId};
+
+/// Identifies a subscription, for unsubscribing later.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(pub usize);
+
+/// A series' value at the bar an event refers to.
+#[derive(Clone, Debug)]
+pub enum SeriesValue {
+    Candle(Candle),
+    Line(f64),
+    Histogram(f64),
+    /// Both edges of a cloud band
+    Cloud(f64, f64),
+}
+
+/// What lies under the pointer for crosshair and click events.
+#[derive(Clone, Debug)]
+pub struct MouseEventParams {
+    /// Pointer position in widget pixels, `None` when the pointer left the
+    /// chart
+    pub point: Option<(f64, f64)>,
+    /// Time of the bar under the pointer
+    pub time: Option<OffsetDateTime>,
+    /// Bar index under the pointer. Counts on past the last bar and below
+    /// zero before the first, so empty space has an index too.
+    pub logical: Option<i64>,
+    /// Panel under the pointer
+    pub panel: Option<PanelId>,
+    /// Value of each series that has a point at `time`, by series id
+    pub series_values: Vec<(usize, SeriesValue)>,
+}
+
+/// Range of bar indexes, fractional at the edges, as used by
+/// `MouseEventParams::logical`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LogicalRange {
+    pub from: f64,
+    pub to: f64,
+}
+
+/// Stage of a price line drag.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PriceLineDragPhase {
+    Start,
+    Move,
+    End,
+}
+
+/// A price line being dragged, as passed to drag handlers and vetoes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PriceLineDragParams {
+    /// Series the line belongs to, by `series_id`
+    pub series: usize,
+    /// The line, by `PriceLineApi::line_id`
+    pub line: usize,
+    pub phase: PriceLineDragPhase,
+    /// Proposed price, snapped to the series' `min_move`
+    pub price: f64,
+    /// Price the line had when the drag started
+    pub start_price: f64,
+}
+
+/// Pointer events delivered with `MouseEventParams`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum MouseEventKind {
+    CrosshairMove,
+    Click,
+    DoubleClick,
+}
+
+pub(crate) type MouseHandler = Rc<dyn Fn(&MouseEventParams)>;
+pub(crate) type TimeRangeHandler = Rc<dyn Fn(f64, f64)>;
+pub(crate) type LogicalRangeHandler = Rc<dyn Fn(Option<LogicalRange>)>;
+pub(crate) type SizeHandler = Rc<dyn Fn(f64, f64)>;
+pub(crate) type DrawingsHandler = Rc<dyn Fn()>;
+/// Called with the series and order line whose cancel button was clicked.
+pub(crate) type OrderCancelHandler = Rc<dyn Fn(usize, usize)>;
+pub(crate) type PriceLineDragHandler = Rc<dyn Fn(&PriceLineDragParams)>;
+/// Returns `true` to reject the proposed price.
+pub(crate) type PriceLineDragVeto = Rc<dyn Fn(&PriceLineDragParams) -> bool>;
+
+/// Callbacks an event is about to be delivered to, bound to its arguments.
+/// They run after the chart borrow is released so handlers can call back
+/// into the chart.
+pub(crate) type EventCalls = Vec<Box<dyn FnOnce()>>;
+
+/// Subscribers for each chart event, plus the last values delivered so
+/// change events only fire on an actual change.
+#[derive(Clone, Default)]
+pub(crate) struct EventHandlers {
+    next_id: usize,
+    pub(crate) crosshair_move: Vec<(SubscriptionId, MouseHandler)>,
+    pub(crate) click: Vec<(SubscriptionId, MouseHandler)>,
+    pub(crate) double_click: Vec<(SubscriptionId, MouseHandler)>,
+    pub(crate) visible_time_range: Vec<(SubscriptionId, TimeRangeHandler)>,
+    pub(crate) visible_logical_range: Vec<(SubscriptionId, LogicalRangeHandler)>,
+    pub(crate) size: Vec<(SubscriptionId, SizeHandler)>,
+    pub(crate) drawings: Vec<(SubscriptionId, DrawingsHandler)>,
+    pub(crate) price_line_drag: Vec<(SubscriptionId, PriceLineDragHandler)>,
+    pub(crate) price_line_drag_veto: Vec<(SubscriptionId, PriceLineDragVeto)>,
+    pub(crate) order_cancel: Vec<(SubscriptionId, OrderCancelHandler)>,
+    pub(crate) last_time_range: Option<(f64, f64)>,
+    pub(crate) last_logical_range: Option<LogicalRange>,
+    pub(crate) last_size: Option<(f64, f64)>,
+    pub(crate) last_drawing_revision: u64,
+    /// Size of the last draw, used to lay out pointer events
+    pub(crate) drawn_size: Option<(f64, f64)>,
+}
+
+impl fmt::Debug for EventHandlers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventHandlers")
+            .field("last_time_range", &self.last_time_range)
+            .field("last_logical_range", &self.last_logical_range)
+            .field("last_size", &self.last_size)
+            .field("last_drawing_revision", &self.last_drawing_revision)
+            .field("drawn_size", &self.drawn_size)
+            .finish_non_exhaustive()
+    }
+}
+
+impl EventHandlers {
+    pub(crate) fn next_id(&mut self) -> SubscriptionId {
+        self.next_id += 1;
+        SubscriptionId(self.next_id)
+    }
+
+    pub(crate) fn mouse_handlers(
+        &mut self,
+        kind: MouseEventKind,
+    ) -> &mut Vec<(SubscriptionId, MouseHandler)> {
+        match kind {
+            MouseEventKind::CrosshairMove => &mut self.crosshair_move,
+            MouseEventKind::Click => &mut self.click,
+            MouseEventKind::DoubleClick => &mut self.double_click,
+        }
+    }
+
+    pub(crate) fn unsubscribe(&mut self, id: SubscriptionId) {
+        self.crosshair_move.retain(|(key, _)| *key != id);
+        self.click.retain(|(key, _)| *key != id);
+        self.double_click.retain(|(key, _)| *key != id);
+        self.visible_time_range.retain(|(key, _)| *key != id);
+        self.visible_logical_range.retain(|(key, _)| *key != id);
+        self.size.retain(|(key, _)| *key != id);
+        self.drawings.retain(|(key, _)| *key != id);
+        self.price_line_drag.retain(|(key, _)| *key != id);
+        self.price_line_drag_veto.retain(|(key, _)| *key != id);
+        self.order_cancel.retain(|(key, _)| *key != id);
+    }
+}
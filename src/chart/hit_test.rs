@@ -0,0 +1,48 @@
+use time::OffsetDateTime;
+
+use super::types::Rect;
+
+/// What lies under a point, as found by `ChartApi::hit_test`. Series are
+/// identified by their `series_id`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HitTarget {
+    /// A candle's body or wick
+    Candle { series: usize, time: OffsetDateTime },
+    /// A line series' stroke, with the time of the nearest point
+    Line { series: usize, time: OffsetDateTime },
+    /// A histogram bar
+    Histogram { series: usize, time: OffsetDateTime },
+    /// A marker or its label, by index into the series' markers
+    Marker { series: usize, index: usize },
+    /// A price line, by `PriceLineApi::line_id`
+    PriceLine { series: usize, line: usize },
+    /// A position line or its badges, by `PositionLineApi::line_id`
+    Position { series: usize, line: usize },
+    /// An order line or its badges, by `OrderLineApi::line_id`
+    Order { series: usize, line: usize },
+    /// The cancel button of an order line
+    OrderCancel { series: usize, line: usize },
+}
+
+/// Bounding box of a marker and its label from the last draw.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct MarkerHit {
+    pub(crate) series: usize,
+    pub(crate) index: usize,
+    pub(crate) rect: Rect,
+}
+
+/// Axis label box of a price line from the last draw.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct PriceLineLabelHit {
+    pub(crate) series: usize,
+    pub(crate) line: usize,
+    pub(crate) rect: Rect,
+}
+
+/// Badge of a position or order line from the last draw.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct BadgeHit {
+    pub(crate) target: HitTarget,
+    pub(crate) rect: Rect,
+}
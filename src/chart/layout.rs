@@ -268,6 +268,21 @@ impl ChartLayout {
         }
     }
 
+    /// This layout with the main plot and histogram areas both covering the
+    /// indicator panel, for series moved there.
+    pub(crate) fn on_rsi_panel(&self) -> Self {
+        let mut layout = self.clone();
+        layout.plot_top = self.rsi_top;
+        layout.plot_bottom = self.rsi_bottom;
+        layout.plot_height = self.rsi_height;
+        layout.main_bottom = self.rsi_bottom;
+        layout.main_height = self.rsi_height;
+        layout.hist_top = self.rsi_top;
+        layout.hist_bottom = self.rsi_bottom;
+        layout.hist_height = self.rsi_height;
+        layout
+    }
+
     pub(crate) fn panel_at(&self, y: f64) -> Option<PanelId> {
         self.panels
             .iter()
@@ -1,27 +1,46 @@
+pub mod animation;
 pub mod api;
 pub mod core;
 pub mod data;
+pub mod drawings;
+pub mod events;
 pub mod format;
+pub mod hit_test;
 pub mod layout;
 pub mod options;
 pub mod scales;
+pub mod sync;
 pub mod ticks;
 pub mod types;
 pub mod util;
+pub mod volume_profile;
 
 pub use api::{
-    create_chart, CandlestickSeriesApi, ChartApi, HistogramSeriesApi, LineSeriesApi, PriceLineApi,
+    create_chart, CandlestickSeriesApi, ChartApi, CloudSeriesApi, HistogramSeriesApi,
+    LineSeriesApi, OrderLineApi, PositionLineApi, PriceLineApi, VolumeProfileApi,
 };
+pub use drawings::{
+    level_color, Drawing, DrawingAnchor, DrawingHit, DrawingHitTarget, DrawingId, DrawingLevel,
+    DrawingStyle, DrawingTool,
+};
+pub use events::{
+    LogicalRange, MouseEventParams, PriceLineDragParams, PriceLineDragPhase, SeriesValue,
+    SubscriptionId,
+};
+pub use hit_test::HitTarget;
 pub use options::{
-    ChartStyle, CrosshairOptions, HandleScaleOptions, HandleScrollOptions,
-    InteractionSensitivityOptions, KineticScrollOptions, PriceScaleOptions, TimeScaleOptions,
-    TooltipOptions, TrackingModeExitMode, TrackingModeOptions,
+    AnimationOptions, ChartStyle, CrosshairOptions, Easing, HandleScaleOptions,
+    HandleScrollOptions, InteractionSensitivityOptions, KineticScrollOptions, PriceScaleOptions,
+    TimeScaleOptions, TooltipOptions, TrackingModeExitMode, TrackingModeOptions,
 };
+pub use sync::{ChartSyncGroup, ChartSyncOptions};
 pub use types::{
-    Bar, BarConversionError, Candle, Color, CrosshairCenter, CrosshairMode, HistogramPoint,
-    LinePoint, LineStyle, Marker, MarkerPosition, MarkerShape, MarkerZOrder, PanelControlAction,
-    PanelId, PanelResizeHandle, PanelRole, PriceFormat, PriceLineOptions, PriceScale,
-    PriceScaleMode, ScaleMargins, SeriesMarkersOptions, TimeLabelMode, TooltipPosition,
+    Bar, BarConversionError, Candle, CloudPoint, Color, CrosshairCenter, CrosshairMode,
+    HistogramPoint, LinePoint, LineStyle, Marker, MarkerPosition, MarkerShape, MarkerZOrder,
+    OrderLineOptions, OrderSide, OrderType, PanelControlAction, PanelId, PanelResizeHandle,
+    PanelRole, PositionLineOptions, PriceFormat, PriceLineOptions, PriceScale, PriceScaleMode,
+    PriceSegment, ScaleMargins, SeriesMarkersOptions, TimeLabelMode, TooltipPosition,
+    VolumeProfileLevels, VolumeProfileOptions, VolumeProfileRange,
 };
 
 use time::OffsetDateTime;
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use super::types::{
     Color, CrosshairCenter, CrosshairMode, LineStyle, PriceScaleMode, ScaleMargins, TimeLabelMode,
     TooltipPosition,
@@ -175,6 +177,61 @@ impl Default for KineticScrollOptions {
     }
 }
 
+/// How an animated transition moves between its start and end.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Easing {
+    /// Constant speed
+    Linear,
+    /// Starts fast and slows into the end
+    EaseOut,
+    /// Speeds up, then slows down
+    EaseInOut,
+}
+
+impl Easing {
+    /// Maps linear progress in `0..=1` to eased progress.
+    pub fn apply(self, progress: f64) -> f64 {
+        let t = progress.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+        }
+    }
+}
+
+/// Animated view changes: programmatic scrolls and zooms of the time scale,
+/// and auto scaled price ranges following new data or a reset.
+///
+/// The chart advances animations as it draws, so the host keeps redrawing
+/// while `ChartApi::is_animating` is `true`.
+#[derive(Clone, Copy, Debug)]
+pub struct AnimationOptions {
+    /// Whether to animate at all; off makes every change immediate, which
+    /// saves redraws on low-power setups
+    pub enabled: bool,
+    /// Length of one transition
+    pub duration: Duration,
+    /// Shape of every transition
+    pub easing: Easing,
+}
+
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            duration: Duration::from_millis(300),
+            easing: Easing::EaseOut,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub enum TrackingModeExitMode {
     OnTouchEnd,
@@ -348,6 +405,7 @@ pub(crate) struct ChartOptions {
     pub handle_scroll: HandleScrollOptions,
     pub handle_scale: HandleScaleOptions,
     pub kinetic_scroll: KineticScrollOptions,
+    pub animation: AnimationOptions,
     pub tracking_mode: TrackingModeOptions,
     pub interaction_sensitivity: InteractionSensitivityOptions,
     pub tooltip: TooltipOptions,
@@ -373,6 +431,7 @@ impl Default for ChartOptions {
             handle_scroll: HandleScrollOptions::default(),
             handle_scale: HandleScaleOptions::default(),
             kinetic_scroll: KineticScrollOptions::default(),
+            animation: AnimationOptions::default(),
             tracking_mode: TrackingModeOptions::default(),
             interaction_sensitivity: InteractionSensitivityOptions::default(),
             tooltip: TooltipOptions::default(),
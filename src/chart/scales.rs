@@ -1,5 +1,6 @@
 use super::data::{PriceScaleState, Series, SeriesData, SeriesKind};
-use super::types::{Candle, LinePoint};
+use super::options::AnimationOptions;
+use super::types::{Candle, CloudPoint, LinePoint};
 use super::util::{candle_time, expand_range};
 
 #[derive(Clone, Copy, Debug)]
@@ -15,6 +16,7 @@ pub(crate) struct TimeScale {
     pub(crate) fix_right_edge: bool,
     pub(crate) right_offset: f64,
     pub(crate) right_offset_pixels: f64,
+    pub(crate) projected_max: f64,
     bar_time: f64,
 }
 
@@ -32,6 +34,7 @@ impl Default for TimeScale {
             fix_right_edge: false,
             right_offset: 0.0,
             right_offset_pixels: 0.0,
+            projected_max: 1.0,
             bar_time: 1.0,
         }
     }
@@ -41,6 +44,7 @@ impl TimeScale {
     pub(crate) fn recalculate(&mut self, series: &[Series]) {
         let mut min = f64::INFINITY;
         let mut max = f64::NEG_INFINITY;
+        let mut bar_max = f64::NEG_INFINITY;
         let mut has_data = false;
         let mut times: Vec<f64> = Vec::new();
 
@@ -52,6 +56,7 @@ impl TimeScale {
                             let time = candle_time(candle.time);
                             min = min.min(time);
                             max = max.max(time);
+                            bar_max = bar_max.max(time);
                             has_data = true;
                             times.push(time);
                         }
@@ -79,6 +84,17 @@ impl TimeScale {
                         }
                     }
                 }
+                SeriesKind::Cloud => {
+                    if let SeriesData::Cloud { data } = &item.data {
+                        for point in data {
+                            let time = candle_time(point.time);
+                            min = min.min(time);
+                            max = max.max(time);
+                            has_data = true;
+                            times.push(time);
+                        }
+                    }
+                }
             }
         }
 
@@ -87,6 +103,13 @@ impl TimeScale {
             return;
         }
 
+        // Points past the last candle (displaced indicators) do not move the
+        // last bar; they only widen the range the view may scroll into.
+        let projected_max = max;
+        if bar_max.is_finite() && bar_max > min {
+            max = bar_max;
+        }
+
         if (max - min).abs() < f64::EPSILON {
             max += 1.0;
         }
@@ -94,8 +117,9 @@ impl TimeScale {
         self.bar_time = average_bar_time(&mut times);
         self.min = min;
         self.max = max;
+        self.projected_max = projected_max.max(max);
         self.start = min;
-        self.end = max + self.effective_right_offset() * self.bar_time;
+        self.end = self.max_end();
     }
 
     pub(crate) fn visible_range(&self) -> f64 {
@@ -106,7 +130,7 @@ impl TimeScale {
         let range = self.visible_range();
         let mut start = self.start + delta;
         let mut end = self.end + delta;
-        let max_end = self.max + self.effective_right_offset() * self.bar_time();
+        let max_end = self.max_end();
         let (min_limit, max_limit) = self.pan_limits(range, max_end);
 
         if start < min_limit {
@@ -125,7 +149,7 @@ impl TimeScale {
 
     pub(crate) fn zoom_by(&mut self, factor: f64, anchor: f64) {
         let range = self.visible_range();
-        let max_end = self.max + self.effective_right_offset() * self.bar_time();
+        let max_end = self.max_end();
         let max_range = (max_end - self.min).max(1.0);
         let min_range = (max_range / 200.0).max(1.0);
 
@@ -203,6 +227,10 @@ impl TimeScale {
         (min_limit, max_limit)
     }
 
+    pub(crate) fn max_end(&self) -> f64 {
+        (self.max + self.effective_right_offset() * self.bar_time()).max(self.projected_max)
+    }
+
     pub(crate) fn effective_right_offset(&self) -> f64 {
         if self.right_offset_pixels > 0.0 {
             (self.right_offset_pixels / self.bar_spacing.max(1.0)).max(0.0)
@@ -219,6 +247,8 @@ pub(crate) fn update_price_scale_state(
     auto_scale: bool,
     mode: super::types::PriceScaleMode,
     base: f64,
+    animation: AnimationOptions,
+    data_revision: u64,
 ) {
     let (auto_min, auto_max) = match mode {
         super::types::PriceScaleMode::Logarithmic => {
@@ -236,10 +266,10 @@ pub(crate) fn update_price_scale_state(
     state.data_min = min;
     state.data_max = max;
     if auto_scale {
-        state.view_min = auto_min;
-        state.view_max = auto_max;
+        state.ease_to((auto_min, auto_max), animation, data_revision);
         state.auto = true;
     } else {
+        state.transition = None;
         state.auto = false;
     }
 }
@@ -307,6 +337,27 @@ pub(crate) fn data_range_line(data: &[LinePoint], start: f64, end: f64) -> Optio
     }
 }
 
+pub(crate) fn data_range_cloud(data: &[CloudPoint], start: f64, end: f64) -> Option<(f64, f64)> {
+    let mut min = f64::INFINITY;
+    let mut max = f64::NEG_INFINITY;
+    let mut has = false;
+
+    for point in data {
+        let time = candle_time(point.time);
+        if time >= start && time <= end {
+            min = min.min(point.value_a.min(point.value_b));
+            max = max.max(point.value_a.max(point.value_b));
+            has = true;
+        }
+    }
+
+    if has {
+        Some((min, max))
+    } else {
+        None
+    }
+}
+
 pub(crate) fn merge_range(current: &mut Option<(f64, f64)>, next: Option<(f64, f64)>) {
     if let Some((min, max)) = next {
         match current {
@@ -0,0 +1,174 @@
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+use super::api::ChartApi;
+use super::events::SubscriptionId;
+use super::util::candle_time;
+
+/// What a `ChartSyncGroup` keeps in step between its charts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChartSyncOptions {
+    /// Show the crosshair on the same time on every chart
+    pub crosshair: bool,
+    /// Scroll every chart to centre the same time
+    pub time_range: bool,
+    /// Also show the same time span everywhere, implying `time_range`.
+    /// Charts on different intervals end up with different bar spacings.
+    pub zoom: bool,
+}
+
+impl Default for ChartSyncOptions {
+    fn default() -> Self {
+        Self {
+            crosshair: true,
+            time_range: true,
+            zoom: false,
+        }
+    }
+}
+
+struct Member {
+    id: usize,
+    chart: ChartApi,
+    on_update: Rc<dyn Fn()>,
+    subscriptions: Vec<SubscriptionId>,
+}
+
+#[derive(Default)]
+struct SyncState {
+    options: ChartSyncOptions,
+    members: Vec<Member>,
+    next_id: usize,
+    /// Set while a change is being copied to the other charts, so the
+    /// events it raises there are not copied back.
+    syncing: bool,
+}
+
+/// Keeps the crosshair and visible range of several charts in step, such as
+/// one symbol on several intervals side by side. Charts are matched by
+/// time rather than by bar index, so the crosshair lands on the enclosing
+/// bar of a chart with a longer interval.
+///
+/// Panels inside one chart already share its time scale; a group does the
+/// same across `ChartApi` instances.
+#[derive(Clone, Default)]
+pub struct ChartSyncGroup {
+    state: Rc<RefCell<SyncState>>,
+}
+
+/// Runs `apply` on every member but `source`, then their update callbacks.
+fn propagate(
+    state: &Weak<RefCell<SyncState>>,
+    source: usize,
+    apply: impl Fn(&ChartApi, ChartSyncOptions),
+) {
+    let Some(state) = state.upgrade() else {
+        return;
+    };
+    let (options, targets) = {
+        let mut state = state.borrow_mut();
+        if state.syncing {
+            return;
+        }
+        state.syncing = true;
+        let targets: Vec<_> = state
+            .members
+            .iter()
+            .filter(|member| member.id != source)
+            .map(|member| (member.chart.clone(), member.on_update.clone()))
+            .collect();
+        (state.options, targets)
+    };
+    for (chart, on_update) in targets {
+        apply(&chart, options);
+        on_update();
+    }
+    state.borrow_mut().syncing = false;
+}
+
+impl ChartSyncGroup {
+    pub fn new(options: ChartSyncOptions) -> Self {
+        let group = Self::default();
+        group.state.borrow_mut().options = options;
+        group
+    }
+
+    pub fn options(&self) -> ChartSyncOptions {
+        self.state.borrow().options
+    }
+
+    pub fn set_options(&self, options: ChartSyncOptions) {
+        self.state.borrow_mut().options = options;
+    }
+
+    /// Adds `chart` to the group. `on_update` runs whenever the group has
+    /// changed the chart, typically to queue a redraw.
+    pub fn add(&self, chart: &ChartApi, on_update: impl Fn() + 'static) {
+        if self.contains(chart) {
+            return;
+        }
+        let id = {
+            let mut state = self.state.borrow_mut();
+            state.next_id += 1;
+            state.next_id
+        };
+        let weak = Rc::downgrade(&self.state);
+        let crosshair = chart.subscribe_crosshair_move({
+            let weak = weak.clone();
+            move |params| {
+                let time = params.point.and(params.time).map(candle_time);
+                propagate(&weak, id, |chart, options| {
+                    if !options.crosshair {
+                        return;
+                    }
+                    match time {
+                        Some(time) => {
+                            chart.set_crosshair_time(time);
+                        }
+                        None => chart.clear_crosshair(),
+                    }
+                });
+            }
+        });
+        let range = chart.subscribe_visible_time_range_change(move |from, to| {
+            propagate(&weak, id, |chart, options| {
+                if options.zoom {
+                    chart.set_visible_time_range(from, to);
+                } else if options.time_range {
+                    chart.scroll_to_time((from + to) / 2.0, false);
+                }
+            });
+        });
+        self.state.borrow_mut().members.push(Member {
+            id,
+            chart: chart.clone(),
+            on_update: Rc::new(on_update),
+            subscriptions: vec![crosshair, range],
+        });
+    }
+
+    /// Takes `chart` out of the group and drops its subscriptions.
+    pub fn remove(&self, chart: &ChartApi) {
+        let removed: Vec<Member> = {
+            let mut state = self.state.borrow_mut();
+            let (removed, kept) = std::mem::take(&mut state.members)
+                .into_iter()
+                .partition(|member| member.chart.same_chart(chart));
+            state.members = kept;
+            removed
+        };
+        for member in removed {
+            for id in member.subscriptions {
+                member.chart.unsubscribe(id);
+            }
+        }
+    }
+
+    pub fn contains(&self, chart: &ChartApi) -> bool {
+        self.state
+            .borrow()
+            .members
+            .iter()
+            .any(|member| member.chart.same_chart(chart))
+    }
+}
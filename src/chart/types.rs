@@ -1,6 +1,6 @@
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
-use time::OffsetDateTime;
+use time::{Duration, OffsetDateTime};
 
 /// Represents a single candlestick (OHLC) data point.
 ///
@@ -48,6 +48,21 @@ pub struct HistogramPoint {
     pub color: Option<Color>,
 }
 
+/// Represents a single point in a cloud (filled band) series.
+///
+/// The band between the two values is filled with the series' up color
+/// while `value_a` is at or above `value_b`, and with the down color
+/// otherwise. The fill switches color exactly where the two edges cross.
+#[derive(Clone, Debug)]
+pub struct CloudPoint {
+    /// The timestamp for this point
+    pub time: OffsetDateTime,
+    /// The first edge of the band
+    pub value_a: f64,
+    /// The second edge of the band
+    pub value_b: f64,
+}
+
 /// Result of a pan operation, indicating what was affected.
 #[derive(Clone, Copy, Debug)]
 pub struct PanResult {
@@ -98,7 +113,7 @@ pub struct Bar {
 ///
 /// Color components are typically in the range 0.0 to 1.0,
 /// where 0.0 is no intensity and 1.0 is full intensity.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Color {
     /// Red component (0.0 to 1.0)
     pub r: f64,
@@ -374,7 +389,7 @@ impl Default for PriceFormat {
 ///
 /// Price lines are horizontal lines that mark specific price levels
 /// with optional labels and styling.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct PriceLineOptions {
     /// The price level where the line should be drawn
     pub price: f64,
@@ -406,6 +421,9 @@ pub struct PriceLineOptions {
     pub axis_label_border_width: f64,
     /// Optional title text for the price line
     pub title: Option<String>,
+    /// Whether the line can be moved with the mouse, on the plot or by its
+    /// axis label
+    pub draggable: bool,
 }
 
 impl Default for PriceLineOptions {
@@ -426,10 +444,216 @@ impl Default for PriceLineOptions {
             axis_label_border_color: None,
             axis_label_border_width: 0.0,
             title: None,
+            draggable: false,
+        }
+    }
+}
+
+/// A horizontal price level limited to a time span.
+///
+/// Segments are drawn like price lines but only between `start` and `end`,
+/// which suits levels that change per session such as pivot points. The label
+/// uses the axis label styling of `options` and sits at the segment's right end.
+#[derive(Clone, Debug)]
+pub struct PriceSegment {
+    /// The time where the segment begins
+    pub start: OffsetDateTime,
+    /// The time where the segment ends
+    pub end: OffsetDateTime,
+    /// Price, line and label styling for the segment
+    pub options: PriceLineOptions,
+}
+
+/// An open position drawn at its average entry price with its size and
+/// unrealized profit or loss against the series' last close.
+#[derive(Clone, Debug)]
+pub struct PositionLineOptions {
+    /// Average entry price
+    pub price: f64,
+    /// Position size; negative for a short position
+    pub quantity: f64,
+    /// Profit or loss per unit of quantity for a price move of one
+    pub point_value: f64,
+    /// Line color; the profit and loss badge is colored like up and down
+    /// candles
+    pub color: Color,
+    pub line_width: f64,
+    pub line_style: LineStyle,
+    /// Optional text shown before the size, e.g. the account
+    pub title: Option<String>,
+    /// Whether the entry price is labelled on the price axis
+    pub axis_label_visible: bool,
+}
+
+impl Default for PositionLineOptions {
+    fn default() -> Self {
+        Self {
+            price: 0.0,
+            quantity: 0.0,
+            point_value: 1.0,
+            color: Color::new(0.2, 0.5, 0.9),
+            line_width: 1.0,
+            line_style: LineStyle::Solid,
+            title: None,
+            axis_label_visible: true,
         }
     }
 }
 
+/// Direction of a working order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+/// Kind of a working order, shown as a short badge on its line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OrderType {
+    Limit,
+    Stop,
+    StopLimit,
+    TakeProfit,
+}
+
+impl OrderType {
+    pub fn badge(self) -> &'static str {
+        match self {
+            OrderType::Limit => "LMT",
+            OrderType::Stop => "STP",
+            OrderType::StopLimit => "STP LMT",
+            OrderType::TakeProfit => "TP",
+        }
+    }
+}
+
+/// A working order drawn at its price with side, type and quantity badges
+/// and an optional cancel button.
+#[derive(Clone, Debug)]
+pub struct OrderLineOptions {
+    /// Order price
+    pub price: f64,
+    pub side: OrderSide,
+    pub order_type: OrderType,
+    pub quantity: f64,
+    /// Line and badge color; `None` uses the up candle color for buys and
+    /// the down candle color for sells
+    pub color: Option<Color>,
+    pub line_width: f64,
+    pub line_style: LineStyle,
+    /// Optional text shown after the badges
+    pub title: Option<String>,
+    /// Whether the line shows a cancel button, reported through
+    /// `ChartApi::subscribe_order_cancel`
+    pub cancellable: bool,
+    /// Whether the order price is labelled on the price axis
+    pub axis_label_visible: bool,
+}
+
+impl Default for OrderLineOptions {
+    fn default() -> Self {
+        Self {
+            price: 0.0,
+            side: OrderSide::Buy,
+            order_type: OrderType::Limit,
+            quantity: 0.0,
+            color: None,
+            line_width: 1.0,
+            line_style: LineStyle::Dashed,
+            title: None,
+            cancellable: true,
+            axis_label_visible: true,
+        }
+    }
+}
+
+/// Selects which bars a volume profile aggregates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VolumeProfileRange {
+    /// The bars currently in view, drawn against the price axis
+    Visible,
+    /// The bars between two times, drawn inside that span
+    Fixed {
+        /// First bar time included in the profile
+        start: OffsetDateTime,
+        /// Last bar time included in the profile
+        end: OffsetDateTime,
+    },
+    /// One profile per session, drawn inside each session's span
+    Session {
+        /// Session length; sessions are counted from the Unix epoch, so a
+        /// one-day session starts at UTC midnight
+        length: Duration,
+    },
+}
+
+/// Configuration options for a volume-by-price profile.
+///
+/// Row height is always a whole multiple of the series'
+/// `PriceFormat::Price { min_move }`, so rows line up with tradable prices.
+#[derive(Clone, Debug)]
+pub struct VolumeProfileOptions {
+    /// Bars aggregated into the profile
+    pub range: VolumeProfileRange,
+    /// Upper bound on the number of price rows
+    pub max_rows: usize,
+    /// Share of the total volume the value area should hold, in percent
+    pub value_area_percent: f64,
+    /// Length of the largest row as a fraction of the width the profile is drawn in
+    pub width: f64,
+    /// Color for volume of bars that closed at or above their open
+    pub up_color: Color,
+    /// Color for volume of bars that closed below their open
+    pub down_color: Color,
+    /// Row opacity inside the value area (0.0 to 1.0)
+    pub value_area_alpha: f64,
+    /// Row opacity outside the value area (0.0 to 1.0)
+    pub alpha: f64,
+    /// Color of the point of control line
+    pub poc_color: Color,
+    /// Color of the value area high and low lines
+    pub value_area_line_color: Color,
+    /// Whether POC, VAH and VAL are labelled with their prices
+    pub labels_visible: bool,
+}
+
+impl Default for VolumeProfileOptions {
+    fn default() -> Self {
+        Self {
+            range: VolumeProfileRange::Visible,
+            max_rows: 48,
+            value_area_percent: 70.0,
+            width: 0.3,
+            up_color: Color::new(0.15, 0.65, 0.6),
+            down_color: Color::new(0.94, 0.33, 0.31),
+            value_area_alpha: 0.55,
+            alpha: 0.25,
+            poc_color: Color::new(0.96, 0.65, 0.14),
+            value_area_line_color: Color::new(0.6, 0.65, 0.75),
+            labels_visible: true,
+        }
+    }
+}
+
+/// Key levels of one computed volume profile.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VolumeProfileLevels {
+    /// Time of the first bar in the profile
+    pub start: OffsetDateTime,
+    /// Time of the last bar in the profile
+    pub end: OffsetDateTime,
+    /// Point of control: the middle of the row with the most volume
+    pub poc: f64,
+    /// Upper edge of the value area
+    pub value_area_high: f64,
+    /// Lower edge of the value area
+    pub value_area_low: f64,
+    /// Share of the total volume inside the value area, in percent
+    pub value_area_percent: f64,
+    /// Total volume aggregated by the profile
+    pub total_volume: f64,
+}
+
 /// A marker that can be placed on a series to highlight specific points.
 ///
 /// Markers are used to draw attention to specific data points, events,
@@ -1,7 +1,9 @@
 use cairo::Context;
 use time::OffsetDateTime;
 
-use super::types::{Candle, HistogramPoint, LinePoint, LineStyle, PriceScaleMode, ScaleMargins};
+use super::types::{
+    Candle, CloudPoint, HistogramPoint, LinePoint, LineStyle, PriceScaleMode, ScaleMargins,
+};
 
 pub(crate) fn map_price_to_y(price: f64, min: f64, max: f64, top: f64, height: f64) -> f64 {
     let norm = (price - min) / (max - min);
@@ -197,6 +199,22 @@ pub(crate) fn visible_histogram_points(
     visible
 }
 
+pub(crate) fn visible_cloud_points(data: &[CloudPoint], start: f64, end: f64) -> Vec<&CloudPoint> {
+    let mut visible: Vec<&CloudPoint> = data
+        .iter()
+        .filter(|point| {
+            let time = candle_time(point.time);
+            time >= start && time <= end
+        })
+        .collect();
+
+    if visible.is_empty() {
+        visible = data.iter().collect();
+    }
+
+    visible
+}
+
 pub(crate) fn histogram_range(points: &[&HistogramPoint]) -> Option<(f64, f64)> {
     if points.is_empty() {
         return None;
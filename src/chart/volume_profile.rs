@@ -0,0 +1,264 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::types::{
+    Candle, HistogramPoint, PriceFormat, VolumeProfileLevels, VolumeProfileOptions,
+    VolumeProfileRange,
+};
+use super::util::candle_time;
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ProfileRow {
+    pub(crate) low: f64,
+    pub(crate) high: f64,
+    pub(crate) up: f64,
+    pub(crate) down: f64,
+}
+
+impl ProfileRow {
+    pub(crate) fn total(&self) -> f64 {
+        self.up + self.down
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Profile {
+    /// Time span the profile is drawn in, in unix seconds.
+    pub(crate) span: (f64, f64),
+    pub(crate) rows: Vec<ProfileRow>,
+    pub(crate) poc: usize,
+    /// Inclusive row range of the value area.
+    pub(crate) value_area: (usize, usize),
+    pub(crate) levels: VolumeProfileLevels,
+}
+
+/// Bar index range and data revision a set of profiles was built from. When
+/// a pan leaves these unchanged the cached profiles are reused as is.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct ProfileKey {
+    pub(crate) first: usize,
+    pub(crate) last: usize,
+    pub(crate) revision: u64,
+    pub(crate) min_move: Option<u64>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct VolumeProfileState {
+    pub(crate) id: usize,
+    pub(crate) candle_series: usize,
+    pub(crate) volume_series: usize,
+    pub(crate) options: VolumeProfileOptions,
+    pub(crate) cache: RefCell<Option<(ProfileKey, Rc<Vec<Profile>>)>>,
+}
+
+/// Index range of the candles a profile aggregates, before splitting into
+/// sessions, along with the session length in seconds if any.
+pub(crate) fn profile_bar_range(
+    candles: &[Candle],
+    range: VolumeProfileRange,
+    start_time: f64,
+    end_time: f64,
+) -> (usize, usize, Option<f64>) {
+    let index_at = |time: f64| candles.partition_point(|candle| candle_time(candle.time) < time);
+    let index_after =
+        |time: f64| candles.partition_point(|candle| candle_time(candle.time) <= time);
+    match range {
+        VolumeProfileRange::Visible => (index_at(start_time), index_after(end_time), None),
+        VolumeProfileRange::Fixed { start, end } => {
+            let (start, end) = (candle_time(start.min(end)), candle_time(start.max(end)));
+            (index_at(start), index_after(end), None)
+        }
+        VolumeProfileRange::Session { length } => {
+            let length = (length.whole_seconds() as f64).max(60.0);
+            let first_session = (start_time / length).floor() * length;
+            let last_session = (end_time / length).floor() * length + length;
+            (
+                index_at(first_session),
+                index_at(last_session),
+                Some(length),
+            )
+        }
+    }
+}
+
+pub(crate) fn compute_profiles(
+    candles: &[Candle],
+    volumes: &[HistogramPoint],
+    options: &VolumeProfileOptions,
+    format: &PriceFormat,
+    session: Option<f64>,
+) -> Vec<Profile> {
+    let min_move = match format {
+        PriceFormat::Price { min_move, .. } if *min_move > 0.0 => Some(*min_move),
+        _ => None,
+    };
+    let Some(length) = session else {
+        let span = match (candles.first(), candles.last()) {
+            (Some(first), Some(last)) => (candle_time(first.time), candle_time(last.time)),
+            _ => return Vec::new(),
+        };
+        return compute_profile(candles, volumes, options, min_move, span)
+            .into_iter()
+            .collect();
+    };
+
+    let mut profiles = Vec::new();
+    let mut start = 0;
+    while start < candles.len() {
+        let session_start = (candle_time(candles[start].time) / length).floor() * length;
+        let end = start
+            + candles[start..]
+                .partition_point(|candle| candle_time(candle.time) < session_start + length);
+        let span = (session_start, session_start + length);
+        profiles.extend(compute_profile(
+            &candles[start..end],
+            volumes,
+            options,
+            min_move,
+            span,
+        ));
+        start = end;
+    }
+    profiles
+}
+
+fn compute_profile(
+    candles: &[Candle],
+    volumes: &[HistogramPoint],
+    options: &VolumeProfileOptions,
+    min_move: Option<f64>,
+    span: (f64, f64),
+) -> Option<Profile> {
+    let (first, last) = (candles.first()?, candles.last()?);
+    let bars = paired_volumes(candles, volumes);
+    let (low, high) = bars.iter().fold(
+        (f64::INFINITY, f64::NEG_INFINITY),
+        |(low, high), (candle, _)| (low.min(candle.low), high.max(candle.high)),
+    );
+    if !low.is_finite() || !high.is_finite() {
+        return None;
+    }
+
+    let max_rows = options.max_rows.max(1);
+    let span_price = (high - low).max(f64::EPSILON);
+    let row_size = match min_move {
+        Some(step) => step * (span_price / step / max_rows as f64).ceil().max(1.0),
+        None => span_price / max_rows as f64,
+    };
+    let origin = (low / row_size).floor() * row_size;
+    let row_count = (((high - origin) / row_size).floor() as usize + 1).min(max_rows + 1);
+    let mut rows: Vec<ProfileRow> = (0..row_count)
+        .map(|idx| ProfileRow {
+            low: origin + idx as f64 * row_size,
+            high: origin + (idx + 1) as f64 * row_size,
+            up: 0.0,
+            down: 0.0,
+        })
+        .collect();
+    let row_of =
+        |price: f64| (((price - origin) / row_size).floor().max(0.0) as usize).min(row_count - 1);
+
+    // Each bar's volume is spread evenly over its high-low range.
+    for (candle, volume) in &bars {
+        let up = candle.close >= candle.open;
+        let range = candle.high - candle.low;
+        let (first_row, last_row) = (row_of(candle.low), row_of(candle.high));
+        for (idx, row) in rows
+            .iter_mut()
+            .enumerate()
+            .take(last_row + 1)
+            .skip(first_row)
+        {
+            let share = if range <= f64::EPSILON || first_row == last_row {
+                if idx == first_row {
+                    *volume
+                } else {
+                    0.0
+                }
+            } else {
+                let overlap = candle.high.min(row.high) - candle.low.max(row.low);
+                volume * overlap.max(0.0) / range
+            };
+            if up {
+                row.up += share;
+            } else {
+                row.down += share;
+            }
+        }
+    }
+
+    let total: f64 = rows.iter().map(ProfileRow::total).sum();
+    if total <= 0.0 {
+        return None;
+    }
+    let poc = rows
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total().total_cmp(&b.1.total()))
+        .map(|(idx, _)| idx)
+        .unwrap_or(0);
+
+    // Grow the value area from the POC, always taking the heavier neighbour.
+    let target = total * options.value_area_percent.clamp(0.0, 100.0) / 100.0;
+    let (mut lower, mut upper) = (poc, poc);
+    let mut inside = rows[poc].total();
+    while inside < target && (lower > 0 || upper + 1 < rows.len()) {
+        let below = lower.checked_sub(1).map(|idx| rows[idx].total());
+        let above = rows.get(upper + 1).map(ProfileRow::total);
+        match (below, above) {
+            (Some(below), Some(above)) if above >= below => {
+                upper += 1;
+                inside += above;
+            }
+            (Some(below), _) => {
+                lower -= 1;
+                inside += below;
+            }
+            (None, Some(above)) => {
+                upper += 1;
+                inside += above;
+            }
+            (None, None) => break,
+        }
+    }
+
+    Some(Profile {
+        span,
+        levels: VolumeProfileLevels {
+            start: first.time,
+            end: last.time,
+            poc: (rows[poc].low + rows[poc].high) / 2.0,
+            value_area_high: rows[upper].high,
+            value_area_low: rows[lower].low,
+            value_area_percent: inside / total * 100.0,
+            total_volume: total,
+        },
+        rows,
+        poc,
+        value_area: (lower, upper),
+    })
+}
+
+/// Pairs each candle with the volume point at the same time; both inputs are
+/// sorted, so a single merge pass is enough.
+fn paired_volumes<'a>(candles: &'a [Candle], volumes: &[HistogramPoint]) -> Vec<(&'a Candle, f64)> {
+    let Some(first) = candles.first() else {
+        return Vec::new();
+    };
+    let mut volume_idx = volumes.partition_point(|point| point.time < first.time);
+    candles
+        .iter()
+        .filter_map(|candle| {
+            while volumes
+                .get(volume_idx)
+                .is_some_and(|point| point.time < candle.time)
+            {
+                volume_idx += 1;
+            }
+            volumes
+                .get(volume_idx)
+                .filter(|point| point.time == candle.time && point.value > 0.0)
+                .map(|point| (candle, point.value))
+        })
+        .collect()
+}
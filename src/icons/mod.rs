@@ -163,6 +163,10 @@ pub fn draw_svg_icon(cr: &Context, icon: IconName, x: f64, y: f64, size: f64, co
     draw_svg_source(cr, svg_source(icon), x, y, size, color);
 }
 
+pub fn is_marker_icon(text: &str) -> bool {
+    marker_svg_source(text).is_some()
+}
+
 pub fn draw_marker_svg_icon(
     cr: &Context,
     icon_text: &str,
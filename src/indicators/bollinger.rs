@@ -1,4 +1,5 @@
 use crate::chart::{Candle, LinePoint};
+use time::OffsetDateTime;
 
 pub struct BollingerBands {
     pub middle: Vec<LinePoint>,
@@ -7,6 +8,15 @@ pub struct BollingerBands {
 }
 
 pub fn compute_bollinger(candles: &[Candle], period: usize, mult: f64) -> BollingerBands {
+    let values: Vec<(OffsetDateTime, f64)> = candles.iter().map(|c| (c.time, c.close)).collect();
+    compute_bollinger_for_times(&values, period, mult)
+}
+
+pub fn compute_bollinger_for_times(
+    values: &[(OffsetDateTime, f64)],
+    period: usize,
+    mult: f64,
+) -> BollingerBands {
     if period == 0 {
         return BollingerBands {
             middle: Vec::new(),
@@ -14,13 +24,13 @@ pub fn compute_bollinger(candles: &[Candle], period: usize, mult: f64) -> Bollin
             lower: Vec::new(),
         };
     }
-    let mut middle = Vec::with_capacity(candles.len());
-    let mut upper = Vec::with_capacity(candles.len());
-    let mut lower = Vec::with_capacity(candles.len());
+    let mut middle = Vec::with_capacity(values.len());
+    let mut upper = Vec::with_capacity(values.len());
+    let mut lower = Vec::with_capacity(values.len());
 
     let mut window: Vec<f64> = Vec::with_capacity(period);
-    for candle in candles {
-        window.push(candle.close);
+    for (time, value) in values {
+        window.push(*value);
         if window.len() > period {
             window.remove(0);
         }
@@ -32,15 +42,15 @@ pub fn compute_bollinger(candles: &[Candle], period: usize, mult: f64) -> Bollin
             let up = mean + std * mult;
             let down = mean - std * mult;
             middle.push(LinePoint {
-                time: candle.time,
+                time: *time,
                 value: mean,
             });
             upper.push(LinePoint {
-                time: candle.time,
+                time: *time,
                 value: up,
             });
             lower.push(LinePoint {
-                time: candle.time,
+                time: *time,
                 value: down,
             });
         }
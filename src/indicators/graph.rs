@@ -0,0 +1,494 @@
+use crate::chart::{Candle, HistogramPoint, LinePoint};
+use crate::indicators::bollinger::compute_bollinger_for_times;
+use crate::indicators::ema::compute_ema_for_times;
+use crate::indicators::macd::compute_macd_for_times;
+use crate::indicators::obv::compute_obv_for_times;
+use crate::indicators::rsi::compute_rsi_for_times;
+use crate::indicators::sma::compute_sma_for_times;
+use std::collections::HashMap;
+use std::fmt;
+use time::OffsetDateTime;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct NodeId(pub usize);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PriceField {
+    Open,
+    High,
+    Low,
+    Close,
+    Hl2,
+    Hlc3,
+    Ohlc4,
+}
+
+impl PriceField {
+    pub fn all() -> &'static [PriceField] {
+        &[
+            PriceField::Close,
+            PriceField::Open,
+            PriceField::High,
+            PriceField::Low,
+            PriceField::Hl2,
+            PriceField::Hlc3,
+            PriceField::Ohlc4,
+        ]
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PriceField::Open => "Open",
+            PriceField::High => "High",
+            PriceField::Low => "Low",
+            PriceField::Close => "Close",
+            PriceField::Hl2 => "HL/2",
+            PriceField::Hlc3 => "HLC/3",
+            PriceField::Ohlc4 => "OHLC/4",
+        }
+    }
+
+    fn value(self, candle: &Candle) -> f64 {
+        match self {
+            PriceField::Open => candle.open,
+            PriceField::High => candle.high,
+            PriceField::Low => candle.low,
+            PriceField::Close => candle.close,
+            PriceField::Hl2 => (candle.high + candle.low) / 2.0,
+            PriceField::Hlc3 => (candle.high + candle.low + candle.close) / 3.0,
+            PriceField::Ohlc4 => (candle.open + candle.high + candle.low + candle.close) / 4.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IndicatorSource {
+    Price(PriceField),
+    Node { id: NodeId, output: usize },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Study {
+    Sma {
+        period: usize,
+    },
+    Ema {
+        period: usize,
+    },
+    Rsi {
+        period: usize,
+    },
+    Bollinger {
+        period: usize,
+        mult: f64,
+    },
+    Macd {
+        fast: usize,
+        slow: usize,
+        signal: usize,
+    },
+    Obv,
+}
+
+impl Study {
+    pub fn label(&self) -> String {
+        match self {
+            Study::Sma { period } => format!("SMA({period})"),
+            Study::Ema { period } => format!("EMA({period})"),
+            Study::Rsi { period } => format!("RSI({period})"),
+            Study::Bollinger { period, mult } => format!("BB({period}, {mult})"),
+            Study::Macd { fast, slow, signal } => format!("MACD({fast}, {slow}, {signal})"),
+            Study::Obv => "OBV".to_string(),
+        }
+    }
+
+    pub fn output_names(&self) -> &'static [&'static str] {
+        match self {
+            Study::Sma { .. } | Study::Ema { .. } | Study::Rsi { .. } | Study::Obv => &[""],
+            Study::Bollinger { .. } => &["Basis", "Upper", "Lower"],
+            Study::Macd { .. } => &["MACD", "Signal", "Histogram"],
+        }
+    }
+
+    /// Whether output `output` is drawn as bars rather than a line.
+    pub fn is_histogram(&self, output: usize) -> bool {
+        matches!(self, Study::Macd { .. }) && output == 2
+    }
+
+    fn compute(
+        &self,
+        values: &[(OffsetDateTime, f64)],
+        volumes: &HashMap<OffsetDateTime, f64>,
+    ) -> Vec<Vec<LinePoint>> {
+        match *self {
+            Study::Sma { period } => vec![compute_sma_for_times(values, period)],
+            Study::Ema { period } => vec![compute_ema_for_times(values, period)],
+            Study::Rsi { period } => vec![compute_rsi_for_times(values, period)],
+            Study::Bollinger { period, mult } => {
+                let bands = compute_bollinger_for_times(values, period, mult);
+                vec![bands.middle, bands.upper, bands.lower]
+            }
+            Study::Macd { fast, slow, signal } => {
+                let macd = compute_macd_for_times(values, fast, slow, signal);
+                let histogram = macd
+                    .histogram
+                    .into_iter()
+                    .map(|point| LinePoint {
+                        time: point.time,
+                        value: point.value,
+                    })
+                    .collect();
+                vec![macd.macd, macd.signal, histogram]
+            }
+            Study::Obv => vec![compute_obv_for_times(values, volumes)],
+        }
+    }
+}
+
+pub struct IndicatorNode {
+    pub id: NodeId,
+    pub study: Study,
+    pub source: IndicatorSource,
+    pub outputs: Vec<Vec<LinePoint>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GraphError {
+    UnknownNode(NodeId),
+    UnknownOutput { id: NodeId, output: usize },
+    Cycle(NodeId),
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::UnknownNode(id) => write!(f, "indicator #{} does not exist", id.0),
+            GraphError::UnknownOutput { id, output } => {
+                write!(f, "indicator #{} has no output {}", id.0, output)
+            }
+            GraphError::Cycle(id) => {
+                write!(f, "indicator #{} would depend on its own output", id.0)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GraphError {}
+
+#[derive(Default)]
+pub struct IndicatorGraph {
+    nodes: Vec<IndicatorNode>,
+    next_id: usize,
+}
+
+impl IndicatorGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, study: Study, source: IndicatorSource) -> Result<NodeId, GraphError> {
+        self.check_source(source)?;
+        let id = NodeId(self.next_id);
+        self.next_id += 1;
+        self.nodes.push(IndicatorNode {
+            id,
+            study,
+            source,
+            outputs: vec![Vec::new(); study.output_names().len()],
+        });
+        Ok(id)
+    }
+
+    pub fn set_source(&mut self, id: NodeId, source: IndicatorSource) -> Result<(), GraphError> {
+        self.check_source(source)?;
+        if let IndicatorSource::Node { id: upstream, .. } = source {
+            if upstream == id || self.depends_on(upstream, id) {
+                return Err(GraphError::Cycle(id));
+            }
+        }
+        let node = self.node_mut(id).ok_or(GraphError::UnknownNode(id))?;
+        node.source = source;
+        Ok(())
+    }
+
+    pub fn remove(&mut self, id: NodeId) -> Vec<NodeId> {
+        if self.node(id).is_none() {
+            return Vec::new();
+        }
+        let mut removed = vec![id];
+        let mut idx = 0;
+        while idx < removed.len() {
+            let current = removed[idx];
+            for node in &self.nodes {
+                if let IndicatorSource::Node { id: upstream, .. } = node.source {
+                    if upstream == current && !removed.contains(&node.id) {
+                        removed.push(node.id);
+                    }
+                }
+            }
+            idx += 1;
+        }
+        self.nodes.retain(|node| !removed.contains(&node.id));
+        removed
+    }
+
+    pub fn node(&self, id: NodeId) -> Option<&IndicatorNode> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    pub fn nodes(&self) -> &[IndicatorNode] {
+        &self.nodes
+    }
+
+    pub fn describe_source(&self, source: IndicatorSource) -> String {
+        match source {
+            IndicatorSource::Price(field) => field.label().to_string(),
+            IndicatorSource::Node { id, output } => match self.node(id) {
+                Some(node) => {
+                    let name = node.study.output_names().get(output).copied();
+                    match name {
+                        Some(name) if !name.is_empty() => {
+                            format!("{} {}", node.study.label(), name)
+                        }
+                        _ => node.study.label(),
+                    }
+                }
+                None => format!("#{}", id.0),
+            },
+        }
+    }
+
+    pub fn evaluation_order(&self) -> Result<Vec<NodeId>, GraphError> {
+        let mut order: Vec<NodeId> = Vec::with_capacity(self.nodes.len());
+        let mut pending: Vec<&IndicatorNode> = self.nodes.iter().collect();
+        while !pending.is_empty() {
+            let before = pending.len();
+            pending.retain(|node| match node.source {
+                IndicatorSource::Node { id, .. } if !order.contains(&id) => true,
+                _ => {
+                    order.push(node.id);
+                    false
+                }
+            });
+            if pending.len() == before {
+                return Err(GraphError::Cycle(pending[0].id));
+            }
+        }
+        Ok(order)
+    }
+
+    pub fn recompute(
+        &mut self,
+        candles: &[Candle],
+        volumes: &[HistogramPoint],
+    ) -> Result<(), GraphError> {
+        let order = self.evaluation_order()?;
+        let volumes: HashMap<OffsetDateTime, f64> = volumes
+            .iter()
+            .map(|point| (point.time, point.value))
+            .collect();
+        let mut computed: HashMap<NodeId, Vec<Vec<LinePoint>>> = HashMap::new();
+        for id in order {
+            let (study, source) = match self.node(id) {
+                Some(node) => (node.study, node.source),
+                None => continue,
+            };
+            let values: Vec<(OffsetDateTime, f64)> = match source {
+                IndicatorSource::Price(field) => candles
+                    .iter()
+                    .map(|candle| (candle.time, field.value(candle)))
+                    .collect(),
+                IndicatorSource::Node {
+                    id: upstream,
+                    output,
+                } => computed
+                    .get(&upstream)
+                    .and_then(|outputs| outputs.get(output))
+                    .ok_or(GraphError::UnknownOutput {
+                        id: upstream,
+                        output,
+                    })?
+                    .iter()
+                    .map(|point| (point.time, point.value))
+                    .collect(),
+            };
+            computed.insert(id, study.compute(&values, &volumes));
+        }
+        for node in &mut self.nodes {
+            if let Some(outputs) = computed.remove(&node.id) {
+                node.outputs = outputs;
+            }
+        }
+        Ok(())
+    }
+
+    fn node_mut(&mut self, id: NodeId) -> Option<&mut IndicatorNode> {
+        self.nodes.iter_mut().find(|node| node.id == id)
+    }
+
+    fn check_source(&self, source: IndicatorSource) -> Result<(), GraphError> {
+        if let IndicatorSource::Node { id, output } = source {
+            let node = self.node(id).ok_or(GraphError::UnknownNode(id))?;
+            if output >= node.study.output_names().len() {
+                return Err(GraphError::UnknownOutput { id, output });
+            }
+        }
+        Ok(())
+    }
+
+    fn depends_on(&self, id: NodeId, ancestor: NodeId) -> bool {
+        let mut current = id;
+        let mut steps = 0;
+        while let Some(node) = self.node(current) {
+            match node.source {
+                IndicatorSource::Node { id: upstream, .. } => {
+                    if upstream == ancestor {
+                        return true;
+                    }
+                    current = upstream;
+                }
+                IndicatorSource::Price(_) => return false,
+            }
+            steps += 1;
+            if steps > self.nodes.len() {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Duration;
+
+    const CLOSE: IndicatorSource = IndicatorSource::Price(PriceField::Close);
+
+    fn output_of(id: NodeId) -> IndicatorSource {
+        IndicatorSource::Node { id, output: 0 }
+    }
+
+    fn candles(closes: &[f64]) -> Vec<Candle> {
+        closes
+            .iter()
+            .enumerate()
+            .map(|(idx, &close)| Candle {
+                time: OffsetDateTime::UNIX_EPOCH + Duration::minutes(idx as i64),
+                open: close,
+                high: close,
+                low: close,
+                close,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn evaluation_order_puts_sources_first() {
+        let mut graph = IndicatorGraph::new();
+        let first = graph.add(Study::Sma { period: 2 }, CLOSE).unwrap();
+        let second = graph.add(Study::Ema { period: 3 }, CLOSE).unwrap();
+        let third = graph.add(Study::Rsi { period: 2 }, CLOSE).unwrap();
+        // Chain them backwards, so insertion order is not a valid order.
+        graph.set_source(second, output_of(third)).unwrap();
+        graph.set_source(first, output_of(second)).unwrap();
+        assert_eq!(graph.evaluation_order().unwrap(), [third, second, first]);
+    }
+
+    #[test]
+    fn set_source_rejects_a_self_loop() {
+        let mut graph = IndicatorGraph::new();
+        let node = graph.add(Study::Sma { period: 2 }, CLOSE).unwrap();
+        assert_eq!(
+            graph.set_source(node, output_of(node)),
+            Err(GraphError::Cycle(node))
+        );
+        assert_eq!(graph.node(node).unwrap().source, CLOSE);
+    }
+
+    #[test]
+    fn set_source_rejects_an_indirect_loop() {
+        let mut graph = IndicatorGraph::new();
+        let first = graph.add(Study::Sma { period: 2 }, CLOSE).unwrap();
+        let second = graph
+            .add(Study::Ema { period: 2 }, output_of(first))
+            .unwrap();
+        let third = graph
+            .add(Study::Rsi { period: 2 }, output_of(second))
+            .unwrap();
+        assert_eq!(
+            graph.set_source(first, output_of(third)),
+            Err(GraphError::Cycle(first))
+        );
+        assert!(graph.evaluation_order().is_ok());
+    }
+
+    #[test]
+    fn remove_takes_dependent_nodes_along() {
+        let mut graph = IndicatorGraph::new();
+        let first = graph.add(Study::Sma { period: 2 }, CLOSE).unwrap();
+        let second = graph
+            .add(Study::Ema { period: 2 }, output_of(first))
+            .unwrap();
+        let third = graph
+            .add(Study::Rsi { period: 2 }, output_of(second))
+            .unwrap();
+        let other = graph.add(Study::Obv, CLOSE).unwrap();
+        assert_eq!(graph.remove(first), [first, second, third]);
+        let left: Vec<NodeId> = graph.nodes().iter().map(|node| node.id).collect();
+        assert_eq!(left, [other]);
+    }
+
+    #[test]
+    fn recompute_feeds_one_output_into_the_next_node() {
+        let mut graph = IndicatorGraph::new();
+        let first = graph.add(Study::Sma { period: 2 }, CLOSE).unwrap();
+        let second = graph
+            .add(Study::Sma { period: 2 }, output_of(first))
+            .unwrap();
+        graph
+            .recompute(&candles(&[1.0, 2.0, 3.0, 4.0, 5.0]), &[])
+            .unwrap();
+        let values = |id| -> Vec<f64> {
+            graph.node(id).unwrap().outputs[0]
+                .iter()
+                .map(|point| point.value)
+                .collect()
+        };
+        assert_eq!(values(first), [1.5, 2.5, 3.5, 4.5]);
+        assert_eq!(values(second), [2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn check_source_rejects_a_missing_output() {
+        let mut graph = IndicatorGraph::new();
+        let bands = graph
+            .add(
+                Study::Bollinger {
+                    period: 2,
+                    mult: 2.0,
+                },
+                CLOSE,
+            )
+            .unwrap();
+        let source = IndicatorSource::Node {
+            id: bands,
+            output: 3,
+        };
+        assert_eq!(
+            graph.add(Study::Sma { period: 2 }, source),
+            Err(GraphError::UnknownOutput {
+                id: bands,
+                output: 3
+            })
+        );
+        let node = graph.add(Study::Sma { period: 2 }, CLOSE).unwrap();
+        assert_eq!(
+            graph.set_source(node, source),
+            Err(GraphError::UnknownOutput {
+                id: bands,
+                output: 3
+            })
+        );
+    }
+}
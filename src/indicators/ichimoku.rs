@@ -0,0 +1,126 @@
+use crate::chart::{Candle, CloudPoint, LinePoint};
+use time::{Duration, OffsetDateTime};
+
+pub struct IchimokuSeries {
+    pub tenkan: Vec<LinePoint>,
+    pub kijun: Vec<LinePoint>,
+    pub senkou_a: Vec<LinePoint>,
+    pub senkou_b: Vec<LinePoint>,
+    pub chikou: Vec<LinePoint>,
+    pub cloud: Vec<CloudPoint>,
+}
+
+pub fn compute_ichimoku(
+    candles: &[Candle],
+    tenkan_period: usize,
+    kijun_period: usize,
+    senkou_b_period: usize,
+    displacement: usize,
+) -> IchimokuSeries {
+    let mut series = IchimokuSeries {
+        tenkan: Vec::new(),
+        kijun: Vec::new(),
+        senkou_a: Vec::new(),
+        senkou_b: Vec::new(),
+        chikou: Vec::new(),
+        cloud: Vec::new(),
+    };
+    if candles.is_empty() || tenkan_period == 0 || kijun_period == 0 || senkou_b_period == 0 {
+        return series;
+    }
+
+    let interval = bar_interval(candles);
+    // Senkou spans are plotted `displacement` bars ahead, which runs past the
+    // last candle; those bars get timestamps extrapolated from the interval.
+    let time_at = |index: usize| -> OffsetDateTime {
+        match candles.get(index) {
+            Some(candle) => candle.time,
+            None => {
+                let last = &candles[candles.len() - 1];
+                last.time + interval * (index + 1 - candles.len()) as i32
+            }
+        }
+    };
+
+    for (idx, candle) in candles.iter().enumerate() {
+        let tenkan = midpoint(candles, idx, tenkan_period);
+        let kijun = midpoint(candles, idx, kijun_period);
+        let span_b = midpoint(candles, idx, senkou_b_period);
+
+        if let Some(value) = tenkan {
+            series.tenkan.push(LinePoint {
+                time: candle.time,
+                value,
+            });
+        }
+        if let Some(value) = kijun {
+            series.kijun.push(LinePoint {
+                time: candle.time,
+                value,
+            });
+        }
+
+        let projected = time_at(idx + displacement);
+        let span_a = match (tenkan, kijun) {
+            (Some(tenkan), Some(kijun)) => Some((tenkan + kijun) / 2.0),
+            _ => None,
+        };
+        if let Some(value) = span_a {
+            series.senkou_a.push(LinePoint {
+                time: projected,
+                value,
+            });
+        }
+        if let Some(value) = span_b {
+            series.senkou_b.push(LinePoint {
+                time: projected,
+                value,
+            });
+        }
+        if let (Some(value_a), Some(value_b)) = (span_a, span_b) {
+            series.cloud.push(CloudPoint {
+                time: projected,
+                value_a,
+                value_b,
+            });
+        }
+
+        if idx >= displacement {
+            series.chikou.push(LinePoint {
+                time: candles[idx - displacement].time,
+                value: candle.close,
+            });
+        }
+    }
+
+    series
+}
+
+fn midpoint(candles: &[Candle], idx: usize, period: usize) -> Option<f64> {
+    if idx + 1 < period {
+        return None;
+    }
+    let window = &candles[idx + 1 - period..=idx];
+    let high = window
+        .iter()
+        .map(|candle| candle.high)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let low = window
+        .iter()
+        .map(|candle| candle.low)
+        .fold(f64::INFINITY, f64::min);
+    Some((high + low) / 2.0)
+}
+
+fn bar_interval(candles: &[Candle]) -> Duration {
+    let mut deltas: Vec<Duration> = candles
+        .windows(2)
+        .map(|pair| pair[1].time - pair[0].time)
+        .filter(|delta| delta.is_positive())
+        .collect();
+    if deltas.is_empty() {
+        return Duration::minutes(1);
+    }
+    deltas.sort();
+    deltas[deltas.len() / 2]
+}
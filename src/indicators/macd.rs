@@ -8,8 +8,17 @@ pub struct MacdSeries {
 }
 
 pub fn compute_macd(candles: &[Candle], fast: usize, slow: usize, signal: usize) -> MacdSeries {
-    let mut times: Vec<(time::OffsetDateTime, f64)> =
+    let times: Vec<(time::OffsetDateTime, f64)> =
         candles.iter().map(|c| (c.time, c.close)).collect();
+    compute_macd_for_times(&times, fast, slow, signal)
+}
+
+pub fn compute_macd_for_times(
+    times: &[(time::OffsetDateTime, f64)],
+    fast: usize,
+    slow: usize,
+    signal: usize,
+) -> MacdSeries {
     if times.is_empty() {
         return MacdSeries {
             macd: Vec::new(),
@@ -17,8 +26,8 @@ pub fn compute_macd(candles: &[Candle], fast: usize, slow: usize, signal: usize)
             histogram: Vec::new(),
         };
     }
-    let fast_ema = compute_ema_for_times(&times, fast);
-    let slow_ema = compute_ema_for_times(&times, slow);
+    let fast_ema = compute_ema_for_times(times, fast);
+    let slow_ema = compute_ema_for_times(times, slow);
 
     let count = fast_ema.len().min(slow_ema.len());
     let mut macd_vals: Vec<(time::OffsetDateTime, f64)> = Vec::with_capacity(count);
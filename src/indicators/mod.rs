@@ -1,7 +1,13 @@
 pub mod bollinger;
 pub mod ema;
+pub mod graph;
+pub mod ichimoku;
 pub mod macd;
+pub mod obv;
+pub mod pivots;
 pub mod rsi;
+pub mod script;
 pub mod sma;
 pub mod stoch_rsi;
 pub mod stochastic;
+pub mod zigzag;
@@ -0,0 +1,31 @@
+use crate::chart::LinePoint;
+use std::collections::HashMap;
+use time::OffsetDateTime;
+
+/// On-balance volume of `values`: the running total of each bar's volume,
+/// added when the value rose and subtracted when it fell. Bars without a
+/// volume in `volumes` count as zero.
+pub fn compute_obv_for_times(
+    values: &[(OffsetDateTime, f64)],
+    volumes: &HashMap<OffsetDateTime, f64>,
+) -> Vec<LinePoint> {
+    let mut out = Vec::with_capacity(values.len());
+    let mut obv = 0.0;
+    let mut previous: Option<f64> = None;
+    for (time, value) in values {
+        if let Some(previous) = previous {
+            let volume = volumes.get(time).copied().unwrap_or(0.0);
+            if *value > previous {
+                obv += volume;
+            } else if *value < previous {
+                obv -= volume;
+            }
+        }
+        previous = Some(*value);
+        out.push(LinePoint {
+            time: *time,
+            value: obv,
+        });
+    }
+    out
+}
@@ -0,0 +1,140 @@
+use crate::chart::Candle;
+use time::{Date, Duration, Month, OffsetDateTime, Time};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PivotKind {
+    Classic,
+    Fibonacci,
+    Camarilla,
+    Woodie,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PivotPeriod {
+    Day,
+    Week,
+    Month,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PivotLevel {
+    pub name: &'static str,
+    pub price: f64,
+}
+
+/// Levels that apply from `start` to `end`, derived from the previous
+/// period's high, low and close.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PivotSet {
+    pub start: OffsetDateTime,
+    pub end: OffsetDateTime,
+    pub levels: Vec<PivotLevel>,
+}
+
+pub fn compute_pivots(candles: &[Candle], kind: PivotKind, period: PivotPeriod) -> Vec<PivotSet> {
+    let mut sets = Vec::new();
+    // (period start, high, low, close) of the period being accumulated.
+    let mut current: Option<(OffsetDateTime, f64, f64, f64)> = None;
+    for candle in candles {
+        let start = period_start(candle.time, period);
+        match &mut current {
+            Some((current_start, high, low, close)) if *current_start == start => {
+                *high = high.max(candle.high);
+                *low = low.min(candle.low);
+                *close = candle.close;
+            }
+            _ => {
+                if let Some((_, high, low, close)) = current {
+                    sets.push(PivotSet {
+                        start,
+                        end: period_end(start, period),
+                        levels: pivot_levels(kind, high, low, close),
+                    });
+                }
+                current = Some((start, candle.high, candle.low, candle.close));
+            }
+        }
+    }
+    sets
+}
+
+pub fn pivot_levels(kind: PivotKind, high: f64, low: f64, close: f64) -> Vec<PivotLevel> {
+    let range = high - low;
+    let level = |name, price| PivotLevel { name, price };
+    match kind {
+        PivotKind::Classic => {
+            let p = (high + low + close) / 3.0;
+            vec![
+                level("R3", high + 2.0 * (p - low)),
+                level("R2", p + range),
+                level("R1", 2.0 * p - low),
+                level("P", p),
+                level("S1", 2.0 * p - high),
+                level("S2", p - range),
+                level("S3", low - 2.0 * (high - p)),
+            ]
+        }
+        PivotKind::Fibonacci => {
+            let p = (high + low + close) / 3.0;
+            vec![
+                level("R3", p + range),
+                level("R2", p + 0.618 * range),
+                level("R1", p + 0.382 * range),
+                level("P", p),
+                level("S1", p - 0.382 * range),
+                level("S2", p - 0.618 * range),
+                level("S3", p - range),
+            ]
+        }
+        PivotKind::Camarilla => {
+            let p = (high + low + close) / 3.0;
+            vec![
+                level("R4", close + range * 1.1 / 2.0),
+                level("R3", close + range * 1.1 / 4.0),
+                level("R2", close + range * 1.1 / 6.0),
+                level("R1", close + range * 1.1 / 12.0),
+                level("P", p),
+                level("S1", close - range * 1.1 / 12.0),
+                level("S2", close - range * 1.1 / 6.0),
+                level("S3", close - range * 1.1 / 4.0),
+                level("S4", close - range * 1.1 / 2.0),
+            ]
+        }
+        PivotKind::Woodie => {
+            let p = (high + low + 2.0 * close) / 4.0;
+            vec![
+                level("R2", p + range),
+                level("R1", 2.0 * p - low),
+                level("P", p),
+                level("S1", 2.0 * p - high),
+                level("S2", p - range),
+            ]
+        }
+    }
+}
+
+fn period_start(time: OffsetDateTime, period: PivotPeriod) -> OffsetDateTime {
+    let day = time.replace_time(Time::MIDNIGHT);
+    match period {
+        PivotPeriod::Day => day,
+        PivotPeriod::Week => day - Duration::days(day.weekday().number_days_from_monday() as i64),
+        PivotPeriod::Month => day.replace_day(1).unwrap_or(day),
+    }
+}
+
+fn period_end(start: OffsetDateTime, period: PivotPeriod) -> OffsetDateTime {
+    match period {
+        PivotPeriod::Day => start + Duration::days(1),
+        PivotPeriod::Week => start + Duration::weeks(1),
+        PivotPeriod::Month => {
+            let (year, month) = match start.month() {
+                Month::December => (start.year() + 1, Month::January),
+                month => (start.year(), month.next()),
+            };
+            match Date::from_calendar_date(year, month, 1) {
+                Ok(date) => start.replace_date(date),
+                Err(_) => start + Duration::days(31),
+            }
+        }
+    }
+}
@@ -1,14 +1,20 @@
 use crate::chart::{Candle, LinePoint};
+use time::OffsetDateTime;
 
 pub fn compute_rsi(candles: &[Candle], period: usize) -> Vec<LinePoint> {
-    if candles.len() <= period {
+    let values: Vec<(OffsetDateTime, f64)> = candles.iter().map(|c| (c.time, c.close)).collect();
+    compute_rsi_for_times(&values, period)
+}
+
+pub fn compute_rsi_for_times(values: &[(OffsetDateTime, f64)], period: usize) -> Vec<LinePoint> {
+    if period == 0 || values.len() <= period {
         return Vec::new();
     }
 
     let mut gains = 0.0;
     let mut losses = 0.0;
     for i in 1..=period {
-        let delta = candles[i].close - candles[i - 1].close;
+        let delta = values[i].1 - values[i - 1].1;
         if delta >= 0.0 {
             gains += delta;
         } else {
@@ -18,21 +24,21 @@ pub fn compute_rsi(candles: &[Candle], period: usize) -> Vec<LinePoint> {
 
     let mut avg_gain = gains / period as f64;
     let mut avg_loss = losses / period as f64;
-    let mut points = Vec::with_capacity(candles.len().saturating_sub(period));
+    let mut points = Vec::with_capacity(values.len().saturating_sub(period));
 
     points.push(LinePoint {
-        time: candles[period].time,
+        time: values[period].0,
         value: rsi_from_avgs(avg_gain, avg_loss),
     });
 
-    for i in (period + 1)..candles.len() {
-        let delta = candles[i].close - candles[i - 1].close;
+    for i in (period + 1)..values.len() {
+        let delta = values[i].1 - values[i - 1].1;
         let gain = if delta > 0.0 { delta } else { 0.0 };
         let loss = if delta < 0.0 { -delta } else { 0.0 };
         avg_gain = (avg_gain * (period as f64 - 1.0) + gain) / period as f64;
         avg_loss = (avg_loss * (period as f64 - 1.0) + loss) / period as f64;
         points.push(LinePoint {
-            time: candles[i].time,
+            time: values[i].0,
             value: rsi_from_avgs(avg_gain, avg_loss),
         });
     }
@@ -0,0 +1,222 @@
+use super::ScriptError;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum TokenKind {
+    Number(f64),
+    Ident(String),
+    Str(String),
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+    Assign,
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Less,
+    LessEq,
+    Greater,
+    GreaterEq,
+    Equal,
+    NotEqual,
+    Newline,
+    Eof,
+}
+
+#[derive(Clone, Debug)]
+pub(super) struct Token {
+    pub(super) kind: TokenKind,
+    pub(super) line: usize,
+    pub(super) column: usize,
+}
+
+pub(super) fn tokenize(source: &str) -> Result<Vec<Token>, ScriptError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut idx = 0;
+    let mut line = 1;
+    let mut column = 1;
+
+    while idx < chars.len() {
+        let ch = chars[idx];
+        let start_column = column;
+        let mut push = |kind: TokenKind, len: usize| {
+            tokens.push(Token {
+                kind,
+                line,
+                column: start_column,
+            });
+            len
+        };
+
+        let consumed = match ch {
+            '\n' => {
+                push(TokenKind::Newline, 1);
+                idx += 1;
+                line += 1;
+                column = 1;
+                continue;
+            }
+            ' ' | '\t' | '\r' => 1,
+            '#' => {
+                let mut len = 0;
+                while idx + len < chars.len() && chars[idx + len] != '\n' {
+                    len += 1;
+                }
+                len
+            }
+            ';' => push(TokenKind::Newline, 1),
+            '(' => push(TokenKind::LParen, 1),
+            ')' => push(TokenKind::RParen, 1),
+            '[' => push(TokenKind::LBracket, 1),
+            ']' => push(TokenKind::RBracket, 1),
+            ',' => push(TokenKind::Comma, 1),
+            '+' => push(TokenKind::Plus, 1),
+            '-' => push(TokenKind::Minus, 1),
+            '*' => push(TokenKind::Star, 1),
+            '/' => push(TokenKind::Slash, 1),
+            '<' if chars.get(idx + 1) == Some(&'=') => push(TokenKind::LessEq, 2),
+            '<' => push(TokenKind::Less, 1),
+            '>' if chars.get(idx + 1) == Some(&'=') => push(TokenKind::GreaterEq, 2),
+            '>' => push(TokenKind::Greater, 1),
+            '=' if chars.get(idx + 1) == Some(&'=') => push(TokenKind::Equal, 2),
+            '=' => push(TokenKind::Assign, 1),
+            '!' if chars.get(idx + 1) == Some(&'=') => push(TokenKind::NotEqual, 2),
+            '"' => {
+                let mut len = 1;
+                let mut text = String::new();
+                loop {
+                    match chars.get(idx + len) {
+                        Some('"') => break,
+                        Some('\n') | None => {
+                            return Err(ScriptError::new(
+                                line,
+                                start_column,
+                                "unterminated string",
+                            ));
+                        }
+                        Some(c) => text.push(*c),
+                    }
+                    len += 1;
+                }
+                push(TokenKind::Str(text), len + 1)
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut len = 0;
+                while idx + len < chars.len()
+                    && (chars[idx + len].is_ascii_digit() || chars[idx + len] == '.')
+                {
+                    len += 1;
+                }
+                let text: String = chars[idx..idx + len].iter().collect();
+                let value = text.parse::<f64>().map_err(|_| {
+                    ScriptError::new(line, start_column, format!("invalid number `{text}`"))
+                })?;
+                push(TokenKind::Number(value), len)
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut len = 0;
+                while idx + len < chars.len()
+                    && (chars[idx + len].is_ascii_alphanumeric() || chars[idx + len] == '_')
+                {
+                    len += 1;
+                }
+                let text: String = chars[idx..idx + len].iter().collect();
+                push(TokenKind::Ident(text), len)
+            }
+            other => {
+                return Err(ScriptError::new(
+                    line,
+                    start_column,
+                    format!("unexpected character `{other}`"),
+                ));
+            }
+        };
+        idx += consumed;
+        column += consumed;
+    }
+
+    tokens.push(Token {
+        kind: TokenKind::Eof,
+        line,
+        column,
+    });
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(source: &str) -> Vec<TokenKind> {
+        tokenize(source)
+            .unwrap()
+            .into_iter()
+            .map(|token| token.kind)
+            .collect()
+    }
+
+    #[test]
+    fn tokenizes_operators_and_literals() {
+        assert_eq!(
+            kinds("x = close[1] >= 2.5 # note"),
+            vec![
+                TokenKind::Ident("x".to_string()),
+                TokenKind::Assign,
+                TokenKind::Ident("close".to_string()),
+                TokenKind::LBracket,
+                TokenKind::Number(1.0),
+                TokenKind::RBracket,
+                TokenKind::GreaterEq,
+                TokenKind::Number(2.5),
+                TokenKind::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn semicolon_ends_a_statement() {
+        assert_eq!(
+            kinds("study \"A\"; a == b"),
+            vec![
+                TokenKind::Ident("study".to_string()),
+                TokenKind::Str("A".to_string()),
+                TokenKind::Newline,
+                TokenKind::Ident("a".to_string()),
+                TokenKind::Equal,
+                TokenKind::Ident("b".to_string()),
+                TokenKind::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn tracks_lines_and_columns() {
+        let tokens = tokenize("a = 1\n  b = 2").unwrap();
+        let b = &tokens[4];
+        assert_eq!(b.kind, TokenKind::Ident("b".to_string()));
+        assert_eq!((b.line, b.column), (2, 3));
+    }
+
+    #[test]
+    fn reports_unterminated_string() {
+        let err = tokenize("study \"open").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 7));
+        assert_eq!(err.message, "unterminated string");
+    }
+
+    #[test]
+    fn reports_unexpected_character() {
+        let err = tokenize("a = 1\nb = $").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 5));
+        assert_eq!(err.message, "unexpected character `$`");
+    }
+
+    #[test]
+    fn reports_invalid_number() {
+        let err = tokenize("a = 1.2.3").unwrap_err();
+        assert_eq!(err.message, "invalid number `1.2.3`");
+    }
+}
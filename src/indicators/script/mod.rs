@@ -0,0 +1,248 @@
+mod lexer;
+mod parser;
+mod program;
+
+use crate::chart::{Color, HistogramPoint, LinePoint};
+use parser::Statement;
+use program::{truthy, Program};
+use std::fmt;
+use time::OffsetDateTime;
+
+pub const MAX_SOURCE_LEN: usize = 16 * 1024;
+pub const MAX_NODES: usize = 1024;
+pub const MAX_PLOTS: usize = 8;
+pub const MAX_NESTING: usize = 64;
+pub const MAX_LOOKBACK: usize = 5000;
+pub const MAX_STEPS_PER_UPDATE: u64 = 20_000_000;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ScriptPanel {
+    Main,
+    Indicator,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PlotKind {
+    Line,
+    Histogram,
+    Markers,
+}
+
+#[derive(Clone, Debug)]
+pub struct PlotSpec {
+    pub kind: PlotKind,
+    pub title: String,
+    pub color: Option<Color>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ScriptBar {
+    pub time: OffsetDateTime,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScriptError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ScriptError {
+    pub(crate) fn new(line: usize, column: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(
+                f,
+                "line {}, column {}: {}",
+                self.line, self.column, self.message
+            )
+        }
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+/// A compiled indicator script. Scripts have no loops, I/O or user-defined
+/// functions, and every update runs under a fixed step budget, so a script
+/// can be slow to write but never hang the UI.
+#[derive(Clone, Debug)]
+pub struct ScriptIndicator {
+    name: String,
+    panel: ScriptPanel,
+    plots: Vec<PlotSpec>,
+    plot_nodes: Vec<usize>,
+    program: Program,
+    times: Vec<OffsetDateTime>,
+}
+
+impl ScriptIndicator {
+    pub fn compile(source: &str) -> Result<Self, ScriptError> {
+        if source.len() > MAX_SOURCE_LEN {
+            return Err(ScriptError::new(
+                0,
+                0,
+                format!("script is longer than {MAX_SOURCE_LEN} bytes"),
+            ));
+        }
+        let tokens = lexer::tokenize(source)?;
+        let statements = parser::parse(&tokens)?;
+
+        let mut script = Self {
+            name: "Script".to_string(),
+            panel: ScriptPanel::Main,
+            plots: Vec::new(),
+            plot_nodes: Vec::new(),
+            program: Program::default(),
+            times: Vec::new(),
+        };
+        for (line, statement) in statements {
+            match statement {
+                Statement::Study(name) => script.name = name,
+                Statement::Panel(panel) => script.panel = panel,
+                Statement::Assign { name, value } => script.program.bind(&name, &value)?,
+                Statement::Plot {
+                    kind,
+                    title,
+                    value,
+                    color,
+                } => {
+                    if script.plots.len() >= MAX_PLOTS {
+                        return Err(ScriptError::new(
+                            line,
+                            1,
+                            format!("a script can declare at most {MAX_PLOTS} plots"),
+                        ));
+                    }
+                    let color = match color {
+                        Some(text) => Some(parse_color(&text).ok_or_else(|| {
+                            ScriptError::new(
+                                line,
+                                1,
+                                format!("invalid color \"{text}\", expected \"#rrggbb\""),
+                            )
+                        })?),
+                        None => None,
+                    };
+                    let node = script.program.compile(&value)?;
+                    script.plots.push(PlotSpec { kind, title, color });
+                    script.plot_nodes.push(node);
+                }
+            }
+        }
+        if script.plots.is_empty() {
+            return Err(ScriptError::new(
+                0,
+                0,
+                "script declares no plots; add e.g. `plot line \"Close\" = close`",
+            ));
+        }
+        Ok(script)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn panel(&self) -> ScriptPanel {
+        self.panel
+    }
+
+    pub fn plots(&self) -> &[PlotSpec] {
+        &self.plots
+    }
+
+    /// Brings the script up to date with `bars`. Only bars that are new or
+    /// changed since the last call are evaluated: a live update to the last
+    /// bar re-runs just that bar, while a history prepend starts over.
+    pub fn update(&mut self, bars: &[ScriptBar]) -> Result<(), ScriptError> {
+        let processed = self.times.len();
+        let same_history = processed > 0
+            && processed <= bars.len()
+            && bars[0].time == self.times[0]
+            && bars[processed - 1].time == self.times[processed - 1];
+        let keep = if same_history { processed - 1 } else { 0 };
+        self.times.truncate(keep);
+        self.program.truncate(keep);
+
+        let mut steps = 0;
+        for bar in &bars[keep..] {
+            steps += self.program.step(bar);
+            self.times.push(bar.time);
+            if steps > MAX_STEPS_PER_UPDATE {
+                self.times.clear();
+                self.program.truncate(0);
+                return Err(ScriptError::new(
+                    0,
+                    0,
+                    format!(
+                        "execution limit of {MAX_STEPS_PER_UPDATE} steps exceeded; \
+                         use shorter lengths or fewer studies"
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn line(&self, plot: usize) -> Vec<LinePoint> {
+        self.plot_values(plot)
+            .filter(|(_, value)| value.is_finite())
+            .map(|(time, value)| LinePoint { time, value })
+            .collect()
+    }
+
+    pub fn histogram(&self, plot: usize) -> Vec<HistogramPoint> {
+        self.plot_values(plot)
+            .filter(|(_, value)| value.is_finite())
+            .map(|(time, value)| HistogramPoint {
+                time,
+                value,
+                color: None,
+            })
+            .collect()
+    }
+
+    pub fn signals(&self, plot: usize) -> Vec<OffsetDateTime> {
+        self.plot_values(plot)
+            .filter(|(_, value)| truthy(*value))
+            .map(|(time, _)| time)
+            .collect()
+    }
+
+    fn plot_values(&self, plot: usize) -> impl Iterator<Item = (OffsetDateTime, f64)> + '_ {
+        let values = match self.plot_nodes.get(plot) {
+            Some(node) => self.program.values(*node),
+            None => &[],
+        };
+        self.times.iter().copied().zip(values.iter().copied())
+    }
+}
+
+fn parse_color(text: &str) -> Option<Color> {
+    let hex = text.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |range: std::ops::Range<usize>| {
+        u8::from_str_radix(hex.get(range)?, 16)
+            .ok()
+            .map(|value| value as f64 / 255.0)
+    };
+    Some(Color::new(channel(0..2)?, channel(2..4)?, channel(4..6)?))
+}
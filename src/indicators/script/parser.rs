@@ -0,0 +1,507 @@
+use super::lexer::{Token, TokenKind};
+use super::{PlotKind, ScriptError, ScriptPanel, MAX_NESTING};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Less,
+    LessEq,
+    Greater,
+    GreaterEq,
+    Equal,
+    NotEqual,
+    And,
+    Or,
+}
+
+#[derive(Clone, Debug)]
+pub(super) enum ExprKind {
+    Number(f64),
+    Ident(String),
+    Neg(Box<Expr>),
+    Not(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+    History(Box<Expr>, Box<Expr>),
+}
+
+#[derive(Clone, Debug)]
+pub(super) struct Expr {
+    pub(super) kind: ExprKind,
+    pub(super) line: usize,
+    pub(super) column: usize,
+}
+
+#[derive(Clone, Debug)]
+pub(super) enum Statement {
+    Study(String),
+    Panel(ScriptPanel),
+    Assign {
+        name: String,
+        value: Expr,
+    },
+    Plot {
+        kind: PlotKind,
+        title: String,
+        value: Expr,
+        color: Option<String>,
+    },
+}
+
+pub(super) fn parse(tokens: &[Token]) -> Result<Vec<(usize, Statement)>, ScriptError> {
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        depth: 0,
+    };
+    let mut statements = Vec::new();
+    loop {
+        while parser.peek().kind == TokenKind::Newline {
+            parser.pos += 1;
+        }
+        if parser.peek().kind == TokenKind::Eof {
+            break;
+        }
+        let line = parser.peek().line;
+        statements.push((line, parser.statement()?));
+        match parser.peek().kind {
+            TokenKind::Newline | TokenKind::Eof => {}
+            _ => return Err(parser.unexpected("end of line")),
+        }
+    }
+    Ok(statements)
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos.min(self.tokens.len() - 1)]
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.peek().clone();
+        if self.pos < self.tokens.len() - 1 {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn unexpected(&self, expected: &str) -> ScriptError {
+        let token = self.peek();
+        let found = match &token.kind {
+            TokenKind::Number(value) => format!("number {value}"),
+            TokenKind::Ident(name) => format!("`{name}`"),
+            TokenKind::Str(text) => format!("\"{text}\""),
+            TokenKind::Newline => "end of line".to_string(),
+            TokenKind::Eof => "end of script".to_string(),
+            TokenKind::LParen => "`(`".to_string(),
+            TokenKind::RParen => "`)`".to_string(),
+            TokenKind::LBracket => "`[`".to_string(),
+            TokenKind::RBracket => "`]`".to_string(),
+            TokenKind::Comma => "`,`".to_string(),
+            TokenKind::Assign => "`=`".to_string(),
+            TokenKind::Plus => "`+`".to_string(),
+            TokenKind::Minus => "`-`".to_string(),
+            TokenKind::Star => "`*`".to_string(),
+            TokenKind::Slash => "`/`".to_string(),
+            TokenKind::Less => "`<`".to_string(),
+            TokenKind::LessEq => "`<=`".to_string(),
+            TokenKind::Greater => "`>`".to_string(),
+            TokenKind::GreaterEq => "`>=`".to_string(),
+            TokenKind::Equal => "`==`".to_string(),
+            TokenKind::NotEqual => "`!=`".to_string(),
+        };
+        ScriptError::new(
+            token.line,
+            token.column,
+            format!("expected {expected}, found {found}"),
+        )
+    }
+
+    fn expect(&mut self, kind: TokenKind, expected: &str) -> Result<Token, ScriptError> {
+        if self.peek().kind == kind {
+            Ok(self.advance())
+        } else {
+            Err(self.unexpected(expected))
+        }
+    }
+
+    fn ident(&mut self, expected: &str) -> Result<String, ScriptError> {
+        match &self.peek().kind {
+            TokenKind::Ident(name) => {
+                let name = name.clone();
+                self.advance();
+                Ok(name)
+            }
+            _ => Err(self.unexpected(expected)),
+        }
+    }
+
+    fn string(&mut self, expected: &str) -> Result<String, ScriptError> {
+        match &self.peek().kind {
+            TokenKind::Str(text) => {
+                let text = text.clone();
+                self.advance();
+                Ok(text)
+            }
+            _ => Err(self.unexpected(expected)),
+        }
+    }
+
+    fn statement(&mut self) -> Result<Statement, ScriptError> {
+        let start = self.peek().clone();
+        let keyword = self.ident("a statement")?;
+        match keyword.as_str() {
+            "study" => Ok(Statement::Study(self.string("a study name in quotes")?)),
+            "panel" => {
+                let target = self.peek().clone();
+                match self.ident("`main` or `indicator`")?.as_str() {
+                    "main" => Ok(Statement::Panel(ScriptPanel::Main)),
+                    "indicator" => Ok(Statement::Panel(ScriptPanel::Indicator)),
+                    other => Err(ScriptError::new(
+                        target.line,
+                        target.column,
+                        format!("unknown panel `{other}`, expected `main` or `indicator`"),
+                    )),
+                }
+            }
+            "plot" => {
+                let style = self.peek().clone();
+                let kind = match self.ident("`line`, `histogram` or `markers`")?.as_str() {
+                    "line" => PlotKind::Line,
+                    "histogram" => PlotKind::Histogram,
+                    "markers" => PlotKind::Markers,
+                    other => {
+                        return Err(ScriptError::new(
+                            style.line,
+                            style.column,
+                            format!(
+                                "unknown plot style `{other}`, expected `line`, `histogram` or `markers`"
+                            ),
+                        ));
+                    }
+                };
+                let title = self.string("a plot title in quotes")?;
+                self.expect(TokenKind::Assign, "`=`")?;
+                let value = self.expr()?;
+                let color = match &self.peek().kind {
+                    TokenKind::Ident(word) if word == "color" => {
+                        self.advance();
+                        Some(self.string("a color such as \"#f5a623\"")?)
+                    }
+                    _ => None,
+                };
+                Ok(Statement::Plot {
+                    kind,
+                    title,
+                    value,
+                    color,
+                })
+            }
+            _ => {
+                if self.peek().kind != TokenKind::Assign {
+                    return Err(ScriptError::new(
+                        start.line,
+                        start.column,
+                        format!(
+                            "expected `{keyword} = ...` or a `study`, `panel` or `plot` statement"
+                        ),
+                    ));
+                }
+                self.advance();
+                Ok(Statement::Assign {
+                    name: keyword,
+                    value: self.expr()?,
+                })
+            }
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, ScriptError> {
+        self.nested(Self::or)
+    }
+
+    fn nested(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<Expr, ScriptError>,
+    ) -> Result<Expr, ScriptError> {
+        if self.depth >= MAX_NESTING {
+            let token = self.peek();
+            return Err(ScriptError::new(
+                token.line,
+                token.column,
+                format!("expression nested deeper than {MAX_NESTING} levels"),
+            ));
+        }
+        self.depth += 1;
+        let expr = parse(self);
+        self.depth -= 1;
+        expr
+    }
+
+    fn binary_at(op: BinaryOp, lhs: Expr, rhs: Expr) -> Expr {
+        let (line, column) = (lhs.line, lhs.column);
+        Expr {
+            kind: ExprKind::Binary(op, Box::new(lhs), Box::new(rhs)),
+            line,
+            column,
+        }
+    }
+
+    fn keyword_op(&self, word: &str) -> bool {
+        matches!(&self.peek().kind, TokenKind::Ident(name) if name == word)
+    }
+
+    fn or(&mut self) -> Result<Expr, ScriptError> {
+        let mut lhs = self.and()?;
+        while self.keyword_op("or") {
+            self.advance();
+            let rhs = self.and()?;
+            lhs = Self::binary_at(BinaryOp::Or, lhs, rhs);
+        }
+        Ok(lhs)
+    }
+
+    fn and(&mut self) -> Result<Expr, ScriptError> {
+        let mut lhs = self.not()?;
+        while self.keyword_op("and") {
+            self.advance();
+            let rhs = self.not()?;
+            lhs = Self::binary_at(BinaryOp::And, lhs, rhs);
+        }
+        Ok(lhs)
+    }
+
+    fn not(&mut self) -> Result<Expr, ScriptError> {
+        if self.keyword_op("not") {
+            let token = self.advance();
+            let inner = self.nested(Self::not)?;
+            return Ok(Expr {
+                kind: ExprKind::Not(Box::new(inner)),
+                line: token.line,
+                column: token.column,
+            });
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, ScriptError> {
+        let mut lhs = self.additive()?;
+        loop {
+            let op = match self.peek().kind {
+                TokenKind::Less => BinaryOp::Less,
+                TokenKind::LessEq => BinaryOp::LessEq,
+                TokenKind::Greater => BinaryOp::Greater,
+                TokenKind::GreaterEq => BinaryOp::GreaterEq,
+                TokenKind::Equal => BinaryOp::Equal,
+                TokenKind::NotEqual => BinaryOp::NotEqual,
+                _ => return Ok(lhs),
+            };
+            self.advance();
+            let rhs = self.additive()?;
+            lhs = Self::binary_at(op, lhs, rhs);
+        }
+    }
+
+    fn additive(&mut self) -> Result<Expr, ScriptError> {
+        let mut lhs = self.multiplicative()?;
+        loop {
+            let op = match self.peek().kind {
+                TokenKind::Plus => BinaryOp::Add,
+                TokenKind::Minus => BinaryOp::Sub,
+                _ => return Ok(lhs),
+            };
+            self.advance();
+            let rhs = self.multiplicative()?;
+            lhs = Self::binary_at(op, lhs, rhs);
+        }
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr, ScriptError> {
+        let mut lhs = self.unary()?;
+        loop {
+            let op = match self.peek().kind {
+                TokenKind::Star => BinaryOp::Mul,
+                TokenKind::Slash => BinaryOp::Div,
+                _ => return Ok(lhs),
+            };
+            self.advance();
+            let rhs = self.unary()?;
+            lhs = Self::binary_at(op, lhs, rhs);
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, ScriptError> {
+        if self.peek().kind == TokenKind::Minus {
+            let token = self.advance();
+            let inner = self.nested(Self::unary)?;
+            return Ok(Expr {
+                kind: ExprKind::Neg(Box::new(inner)),
+                line: token.line,
+                column: token.column,
+            });
+        }
+        self.postfix()
+    }
+
+    fn postfix(&mut self) -> Result<Expr, ScriptError> {
+        let mut expr = self.primary()?;
+        while self.peek().kind == TokenKind::LBracket {
+            self.advance();
+            let offset = self.expr()?;
+            self.expect(TokenKind::RBracket, "`]`")?;
+            let (line, column) = (expr.line, expr.column);
+            expr = Expr {
+                kind: ExprKind::History(Box::new(expr), Box::new(offset)),
+                line,
+                column,
+            };
+        }
+        Ok(expr)
+    }
+
+    fn primary(&mut self) -> Result<Expr, ScriptError> {
+        let token = self.peek().clone();
+        let kind = match &token.kind {
+            TokenKind::Number(value) => {
+                self.advance();
+                ExprKind::Number(*value)
+            }
+            TokenKind::LParen => {
+                self.advance();
+                let inner = self.expr()?;
+                self.expect(TokenKind::RParen, "`)`")?;
+                return Ok(inner);
+            }
+            TokenKind::Ident(name) => {
+                let name = name.clone();
+                self.advance();
+                if self.peek().kind == TokenKind::LParen {
+                    self.advance();
+                    let mut args = Vec::new();
+                    if self.peek().kind != TokenKind::RParen {
+                        loop {
+                            args.push(self.expr()?);
+                            if self.peek().kind == TokenKind::Comma {
+                                self.advance();
+                            } else {
+                                break;
+                            }
+                        }
+                    }
+                    self.expect(TokenKind::RParen, "`,` or `)`")?;
+                    ExprKind::Call(name, args)
+                } else {
+                    ExprKind::Ident(name)
+                }
+            }
+            _ => return Err(self.unexpected("an expression")),
+        };
+        Ok(Expr {
+            kind,
+            line: token.line,
+            column: token.column,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::lexer::tokenize;
+    use super::*;
+
+    fn parse_source(source: &str) -> Result<Vec<(usize, Statement)>, ScriptError> {
+        parse(&tokenize(source)?)
+    }
+
+    #[test]
+    fn parses_statements_with_their_lines() {
+        let statements =
+            parse_source("study \"Demo\"\npanel indicator\n\nfast = ema(close, 9)\nplot line \"Fast\" = fast color \"#f5a623\"")
+                .unwrap();
+        let lines: Vec<usize> = statements.iter().map(|(line, _)| *line).collect();
+        assert_eq!(lines, vec![1, 2, 4, 5]);
+        assert!(matches!(&statements[0].1, Statement::Study(name) if name == "Demo"));
+        assert!(matches!(
+            statements[1].1,
+            Statement::Panel(ScriptPanel::Indicator)
+        ));
+        assert!(matches!(&statements[2].1, Statement::Assign { name, .. } if name == "fast"));
+        assert!(matches!(
+            &statements[3].1,
+            Statement::Plot {
+                kind: PlotKind::Line,
+                title,
+                color: Some(color),
+                ..
+            } if title == "Fast" && color == "#f5a623"
+        ));
+    }
+
+    #[test]
+    fn multiplication_binds_tighter_than_addition() {
+        let statements = parse_source("x = 1 + 2 * 3").unwrap();
+        let Statement::Assign { value, .. } = &statements[0].1 else {
+            panic!("expected an assignment");
+        };
+        let ExprKind::Binary(BinaryOp::Add, _, rhs) = &value.kind else {
+            panic!("expected an addition at the top");
+        };
+        assert!(matches!(rhs.kind, ExprKind::Binary(BinaryOp::Mul, _, _)));
+    }
+
+    #[test]
+    fn reports_unknown_panel() {
+        let err = parse_source("panel side").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 7));
+        assert_eq!(
+            err.message,
+            "unknown panel `side`, expected `main` or `indicator`"
+        );
+    }
+
+    #[test]
+    fn reports_statement_without_assignment() {
+        let err = parse_source("x = 1\nclose").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+        assert_eq!(
+            err.message,
+            "expected `close = ...` or a `study`, `panel` or `plot` statement"
+        );
+    }
+
+    #[test]
+    fn reports_trailing_tokens() {
+        let err = parse_source("x = 1 2").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 7));
+        assert_eq!(err.message, "expected end of line, found number 2");
+    }
+
+    #[test]
+    fn reports_unclosed_call() {
+        let err = parse_source("x = sma(close, 5").unwrap_err();
+        assert_eq!(err.message, "expected `,` or `)`, found end of script");
+    }
+
+    #[test]
+    fn limits_nesting() {
+        let source = format!(
+            "x = {}1{}",
+            "(".repeat(MAX_NESTING),
+            ")".repeat(MAX_NESTING)
+        );
+        let err = parse_source(&source).unwrap_err();
+        assert_eq!(
+            err.message,
+            format!("expression nested deeper than {MAX_NESTING} levels")
+        );
+    }
+}
@@ -0,0 +1,551 @@
+use std::collections::HashMap;
+
+use super::parser::{BinaryOp, Expr, ExprKind};
+use super::{ScriptBar, ScriptError, MAX_LOOKBACK, MAX_NODES};
+
+#[derive(Clone, Copy, Debug)]
+pub(super) enum Field {
+    Open,
+    High,
+    Low,
+    Close,
+    Volume,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Window {
+    Sma,
+    Highest,
+    Lowest,
+    Stdev,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Op {
+    Const(f64),
+    Field(Field),
+    Neg(usize),
+    Not(usize),
+    Abs(usize),
+    Binary(BinaryOp, usize, usize),
+    Min(usize, usize),
+    Max(usize, usize),
+    History(usize, usize),
+    Window(Window, usize, usize),
+    Ema(usize, usize),
+    Rsi(usize, usize),
+    Crossover(usize, usize),
+    Crossunder(usize, usize),
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    op: Op,
+    values: Vec<f64>,
+    averages: Vec<(f64, f64)>,
+}
+
+/// Flattened script: every expression becomes a node whose inputs precede it,
+/// so evaluating nodes in order is always dependency-safe.
+#[derive(Clone, Debug, Default)]
+pub(super) struct Program {
+    nodes: Vec<Node>,
+    names: HashMap<String, usize>,
+}
+
+const RESERVED: &[&str] = &[
+    "open", "high", "low", "close", "volume", "na", "true", "false", "and", "or", "not", "study",
+    "panel", "plot", "color",
+];
+
+impl Program {
+    pub(super) fn bind(&mut self, name: &str, expr: &Expr) -> Result<(), ScriptError> {
+        if RESERVED.contains(&name) || builtin_arity(name).is_some() {
+            return Err(ScriptError::new(
+                expr.line,
+                expr.column,
+                format!("`{name}` is reserved and cannot be assigned"),
+            ));
+        }
+        let node = self.compile(expr)?;
+        self.names.insert(name.to_string(), node);
+        Ok(())
+    }
+
+    pub(super) fn compile(&mut self, expr: &Expr) -> Result<usize, ScriptError> {
+        self.compile_nested(0, expr)
+    }
+
+    /// Compiles `expr`, `depth` levels below a bound expression. Each level
+    /// above adds a node once its inputs are done, so a script that is too
+    /// large is refused before a long chain like `a + a + … + a` can
+    /// recurse deep enough to overflow the stack.
+    fn compile_nested(&mut self, depth: usize, expr: &Expr) -> Result<usize, ScriptError> {
+        if self.nodes.len() + depth > MAX_NODES {
+            return Err(too_large(expr));
+        }
+        let op = match &expr.kind {
+            ExprKind::Number(value) => Op::Const(*value),
+            ExprKind::Ident(name) => match name.as_str() {
+                "open" => Op::Field(Field::Open),
+                "high" => Op::Field(Field::High),
+                "low" => Op::Field(Field::Low),
+                "close" => Op::Field(Field::Close),
+                "volume" => Op::Field(Field::Volume),
+                "na" => Op::Const(f64::NAN),
+                "true" => Op::Const(1.0),
+                "false" => Op::Const(0.0),
+                _ => match self.names.get(name) {
+                    Some(node) => return Ok(*node),
+                    None => {
+                        return Err(ScriptError::new(
+                            expr.line,
+                            expr.column,
+                            format!("unknown variable `{name}`"),
+                        ));
+                    }
+                },
+            },
+            ExprKind::Neg(inner) => Op::Neg(self.compile_nested(depth + 1, inner)?),
+            ExprKind::Not(inner) => Op::Not(self.compile_nested(depth + 1, inner)?),
+            ExprKind::Binary(op, lhs, rhs) => {
+                let lhs = self.compile_nested(depth + 1, lhs)?;
+                let rhs = self.compile_nested(depth + 1, rhs)?;
+                Op::Binary(*op, lhs, rhs)
+            }
+            ExprKind::History(inner, offset) => {
+                let node = self.compile_nested(depth + 1, inner)?;
+                let offset = constant_length(offset, 0, "history offset")?;
+                Op::History(node, offset)
+            }
+            ExprKind::Call(name, args) => {
+                let Some(arity) = builtin_arity(name) else {
+                    return Err(ScriptError::new(
+                        expr.line,
+                        expr.column,
+                        format!("unknown function `{name}`"),
+                    ));
+                };
+                if args.len() != arity {
+                    return Err(ScriptError::new(
+                        expr.line,
+                        expr.column,
+                        format!(
+                            "`{name}` takes {arity} argument{}, got {}",
+                            if arity == 1 { "" } else { "s" },
+                            args.len()
+                        ),
+                    ));
+                }
+                match name.as_str() {
+                    "sma" | "ema" | "rsi" | "highest" | "lowest" | "stdev" => {
+                        let source = self.compile_nested(depth + 1, &args[0])?;
+                        let length = constant_length(&args[1], 1, "length")?;
+                        match name.as_str() {
+                            "sma" => Op::Window(Window::Sma, source, length),
+                            "highest" => Op::Window(Window::Highest, source, length),
+                            "lowest" => Op::Window(Window::Lowest, source, length),
+                            "stdev" => Op::Window(Window::Stdev, source, length),
+                            "ema" => Op::Ema(source, length),
+                            _ => Op::Rsi(source, length),
+                        }
+                    }
+                    "abs" => Op::Abs(self.compile_nested(depth + 1, &args[0])?),
+                    _ => {
+                        let lhs = self.compile_nested(depth + 1, &args[0])?;
+                        let rhs = self.compile_nested(depth + 1, &args[1])?;
+                        match name.as_str() {
+                            "min" => Op::Min(lhs, rhs),
+                            "max" => Op::Max(lhs, rhs),
+                            "crossover" => Op::Crossover(lhs, rhs),
+                            _ => Op::Crossunder(lhs, rhs),
+                        }
+                    }
+                }
+            }
+        };
+        if self.nodes.len() >= MAX_NODES {
+            return Err(too_large(expr));
+        }
+        self.nodes.push(Node {
+            op,
+            values: Vec::new(),
+            averages: Vec::new(),
+        });
+        Ok(self.nodes.len() - 1)
+    }
+
+    pub(super) fn values(&self, node: usize) -> &[f64] {
+        &self.nodes[node].values
+    }
+
+    pub(super) fn truncate(&mut self, bars: usize) {
+        for node in &mut self.nodes {
+            node.values.truncate(bars);
+            node.averages.truncate(bars);
+        }
+    }
+
+    /// Evaluates one bar, returning the number of steps spent so callers can
+    /// enforce the execution budget.
+    pub(super) fn step(&mut self, bar: &ScriptBar) -> u64 {
+        let mut steps = 0;
+        for idx in 0..self.nodes.len() {
+            let (inputs, rest) = self.nodes.split_at_mut(idx);
+            let node = &mut rest[0];
+            let i = node.values.len();
+            let at = |n: usize, bar_idx: usize| inputs[n].values[bar_idx];
+            steps += 1;
+            let value = match node.op {
+                Op::Const(value) => value,
+                Op::Field(field) => match field {
+                    Field::Open => bar.open,
+                    Field::High => bar.high,
+                    Field::Low => bar.low,
+                    Field::Close => bar.close,
+                    Field::Volume => bar.volume,
+                },
+                Op::Neg(n) => -at(n, i),
+                Op::Not(n) => bool_value(!truthy(at(n, i))),
+                Op::Abs(n) => at(n, i).abs(),
+                Op::Binary(op, lhs, rhs) => binary(op, at(lhs, i), at(rhs, i)),
+                Op::Min(lhs, rhs) => at(lhs, i).min(at(rhs, i)),
+                Op::Max(lhs, rhs) => at(lhs, i).max(at(rhs, i)),
+                Op::History(n, offset) => {
+                    if i >= offset {
+                        at(n, i - offset)
+                    } else {
+                        f64::NAN
+                    }
+                }
+                Op::Window(kind, n, length) => {
+                    steps += length as u64;
+                    if i + 1 < length {
+                        f64::NAN
+                    } else {
+                        window(kind, &inputs[n].values[i + 1 - length..=i])
+                    }
+                }
+                Op::Ema(n, length) => {
+                    let source = at(n, i);
+                    let prev = if i > 0 { node.values[i - 1] } else { f64::NAN };
+                    if prev.is_nan() {
+                        source
+                    } else {
+                        let k = 2.0 / (length as f64 + 1.0);
+                        source * k + prev * (1.0 - k)
+                    }
+                }
+                Op::Rsi(n, length) => {
+                    let prev = if i > 0 {
+                        node.averages[i - 1]
+                    } else {
+                        (f64::NAN, f64::NAN)
+                    };
+                    let averages = if !prev.0.is_nan() {
+                        let (gain, loss) = change(at(n, i - 1), at(n, i));
+                        let period = length as f64;
+                        (
+                            (prev.0 * (period - 1.0) + gain) / period,
+                            (prev.1 * (period - 1.0) + loss) / period,
+                        )
+                    } else if i >= length
+                        && !inputs[n].values[i - length..=i].iter().any(|v| v.is_nan())
+                    {
+                        steps += length as u64;
+                        let mut sums = (0.0, 0.0);
+                        for j in i + 1 - length..=i {
+                            let (gain, loss) = change(at(n, j - 1), at(n, j));
+                            sums.0 += gain;
+                            sums.1 += loss;
+                        }
+                        (sums.0 / length as f64, sums.1 / length as f64)
+                    } else {
+                        (f64::NAN, f64::NAN)
+                    };
+                    node.averages.push(averages);
+                    rsi_value(averages)
+                }
+                Op::Crossover(lhs, rhs) => {
+                    bool_value(i > 0 && at(lhs, i - 1) <= at(rhs, i - 1) && at(lhs, i) > at(rhs, i))
+                }
+                Op::Crossunder(lhs, rhs) => {
+                    bool_value(i > 0 && at(lhs, i - 1) >= at(rhs, i - 1) && at(lhs, i) < at(rhs, i))
+                }
+            };
+            node.values.push(value);
+        }
+        steps
+    }
+}
+
+fn builtin_arity(name: &str) -> Option<usize> {
+    match name {
+        "sma" | "ema" | "rsi" | "highest" | "lowest" | "stdev" => Some(2),
+        "crossover" | "crossunder" | "min" | "max" => Some(2),
+        "abs" => Some(1),
+        _ => None,
+    }
+}
+
+fn too_large(expr: &Expr) -> ScriptError {
+    ScriptError::new(
+        expr.line,
+        expr.column,
+        format!("script is too large (more than {MAX_NODES} operations)"),
+    )
+}
+
+fn constant_length(expr: &Expr, min: usize, what: &str) -> Result<usize, ScriptError> {
+    let error = || {
+        ScriptError::new(
+            expr.line,
+            expr.column,
+            format!("{what} must be a whole number between {min} and {MAX_LOOKBACK}"),
+        )
+    };
+    match expr.kind {
+        ExprKind::Number(value) if value.fract() == 0.0 => {
+            let value = value as usize;
+            if (min..=MAX_LOOKBACK).contains(&value) {
+                Ok(value)
+            } else {
+                Err(error())
+            }
+        }
+        _ => Err(error()),
+    }
+}
+
+pub(super) fn truthy(value: f64) -> bool {
+    !value.is_nan() && value != 0.0
+}
+
+fn bool_value(value: bool) -> f64 {
+    if value {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+fn binary(op: BinaryOp, lhs: f64, rhs: f64) -> f64 {
+    match op {
+        BinaryOp::Add => lhs + rhs,
+        BinaryOp::Sub => lhs - rhs,
+        BinaryOp::Mul => lhs * rhs,
+        BinaryOp::Div => {
+            if rhs == 0.0 {
+                f64::NAN
+            } else {
+                lhs / rhs
+            }
+        }
+        BinaryOp::Less => bool_value(lhs < rhs),
+        BinaryOp::LessEq => bool_value(lhs <= rhs),
+        BinaryOp::Greater => bool_value(lhs > rhs),
+        BinaryOp::GreaterEq => bool_value(lhs >= rhs),
+        BinaryOp::Equal => bool_value(lhs == rhs),
+        BinaryOp::NotEqual => bool_value(lhs != rhs),
+        BinaryOp::And => bool_value(truthy(lhs) && truthy(rhs)),
+        BinaryOp::Or => bool_value(truthy(lhs) || truthy(rhs)),
+    }
+}
+
+fn window(kind: Window, values: &[f64]) -> f64 {
+    if values.iter().any(|value| value.is_nan()) {
+        return f64::NAN;
+    }
+    let count = values.len() as f64;
+    match kind {
+        Window::Sma => values.iter().sum::<f64>() / count,
+        Window::Highest => values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        Window::Lowest => values.iter().copied().fold(f64::INFINITY, f64::min),
+        Window::Stdev => {
+            let mean = values.iter().sum::<f64>() / count;
+            let variance = values
+                .iter()
+                .map(|value| (value - mean) * (value - mean))
+                .sum::<f64>()
+                / count;
+            variance.sqrt()
+        }
+    }
+}
+
+fn change(prev: f64, current: f64) -> (f64, f64) {
+    let delta = current - prev;
+    if delta.is_nan() {
+        (0.0, 0.0)
+    } else if delta > 0.0 {
+        (delta, 0.0)
+    } else {
+        (0.0, -delta)
+    }
+}
+
+fn rsi_value((avg_gain, avg_loss): (f64, f64)) -> f64 {
+    if avg_gain.is_nan() {
+        return f64::NAN;
+    }
+    if avg_loss.abs() < 1e-9 {
+        return 100.0;
+    }
+    100.0 - 100.0 / (1.0 + avg_gain / avg_loss)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::lexer::tokenize;
+    use super::super::parser::{parse, Statement};
+    use super::*;
+    use time::{Duration, OffsetDateTime};
+
+    fn bind_all(source: &str) -> Result<Program, ScriptError> {
+        let mut program = Program::default();
+        for (_, statement) in parse(&tokenize(source)?)? {
+            if let Statement::Assign { name, value } = statement {
+                program.bind(&name, &value)?;
+            }
+        }
+        Ok(program)
+    }
+
+    /// Runs `source` over bars with the given closes and returns the values
+    /// of its `out` variable.
+    fn run(source: &str, closes: &[f64]) -> Vec<f64> {
+        let mut program = bind_all(source).unwrap();
+        for (idx, &close) in closes.iter().enumerate() {
+            program.step(&ScriptBar {
+                time: OffsetDateTime::UNIX_EPOCH + Duration::minutes(idx as i64),
+                open: close,
+                high: close + 1.0,
+                low: close - 1.0,
+                close,
+                volume: 100.0,
+            });
+        }
+        let out = program.names["out"];
+        program.values(out).to_vec()
+    }
+
+    fn assert_values(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (actual, expected) in actual.iter().zip(expected) {
+            if expected.is_nan() {
+                assert!(actual.is_nan(), "expected NaN, got {actual}");
+            } else {
+                assert!(
+                    (actual - expected).abs() < 1e-9,
+                    "expected {expected}, got {actual}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn evaluates_arithmetic_and_fields() {
+        let values = run("out = (high - low) * 2 + close / 4", &[4.0, 8.0]);
+        assert_values(&values, &[5.0, 6.0]);
+    }
+
+    #[test]
+    fn division_by_zero_is_na() {
+        let values = run("out = close / (close - close)", &[1.0]);
+        assert_values(&values, &[f64::NAN]);
+    }
+
+    #[test]
+    fn history_and_sma_wait_for_enough_bars() {
+        let nan = f64::NAN;
+        assert_values(
+            &run("out = close[2]", &[1.0, 2.0, 3.0, 4.0]),
+            &[nan, nan, 1.0, 2.0],
+        );
+        assert_values(
+            &run("out = sma(close, 3)", &[1.0, 2.0, 3.0, 4.0]),
+            &[nan, nan, 2.0, 3.0],
+        );
+    }
+
+    #[test]
+    fn ema_seeds_from_first_value() {
+        let values = run("out = ema(close, 3)", &[2.0, 4.0, 4.0]);
+        assert_values(&values, &[2.0, 3.0, 3.5]);
+    }
+
+    #[test]
+    fn rsi_of_rising_closes_is_100() {
+        let values = run("out = rsi(close, 2)", &[1.0, 2.0, 3.0, 4.0]);
+        assert_values(&values, &[f64::NAN, f64::NAN, 100.0, 100.0]);
+    }
+
+    #[test]
+    fn crossover_fires_on_the_crossing_bar() {
+        let values = run("out = crossover(close, 2)", &[1.0, 3.0, 4.0, 1.0]);
+        assert_values(&values, &[0.0, 1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn logic_treats_na_as_false() {
+        let values = run("out = not (na or close > 1) and true", &[0.0, 2.0]);
+        assert_values(&values, &[1.0, 0.0]);
+    }
+
+    #[test]
+    fn truncate_rewinds_evaluation() {
+        let mut program = bind_all("out = close[1]").unwrap();
+        for close in [1.0, 2.0, 3.0] {
+            program.step(&ScriptBar {
+                time: OffsetDateTime::UNIX_EPOCH,
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: 0.0,
+            });
+        }
+        program.truncate(1);
+        let out = program.names["out"];
+        assert_values(program.values(out), &[f64::NAN]);
+    }
+
+    #[test]
+    fn reports_reserved_names() {
+        let err = bind_all("close = 1").unwrap_err();
+        assert_eq!(err.message, "`close` is reserved and cannot be assigned");
+        let err = bind_all("sma = 1").unwrap_err();
+        assert_eq!(err.message, "`sma` is reserved and cannot be assigned");
+    }
+
+    #[test]
+    fn reports_unknown_names_and_bad_calls() {
+        let err = bind_all("x = 1\ny = z + x").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 5));
+        assert_eq!(err.message, "unknown variable `z`");
+        let err = bind_all("x = median(close, 3)").unwrap_err();
+        assert_eq!(err.message, "unknown function `median`");
+        let err = bind_all("x = abs(close, 3)").unwrap_err();
+        assert_eq!(err.message, "`abs` takes 1 argument, got 2");
+        let err = bind_all("x = sma(close, 0)").unwrap_err();
+        assert_eq!(
+            err.message,
+            format!("length must be a whole number between 1 and {MAX_LOOKBACK}")
+        );
+        let err = bind_all("x = ema(close, close)").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 16));
+    }
+
+    #[test]
+    fn long_operator_chains_are_refused_without_deep_recursion() {
+        let chain = vec!["a"; 8000].join(" + ");
+        let err = bind_all(&format!("a = close\nout = {chain}")).unwrap_err();
+        assert_eq!(
+            err.message,
+            format!("script is too large (more than {MAX_NODES} operations)")
+        );
+        let chain = vec!["a"; 500].join(" + ");
+        let values = run(&format!("a = close\nout = {chain}"), &[2.0]);
+        assert_values(&values, &[1000.0]);
+    }
+}
@@ -0,0 +1,148 @@
+use crate::chart::Candle;
+use time::OffsetDateTime;
+
+pub const FIB_RATIOS: [f64; 7] = [0.0, 0.236, 0.382, 0.5, 0.618, 0.786, 1.0];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ZigZagThreshold {
+    /// Reversal of at least this many percent from the last extreme.
+    Percent(f64),
+    /// Reversal of at least `multiplier` times the ATR over `period` bars.
+    Atr { period: usize, multiplier: f64 },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SwingPoint {
+    pub index: usize,
+    pub time: OffsetDateTime,
+    pub price: f64,
+    pub is_high: bool,
+    /// False for the trailing extreme, which can still move as bars arrive.
+    pub confirmed: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FibRetracement {
+    pub from: SwingPoint,
+    pub to: SwingPoint,
+    /// `(ratio, price)` pairs, retracing from `to` back towards `from`.
+    pub levels: Vec<(f64, f64)>,
+}
+
+pub fn compute_zigzag(candles: &[Candle], threshold: ZigZagThreshold) -> Vec<SwingPoint> {
+    let mut swings = Vec::new();
+    if candles.is_empty() {
+        return swings;
+    }
+    let atr = match threshold {
+        ZigZagThreshold::Atr { period, .. } => compute_atr(candles, period),
+        ZigZagThreshold::Percent(_) => Vec::new(),
+    };
+    let reversal = |idx: usize, price: f64| match threshold {
+        ZigZagThreshold::Percent(percent) => price.abs() * percent / 100.0,
+        ZigZagThreshold::Atr { multiplier, .. } => atr[idx] * multiplier,
+    };
+    let swing = |idx: usize, price: f64, is_high: bool| SwingPoint {
+        index: idx,
+        time: candles[idx].time,
+        price,
+        is_high,
+        confirmed: true,
+    };
+
+    // Until the first reversal the direction is unknown, so both extremes
+    // are tracked; afterwards only the extreme of the current leg matters.
+    let mut rising: Option<bool> = None;
+    let mut high = (0, candles[0].high);
+    let mut low = (0, candles[0].low);
+    for (idx, candle) in candles.iter().enumerate().skip(1) {
+        match rising {
+            None => {
+                if candle.high > high.1 {
+                    high = (idx, candle.high);
+                }
+                if candle.low < low.1 {
+                    low = (idx, candle.low);
+                }
+                if high.0 > low.0 && high.1 - low.1 >= reversal(idx, low.1) {
+                    swings.push(swing(low.0, low.1, false));
+                    rising = Some(true);
+                } else if low.0 > high.0 && high.1 - low.1 >= reversal(idx, high.1) {
+                    swings.push(swing(high.0, high.1, true));
+                    rising = Some(false);
+                }
+            }
+            Some(true) => {
+                if candle.high > high.1 {
+                    high = (idx, candle.high);
+                } else if high.1 - candle.low >= reversal(idx, high.1) {
+                    swings.push(swing(high.0, high.1, true));
+                    low = (idx, candle.low);
+                    rising = Some(false);
+                }
+            }
+            Some(false) => {
+                if candle.low < low.1 {
+                    low = (idx, candle.low);
+                } else if candle.high - low.1 >= reversal(idx, low.1) {
+                    swings.push(swing(low.0, low.1, false));
+                    high = (idx, candle.high);
+                    rising = Some(true);
+                }
+            }
+        }
+    }
+
+    let trailing = match rising {
+        Some(true) => Some(swing(high.0, high.1, true)),
+        Some(false) => Some(swing(low.0, low.1, false)),
+        None => None,
+    };
+    if let Some(point) = trailing {
+        swings.push(SwingPoint {
+            confirmed: false,
+            ..point
+        });
+    }
+    swings
+}
+
+/// Retracement levels of the most recent leg, from the second-to-last swing
+/// to the last one.
+pub fn fib_retracement(swings: &[SwingPoint]) -> Option<FibRetracement> {
+    let [.., from, to] = swings else {
+        return None;
+    };
+    let span = to.price - from.price;
+    Some(FibRetracement {
+        from: *from,
+        to: *to,
+        levels: FIB_RATIOS
+            .iter()
+            .map(|ratio| (*ratio, to.price - span * ratio))
+            .collect(),
+    })
+}
+
+pub fn compute_atr(candles: &[Candle], period: usize) -> Vec<f64> {
+    let mut atr = vec![f64::NAN; candles.len()];
+    if period == 0 || candles.len() < period {
+        return atr;
+    }
+    let true_range = |idx: usize| {
+        let candle = &candles[idx];
+        match idx.checked_sub(1).map(|prev| candles[prev].close) {
+            Some(prev_close) => (candle.high - candle.low)
+                .max((candle.high - prev_close).abs())
+                .max((candle.low - prev_close).abs()),
+            None => candle.high - candle.low,
+        }
+    };
+    let mut value = (0..period).map(true_range).sum::<f64>() / period as f64;
+    atr[period - 1] = value;
+    for (idx, slot) in atr.iter_mut().enumerate().skip(period) {
+        value = (value * (period - 1) as f64 + true_range(idx)) / period as f64;
+        *slot = value;
+    }
+    atr
+}
@@ -3,9 +3,14 @@ mod icons;
 pub mod indicators;
 
 pub use chart::{
-    create_chart, sample_candles, Bar, BarConversionError, Candle, CandlestickSeriesApi, ChartApi,
-    ChartStyle, Color, CrosshairMode, HandleScaleOptions, HistogramPoint, HistogramSeriesApi,
-    LinePoint, LineSeriesApi, PanelId, PanelRole, PriceFormat, PriceLineOptions, PriceScale,
-    PriceScaleMode, PriceScaleOptions, ScaleMargins, TimeLabelMode, TimeScaleOptions,
-    TooltipOptions, TooltipPosition,
+    create_chart, sample_candles, AnimationOptions, Bar, BarConversionError, Candle,
+    CandlestickSeriesApi, ChartApi, ChartStyle, ChartSyncGroup, ChartSyncOptions, CloudPoint,
+    CloudSeriesApi, Color, CrosshairMode, Drawing, DrawingAnchor, DrawingHit, DrawingHitTarget,
+    DrawingId, DrawingLevel, DrawingStyle, DrawingTool, Easing, HandleScaleOptions, HistogramPoint,
+    HistogramSeriesApi, HitTarget, LinePoint, LineSeriesApi, LogicalRange, MouseEventParams,
+    OrderLineApi, OrderLineOptions, OrderSide, OrderType, PanelId, PanelRole, PositionLineApi,
+    PositionLineOptions, PriceFormat, PriceLineDragParams, PriceLineDragPhase, PriceLineOptions,
+    PriceScale, PriceScaleMode, PriceScaleOptions, PriceSegment, ScaleMargins, SeriesValue,
+    SubscriptionId, TimeLabelMode, TimeScaleOptions, TooltipOptions, TooltipPosition,
+    VolumeProfileApi, VolumeProfileLevels, VolumeProfileOptions, VolumeProfileRange,
 };
@@ -84,6 +84,9 @@ pub struct InteractionControls {
     pub axis_reset_price: gtk::Switch,
     pub kinetic_mouse: gtk::Switch,
     pub kinetic_touch: gtk::Switch,
+    pub animations: gtk::Switch,
+    pub animation_duration: gtk::SpinButton,
+    pub animation_easing: gtk::ComboBoxText,
     pub tracking_mode: gtk::Switch,
     pub axis_drag_time: gtk::SpinButton,
     pub axis_drag_price: gtk::SpinButton,
@@ -125,6 +128,9 @@ pub struct SettingsControls {
     pub interaction: InteractionControls,
     pub tooltip: TooltipControls,
     pub profiles: ProfilesControls,
+    /// Set while controls change for reasons other than an edit in the
+    /// settings window, so the change is not recorded as an undo step.
+    pub history_paused: std::rc::Rc<std::cell::Cell<bool>>,
 }
 
 pub fn build_settings(
@@ -170,6 +176,7 @@ pub fn build_settings(
         interaction,
         tooltip,
         profiles,
+        history_paused: std::rc::Rc::new(std::cell::Cell::new(false)),
     }
 }
 
@@ -305,6 +312,10 @@ fn build_interaction_page() -> (gtk::Box, InteractionControls) {
     let axis_reset_price = gtk::Switch::new();
     let kinetic_mouse = gtk::Switch::new();
     let kinetic_touch = gtk::Switch::new();
+    let animations = gtk::Switch::new();
+    let animation_duration = gtk::SpinButton::new(None::<&gtk::Adjustment>, 0.0, 0);
+    animation_duration.set_numeric(true);
+    let animation_easing = gtk::ComboBoxText::new();
     let tracking_mode = gtk::Switch::new();
     let axis_drag_time = gtk::SpinButton::new(None::<&gtk::Adjustment>, 0.0, 4);
     axis_drag_time.set_numeric(true);
@@ -339,6 +350,12 @@ fn build_interaction_page() -> (gtk::Box, InteractionControls) {
     page.append(&row_with_label("Mouse", &kinetic_mouse));
     page.append(&row_with_label("Touch", &kinetic_touch));
 
+    page.append(&separator());
+    page.append(&section_label("Animations"));
+    page.append(&row_with_label("Enabled", &animations));
+    page.append(&row_with_label("Duration (ms)", &animation_duration));
+    page.append(&row_with_label("Easing", &animation_easing));
+
     page.append(&separator());
     page.append(&section_label("Tracking Mode"));
     page.append(&row_with_label("Enabled", &tracking_mode));
@@ -365,6 +382,9 @@ fn build_interaction_page() -> (gtk::Box, InteractionControls) {
             axis_reset_price,
             kinetic_mouse,
             kinetic_touch,
+            animations,
+            animation_duration,
+            animation_easing,
             tracking_mode,
             axis_drag_time,
             axis_drag_price,